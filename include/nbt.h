@@ -0,0 +1,24 @@
+/* C bindings for luna_nbt, built with the `ffi` feature. See src/ffi.rs. */
+#ifndef LUNA_NBT_H
+#define LUNA_NBT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct NbtHandle NbtHandle;
+
+NbtHandle *nbt_parse(const uint8_t *data, size_t len);
+NbtHandle *nbt_get_path(const NbtHandle *handle, const char *path);
+char *nbt_to_snbt(const NbtHandle *handle);
+void nbt_free(NbtHandle *handle);
+void nbt_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,72 @@
+//! # luna_nbt_macros
+//! Procedural macros for `luna_nbt`. Use them through the `macros` feature of `luna_nbt`, which
+//! re-exports them with the paths they expand to filled in.
+
+mod parse;
+
+use parse::{Parser, Value};
+use proc_macro2::{TokenStream, TokenTree};
+use quote::quote;
+use syn::LitStr;
+
+// Expand a parsed value into an expression constructing the `Tag`, with `root` the path of the crate.
+fn expand(value: &Value, root: &TokenStream) -> TokenStream {
+    match value {
+        Value::Byte(x) => quote!(#root::Tag::Byte(#x)),
+        Value::Short(x) => quote!(#root::Tag::Short(#x)),
+        Value::Int(x) => quote!(#root::Tag::Int(#x)),
+        Value::Long(x) => quote!(#root::Tag::Long(#x)),
+        // Floats are expanded from their bits so that every value, including NaN, is exact.
+        Value::Float(x) => {
+            let bits = x.to_bits();
+            quote!(#root::Tag::Float(f32::from_bits(#bits)))
+        }
+        Value::Double(x) => {
+            let bits = x.to_bits();
+            quote!(#root::Tag::Double(f64::from_bits(#bits)))
+        }
+        Value::ByteArray(x) => quote!(#root::Tag::ByteArray(vec![#(#x),*])),
+        Value::String(x) => quote!(#root::Tag::String(String::from(#x))),
+        Value::List(items) => {
+            let items = items.iter().map(|item| expand(item, root));
+            quote!(#root::Tag::List(vec![#(#items),*]))
+        }
+        Value::Compound(entries) => {
+            let keys = entries.iter().map(|(key, _)| key);
+            let values = entries.iter().map(|(_, value)| expand(value, root));
+            quote!({
+                let mut map = #root::CompoundMap::default();
+                #(map.insert(String::from(#keys), #values);)*
+                #root::Tag::Compound(map)
+            })
+        }
+        Value::IntArray(x) => quote!(#root::Tag::IntArray(vec![#(#x),*])),
+        Value::LongArray(x) => quote!(#root::Tag::LongArray(vec![#(#x),*])),
+    }
+}
+
+/// Parse a SNBT string literal into an expression constructing a `Tag`.
+///
+/// Takes the path of the `nbt` crate, a comma, and the literal. Use `nbt::snbt!` instead.
+#[proc_macro]
+pub fn snbt(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let mut tokens = TokenStream::from(input).into_iter();
+
+    let mut root = TokenStream::new();
+    for token in tokens.by_ref() {
+        match &token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => break,
+            _ => root.extend(Some(token)),
+        }
+    }
+
+    let literal: LitStr = match syn::parse2(tokens.collect()) {
+        Ok(literal) => literal,
+        Err(error) => return error.to_compile_error().into(),
+    };
+
+    match Parser::new(&literal.value()).parse() {
+        Ok(value) => expand(&value, &root).into(),
+        Err(message) => syn::Error::new(literal.span(), format!("invalid SNBT: {}", message)).to_compile_error().into(),
+    }
+}
@@ -0,0 +1,309 @@
+// A parser for stringified NBT (SNBT), producing values to be expanded into `Tag` constructors.
+//
+// This crate can't depend on `luna_nbt`, so the runtime parser in its `snbt` module is a copy of
+// this one. Keep the two accepting the same input; the `snbt_macro_parity` test compares them.
+
+pub enum Value {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Value>),
+    Compound(Vec<(String, Value)>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Value {
+    // The type id of the value, used to check that lists hold a single type.
+    fn id(&self) -> u8 {
+        match self {
+            Value::Byte(_) => 1,
+            Value::Short(_) => 2,
+            Value::Int(_) => 3,
+            Value::Long(_) => 4,
+            Value::Float(_) => 5,
+            Value::Double(_) => 6,
+            Value::ByteArray(_) => 7,
+            Value::String(_) => 8,
+            Value::List(_) => 9,
+            Value::Compound(_) => 10,
+            Value::IntArray(_) => 11,
+            Value::LongArray(_) => 12,
+        }
+    }
+}
+
+// How deeply lists and compounds may nest, the same as the runtime parser.
+const MAX_DEPTH: usize = 512;
+
+pub struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+    depth: usize,
+}
+
+// Characters that may appear in unquoted keys and strings.
+fn is_bare(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Parser<'a> {
+        Parser { input, position: 0, depth: 0 }
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, String> {
+        let before = &self.input[..self.position];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+        Err(format!("{} at line {}, column {}", message, line, column))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() { break; }
+            self.position += c.len_utf8();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            self.error(&format!("expected '{}'", expected))
+        }
+    }
+
+    // Consume `c` if it is next, returning whether it was.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Parse a whole document, failing if anything follows the value.
+    pub fn parse(mut self) -> Result<Value, String> {
+        let value = self.value()?;
+        self.skip_whitespace();
+        if self.position < self.input.len() {
+            return self.error("unexpected trailing characters");
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') | Some('[') => {
+                if self.depth == MAX_DEPTH {
+                    return self.error(&format!("nested more than {} deep", MAX_DEPTH));
+                }
+                self.depth += 1;
+                let value = if self.peek() == Some('{') { self.compound() } else { self.list() };
+                self.depth -= 1;
+                value
+            }
+            Some('"') | Some('\'') => Ok(Value::String(self.quoted()?)),
+            Some(c) if is_bare(c) => {
+                let word = self.bare();
+                Ok(parse_word(word))
+            }
+            Some(_) => self.error("unexpected character"),
+            None => self.error("unexpected end of input"),
+        }
+    }
+
+    fn bare(&mut self) -> &'a str {
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            if !is_bare(c) { break; }
+            self.position += 1;
+        }
+        &self.input[start..self.position]
+    }
+
+    fn quoted(&mut self) -> Result<String, String> {
+        let quote = self.peek().unwrap();
+        self.position += 1;
+        let mut out = String::new();
+        loop {
+            match self.peek() {
+                Some('\\') => {
+                    self.position += 1;
+                    match self.peek() {
+                        Some(c) if c == '\\' || c == '"' || c == '\'' => {
+                            out.push(c);
+                            self.position += 1;
+                        }
+                        _ => return self.error("invalid escape"),
+                    }
+                }
+                Some(c) if c == quote => {
+                    self.position += 1;
+                    return Ok(out);
+                }
+                Some(c) => {
+                    out.push(c);
+                    self.position += c.len_utf8();
+                }
+                None => return self.error("unterminated string"),
+            }
+        }
+    }
+
+    fn key(&mut self) -> Result<String, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('"') | Some('\'') => self.quoted(),
+            Some(c) if is_bare(c) => Ok(self.bare().to_string()),
+            _ => self.error("expected a key"),
+        }
+    }
+
+    fn compound(&mut self) -> Result<Value, String> {
+        self.expect('{')?;
+        let mut entries: Vec<(String, Value)> = Vec::new();
+        if self.eat('}') {
+            return Ok(Value::Compound(entries));
+        }
+        loop {
+            let key = self.key()?;
+            self.expect(':')?;
+            let value = self.value()?;
+            if entries.iter().any(|(k, _)| *k == key) {
+                return self.error(&format!("duplicate key '{}'", key));
+            }
+            entries.push((key, value));
+            if self.eat('}') {
+                return Ok(Value::Compound(entries));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn list(&mut self) -> Result<Value, String> {
+        self.expect('[')?;
+
+        // An array starts with its type and a semicolon.
+        let rest = &self.input[self.position..];
+        let prefix = rest.trim_start();
+        let skipped = rest.len() - prefix.len();
+        if prefix.len() >= 2 && prefix.as_bytes()[1] == b';' {
+            let kind = prefix.as_bytes()[0];
+            if matches!(kind, b'B' | b'I' | b'L') {
+                self.position += skipped + 2;
+                return self.array(kind);
+            }
+        }
+
+        let mut items: Vec<Value> = Vec::new();
+        if self.eat(']') {
+            return Ok(Value::List(items));
+        }
+        loop {
+            let item = self.value()?;
+            if let Some(first) = items.first() {
+                if first.id() != item.id() {
+                    return self.error("list items must all be of the same type");
+                }
+            }
+            items.push(item);
+            if self.eat(']') {
+                return Ok(Value::List(items));
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn array(&mut self, kind: u8) -> Result<Value, String> {
+        let mut items: Vec<i64> = Vec::new();
+        if !self.eat(']') {
+            loop {
+                self.skip_whitespace();
+                let item = match (kind, parse_word(self.bare())) {
+                    (b'B', Value::Byte(x)) => x as i64,
+                    (b'I', Value::Int(x)) => x as i64,
+                    (b'L', Value::Long(x)) => x,
+                    _ => return self.error("array items must all be of the array's type"),
+                };
+                items.push(item);
+                if self.eat(']') { break; }
+                self.expect(',')?;
+            }
+        }
+        Ok(match kind {
+            b'B' => Value::ByteArray(items.into_iter().map(|x| x as i8).collect()),
+            b'I' => Value::IntArray(items.into_iter().map(|x| x as i32).collect()),
+            _ => Value::LongArray(items),
+        })
+    }
+}
+
+// Whether a word is a number, ignoring any suffix.
+// Besides decimal numbers, `NaN` is accepted for a suffixed float, as written by the SNBT formatter.
+fn is_number(word: &str) -> bool {
+    let digits = word.strip_prefix(|c| c == '-' || c == '+').unwrap_or(word);
+    if digits == "NaN" {
+        return true;
+    }
+    let mut seen_digit = false;
+    let mut seen_exponent = false;
+    let mut previous = ' ';
+    for c in digits.chars() {
+        match c {
+            '0'..='9' => seen_digit = true,
+            '.' if !seen_exponent => {}
+            'e' | 'E' if seen_digit && !seen_exponent => seen_exponent = true,
+            '-' | '+' if previous == 'e' || previous == 'E' => {}
+            _ => return false,
+        }
+        previous = c;
+    }
+    seen_digit && (previous.is_ascii_digit() || previous == '.')
+}
+
+// Parse an unquoted word as a number or boolean, falling back to a string.
+fn parse_word(word: &str) -> Value {
+    match word {
+        "true" => return Value::Byte(1),
+        "false" => return Value::Byte(0),
+        _ => {}
+    }
+
+    let (body, suffix) = match word.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() && word != "NaN" => (&word[..i], Some(c.to_ascii_lowercase())),
+        _ => (word, None),
+    };
+
+    if is_number(body) {
+        let parsed = match suffix {
+            Some('b') => body.parse().ok().map(Value::Byte),
+            Some('s') => body.parse().ok().map(Value::Short),
+            Some('l') => body.parse().ok().map(Value::Long),
+            Some('f') => body.parse().ok().map(Value::Float),
+            Some('d') => body.parse().ok().map(Value::Double),
+            None if body.contains(['.', 'e', 'E']) => body.parse().ok().map(Value::Double),
+            None => body.parse().ok().map(Value::Int),
+            _ => None,
+        };
+        if let Some(value) = parsed {
+            return value;
+        }
+    }
+    Value::String(word.to_string())
+}
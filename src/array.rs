@@ -0,0 +1,90 @@
+use crate::tags::Tag;
+use crate::util::ToTag;
+use std::ops::{Deref, DerefMut};
+#[cfg(feature="serde-ser")]
+use serde::{Serialize, Serializer};
+#[cfg(feature="serde-de")]
+use serde::{Deserialize, Deserializer};
+#[cfg(feature="serde-de")]
+use serde::de::{Visitor, SeqAccess};
+#[cfg(feature="serde-de")]
+use std::fmt;
+
+// Newtype names recognised by the serializer, which stores the wrapped sequence as an array.
+#[cfg(any(feature="serde-ser", feature="serde-de"))]
+pub(crate) const INT_ARRAY: &str = "$nbt::IntArray";
+#[cfg(any(feature="serde-ser", feature="serde-de"))]
+pub(crate) const LONG_ARRAY: &str = "$nbt::LongArray";
+
+macro_rules! array_type {
+    ($name: ident, $item: ty, $tag: ident, $token: ident, $doc: literal) => {
+        #[derive(Clone, PartialEq, Default)]
+        #[cfg_attr(feature="debug", derive(Debug))]
+        #[doc = $doc]
+        ///
+        /// Serde stores a `Vec` as a `TAG_List`, so wrap fields that vanilla stores as an array in
+        /// this type. Decoding accepts either an array or a list. Other formats see a plain sequence.
+        pub struct $name(pub Vec<$item>);
+
+        impl From<Vec<$item>> for $name {
+            fn from(items: Vec<$item>) -> Self { $name(items) }
+        }
+
+        impl From<$name> for Vec<$item> {
+            fn from(array: $name) -> Self { array.0 }
+        }
+
+        impl Deref for $name {
+            type Target = Vec<$item>;
+            fn deref(&self) -> &Vec<$item> { &self.0 }
+        }
+
+        impl DerefMut for $name {
+            fn deref_mut(&mut self) -> &mut Vec<$item> { &mut self.0 }
+        }
+
+        impl ToTag for $name {
+            fn into_tag(self) -> Tag { Tag::$tag(self.0) }
+        }
+
+        #[cfg(feature="serde-ser")]
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_newtype_struct($token, &self.0)
+            }
+        }
+
+        #[cfg(feature="serde-de")]
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct ArrayVisitor;
+
+                impl<'de> Visitor<'de> for ArrayVisitor {
+                    type Value = $name;
+
+                    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        f.write_str("an array")
+                    }
+
+                    fn visit_newtype_struct<D: Deserializer<'de>>(self, deserializer: D) -> Result<$name, D::Error> {
+                        Vec::<$item>::deserialize(deserializer).map($name)
+                    }
+
+                    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<$name, S::Error> {
+                        let mut items = Vec::new();
+                        while let Some(item) = seq.next_element()? {
+                            items.push(item);
+                        }
+                        Ok($name(items))
+                    }
+                }
+
+                deserializer.deserialize_newtype_struct($token, ArrayVisitor)
+            }
+        }
+    };
+}
+
+array_type!(IntArray, i32, IntArray, INT_ARRAY, "A `Vec<i32>` stored as a `TAG_Int_Array` by Serde.");
+array_type!(LongArray, i64, LongArray, LONG_ARRAY, "A `Vec<i64>` stored as a `TAG_Long_Array` by Serde.");
+
@@ -0,0 +1,307 @@
+use crate::tags::{Tag, TagIdent, CompoundMap};
+use crate::blob::Blob;
+use crate::file::SourceFormat;
+use crate::error::{NBTResult, NBTError, digest_io};
+use crate::flavor::{NbtFlavor, JavaFlavor};
+use crate::front::NBTWrite;
+use crate::options::{ReadOptions, MemoryBudget, StringMode, DEFAULT_MAX_DEPTH};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use std::mem::size_of;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(feature="serde-ser")]
+use serde::Serialize;
+#[cfg(feature="serde-de")]
+use serde::de::DeserializeOwned;
+
+// State carried through a single asynchronous read. Unlike `ReadContext` it is `Send`, so reads can
+// be spawned onto a multi-threaded runtime.
+struct AsyncContext {
+    budget: Option<MemoryBudget>,
+    // Bytes charged against the budget so far.
+    used: usize,
+    string_mode: StringMode,
+    unsigned_byte_arrays: bool,
+    cancel: Option<Arc<AtomicBool>>,
+    // Reused for the bytes of every string.
+    scratch: Vec<u8>,
+    max_depth: usize,
+}
+
+impl AsyncContext {
+    fn new(options: &ReadOptions) -> Self {
+        Self {
+            budget: options.memory_budget,
+            used: 0,
+            string_mode: options.string_mode,
+            unsigned_byte_arrays: options.unsigned_byte_arrays,
+            cancel: options.cancel.clone(),
+            scratch: Vec::new(),
+            max_depth: options.max_depth.unwrap_or(DEFAULT_MAX_DEPTH),
+        }
+    }
+
+    fn check_cancelled(&self) -> NBTResult<()> {
+        match &self.cancel {
+            Some(flag) if flag.load(Ordering::Relaxed) => Err(NBTError::Cancelled),
+            _ => Ok(())
+        }
+    }
+
+    fn charge(&mut self, bytes: usize) -> NBTResult<()> {
+        if let Some(budget) = &self.budget {
+            self.used = self.used.saturating_add(bytes);
+            if self.used > budget.limit {
+                return Err(NBTError::MemoryBudgetExceeded { limit: budget.limit });
+            }
+        }
+        Ok(())
+    }
+}
+
+// A list or compound that is being read.
+enum Frame {
+    List { ident: TagIdent, remaining: u32, items: Vec<Tag> },
+    // `key` is the name of the element being read.
+    Compound { map: CompoundMap, key: String },
+}
+
+async fn read_ident<R: AsyncRead + Unpin + ?Sized>(reader: &mut R) -> NBTResult<TagIdent> {
+    let byte = digest_io(reader.read_u8().await)?;
+    match TagIdent::parse(&byte) {
+        Some(x) => Ok(x),
+        None => Err(NBTError::InvalidTag { found: byte })
+    }
+}
+
+async fn read_length<R: AsyncRead + Unpin + ?Sized>(reader: &mut R, ctx: &AsyncContext) -> NBTResult<u32> {
+    ctx.check_cancelled()?;
+    digest_io(reader.read_u32().await)
+}
+
+async fn read_string<R: AsyncRead + Unpin + ?Sized>(reader: &mut R, ctx: &mut AsyncContext) -> NBTResult<String> {
+    let length = digest_io(reader.read_u16().await)? as usize;
+    ctx.charge(length)?;
+
+    ctx.scratch.clear();
+    ctx.scratch.resize(length, 0);
+    digest_io(reader.read_exact(&mut ctx.scratch).await)?;
+
+    match ctx.string_mode {
+        StringMode::Flavor => JavaFlavor::decode_string(&ctx.scratch),
+        StringMode::Strict => JavaFlavor::decode_string_strict(&ctx.scratch),
+        StringMode::Utf8 => std::str::from_utf8(&ctx.scratch).map(str::to_string).map_err(|_| NBTError::StringError),
+        StringMode::Lossy => match JavaFlavor::decode_string(&ctx.scratch) {
+            Err(NBTError::StringError) => Ok(JavaFlavor::decode_string_lossy(&ctx.scratch)),
+            result => result
+        }
+    }
+}
+
+// Read the type and name of the next element of a compound, or `None` at its end.
+async fn read_entry<R: AsyncRead + Unpin + ?Sized>(reader: &mut R, ctx: &mut AsyncContext) -> NBTResult<Option<(TagIdent, String)>> {
+    let ident = read_ident(reader).await?;
+    if ident == TagIdent::TAG_End {
+        return Ok(None);
+    }
+    ctx.check_cancelled()?;
+    let name = read_string(reader, ctx).await?;
+    ctx.charge(size_of::<(String, Tag)>())?;
+    Ok(Some((ident, name)))
+}
+
+// Read a tag that holds no other tags.
+async fn read_leaf<R: AsyncRead + Unpin + ?Sized>(reader: &mut R, ident: &TagIdent, ctx: &mut AsyncContext) -> NBTResult<Tag> {
+    Ok(match ident {
+        TagIdent::TAG_Byte => Tag::Byte(digest_io(reader.read_i8().await)?),
+        TagIdent::TAG_Short => Tag::Short(digest_io(reader.read_i16().await)?),
+        TagIdent::TAG_Int => Tag::Int(digest_io(reader.read_i32().await)?),
+        TagIdent::TAG_Long => Tag::Long(digest_io(reader.read_i64().await)?),
+        TagIdent::TAG_Float => Tag::Float(digest_io(reader.read_f32().await)?),
+        TagIdent::TAG_Double => Tag::Double(digest_io(reader.read_f64().await)?),
+        TagIdent::TAG_String => Tag::String(read_string(reader, ctx).await?),
+        TagIdent::TAG_Byte_Array => {
+            let length = read_length(reader, ctx).await?;
+            ctx.charge(length as usize)?;
+
+            // Read through `take` so a bogus length can't allocate more than the data sent.
+            let mut array = Vec::new();
+            digest_io((&mut *reader).take(length as u64).read_to_end(&mut array).await)?;
+            if array.len() < length as usize {
+                return digest_io(Err(std::io::ErrorKind::UnexpectedEof.into()));
+            }
+            if ctx.unsigned_byte_arrays {
+                Tag::UnsignedByteArray(array)
+            } else {
+                Tag::ByteArray(array.into_iter().map(|x| x as i8).collect())
+            }
+        }
+        TagIdent::TAG_Int_Array => {
+            let length = read_length(reader, ctx).await?;
+            ctx.charge((length as usize).saturating_mul(size_of::<i32>()))?;
+            let mut array = Vec::new();
+            for _ in 0..length {
+                array.push(digest_io(reader.read_i32().await)?);
+            }
+            Tag::IntArray(array)
+        }
+        TagIdent::TAG_Long_Array => {
+            let length = read_length(reader, ctx).await?;
+            ctx.charge((length as usize).saturating_mul(size_of::<i64>()))?;
+            let mut array = Vec::new();
+            for _ in 0..length {
+                array.push(digest_io(reader.read_i64().await)?);
+            }
+            Tag::LongArray(array)
+        }
+        TagIdent::TAG_End => return Err(NBTError::UnexpectedEndTag),
+        // Handled by `read_tag`.
+        TagIdent::TAG_List | TagIdent::TAG_Compound => unreachable!(),
+    })
+}
+
+// Read the payload of a tag. Lists and compounds are tracked on a stack rather than by recursion,
+// so the future has a fixed size however deeply the document nests.
+async fn read_tag<R: AsyncRead + Unpin + ?Sized>(reader: &mut R, ident: TagIdent, ctx: &mut AsyncContext) -> NBTResult<Tag> {
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut ident = ident;
+
+    loop {
+        // The stack doesn't grow the call stack, but the limit is kept the same as `ReadContext`.
+        if matches!(ident, TagIdent::TAG_List | TagIdent::TAG_Compound) && stack.len() >= ctx.max_depth {
+            return Err(NBTError::DepthLimitExceeded { limit: ctx.max_depth });
+        }
+
+        // Read a value, or open a list or compound and move on to its first element.
+        let mut value = match ident {
+            TagIdent::TAG_List => {
+                let item = read_ident(reader).await?;
+                let length = read_length(reader, ctx).await?;
+                ctx.charge((length as usize).saturating_mul(size_of::<Tag>()))?;
+                if length == 0 {
+                    Tag::List(Vec::new())
+                } else {
+                    stack.push(Frame::List { ident: item.clone(), remaining: length, items: Vec::new() });
+                    ident = item;
+                    continue;
+                }
+            }
+            TagIdent::TAG_Compound => match read_entry(reader, ctx).await? {
+                Some((item, key)) => {
+                    stack.push(Frame::Compound { map: CompoundMap::default(), key });
+                    ident = item;
+                    continue;
+                }
+                None => Tag::Compound(CompoundMap::default()),
+            },
+            _ => read_leaf(reader, &ident, ctx).await?,
+        };
+
+        // Store the value in its parent, closing every list and compound it completes.
+        loop {
+            let next = match stack.last_mut() {
+                None => return Ok(value),
+                Some(Frame::List { ident: item, remaining, items }) => {
+                    items.push(value);
+                    *remaining -= 1;
+                    if *remaining > 0 { Some(item.clone()) } else { None }
+                }
+                Some(Frame::Compound { map, key }) => {
+                    map.insert(std::mem::take(key), value);
+                    match read_entry(reader, ctx).await? {
+                        Some((item, next_key)) => {
+                            *key = next_key;
+                            Some(item)
+                        }
+                        None => None
+                    }
+                }
+            };
+
+            match next {
+                Some(item) => {
+                    ident = item;
+                    break;
+                }
+                None => value = match stack.pop() {
+                    Some(Frame::List { items, .. }) => Tag::List(items),
+                    Some(Frame::Compound { map, .. }) => Tag::Compound(map),
+                    None => unreachable!(),
+                },
+            }
+        }
+    }
+}
+
+impl Blob {
+    /// Read a Java Edition document from an asynchronous reader.
+    ///
+    /// The document is decoded as it arrives, rather than being buffered first. Reads are small, so
+    /// wrap sockets and files in a `tokio::io::BufReader`.
+    pub async fn read_async<R: AsyncRead + Unpin + ?Sized>(reader: &mut R) -> NBTResult<Blob> {
+        Blob::read_async_with_options(reader, &ReadOptions::default()).await
+    }
+
+    /// Read a Java Edition document from an asynchronous reader with the given options.
+    ///
+    /// The memory budget, string mode, unsigned byte arrays, depth limit and cancellation are supported. Progress
+    /// is not reported, and truncated documents are always rejected.
+    pub async fn read_async_with_options<R: AsyncRead + Unpin + ?Sized>(reader: &mut R, options: &ReadOptions) -> NBTResult<Blob> {
+        let ctx = &mut AsyncContext::new(options);
+
+        let ident = read_ident(reader).await?;
+        if ident != TagIdent::TAG_Compound {
+            return Err(NBTError::InvalidImplicit { found: ident });
+        }
+        let root = read_string(reader, ctx).await?;
+        let elements = match read_tag(reader, TagIdent::TAG_Compound, ctx).await? {
+            Tag::Compound(elements) => elements,
+            _ => unreachable!(),
+        };
+        Ok(Blob { root, elements, source: JavaFlavor::DIALECT.map(SourceFormat::new) })
+    }
+
+    /// Write the blob in the Java dialect to an asynchronous writer.
+    ///
+    /// The document is encoded into memory, then written at once. The writer is not flushed.
+    pub async fn write_async<W: AsyncWrite + Unpin + ?Sized>(&self, writer: &mut W) -> NBTResult<()> {
+        let bytes = self.bytes()?;
+        digest_io(writer.write_all(&bytes).await)
+    }
+}
+
+#[cfg(feature="serde-de")]
+/// Read a Java Edition document from an asynchronous reader, and decode it into a Serde
+/// deserializable value.
+///
+/// See `Blob::read_async`.
+///
+/// ### Example
+/// ```
+/// use nbt::{from_async_reader, to_async_writer};
+/// use std::collections::HashMap;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let mut scores = HashMap::new();
+/// scores.insert("Notch".to_string(), 42);
+///
+/// let mut buffer = Vec::new();
+/// to_async_writer(&mut buffer, &scores).await.unwrap();
+///
+/// let decoded: HashMap<String, i32> = from_async_reader(&buffer[..]).await.unwrap();
+/// assert_eq!(decoded, scores);
+/// # });
+/// ```
+pub async fn from_async_reader<T: DeserializeOwned, R: AsyncRead + Unpin>(mut reader: R) -> NBTResult<T> {
+    crate::front::decode(Blob::read_async(&mut reader).await?)
+}
+
+#[cfg(feature="serde-ser")]
+/// Encode a Serde serializable value, and write it as a Java Edition document with an empty root
+/// name to an asynchronous writer.
+///
+/// See `Blob::write_async`.
+pub async fn to_async_writer<W: AsyncWrite + Unpin, T: Serialize>(mut writer: W, value: &T) -> NBTResult<()> {
+    crate::front::encode(value)?.write_async(&mut writer).await
+}
@@ -0,0 +1,35 @@
+use crate::blob::Blob;
+use crate::compression::{Compression, compress, decompress};
+use crate::error::{NBTResult, NBTError};
+use crate::front::{NBTRead, NBTWrite};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+impl Blob {
+    /// Encode the blob as compressed, then base64 encoded, text.
+    ///
+    /// This is the form blobs are usually shared in between web tools and plugins.
+    ///
+    /// ## Example
+    /// ```
+    /// use nbt::{Blob, Compression};
+    ///
+    /// let mut blob = Blob::new();
+    /// blob.insert("id", "minecraft:stone");
+    ///
+    /// let text = blob.to_base64(Compression::Gzip).unwrap();
+    /// let decoded = Blob::from_base64(&text, Compression::Gzip).unwrap();
+    /// assert_eq!(decoded.get::<String>("id").unwrap(), "minecraft:stone");
+    /// ```
+    pub fn to_base64(&self, compression: Compression) -> NBTResult<String> {
+        Ok(STANDARD.encode(compress(&self.bytes()?, compression)?))
+    }
+
+    /// Decode a blob from compressed, then base64 encoded, text.
+    ///
+    /// Surrounding whitespace is ignored. Fails with `NBTError::InvalidBase64` if the text is not base64.
+    pub fn from_base64(text: &str, compression: Compression) -> NBTResult<Blob> {
+        let data = STANDARD.decode(text.trim()).map_err(|_| NBTError::InvalidBase64)?;
+        Ok(Blob::from_bytes(decompress(&data, compression)?)?.with_compression(Some(compression)))
+    }
+}
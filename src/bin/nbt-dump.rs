@@ -0,0 +1,94 @@
+//! # nbt-dump
+//! Prints a NBT file as a tree, or as pretty printed SNBT with `--snbt`.
+//!
+//! The compression is detected with `Blob::read_auto`, so gzip, zlib and zstd compressed files
+//! are detected from their header, and raw DEFLATE is tried for files that fail to read otherwise.
+//! Files that are not a root compound are read as a single unnamed tag.
+//!
+//! ```text
+//! nbt-dump [--snbt] <file>
+//! nbt-dump [--snbt] -          (read from stdin)
+//! ```
+
+use std::io::Read;
+use std::process::exit;
+use nbt::{Blob, Tag};
+
+fn main() {
+    let (flags, paths): (Vec<String>, Vec<String>) = std::env::args().skip(1).partition(|arg| arg == "--snbt");
+    let snbt = !flags.is_empty();
+    let path = match paths.as_slice() {
+        [path] => path.clone(),
+        _ => {
+            eprintln!("usage: nbt-dump [--snbt] <file>");
+            exit(2);
+        }
+    };
+
+    let mut data = Vec::new();
+    let read = if path == "-" {
+        std::io::stdin().read_to_end(&mut data)
+    } else {
+        std::fs::File::open(&path).and_then(|mut file| file.read_to_end(&mut data))
+    };
+    if let Err(e) = read {
+        eprintln!("failed to read '{}': {}", path, e);
+        exit(1);
+    }
+
+    let mut output = String::new();
+    match Blob::read_auto(&data[..]) {
+        Ok(blob) if snbt => output = blob.to_snbt_pretty() + "\n",
+        Ok(blob) => write_entry(&mut output, Some(&blob.root), &Tag::Compound(blob.elements), 0),
+        Err(blob_error) => match Tag::read_auto(&data[..]) {
+            Ok(tag) if snbt => output = tag.to_snbt_pretty() + "\n",
+            Ok(tag) => write_entry(&mut output, None, &tag, 0),
+            Err(_) => {
+                eprintln!("failed to parse '{}': {}", path, blob_error);
+                exit(1);
+            }
+        }
+    }
+    print!("{}", output);
+}
+
+fn write_entry(output: &mut String, name: Option<&str>, tag: &Tag, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let name = match name {
+        Some(name) => format!("'{}'", name),
+        None => "None".to_string()
+    };
+    let header = format!("{}{}({}): ", indent, tag.ident(), name);
+
+    match tag {
+        Tag::List(list) => {
+            output.push_str(&format!("{}{} entries\n{}{{\n", header, list.len(), indent));
+            for item in list {
+                write_entry(output, None, item, depth + 1);
+            }
+            output.push_str(&format!("{}}}\n", indent));
+        }
+        Tag::Compound(map) => {
+            output.push_str(&format!("{}{} entries\n{}{{\n", header, map.len(), indent));
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            for key in keys {
+                write_entry(output, Some(key), &map[key], depth + 1);
+            }
+            output.push_str(&format!("{}}}\n", indent));
+        }
+        Tag::ByteArray(_) | Tag::UnsignedByteArray(_) => {
+            let array = tag.byte_array_as_i8().unwrap_or_default();
+            output.push_str(&format!("{}[{} bytes] {:?}\n", header, array.len(), array))
+        }
+        Tag::IntArray(array) => output.push_str(&format!("{}[{} ints] {:?}\n", header, array.len(), array)),
+        Tag::LongArray(array) => output.push_str(&format!("{}[{} longs] {:?}\n", header, array.len(), array)),
+        Tag::String(string) => output.push_str(&format!("{}{:?}\n", header, string)),
+        Tag::Byte(v) => output.push_str(&format!("{}{}\n", header, v)),
+        Tag::Short(v) => output.push_str(&format!("{}{}\n", header, v)),
+        Tag::Int(v) => output.push_str(&format!("{}{}\n", header, v)),
+        Tag::Long(v) => output.push_str(&format!("{}{}\n", header, v)),
+        Tag::Float(v) => output.push_str(&format!("{}{}\n", header, v)),
+        Tag::Double(v) => output.push_str(&format!("{}{}\n", header, v)),
+    }
+}
@@ -1,9 +1,16 @@
-use crate::tags::Tag;
+use crate::tags::{Tag, TagIdent};
 use std::ops::Deref;
-use std::collections::HashMap;
+#[cfg(feature="debug")]
+use std::fmt;
+#[cfg(feature="debug")]
+use crate::snbt::write_snbt_compound;
+use crate::tags::CompoundMap;
 use crate::util::{ToTag, FromTag};
+use crate::path::{parse_path, lookup, PathSegment};
+use crate::error::{NBTResult, NBTError};
+use crate::file::{SourceFormat, Compression};
+use crate::entry::Entry;
 
-#[cfg_attr(feature="debug", derive(Debug))]
 #[derive(Clone)]
 /// A NBT Document containing an implicit compound and root name.
 ///
@@ -29,18 +36,63 @@ pub struct Blob {
     /// Name of the root compound
     pub root: String,
     /// Elements of the root compound
-    pub elements: HashMap<String, Tag>
+    pub elements: CompoundMap,
+    // The format the blob was read in. See `source`.
+    pub(crate) source: Option<SourceFormat>
 }
 
 impl Blob {
+    /// The format the blob was read in, if it was read with a built-in flavor.
+    ///
+    /// Used by `save_same_format` to write the blob back the way it was found.
+    pub fn source(&self) -> Option<SourceFormat> {
+        self.source
+    }
+
+    /// Set the format the blob is written in by `save_same_format`, or `None` for uncompressed Java
+    /// NBT.
+    pub fn set_source(&mut self, source: Option<SourceFormat>) {
+        self.source = source;
+    }
+
+    // Record the compression the blob was read through, once its dialect is known.
+    pub(crate) fn with_compression(mut self, compression: Option<Compression>) -> Blob {
+        self.source = self.source.map(|source| source.compression(compression));
+        self
+    }
+
     /// Create a new `Blob` with a given root compound name.
     pub fn create(root: &str) -> Blob {
-        Blob { root: root.to_string() , elements: HashMap::new() }
+        Blob { root: root.to_string() , elements: CompoundMap::default(), source: None }
     }
 
     /// Create a new `Blob` with a empty root name.
     pub fn new() -> Blob {
-        Blob { root: String::new() , elements: HashMap::new() }
+        Blob { root: String::new() , elements: CompoundMap::default(), source: None }
+    }
+
+    /// Create a new `Blob` with a empty root name, with space for at least `capacity` elements.
+    ///
+    /// ```
+    /// # use nbt::Blob;
+    /// let mut blob = Blob::with_capacity(1000);
+    /// for i in 0..1000 {
+    ///     blob.insert(&format!("row{}", i), i);
+    /// }
+    /// # assert!(blob.capacity() >= 1000);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Blob {
+        Blob { root: String::new(), elements: CompoundMap::with_capacity_and_hasher(capacity, Default::default()), source: None }
+    }
+
+    /// Reserve space for at least `additional` more elements in the root compound.
+    pub fn reserve(&mut self, additional: usize) {
+        self.elements.reserve(additional)
+    }
+
+    /// The number of elements the root compound can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.elements.capacity()
     }
 
     /// Insert a element into the root compound.
@@ -56,6 +108,45 @@ impl Blob {
         self.elements.insert(name.to_string(), payload.into_tag())
     }
 
+    /// Insert a element into the root compound, failing if it would change the type of an existing element.
+    ///
+    /// Returns `NBTError::InvalidType` with the inserted and existing types if the types differ, in
+    /// which case the blob is left unchanged.
+    /// ```
+    /// # use nbt::{Blob, NBTError, TagIdent};
+    /// let mut blob = Blob::new();
+    /// blob.insert("DataVersion", 2584);
+    ///
+    /// assert!(blob.insert_checked("DataVersion", 2586).is_ok());
+    /// assert!(matches!(blob.insert_checked("DataVersion", "2586"), Err(NBTError::InvalidType { found: TagIdent::TAG_String, expecting: TagIdent::TAG_Int, .. })));
+    /// # assert_eq!(blob.get::<i32>("DataVersion"), Some(&2586));
+    /// ```
+    pub fn insert_checked<P: ToTag>(&mut self, name: &str, payload: P) -> NBTResult<Option<Tag>> {
+        insert_typed(&mut self.elements, name, payload.into_tag())
+    }
+
+    /// Insert every element of an iterator of names and payloads into the root compound,
+    /// replacing any existing elements with the same names.
+    ///
+    /// Space is reserved once up front, from the iterator's size hint.
+    /// ```
+    /// # use nbt::Blob;
+    /// let rows = vec![("alice", 12), ("bob", 7)];
+    ///
+    /// let mut blob = Blob::new();
+    /// blob.insert_all(rows);
+    /// assert_eq!(blob.get::<i32>("bob"), Some(&7));
+    /// ```
+    pub fn insert_all<K: Into<String>, P: ToTag, I: IntoIterator<Item = (K, P)>>(&mut self, elements: I) {
+        extend_compound(&mut self.elements, elements)
+    }
+
+    /// Get the element of the root compound with the given name, for in-place insertion or
+    /// modification. See `Entry`.
+    pub fn entry(&mut self, name: &str) -> Entry<'_> {
+        Entry::new(self.elements.entry(name.to_string()))
+    }
+
     /// Get a element from the root compound, with a given type.
     ///
     /// Uses the `FromTag` trait to convert a tag into a desired type.
@@ -68,20 +159,281 @@ impl Blob {
     /// # assert_eq!(name.unwrap(), &("Hello World".to_string()));
     /// # assert_eq!(none, None)
     /// ```
+    ///
+    /// Types built from a tag rather than held in one, such as the vector types of the `vecmath`
+    /// feature, cannot be borrowed and are always `None`. Use `get_owned` for those.
     pub fn get<T: FromTag>(&self, name: &str) -> Option<&T> where Self: Sized {
-        T::from_borrowed_tag(self.elements.get(&name.to_string())?)
+        T::from_borrowed_tag(self.elements.get(name)?)
+    }
+
+    /// Get a copy of an element from the root compound, converted to a given type.
+    ///
+    /// Unlike `get`, this works for types that cannot be borrowed out of a tag, at the cost of
+    /// cloning the tag.
+    /// ```
+    /// # use nbt::Blob;
+    /// let mut blob = Blob::new();
+    /// blob.insert("name", "Steve");
+    ///
+    /// assert_eq!(blob.get_owned::<String>("name"), Some("Steve".to_string()));
+    /// ```
+    pub fn get_owned<T: FromTag>(&self, name: &str) -> Option<T> {
+        T::from_tag(self.elements.get(name)?.clone())
+    }
+
+    /// The type of the element of the root compound with the given name, if there is one.
+    ///
+    /// ```
+    /// # use nbt::{Blob, Tag, TagIdent};
+    /// # let mut blob = Blob::new();
+    /// # blob.insert("UUID", Tag::IntArray(vec![1, 2, 3, 4]));
+    /// match blob.type_of("UUID") {
+    ///     Some(TagIdent::TAG_Int_Array) => { /* modern */ }
+    ///     Some(TagIdent::TAG_List) => { /* legacy */ }
+    ///     _ => { /* missing */ }
+    /// }
+    /// # assert_eq!(blob.type_of("UUID"), Some(TagIdent::TAG_Int_Array));
+    /// # assert_eq!(blob.type_of("name"), None);
+    /// ```
+    pub fn type_of(&self, name: &str) -> Option<TagIdent> {
+        self.elements.get(name).map(Tag::ident)
+    }
+
+    /// Insert a boolean into the root compound, as a byte of 0 or 1.
+    pub fn insert_bool(&mut self, name: &str, value: bool) -> Option<Tag> {
+        self.elements.insert(name.to_string(), Tag::from_bool(value))
+    }
+
+    /// Get a boolean from the root compound.
+    ///
+    /// Returns `None` if there is no such element, or it is not a byte of 0 or 1.
+    /// See [`get_bool_lenient`](Blob::get_bool_lenient) to accept any byte.
+    /// ```
+    /// # use nbt::Blob;
+    /// let mut blob = Blob::new();
+    /// blob.insert_bool("OnGround", true);
+    /// blob.insert("Invulnerable", 2i8);
+    ///
+    /// assert_eq!(blob.get_bool("OnGround"), Some(true));
+    /// assert_eq!(blob.get_bool("Invulnerable"), None);
+    /// assert_eq!(blob.get_bool_lenient("Invulnerable"), Some(true));
+    /// ```
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.elements.get(name)?.as_bool()
+    }
+
+    /// Get a boolean from the root compound, treating any nonzero byte as true.
+    pub fn get_bool_lenient(&self, name: &str) -> Option<bool> {
+        self.elements.get(name)?.as_bool_lenient()
+    }
+
+    /// Get a element from the root compound, with a given type, or an error describing why it could not be.
+    ///
+    /// Returns `NBTError::MissingKey` if there is no element with the name, and `NBTError::InvalidType`
+    /// with the found and expected types if the element is of the wrong type. Types that cannot be
+    /// borrowed out of a tag fail with `NBTError::Custom`, see `get_owned`.
+    /// ```
+    /// # use nbt::{Blob, NBTError, TagIdent};
+    /// # let mut blob = Blob::new();
+    /// blob.insert("name", "Hello World");
+    ///
+    /// assert_eq!(blob.try_get::<String>("name").unwrap(), "Hello World");
+    /// assert!(matches!(blob.try_get::<i8>("age"), Err(NBTError::MissingKey { .. })));
+    /// assert!(matches!(blob.try_get::<i8>("name"), Err(NBTError::InvalidType { found: TagIdent::TAG_String, expecting: TagIdent::TAG_Byte, .. })));
+    /// ```
+    pub fn try_get<T: FromTag>(&self, name: &str) -> NBTResult<&T> {
+        let tag = self.elements.get(name).ok_or_else(|| NBTError::MissingKey { key: name.to_string() })?;
+        T::from_borrowed_tag(tag).ok_or_else(|| match T::expected_ident() {
+            Some(_) if T::from_tag(tag.clone()).is_some() => NBTError::Custom(format!("{} at key '{}' cannot be borrowed as this type, use get_owned", tag.ident(), name)),
+            Some(expecting) => NBTError::InvalidType { found: tag.ident(), expecting, when: format!("key '{}'", name) },
+            None => NBTError::Custom(format!("Could not convert {} at key '{}'", tag.ident(), name))
+        })
+    }
+
+    /// Iterate over the elements of the root compound that convert to a given type, with their keys.
+    ///
+    /// Elements of other types are skipped.
+    /// ```
+    /// # use nbt::Blob;
+    /// let mut blob = Blob::new();
+    /// blob.insert("name", "Steve");
+    /// blob.insert("kills", 3);
+    /// blob.insert("deaths", 1);
+    ///
+    /// let mut counts: Vec<(&str, &i32)> = blob.iter_of::<i32>().collect();
+    /// counts.sort();
+    /// assert_eq!(counts, vec![("deaths", &1), ("kills", &3)]);
+    /// ```
+    pub fn iter_of<'a, T: FromTag + 'a>(&'a self) -> impl Iterator<Item = (&'a str, &'a T)> {
+        self.elements.iter().filter_map(|(key, tag)| Some((key.as_str(), T::from_borrowed_tag(tag)?)))
     }
 
     /// Get the NBT blob as a compound tag.
     pub fn compound(self) -> Tag {
         Tag::Compound(self.elements)
     }
+
+    /// Borrow the elements of the root compound, without consuming the blob.
+    ///
+    /// ```
+    /// # use nbt::Blob;
+    /// let mut blob = Blob::new();
+    /// blob.insert("id", 42);
+    ///
+    /// let keys: Vec<&String> = blob.compound_ref().keys().collect();
+    /// assert_eq!(keys, vec!["id"]);
+    /// ```
+    pub fn compound_ref(&self) -> &CompoundMap {
+        &self.elements
+    }
+
+    /// Mutably borrow the elements of the root compound.
+    pub fn compound_mut(&mut self) -> &mut CompoundMap {
+        &mut self.elements
+    }
+
+    /// Remove and return a nested tag, given a path such as `Level.Sections[0].Palette`.
+    ///
+    /// Returns `None`, leaving the blob untouched, if the path is malformed or does not exist.
+    /// ```
+    /// # use nbt::{Blob, Tag, CompoundMap};
+    /// let mut inner = CompoundMap::default();
+    /// inner.insert("name".to_string(), Tag::String("Steve".to_string()));
+    ///
+    /// let mut blob = Blob::new();
+    /// blob.insert("player", Tag::Compound(inner));
+    ///
+    /// assert_eq!(blob.extract("player.name"), Some(Tag::String("Steve".to_string())));
+    /// assert_eq!(blob.extract("player.name"), None);
+    /// ```
+    pub fn extract(&mut self, path: &str) -> Option<Tag> {
+        let mut segments = parse_path(path)?;
+        let last = segments.pop()?;
+
+        // Walk to the parent of the target.
+        let mut parent: Option<&mut Tag> = None;
+        for segment in segments {
+            let next = match (parent, segment) {
+                (None, PathSegment::Key(key)) => self.elements.get_mut(&key)?,
+                (Some(Tag::Compound(map)), PathSegment::Key(key)) => map.get_mut(&key)?,
+                (Some(Tag::List(list)), PathSegment::Index(index)) => list.get_mut(index)?,
+                _ => return None,
+            };
+            parent = Some(next);
+        }
+
+        match (parent, last) {
+            (None, PathSegment::Key(key)) => self.elements.remove(&key),
+            (Some(Tag::Compound(map)), PathSegment::Key(key)) => map.remove(&key),
+            (Some(Tag::List(list)), PathSegment::Index(index)) if index < list.len() => Some(list.remove(index)),
+            _ => None,
+        }
+    }
+
+    /// Remove a nested compound and return it as its own `Blob`, named after the last key of the path.
+    ///
+    /// Returns `None`, leaving the blob untouched, if the path does not point to a compound.
+    pub fn extract_blob(&mut self, path: &str) -> Option<Blob> {
+        let segments = parse_path(path)?;
+        if let Some(Tag::Compound(_)) = lookup(&self.elements, &segments) {} else { return None; }
+
+        let root = match segments.last()? {
+            PathSegment::Key(key) => key.clone(),
+            PathSegment::Index(_) => String::new(),
+        };
+
+        match self.extract(path)? {
+            Tag::Compound(elements) => Some(Blob { root, elements, source: None }),
+            _ => None
+        }
+    }
+
+    /// Merge the elements of another blob into this one, resolving conflicting keys with the given strategy.
+    ///
+    /// The root name of this blob is kept.
+    /// ```
+    /// # use nbt::{Blob, MergeStrategy};
+    /// let mut config = Blob::new();
+    /// config.insert("port", 25565);
+    /// config.insert("motd", "A Minecraft Server");
+    ///
+    /// let mut user = Blob::new();
+    /// user.insert("motd", "Hello!");
+    ///
+    /// config.merge(user, MergeStrategy::Overwrite);
+    /// assert_eq!(config.get::<String>("motd").unwrap(), "Hello!");
+    /// assert_eq!(config.get::<i32>("port"), Some(&25565));
+    /// ```
+    pub fn merge(&mut self, other: Blob, strategy: MergeStrategy) {
+        merge_compound(&mut self.elements, other.elements, strategy)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// How conflicting keys are resolved by `Blob::merge`.
+pub enum MergeStrategy {
+    /// Replace existing values with incoming ones.
+    Overwrite,
+    /// Keep existing values, only adding missing keys.
+    Keep,
+    /// Merge compounds present on both sides recursively, overwriting any other conflicting values.
+    Deep,
+}
+
+pub(crate) fn merge_compound(into: &mut CompoundMap, from: CompoundMap, strategy: MergeStrategy) {
+    for (key, value) in from {
+        match (into.get_mut(&key), value, strategy) {
+            (None, value, _) => { into.insert(key, value); }
+            (Some(_), _, MergeStrategy::Keep) => {}
+            (Some(Tag::Compound(existing)), Tag::Compound(incoming), MergeStrategy::Deep) => {
+                merge_compound(existing, incoming, strategy)
+            }
+            (Some(existing), value, _) => *existing = value,
+        }
+    }
+}
+
+#[cfg(feature="debug")]
+/// Blobs are debug formatted as their root name and SNBT, such as `Blob("hello world") {name:"Bananrama"}`.
+///
+/// The alternate form (`{:#?}`) puts each list element and compound entry on its own line.
+impl fmt::Debug for Blob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Blob({:?}) ", self.root)?;
+        write_snbt_compound(f, &self.elements, f.alternate())
+    }
+}
+
+impl Default for Blob {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Deref for Blob {
-    type Target = HashMap<String, Tag>;
+    type Target = CompoundMap;
 
     fn deref(&self) -> &Self::Target {
         &self.elements
     }
 }
+
+// Insert a tag, failing instead if it would replace a tag of a different type.
+pub(crate) fn insert_typed(map: &mut CompoundMap, name: &str, tag: Tag) -> NBTResult<Option<Tag>> {
+    if let Some(existing) = map.get(name) {
+        if existing.ident() != tag.ident() {
+            return Err(NBTError::InvalidType { found: tag.ident(), expecting: existing.ident(), when: format!("inserting key '{}'", name) });
+        }
+    }
+    Ok(map.insert(name.to_string(), tag))
+}
+
+// Insert many elements, reserving space for them at once.
+pub(crate) fn extend_compound<K: Into<String>, P: ToTag, I: IntoIterator<Item = (K, P)>>(map: &mut CompoundMap, elements: I) {
+    let elements = elements.into_iter();
+    map.reserve(elements.size_hint().0);
+    for (name, payload) in elements {
+        map.insert(name.into(), payload.into_tag());
+    }
+}
@@ -0,0 +1,63 @@
+use std::io::{Write, Result};
+use crc32fast::Hasher as Crc32;
+use sha2::{Sha256, Digest as _};
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// Checksums of written data.
+pub struct Digest {
+    /// The CRC32 (IEEE) checksum.
+    pub crc32: u32,
+    /// The SHA-256 hash.
+    pub sha256: [u8; 32],
+}
+
+/// A writer adapter computing checksums of everything written through it.
+///
+/// ## Example
+/// ```
+/// use nbt::{Blob, NBTWrite, DigestWriter};
+///
+/// let mut blob = Blob::new();
+/// blob.insert("name", "Bananrama");
+///
+/// let mut writer = DigestWriter::new(Vec::new());
+/// blob.write(&mut writer).unwrap();
+/// let (bytes, digest) = writer.finish();
+///
+/// assert_eq!(digest.crc32, crc32fast::hash(&bytes));
+/// ```
+pub struct DigestWriter<W: Write> {
+    inner: W,
+    crc32: Crc32,
+    sha256: Sha256,
+}
+
+impl<W: Write> DigestWriter<W> {
+    /// Wrap a writer.
+    pub fn new(inner: W) -> DigestWriter<W> {
+        DigestWriter { inner, crc32: Crc32::new(), sha256: Sha256::new() }
+    }
+
+    /// Unwrap the writer, returning it and the checksums of the data written.
+    pub fn finish(self) -> (W, Digest) {
+        let digest = Digest {
+            crc32: self.crc32.finalize(),
+            sha256: self.sha256.finalize().into(),
+        };
+        (self.inner, digest)
+    }
+}
+
+impl<W: Write> Write for DigestWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.crc32.update(&buf[..written]);
+        self.sha256.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,150 @@
+use crate::blob::Blob;
+use crate::compression::{Compression, compress, decompress};
+use crate::error::{NBTResult, NBTError};
+use crate::front::{NBTRead, NBTWrite};
+use bytes::{Buf, BufMut, BytesMut};
+use std::io::{Cursor, ErrorKind};
+use tokio_util::codec::{Decoder, Encoder};
+
+// The default of `NBTCodec::max_frame_length`, the same as `tokio_util`'s `LengthDelimitedCodec`.
+const DEFAULT_MAX_FRAME_LENGTH: usize = 8 * 1024 * 1024;
+
+/// A `tokio_util` codec framing `Blob`s, for use with `Framed` transports.
+///
+/// Uncompressed frames are plain NBT documents, which are self delimiting.
+/// Compressed frames are prefixed with the length of the compressed data as a big endian `u32`.
+///
+/// Frames longer than `max_frame_length`, 8 MiB by default, fail to decode with
+/// `NBTError::LengthLimitExceeded`, so a peer can't make the codec buffer unbounded amounts of data.
+///
+/// As uncompressed frames carry no length, the decoder attempts to read the whole buffer each time
+/// more data arrives, so a frame arriving in `n` pieces costs `O(n²)` to decode. Prefer compressed
+/// frames, or keep the frame limit low, for peers sending large documents in small writes.
+///
+/// ## Example
+/// ```
+/// use nbt::{Blob, NBTCodec};
+/// use bytes::BytesMut;
+/// use tokio_util::codec::{Decoder, Encoder};
+///
+/// let mut codec = NBTCodec::new();
+/// let mut buffer = BytesMut::new();
+///
+/// let mut blob = Blob::new();
+/// blob.insert("id", 42);
+/// codec.encode(blob, &mut buffer).unwrap();
+///
+/// let decoded = codec.decode(&mut buffer).unwrap().unwrap();
+/// assert_eq!(decoded.get::<i32>("id"), Some(&42));
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature="debug", derive(Debug))]
+pub struct NBTCodec {
+    compression: Option<Compression>,
+    max_frame_length: usize,
+}
+
+impl Default for NBTCodec {
+    fn default() -> Self {
+        NBTCodec::new()
+    }
+}
+
+impl NBTCodec {
+    /// Create a codec for uncompressed frames.
+    pub fn new() -> NBTCodec {
+        NBTCodec { compression: None, max_frame_length: DEFAULT_MAX_FRAME_LENGTH }
+    }
+
+    /// Create a codec for length prefixed, compressed frames.
+    pub fn compressed(compression: Compression) -> NBTCodec {
+        NBTCodec { compression: Some(compression), max_frame_length: DEFAULT_MAX_FRAME_LENGTH }
+    }
+
+    /// Set the length in bytes of the longest frame accepted when decoding. For compressed frames,
+    /// this is the length of the compressed data.
+    ///
+    /// ## Example
+    /// ```
+    /// use nbt::{NBTCodec, NBTError, Compression};
+    /// use bytes::BytesMut;
+    /// use tokio_util::codec::Decoder;
+    ///
+    /// let mut codec = NBTCodec::compressed(Compression::Gzip).max_frame_length(1024);
+    ///
+    /// // The length prefix of a 4 GiB frame is rejected before any of it arrives.
+    /// let mut buffer = BytesMut::from(&[0xFF, 0xFF, 0xFF, 0xFF][..]);
+    /// assert!(matches!(codec.decode(&mut buffer), Err(NBTError::LengthLimitExceeded { limit: 1024, .. })));
+    /// ```
+    pub fn max_frame_length(mut self, length: usize) -> NBTCodec {
+        self.max_frame_length = length;
+        self
+    }
+}
+
+impl Decoder for NBTCodec {
+    type Item = Blob;
+    type Error = NBTError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> NBTResult<Option<Blob>> {
+        match self.compression {
+            None => {
+                if src.is_empty() {
+                    return Ok(None);
+                }
+
+                // Attempt a read, waiting for more data if the frame is incomplete.
+                let mut cursor = Cursor::new(&src[..]);
+                match Blob::read(&mut cursor) {
+                    Ok(blob) => {
+                        let length = cursor.position() as usize;
+                        src.advance(length);
+                        Ok(Some(blob))
+                    }
+                    Err(NBTError::IO { error }) if error.kind() == ErrorKind::UnexpectedEof => {
+                        if src.len() > self.max_frame_length {
+                            return Err(NBTError::LengthLimitExceeded { length: src.len(), limit: self.max_frame_length });
+                        }
+                        Ok(None)
+                    }
+                    Err(e) => Err(e)
+                }
+            }
+            Some(compression) => {
+                if src.len() < 4 {
+                    return Ok(None);
+                }
+
+                let length = u32::from_be_bytes([src[0], src[1], src[2], src[3]]) as usize;
+                if length > self.max_frame_length {
+                    return Err(NBTError::LengthLimitExceeded { length, limit: self.max_frame_length });
+                }
+                if src.len() < 4 + length {
+                    src.reserve(4 + length - src.len());
+                    return Ok(None);
+                }
+
+                src.advance(4);
+                let frame = src.split_to(length);
+                Ok(Some(Blob::from_bytes(decompress(&frame, compression)?)?.with_compression(Some(compression))))
+            }
+        }
+    }
+}
+
+impl Encoder<Blob> for NBTCodec {
+    type Error = NBTError;
+
+    fn encode(&mut self, item: Blob, dst: &mut BytesMut) -> NBTResult<()> {
+        match self.compression {
+            None => item.write_buf(dst),
+            Some(compression) => {
+                let data = compress(&item.bytes()?, compression)?;
+                dst.reserve(4 + data.len());
+                dst.put_u32(data.len() as u32);
+                dst.put_slice(&data);
+                Ok(())
+            }
+        }
+    }
+}
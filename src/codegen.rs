@@ -0,0 +1,223 @@
+//! # Codegen
+//! Generation of Rust struct definitions from sample NBT documents.
+//!
+//! A schema is inferred from one or more `Blob`s, and emitted as structs deriving `Serialize` and
+//! `Deserialize`, with `#[serde(rename)]` attributes wherever a key is not a valid snake case identifier.
+//!
+//! - Keys missing from some samples (or list elements) become `Option`s.
+//! - Nested compounds become their own structs, named after their key.
+//! - When a key is seen with different types, the first type seen is used.
+//!
+//! ## Example
+//! ```
+//! use nbt::{Blob, Tag};
+//! use nbt::codegen::generate;
+//!
+//! let mut blob = Blob::new();
+//! blob.insert("DataVersion", 2586);
+//! blob.insert("LevelName", "world");
+//!
+//! let source = generate("Level", &[blob]);
+//! assert_eq!(source, "\
+//! #[derive(Debug, Serialize, Deserialize)]
+//! pub struct Level {
+//!     #[serde(rename = \"DataVersion\")]
+//!     pub data_version: i32,
+//!     #[serde(rename = \"LevelName\")]
+//!     pub level_name: String,
+//! }
+//! ");
+//! ```
+
+use crate::blob::Blob;
+use crate::tags::{Tag, TagIdent, CompoundMap};
+use std::collections::BTreeMap;
+
+// The inferred shape of a tag.
+enum Shape {
+    Value(TagIdent),
+    // The element shape, if any elements were seen.
+    List(Option<Box<Shape>>),
+    Compound(Schema),
+}
+
+// The inferred fields of a compound, over a number of samples.
+struct Schema {
+    fields: BTreeMap<String, (Shape, usize)>,
+    samples: usize,
+}
+
+impl Schema {
+    fn new() -> Schema {
+        Schema { fields: BTreeMap::new(), samples: 0 }
+    }
+
+    fn observe(&mut self, map: &CompoundMap) {
+        self.samples += 1;
+        for (key, value) in map {
+            match self.fields.get_mut(key) {
+                Some((shape, seen)) => {
+                    shape.observe(value);
+                    *seen += 1;
+                }
+                None => { self.fields.insert(key.clone(), (Shape::of(value), 1)); }
+            }
+        }
+    }
+}
+
+impl Shape {
+    fn of(tag: &Tag) -> Shape {
+        let mut shape = match tag {
+            Tag::List(_) => Shape::List(None),
+            Tag::Compound(_) => Shape::Compound(Schema::new()),
+            tag => Shape::Value(tag.ident()),
+        };
+        shape.observe(tag);
+        shape
+    }
+
+    fn observe(&mut self, tag: &Tag) {
+        match (self, tag) {
+            (Shape::List(element), Tag::List(list)) => for item in list {
+                match element {
+                    Some(shape) => shape.observe(item),
+                    None => *element = Some(Box::new(Shape::of(item))),
+                }
+            },
+            (Shape::Compound(schema), Tag::Compound(map)) => schema.observe(map),
+            // Same value type, or a conflicting type that is ignored.
+            _ => {}
+        }
+    }
+}
+
+/// Generate Rust struct definitions for the documents, with the root struct given the name `name`.
+///
+/// The output expects `serde::{Serialize, Deserialize}` to be in scope.
+pub fn generate(name: &str, samples: &[Blob]) -> String {
+    let mut schema = Schema::new();
+    for blob in samples {
+        schema.observe(&blob.elements);
+    }
+
+    let mut structs = Vec::new();
+    let mut names = Vec::new();
+    emit_struct(&pascal_case(name), &schema, &mut structs, &mut names);
+    structs.join("\n")
+}
+
+// Emit a struct, and any nested structs after it.
+fn emit_struct(name: &str, schema: &Schema, structs: &mut Vec<String>, names: &mut Vec<String>) {
+    let index = structs.len();
+    structs.push(String::new());
+    names.push(name.to_string());
+
+    let mut source = format!("#[derive(Debug, Serialize, Deserialize)]\npub struct {} {{\n", name);
+    for (key, (shape, seen)) in &schema.fields {
+        let field = snake_case(key);
+        let mut kind = emit_type(key, shape, structs, names);
+        if *seen < schema.samples {
+            kind = format!("Option<{}>", kind);
+        }
+
+        if field != *key {
+            source.push_str(&format!("    #[serde(rename = {:?})]\n", key));
+        }
+        source.push_str(&format!("    pub {}: {},\n", field, kind));
+    }
+    source.push_str("}\n");
+    structs[index] = source;
+}
+
+fn emit_type(key: &str, shape: &Shape, structs: &mut Vec<String>, names: &mut Vec<String>) -> String {
+    match shape {
+        Shape::Value(ident) => match ident {
+            TagIdent::TAG_Byte => "i8",
+            TagIdent::TAG_Short => "i16",
+            TagIdent::TAG_Int => "i32",
+            TagIdent::TAG_Long => "i64",
+            TagIdent::TAG_Float => "f32",
+            TagIdent::TAG_Double => "f64",
+            TagIdent::TAG_String => "String",
+            TagIdent::TAG_Byte_Array => "Vec<i8>",
+            TagIdent::TAG_Int_Array => "Vec<i32>",
+            TagIdent::TAG_Long_Array => "Vec<i64>",
+            TagIdent::TAG_End | TagIdent::TAG_List | TagIdent::TAG_Compound => "()",
+        }.to_string(),
+        Shape::List(Some(element)) => format!("Vec<{}>", emit_type(key, element, structs, names)),
+        Shape::List(None) => "Vec<()>".to_string(),
+        Shape::Compound(schema) => {
+            // Pick a unique name for the nested struct.
+            let base = pascal_case(key);
+            let mut name = base.clone();
+            let mut n = 2;
+            while names.contains(&name) {
+                name = format!("{}{}", base, n);
+                n += 1;
+            }
+            emit_struct(&name, schema, structs, names);
+            name
+        }
+    }
+}
+
+// Split a key into lower case words, on separators and camel case boundaries.
+fn words(key: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+    for c in key.chars() {
+        if !c.is_ascii_alphanumeric() {
+            previous = None;
+            continue;
+        }
+        let boundary = match previous {
+            None => true,
+            Some(p) => c.is_ascii_uppercase() && !p.is_ascii_uppercase(),
+        };
+        if boundary {
+            words.push(String::new());
+        }
+        if let Some(word) = words.last_mut() {
+            word.push(c.to_ascii_lowercase());
+        }
+        previous = Some(c);
+    }
+    words
+}
+
+fn snake_case(key: &str) -> String {
+    let mut name = words(key).join("_");
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    if KEYWORDS.contains(&name.as_str()) {
+        name.push('_');
+    }
+    name
+}
+
+fn pascal_case(key: &str) -> String {
+    let mut name: String = words(key).iter().map(|word| {
+        let mut chars = word.chars();
+        match chars.next() {
+            Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+            None => String::new(),
+        }
+    }).collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    if KEYWORDS.contains(&name.as_str()) {
+        name.push('_');
+    }
+    name
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for", "if",
+    "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self",
+    "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while", "async",
+    "await", "dyn", "abstract", "become", "box", "do", "final", "macro", "override", "priv", "typeof",
+    "unsized", "virtual", "yield", "try",
+];
@@ -1,6 +1,102 @@
-use std::collections::HashMap;
-use crate::Tag;
+use crate::tags::{Tag, TagIdent, CompoundMap};
+use crate::util::ToTag;
+use crate::error::NBTResult;
+use crate::blob::{insert_typed, extend_compound};
+use crate::entry::Entry;
+#[cfg(feature="rayon")]
+use rayon::prelude::*;
 
+#[derive(Default)]
 pub struct Compound {
-    pub elements: HashMap<String, Tag>
-}
\ No newline at end of file
+    pub elements: CompoundMap
+}
+
+impl Compound {
+    /// Create an empty compound.
+    pub fn new() -> Compound {
+        Compound::default()
+    }
+
+    /// Create an empty compound with space for at least `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Compound {
+        Compound { elements: CompoundMap::with_capacity_and_hasher(capacity, Default::default()) }
+    }
+
+    /// Reserve space for at least `additional` more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        self.elements.reserve(additional)
+    }
+
+    /// The number of elements the compound can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.elements.capacity()
+    }
+
+    /// Insert an element, failing if it would change the type of an existing element.
+    ///
+    /// Returns `NBTError::InvalidType` with the inserted and existing types if the types differ, in
+    /// which case the compound is left unchanged.
+    pub fn insert_typed<P: ToTag>(&mut self, name: &str, payload: P) -> NBTResult<Option<Tag>> {
+        insert_typed(&mut self.elements, name, payload.into_tag())
+    }
+
+    /// Insert every element of an iterator of names and payloads, replacing any existing elements
+    /// with the same names. Space is reserved once up front, from the iterator's size hint.
+    pub fn extend_from<K: Into<String>, P: ToTag, I: IntoIterator<Item = (K, P)>>(&mut self, elements: I) {
+        extend_compound(&mut self.elements, elements)
+    }
+
+    /// Get the element with the given name, for in-place insertion or modification. See `Entry`.
+    pub fn entry(&mut self, name: &str) -> Entry<'_> {
+        Entry::new(self.elements.entry(name.to_string()))
+    }
+
+    /// The type of the element with the given name, if there is one.
+    pub fn type_of(&self, name: &str) -> Option<TagIdent> {
+        self.elements.get(name).map(Tag::ident)
+    }
+
+    /// Insert a boolean, as a byte of 0 or 1.
+    pub fn insert_bool(&mut self, name: &str, value: bool) -> Option<Tag> {
+        self.elements.insert(name.to_string(), Tag::from_bool(value))
+    }
+
+    /// Get a boolean. Returns `None` if there is no such element, or it is not a byte of 0 or 1.
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        self.elements.get(name)?.as_bool()
+    }
+
+    /// Get a boolean, treating any nonzero byte as true.
+    pub fn get_bool_lenient(&self, name: &str) -> Option<bool> {
+        self.elements.get(name)?.as_bool_lenient()
+    }
+}
+
+#[cfg(feature="rayon")]
+impl Compound {
+    /// Iterate over the elements in parallel.
+    ///
+    /// ```
+    /// use nbt::{Compound, Tag};
+    /// use rayon::prelude::*;
+    ///
+    /// let mut compound = Compound::new();
+    /// for i in 0..100 {
+    ///     compound.elements.insert(i.to_string(), Tag::Int(i));
+    /// }
+    ///
+    /// let sum: i64 = compound.par_iter().filter_map(|(_, tag)| match tag {
+    ///     Tag::Int(x) => Some(*x as i64),
+    ///     _ => None
+    /// }).sum();
+    /// assert_eq!(sum, 4950);
+    /// ```
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = (&String, &Tag)> {
+        self.elements.par_iter()
+    }
+
+    /// Iterate over the elements in parallel, with mutable access to the tags.
+    pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = (&String, &mut Tag)> {
+        self.elements.par_iter_mut()
+    }
+}
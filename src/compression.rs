@@ -0,0 +1,223 @@
+//! # Compression
+//! Compression formats wrapping NBT data, and compressing and decompressing with them.
+//!
+//! ## Example
+//! ```
+//! use nbt::{Blob, NBTWrite, NBTRead};
+//! use nbt::compression::{Compression, compress, decompress};
+//!
+//! let bytes = Blob::new().bytes().unwrap();
+//! let compressed = compress(&bytes, Compression::Gzip).unwrap();
+//! assert_eq!(decompress(&compressed, Compression::Gzip).unwrap(), bytes);
+//! ```
+
+use crate::error::{NBTResult, digest_io};
+#[cfg(not(feature="zstd"))]
+use crate::error::NBTError;
+use crate::blob::Blob;
+use crate::tags::Tag;
+use crate::front::{NBTRead, NBTWrite};
+use std::io::{Read, Write};
+use flate2::read::{GzDecoder, ZlibDecoder, DeflateDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder, DeflateEncoder};
+
+pub use crate::file::Compression;
+#[cfg(feature="zstd")]
+pub use crate::dictionary::ZstdDictionary;
+
+/// Compress data.
+pub fn compress(data: &[u8], compression: Compression) -> NBTResult<Vec<u8>> {
+    match compression {
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+            digest_io(encoder.write_all(data))?;
+            digest_io(encoder.finish())
+        }
+        Compression::Zlib => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            digest_io(encoder.write_all(data))?;
+            digest_io(encoder.finish())
+        }
+        Compression::Deflate => {
+            let mut encoder = DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+            digest_io(encoder.write_all(data))?;
+            digest_io(encoder.finish())
+        }
+        #[cfg(feature="zstd")]
+        Compression::Zstd => digest_io(zstd::stream::encode_all(data, 0)),
+        #[cfg(not(feature="zstd"))]
+        Compression::Zstd => Err(NBTError::UnsupportedCompression { compression }),
+    }
+}
+
+/// Decompress data.
+pub fn decompress(data: &[u8], compression: Compression) -> NBTResult<Vec<u8>> {
+    let mut buffer = Vec::new();
+    match compression {
+        Compression::Gzip => digest_io(GzDecoder::new(data).read_to_end(&mut buffer))?,
+        Compression::Zlib => digest_io(ZlibDecoder::new(data).read_to_end(&mut buffer))?,
+        Compression::Deflate => digest_io(DeflateDecoder::new(data).read_to_end(&mut buffer))?,
+        #[cfg(feature="zstd")]
+        Compression::Zstd => digest_io(zstd::stream::Decoder::new(data).and_then(|mut x| x.read_to_end(&mut buffer)))?,
+        #[cfg(not(feature="zstd"))]
+        Compression::Zstd => return Err(NBTError::UnsupportedCompression { compression }),
+    };
+    Ok(buffer)
+}
+
+// Detect the compression of data from its header.
+// Raw DEFLATE has no header, so it is never detected here. Readers try it when data with no known
+// header fails to read as uncompressed.
+pub(crate) fn detect(data: &[u8]) -> Option<Compression> {
+    match data {
+        // gzip magic
+        [0x1f, 0x8b, ..] => Some(Compression::Gzip),
+        // zlib header, with the check bits in the second byte
+        [0x78, flags, ..] if (0x7800u16 | *flags as u16).is_multiple_of(31) => Some(Compression::Zlib),
+        // zstd frame magic, detected without the feature so reading fails clearly
+        [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Compression::Zstd),
+        _ => None
+    }
+}
+
+// Wrap a reader so that it decompresses what is read through it.
+fn decoder<'a, R: Read + 'a>(reader: R, compression: Compression) -> NBTResult<Box<dyn Read + 'a>> {
+    Ok(match compression {
+        Compression::Gzip => Box::new(GzDecoder::new(reader)),
+        Compression::Zlib => Box::new(ZlibDecoder::new(reader)),
+        Compression::Deflate => Box::new(DeflateDecoder::new(reader)),
+        #[cfg(feature="zstd")]
+        Compression::Zstd => Box::new(digest_io(zstd::stream::Decoder::new(reader))?),
+        #[cfg(not(feature="zstd"))]
+        Compression::Zstd => return Err(NBTError::UnsupportedCompression { compression }),
+    })
+}
+
+// Keeps a copy of the bytes read through it, so they can be read again.
+struct Recorder<R> {
+    reader: R,
+    recorded: Vec<u8>,
+}
+
+impl<R: Read> Read for Recorder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.recorded.extend_from_slice(&buf[..read]);
+        Ok(read)
+    }
+}
+
+// Read data of unknown compression, detecting it from the first bytes.
+// The bytes sniffed from the header are put back in front of the rest of the reader.
+// Read a value, detecting its compression, and return it along with the compression found.
+fn read_auto<T: NBTRead, R: Read>(mut reader: R) -> NBTResult<(T, Option<Compression>)> {
+    let mut header = [0u8; 4];
+    let mut length = 0;
+    while length < header.len() {
+        match digest_io(reader.read(&mut header[length..]))? {
+            0 => break,
+            read => length += read,
+        }
+    }
+    let reader = (&header[..length]).chain(reader);
+    match detect(&header[..length]) {
+        Some(compression) => Ok((T::read(&mut decoder(reader, compression)?)?, Some(compression))),
+        None => {
+            // Data that fails to read as uncompressed is tried again as raw DEFLATE, keeping the
+            // first error if that fails too.
+            let mut recorder = Recorder { reader, recorded: Vec::new() };
+            match T::read(&mut recorder) {
+                Ok(value) => Ok((value, None)),
+                Err(error) => {
+                    let Recorder { reader, recorded } = recorder;
+                    match T::read(&mut DeflateDecoder::new(recorded.as_slice().chain(reader))) {
+                        Ok(value) => Ok((value, Some(Compression::Deflate))),
+                        Err(_) => Err(error),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Blob {
+    /// Read a blob, detecting whether it is gzip, zlib, zstd or raw DEFLATE compressed, or uncompressed.
+    ///
+    /// Only the first few bytes are read ahead, so this works on sockets as well as files.
+    /// Raw DEFLATE has no header, so data without a known header that fails to read as uncompressed
+    /// is read again as raw DEFLATE. The bytes read are kept until then.
+    /// The detected compression is recorded in `source`, for `save_same_format`.
+    ///
+    /// ## Example
+    /// ```
+    /// use nbt::{Blob, Compression, NBTWrite};
+    ///
+    /// let mut blob = Blob::new();
+    /// blob.insert("Score", 42);
+    ///
+    /// let mut compressed = Vec::new();
+    /// blob.write_gzip(&mut compressed).unwrap();
+    ///
+    /// for data in [compressed, blob.bytes().unwrap()] {
+    ///     assert_eq!(Blob::read_auto(&data[..]).unwrap().get::<i32>("Score"), Some(&42));
+    /// }
+    /// ```
+    pub fn read_auto<R: Read>(reader: R) -> NBTResult<Blob> {
+        let (blob, compression): (Blob, _) = read_auto(reader)?;
+        Ok(blob.with_compression(compression))
+    }
+
+    /// Read a compressed blob, decompressing it as it is read. The compression is recorded in `source`.
+    ///
+    /// ## Example
+    /// ```
+    /// use nbt::{Blob, Compression};
+    ///
+    /// let mut blob = Blob::create("Data");
+    /// blob.insert("LevelName", "New World");
+    ///
+    /// let mut buffer = Vec::new();
+    /// blob.write_compressed(&mut buffer, Compression::Gzip).unwrap();
+    /// assert_eq!(&buffer[..2], &[0x1f, 0x8b]);
+    ///
+    /// let read = Blob::read_compressed(&buffer[..], Compression::Gzip).unwrap();
+    /// assert_eq!(read.get::<String>("LevelName").unwrap(), "New World");
+    /// ```
+    pub fn read_compressed<R: Read>(reader: R, compression: Compression) -> NBTResult<Blob> {
+        Ok(Blob::read(&mut decoder(reader, compression)?)?.with_compression(Some(compression)))
+    }
+
+    /// Write the blob compressed.
+    pub fn write_compressed<W: Write + ?Sized>(&self, writer: &mut W, compression: Compression) -> NBTResult<()> {
+        digest_io(writer.write_all(&compress(&self.bytes()?, compression)?))
+    }
+
+    /// Read a gzip compressed blob, such as `level.dat`.
+    pub fn read_gzip<R: Read>(reader: R) -> NBTResult<Blob> {
+        Blob::read_compressed(reader, Compression::Gzip)
+    }
+
+    /// Write the blob gzip compressed.
+    pub fn write_gzip<W: Write + ?Sized>(&self, writer: &mut W) -> NBTResult<()> {
+        self.write_compressed(writer, Compression::Gzip)
+    }
+
+    /// Read a zlib compressed blob, such as a region chunk.
+    pub fn read_zlib<R: Read>(reader: R) -> NBTResult<Blob> {
+        Blob::read_compressed(reader, Compression::Zlib)
+    }
+
+    /// Write the blob zlib compressed.
+    pub fn write_zlib<W: Write + ?Sized>(&self, writer: &mut W) -> NBTResult<()> {
+        self.write_compressed(writer, Compression::Zlib)
+    }
+}
+
+impl Tag {
+    /// Read a tag, detecting whether it is gzip, zlib, zstd or raw DEFLATE compressed, or uncompressed.
+    ///
+    /// See `Blob::read_auto`.
+    pub fn read_auto<R: Read>(reader: R) -> NBTResult<Tag> {
+        read_auto(reader).map(|(tag, _)| tag)
+    }
+}
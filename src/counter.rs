@@ -0,0 +1,56 @@
+use std::io::{Read, Write, Result};
+
+/// A reader or writer adapter counting the bytes passed through it.
+///
+/// ## Example
+/// ```
+/// use nbt::{Blob, NBTRead, ByteCounter};
+///
+/// // A blob followed by the rest of a packet.
+/// let data = vec![10, 0, 0, 0, 0xFF, 0xFF];
+///
+/// let mut reader = ByteCounter::new(&data[..]);
+/// let blob = Blob::read(&mut reader).unwrap();
+/// assert_eq!(reader.count(), 4);
+/// ```
+pub struct ByteCounter<T> {
+    inner: T,
+    count: usize,
+}
+
+impl<T> ByteCounter<T> {
+    /// Wrap a reader or writer.
+    pub fn new(inner: T) -> ByteCounter<T> {
+        ByteCounter { inner, count: 0 }
+    }
+
+    /// The number of bytes read or written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Unwrap the reader or writer.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for ByteCounter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count += read;
+        Ok(read)
+    }
+}
+
+impl<W: Write> Write for ByteCounter<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
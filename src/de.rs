@@ -2,13 +2,14 @@ use crate::{Tag, TagIdent};
 use serde::Deserializer;
 use serde::de::{Visitor, SeqAccess, DeserializeSeed, MapAccess, EnumAccess, VariantAccess};
 use crate::error::NBTError;
-use std::collections::HashMap;
+use crate::tags::CompoundMap;
+use crate::options::SerdeOptions;
 
-pub struct NBTDeserializer(Option<Tag>);
+pub struct NBTDeserializer<'a>(Option<Tag>, &'a SerdeOptions);
 
-impl NBTDeserializer {
-    pub fn new(s: Option<Tag>) -> Self { Self(s) }
-    pub fn some(t: Tag) -> Self { Self(Some(t)) }
+impl<'a> NBTDeserializer<'a> {
+    pub fn new(s: Option<Tag>, options: &'a SerdeOptions) -> Self { Self(s, options) }
+    pub fn some(t: Tag, options: &'a SerdeOptions) -> Self { Self(Some(t), options) }
 }
 
 macro_rules! basic_type {
@@ -44,20 +45,32 @@ macro_rules! unsigned_type {
                 })
             },
             None => Err(NBTError::NoData { when: $name.to_string() })
-        };
+        }
     };
 }
 
 #[cfg(not(feature="serde_unsigned"))]
 macro_rules! unsigned_type {
     ($value: ident, $visitor: ident, $tag: ident, $ident: ident, $func: ident, $cast: ty, $name: expr) => {
-        return Err(NBTError::UnserializableType { type_name: $name.to_string() });
+        return Err(NBTError::UnserializableType { type_name: $name.to_string() })
     }
 }
 
 
+// The elements of a list, or of an array as tags, for deserializing a sequence from either.
+fn into_list(tag: Tag) -> Result<Vec<Tag>, Tag> {
+    match tag {
+        Tag::List(list) => Ok(list),
+        Tag::ByteArray(list) => Ok(list.into_iter().map(Tag::Byte).collect()),
+        Tag::UnsignedByteArray(list) => Ok(list.into_iter().map(|x| Tag::Byte(x as i8)).collect()),
+        Tag::IntArray(list) => Ok(list.into_iter().map(Tag::Int).collect()),
+        Tag::LongArray(list) => Ok(list.into_iter().map(Tag::Long).collect()),
+        tag => Err(tag)
+    }
+}
+
 #[allow(unused_variables)]
-impl<'de> Deserializer<'de> for NBTDeserializer {
+impl<'a, 'de> Deserializer<'de> for NBTDeserializer<'a> {
     type Error = NBTError;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
@@ -70,12 +83,13 @@ impl<'de> Deserializer<'de> for NBTDeserializer {
                 Tag::Long(v) => visitor.visit_i64(v),
                 Tag::Float(v) => visitor.visit_f32(v),
                 Tag::Double(v) => visitor.visit_f64(v),
-                Tag::ByteArray(list) => visitor.visit_seq(NBTSeqAccess::new(list.into_iter().map(|x| Tag::Byte(x)).collect())),
+                Tag::ByteArray(list) => visitor.visit_seq(NBTSeqAccess::new(list.into_iter().map(Tag::Byte).collect(), self.1)),
+                Tag::UnsignedByteArray(list) => visitor.visit_seq(NBTSeqAccess::new(list.into_iter().map(|x| Tag::Byte(x as i8)).collect(), self.1)),
                 Tag::String(v) => visitor.visit_string(v),
-                Tag::List(array) => visitor.visit_seq(NBTSeqAccess::new(array)),
-                Tag::Compound(compound) => visitor.visit_map(NBTMapAccess::new(compound)),
-                Tag::IntArray(list) => visitor.visit_seq(NBTSeqAccess::new(list.into_iter().map(|x| Tag::Int(x)).collect())),
-                Tag::LongArray(list) => visitor.visit_seq(NBTSeqAccess::new(list.into_iter().map(|x| Tag::Long(x)).collect())),
+                Tag::List(array) => visitor.visit_seq(NBTSeqAccess::new(array, self.1)),
+                Tag::Compound(compound) => visitor.visit_map(NBTMapAccess::new(compound, self.1)),
+                Tag::IntArray(list) => visitor.visit_seq(NBTSeqAccess::new(list.into_iter().map(Tag::Int).collect(), self.1)),
+                Tag::LongArray(list) => visitor.visit_seq(NBTSeqAccess::new(list.into_iter().map(Tag::Long).collect(), self.1)),
             }
             None => visitor.visit_none()
         }
@@ -131,7 +145,14 @@ impl<'de> Deserializer<'de> for NBTDeserializer {
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
         V: Visitor<'de> {
-        unsigned_type!(self, visitor, Long, TAG_Long, visit_u64, u64, "u64")
+        #[cfg(feature="serde_unsigned")]
+        return match self.0 {
+            Some(tag) => visitor.visit_u64(self.1.unsigned_overflow.decode(tag)?),
+            None => Err(NBTError::NoData { when: "u64".to_string() })
+        };
+
+        #[cfg(not(feature="serde_unsigned"))]
+        return Err(NBTError::UnserializableType { type_name: "u64".to_string() });
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
@@ -148,10 +169,10 @@ impl<'de> Deserializer<'de> for NBTDeserializer {
         V: Visitor<'de> {
         match self.0 {
             Some(tag) => if let Tag::String(x) = tag {
-                if x.len() == 1 {
-                    visitor.visit_char(x.chars().nth(0).unwrap())
-                } else {
-                    Err(NBTError::InvalidChar)
+                let mut chars = x.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => visitor.visit_char(c),
+                    _ => Err(NBTError::InvalidChar)
                 }
             } else {
                 Err(NBTError::InvalidType {
@@ -198,19 +219,29 @@ impl<'de> Deserializer<'de> for NBTDeserializer {
 
     fn deserialize_bytes<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
         V: Visitor<'de> {
-        return Err(NBTError::UnserializableType {type_name: "bytes".to_string()})
+        self.deserialize_byte_buf(visitor)
     }
 
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
         V: Visitor<'de> {
-        return Err(NBTError::UnserializableType {type_name: "bytes".to_string()})
+        match self.0 {
+            Some(tag) => match tag.ident() {
+                TagIdent::TAG_Byte_Array => visitor.visit_byte_buf(tag.into_u8_vec().unwrap_or_default()),
+                found => Err(NBTError::InvalidType {
+                    found,
+                    expecting: TagIdent::TAG_Byte_Array,
+                    when: "bytes".to_string()
+                })
+            },
+            None => Err(NBTError::NoData { when: "bytes".to_string() })
+        }
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
         V: Visitor<'de> {
         match self.0 {
             None => visitor.visit_none(),
-            Some(t) => visitor.visit_some(Self::some(t))
+            Some(t) => visitor.visit_some(Self::some(t, self.1))
         }
     }
 
@@ -235,7 +266,7 @@ impl<'de> Deserializer<'de> for NBTDeserializer {
         V: Visitor<'de> {
         match self.0 {
             None => Err(NBTError::NoData { when: "newtype_struct".to_string() }),
-            Some(t) => visitor.visit_newtype_struct(NBTDeserializer::some(t))
+            Some(t) => visitor.visit_newtype_struct(NBTDeserializer::some(t, self.1))
         }
     }
 
@@ -243,10 +274,9 @@ impl<'de> Deserializer<'de> for NBTDeserializer {
         V: Visitor<'de> {
         match self.0 {
             None => Err(NBTError::NoData { when: "seq".to_string() }),
-            Some(data) => if let Tag::List(list) = data {
-                visitor.visit_seq(NBTSeqAccess::new(list))
-            } else {
-                Err(NBTError::InvalidType {
+            Some(data) => match into_list(data) {
+                Ok(list) => visitor.visit_seq(NBTSeqAccess::new(list, self.1)),
+                Err(data) => Err(NBTError::InvalidType {
                     found: data.ident(),
                     expecting: TagIdent::TAG_List,
                     when: "seq".to_string()
@@ -259,10 +289,9 @@ impl<'de> Deserializer<'de> for NBTDeserializer {
         V: Visitor<'de> {
         match self.0 {
             None => Err(NBTError::NoData { when: "tuple".to_string() }),
-            Some(data) => if let Tag::List(list) = data {
-                visitor.visit_seq(NBTSeqAccess::new(list))
-            } else {
-                Err(NBTError::InvalidType {
+            Some(data) => match into_list(data) {
+                Ok(list) => visitor.visit_seq(NBTSeqAccess::new(list, self.1)),
+                Err(data) => Err(NBTError::InvalidType {
                     found: data.ident(),
                     expecting: TagIdent::TAG_List,
                     when: "tuple".to_string()
@@ -275,10 +304,9 @@ impl<'de> Deserializer<'de> for NBTDeserializer {
         V: Visitor<'de> {
         match self.0 {
             None => Err(NBTError::NoData { when: "tuple".to_string() }),
-            Some(data) => if let Tag::List(list) = data {
-                visitor.visit_seq(NBTSeqAccess::new(list))
-            } else {
-                Err(NBTError::InvalidType {
+            Some(data) => match into_list(data) {
+                Ok(list) => visitor.visit_seq(NBTSeqAccess::new(list, self.1)),
+                Err(data) => Err(NBTError::InvalidType {
                     found: data.ident(),
                     expecting: TagIdent::TAG_List,
                     when: "tuple".to_string()
@@ -292,7 +320,7 @@ impl<'de> Deserializer<'de> for NBTDeserializer {
         match self.0 {
             None => Err(NBTError::NoData { when: "tag".to_string() }),
             Some(data) => if let Tag::Compound(comp) = data {
-                visitor.visit_map(NBTMapAccess::new(comp))
+                visitor.visit_map(NBTMapAccess::new(comp, self.1))
             } else {
                 Err(NBTError::InvalidType {
                     found: data.ident(),
@@ -308,7 +336,7 @@ impl<'de> Deserializer<'de> for NBTDeserializer {
         match self.0 {
             None => Err(NBTError::NoData { when: "struct".to_string() }),
             Some(data) => if let Tag::Compound(comp) = data {
-                visitor.visit_map(NBTMapAccess::new(comp))
+                visitor.visit_map(NBTMapAccess::new(comp, self.1))
             } else {
                 Err(NBTError::InvalidType {
                     found: data.ident(),
@@ -321,7 +349,7 @@ impl<'de> Deserializer<'de> for NBTDeserializer {
 
     fn deserialize_enum<V>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
         V: Visitor<'de> {
-        visitor.visit_enum(NBTEnumAccess::new(self.0))
+        visitor.visit_enum(NBTEnumAccess::new(self.0, self.1))
     }
 
     fn deserialize_identifier<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
@@ -342,23 +370,26 @@ impl<'de> Deserializer<'de> for NBTDeserializer {
 
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
         V: Visitor<'de> {
-        unimplemented!()
+        // The value has already been read, so there is nothing to skip.
+        visitor.visit_unit()
     }
 }
 
-pub struct NBTSeqAccess {
-    data: Vec<Tag>
+pub struct NBTSeqAccess<'a> {
+    data: Vec<Tag>,
+    options: &'a SerdeOptions,
 }
 
-impl NBTSeqAccess {
-    pub fn new(s: Vec<Tag>) -> Self {
+impl<'a> NBTSeqAccess<'a> {
+    pub fn new(s: Vec<Tag>, options: &'a SerdeOptions) -> Self {
         Self {
-            data: s
+            data: s,
+            options,
         }
     }
 }
 
-impl<'de> SeqAccess<'de> for NBTSeqAccess {
+impl<'a, 'de> SeqAccess<'de> for NBTSeqAccess<'a> {
     type Error = NBTError;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<<T as DeserializeSeed<'de>>::Value>, Self::Error> where
@@ -366,27 +397,29 @@ impl<'de> SeqAccess<'de> for NBTSeqAccess {
         if self.data.is_empty() {
             Ok(None)
         } else {
-            Ok(Some(seed.deserialize(NBTDeserializer::some(self.data.remove(0)))?))
+            Ok(Some(seed.deserialize(NBTDeserializer::some(self.data.remove(0), self.options))?))
         }
     }
 }
 
-pub struct NBTMapAccess {
+pub struct NBTMapAccess<'a> {
     data: Vec<(String, Tag)>,
-    value: Option<Tag>
+    value: Option<Tag>,
+    options: &'a SerdeOptions,
 }
 
-impl NBTMapAccess {
-    pub fn new(s: HashMap<String, Tag>) -> Self {
+impl<'a> NBTMapAccess<'a> {
+    pub fn new(s: CompoundMap, options: &'a SerdeOptions) -> Self {
         Self {
             data: s.into_iter().collect(),
-            value: None
+            value: None,
+            options,
         }
     }
 }
 
 
-impl<'de> MapAccess<'de> for NBTMapAccess {
+impl<'a, 'de> MapAccess<'de> for NBTMapAccess<'a> {
     type Error = NBTError;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<<K as DeserializeSeed<'de>>::Value>, Self::Error> where
@@ -396,38 +429,45 @@ impl<'de> MapAccess<'de> for NBTMapAccess {
         } else {
             let (key, value) = self.data.remove(0);
             self.value = Some(value);
-            Ok(Some(seed.deserialize(NBTDeserializer::some(Tag::String(key)))?))
+            Ok(Some(seed.deserialize(NBTDeserializer::some(Tag::String(key), self.options))?))
         }
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<<V as DeserializeSeed<'de>>::Value, Self::Error> where
         V: DeserializeSeed<'de> {
-        seed.deserialize(NBTDeserializer::new(std::mem::replace(&mut self.value, None)))
+        seed.deserialize(NBTDeserializer::new(self.value.take(), self.options))
     }
 }
 
-pub struct NBTEnumAccess {
-    content: Option<Tag>
+pub struct NBTEnumAccess<'a> {
+    content: Option<Tag>,
+    options: &'a SerdeOptions,
 }
 
-impl NBTEnumAccess {
-    pub fn new(s: Option<Tag>) -> Self {
-        Self { content: s }
+impl<'a> NBTEnumAccess<'a> {
+    pub fn new(s: Option<Tag>, options: &'a SerdeOptions) -> Self {
+        Self { content: s, options }
     }
 }
 
-impl<'de> EnumAccess<'de> for NBTEnumAccess {
+impl<'a, 'de> EnumAccess<'de> for NBTEnumAccess<'a> {
     type Error = NBTError;
-    type Variant = NBTEnumAccess;
+    type Variant = NBTEnumAccess<'a>;
 
     fn variant_seed<V>(self, seed: V) -> Result<(<V as DeserializeSeed<'de>>::Value, Self::Variant), Self::Error> where
         V: DeserializeSeed<'de> {
 
         match self.content {
+            // Unit variants are serialized as their name alone.
+            // Unknown names are left to the identifier, so they reach a `#[serde(other)]` variant.
+            Some(Tag::String(name)) => {
+                let seed = seed.deserialize(NBTDeserializer::some(Tag::String(name), self.options))?;
+                Ok((seed, NBTEnumAccess::new(None, self.options)))
+            }
             Some(tag) => if let Tag::Compound(map) = tag {
                 if let Some((key, value)) = map.into_iter().nth(0) {
-                    let seed = seed.deserialize(NBTDeserializer::some(Tag::String(key)))?;
-                    let access = NBTEnumAccess::new(Some(value));
+                    let seed = seed.deserialize(NBTDeserializer::some(Tag::String(key), self.options))?;
+                    let access = NBTEnumAccess::new(Some(value), self.options);
                     Ok((seed, access))
                 } else {
                     Err(NBTError::InvalidType {
@@ -448,7 +488,7 @@ impl<'de> EnumAccess<'de> for NBTEnumAccess {
     }
 }
 
-impl<'de> VariantAccess<'de> for NBTEnumAccess {
+impl<'a, 'de> VariantAccess<'de> for NBTEnumAccess<'a> {
     type Error = NBTError;
 
     fn unit_variant(self) -> Result<(), Self::Error> {
@@ -457,16 +497,16 @@ impl<'de> VariantAccess<'de> for NBTEnumAccess {
 
     fn newtype_variant_seed<T>(self, seed: T) -> Result<<T as DeserializeSeed<'de>>::Value, Self::Error> where
         T: DeserializeSeed<'de> {
-        seed.deserialize(NBTDeserializer::new(self.content))
+        seed.deserialize(NBTDeserializer::new(self.content, self.options))
     }
 
     fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
         V: Visitor<'de> {
-        NBTDeserializer::new(self.content).deserialize_tuple(len, visitor)
+        NBTDeserializer::new(self.content, self.options).deserialize_tuple(len, visitor)
     }
 
     fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
         V: Visitor<'de> {
-        NBTDeserializer::new(self.content).deserialize_struct("", fields, visitor)
+        NBTDeserializer::new(self.content, self.options).deserialize_struct("", fields, visitor)
     }
 }
\ No newline at end of file
@@ -0,0 +1,595 @@
+// Deserializing straight from the bytes of a Java Edition document, without building tags first.
+
+use crate::tags::TagIdent;
+use crate::error::{NBTResult, NBTError, digest_io};
+use crate::decode::read_ident;
+use crate::flavor::{NbtFlavor, JavaFlavor};
+use crate::options::SerdeOptions;
+use crate::validate::{Validator, ValidateLimits};
+use crate::de::NBTDeserializer;
+use serde::{Deserialize, Deserializer};
+use serde::de::{Visitor, SeqAccess, DeserializeSeed, MapAccess, EnumAccess, VariantAccess};
+use std::borrow::Cow;
+use byteorder::ReadBytesExt;
+
+/// Decode a Java Edition document held in memory into a Serde deserializable value.
+///
+/// The value is read straight from the bytes, without building a `Blob` first, so strings and
+/// byte arrays may borrow from the buffer: fields of type `&str`, `Cow<str>` or `&[u8]` point into
+/// `bytes` rather than being copied. Strings holding the nul or supplementary characters that modified
+/// UTF-8 encodes differently are decoded into owned strings instead, so deserializing those into
+/// `&str` fails, while `Cow<str>` and `String` always work.
+///
+/// Apart from borrowing, values decode as they would through `Blob::from_bytes` and `decode`. The
+/// root name and any bytes after the document are ignored. Lists and compounds nested more than 512
+/// deep fail with `NBTError::DepthLimitExceeded`, so hostile input can't overflow the stack.
+///
+/// ### Example
+/// ```
+/// use nbt::{from_slice, nbt, NBTWrite};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, PartialEq, Debug)]
+/// struct Player<'a> {
+///     name: &'a str,
+///     #[serde(rename = "Health")]
+///     health: f32,
+/// }
+///
+/// let bytes = nbt!(Blob { "name": "Dinnerbone", "Health": 20.0f32 }).bytes().unwrap();
+///
+/// let player: Player = from_slice(&bytes).unwrap();
+/// assert_eq!(player, Player { name: "Dinnerbone", health: 20.0 });
+/// ```
+pub fn from_slice<'de, T: Deserialize<'de>>(bytes: &'de [u8]) -> NBTResult<T> {
+    from_slice_with(bytes, &SerdeOptions::default())
+}
+
+/// Decode a Java Edition document held in memory into a Serde deserializable value with the given
+/// options. See `from_slice`.
+pub fn from_slice_with<'de, T: Deserialize<'de>>(bytes: &'de [u8], options: &SerdeOptions) -> NBTResult<T> {
+    let mut input = bytes;
+    match read_ident(&mut input)? {
+        TagIdent::TAG_Compound => {}
+        found => return Err(NBTError::InvalidImplicit { found }),
+    }
+    read_str(&mut input)?;
+    T::deserialize(SliceDeserializer { input: &mut input, ident: TagIdent::TAG_Compound, depth: 0, options })
+}
+
+// Take the next `length` bytes of the input.
+fn take<'de>(input: &mut &'de [u8], length: usize) -> NBTResult<&'de [u8]> {
+    if input.len() < length {
+        return digest_io(Err(std::io::ErrorKind::UnexpectedEof.into()));
+    }
+    let (bytes, rest) = input.split_at(length);
+    *input = rest;
+    Ok(bytes)
+}
+
+// Read a string, borrowing it from the input unless modified UTF-8 encodes it differently.
+fn read_str<'de>(input: &mut &'de [u8]) -> NBTResult<Cow<'de, str>> {
+    let length = digest_io(JavaFlavor::read_string_length(input))?;
+    match cesu8::from_java_cesu8(take(input, length)?) {
+        Ok(string) => Ok(string),
+        Err(_) => Err(NBTError::StringError),
+    }
+}
+
+// Skip over a payload, checking it as it goes.
+fn skip(input: &mut &[u8], ident: &TagIdent) -> NBTResult<()> {
+    Validator::new(&ValidateLimits::default()).payload::<JavaFlavor, &[u8]>(input, ident)
+}
+
+// Skip the remaining elements of a compound, and its end.
+fn skip_entries(input: &mut &[u8]) -> NBTResult<()> {
+    loop {
+        let ident = read_ident(input)?;
+        if ident == TagIdent::TAG_End {
+            return Ok(());
+        }
+        read_str(input)?;
+        skip(input, &ident)?;
+    }
+}
+
+// The item type of a list, or of an array as a list, for deserializing a sequence from either.
+fn list_item(ident: &TagIdent) -> Option<TagIdent> {
+    match ident {
+        TagIdent::TAG_Byte_Array => Some(TagIdent::TAG_Byte),
+        TagIdent::TAG_Int_Array => Some(TagIdent::TAG_Int),
+        TagIdent::TAG_Long_Array => Some(TagIdent::TAG_Long),
+        _ => None,
+    }
+}
+
+// How deeply lists and compounds may nest, as with `ValidateLimits`, so hostile input can't
+// overflow the stack.
+const MAX_DEPTH: usize = 512;
+
+// Deserializes the payload of a tag, reading it from the input.
+struct SliceDeserializer<'a, 'de> {
+    input: &'a mut &'de [u8],
+    ident: TagIdent,
+    // The number of lists and compounds the payload is within.
+    depth: usize,
+    options: &'a SerdeOptions,
+}
+
+macro_rules! basic_type {
+    ($value: ident, $visitor: ident, $ident: ident, $read: expr, $func: ident, $name: expr) => {
+        return match $value.ident {
+            TagIdent::$ident => $visitor.$func(digest_io($read($value.input))?),
+            found => Err(NBTError::InvalidType {
+                found,
+                expecting: TagIdent::$ident,
+                when: $name.to_string()
+            })
+        };
+    };
+}
+
+#[cfg(feature="serde_unsigned")]
+macro_rules! unsigned_type {
+    ($value: ident, $visitor: ident, $ident: ident, $read: expr, $func: ident, $cast: ty, $name: expr) => {
+        return match $value.ident {
+            TagIdent::$ident => $visitor.$func(digest_io($read($value.input))? as $cast),
+            found => Err(NBTError::InvalidType {
+                found,
+                expecting: TagIdent::$ident,
+                when: $name.to_string()
+            })
+        }
+    };
+}
+
+#[cfg(not(feature="serde_unsigned"))]
+macro_rules! unsigned_type {
+    ($value: ident, $visitor: ident, $ident: ident, $read: expr, $func: ident, $cast: ty, $name: expr) => {
+        return Err(NBTError::UnserializableType { type_name: $name.to_string() })
+    };
+}
+
+impl<'a, 'de> SliceDeserializer<'a, 'de> {
+    // The depth of the tags within this list or compound.
+    fn descend(&self) -> NBTResult<usize> {
+        if self.depth == MAX_DEPTH {
+            return Err(NBTError::DepthLimitExceeded { limit: MAX_DEPTH });
+        }
+        Ok(self.depth + 1)
+    }
+
+    fn string<V: Visitor<'de>>(self, visitor: V, when: &str) -> NBTResult<V::Value> {
+        match self.ident {
+            TagIdent::TAG_String => match read_str(self.input)? {
+                Cow::Borrowed(string) => visitor.visit_borrowed_str(string),
+                Cow::Owned(string) => visitor.visit_string(string),
+            },
+            found => Err(NBTError::InvalidType { found, expecting: TagIdent::TAG_String, when: when.to_string() })
+        }
+    }
+
+    fn seq<V: Visitor<'de>>(self, visitor: V, when: &str) -> NBTResult<V::Value> {
+        let depth = self.descend()?;
+        let item = match self.ident {
+            TagIdent::TAG_List => read_ident(self.input)?,
+            ref ident => match list_item(ident) {
+                Some(item) => item,
+                None => return Err(NBTError::InvalidType { found: self.ident, expecting: TagIdent::TAG_List, when: when.to_string() })
+            }
+        };
+        let remaining = digest_io(JavaFlavor::read_length(self.input))?;
+        let mut access = SliceSeqAccess { input: self.input, item, remaining, depth, options: self.options };
+        let value = visitor.visit_seq(&mut access)?;
+        access.finish()?;
+        Ok(value)
+    }
+
+    fn map<V: Visitor<'de>>(self, visitor: V, when: &str) -> NBTResult<V::Value> {
+        match self.ident {
+            TagIdent::TAG_Compound => {
+                let depth = self.descend()?;
+                let mut access = SliceMapAccess { input: self.input, value: None, done: false, depth, options: self.options };
+                let value = visitor.visit_map(&mut access)?;
+                access.finish()?;
+                Ok(value)
+            }
+            found => Err(NBTError::InvalidType { found, expecting: TagIdent::TAG_Compound, when: when.to_string() })
+        }
+    }
+}
+
+#[allow(unused_variables)]
+impl<'a, 'de> Deserializer<'de> for SliceDeserializer<'a, 'de> {
+    type Error = NBTError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        match self.ident {
+            TagIdent::TAG_Byte => visitor.visit_i8(digest_io(self.input.read_i8())?),
+            TagIdent::TAG_Short => visitor.visit_i16(digest_io(JavaFlavor::read_short(self.input))?),
+            TagIdent::TAG_Int => visitor.visit_i32(digest_io(JavaFlavor::read_int(self.input))?),
+            TagIdent::TAG_Long => visitor.visit_i64(digest_io(JavaFlavor::read_long(self.input))?),
+            TagIdent::TAG_Float => visitor.visit_f32(digest_io(JavaFlavor::read_float(self.input))?),
+            TagIdent::TAG_Double => visitor.visit_f64(digest_io(JavaFlavor::read_double(self.input))?),
+            TagIdent::TAG_String => self.string(visitor, "any"),
+            TagIdent::TAG_Compound => self.map(visitor, "any"),
+            TagIdent::TAG_End => Err(NBTError::UnexpectedEndTag),
+            _ => self.seq(visitor, "any"),
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        #[cfg(not(feature="serde_boolean"))]
+            return Err(NBTError::UnserializableType { type_name: "bool".to_string() });
+
+        #[cfg(feature="serde_boolean")]
+        match self.ident {
+            TagIdent::TAG_Byte => visitor.visit_bool(digest_io(self.input.read_i8())? == 0x01i8),
+            found => Err(NBTError::InvalidType {
+                found,
+                expecting: TagIdent::TAG_Byte,
+                when: "bool".to_string()
+            })
+        }
+    }
+
+    fn deserialize_i8<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where V: Visitor<'de> {
+        basic_type!(self, visitor, TAG_Byte, ReadBytesExt::read_i8, visit_i8, "i8");
+    }
+
+    fn deserialize_i16<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where V: Visitor<'de> {
+        basic_type!(self, visitor, TAG_Short, JavaFlavor::read_short, visit_i16, "i16");
+    }
+
+    fn deserialize_i32<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where V: Visitor<'de> {
+        basic_type!(self, visitor, TAG_Int, JavaFlavor::read_int, visit_i32, "i32");
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where V: Visitor<'de> {
+        basic_type!(self, visitor, TAG_Long, JavaFlavor::read_long, visit_i64, "i64");
+    }
+
+    fn deserialize_u8<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where V: Visitor<'de> {
+        unsigned_type!(self, visitor, TAG_Byte, ReadBytesExt::read_i8, visit_u8, u8, "u8")
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where V: Visitor<'de> {
+        unsigned_type!(self, visitor, TAG_Short, JavaFlavor::read_short, visit_u16, u16, "u16")
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where V: Visitor<'de> {
+        unsigned_type!(self, visitor, TAG_Int, JavaFlavor::read_int, visit_u32, u32, "u32")
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        // Build the tag, as how it is read depends on the overflow option.
+        #[cfg(feature="serde_unsigned")]
+        return {
+            let options = crate::options::ReadOptions::default();
+            let tag = crate::decode::read_tag::<JavaFlavor, &[u8]>(self.input, &self.ident, &mut crate::decode::ReadContext::new(&options, None))?;
+            visitor.visit_u64(self.options.unsigned_overflow.decode(tag)?)
+        };
+
+        #[cfg(not(feature="serde_unsigned"))]
+        return Err(NBTError::UnserializableType { type_name: "u64".to_string() });
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        basic_type!(self, visitor, TAG_Float, JavaFlavor::read_float, visit_f32, "f32");
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        basic_type!(self, visitor, TAG_Double, JavaFlavor::read_double, visit_f64, "f64");
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        match self.ident {
+            TagIdent::TAG_String => {
+                let string = read_str(self.input)?;
+                let mut chars = string.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => visitor.visit_char(c),
+                    _ => Err(NBTError::InvalidChar)
+                }
+            }
+            found => Err(NBTError::InvalidType {
+                found,
+                expecting: TagIdent::TAG_String,
+                when: "char".to_string()
+            })
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        self.string(visitor, "str")
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        self.string(visitor, "string")
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        match self.ident {
+            TagIdent::TAG_Byte_Array => {
+                let length = digest_io(JavaFlavor::read_length(self.input))?;
+                visitor.visit_borrowed_bytes(take(self.input, length as usize)?)
+            }
+            found => Err(NBTError::InvalidType {
+                found,
+                expecting: TagIdent::TAG_Byte_Array,
+                when: "bytes".to_string()
+            })
+        }
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        Err(NBTError::InvalidType {
+            found: self.ident,
+            expecting: TagIdent::TAG_End,
+            when: "unit".to_string()
+        })
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        self.seq(visitor, "seq")
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        self.seq(visitor, "tuple")
+    }
+
+    fn deserialize_tuple_struct<V>(self, _name: &'static str, _len: usize, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        self.seq(visitor, "tuple")
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        self.map(visitor, "map")
+    }
+
+    fn deserialize_struct<V>(self, name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        self.map(visitor, "struct")
+    }
+
+    fn deserialize_enum<V>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        visitor.visit_enum(SliceEnumAccess { input: self.input, ident: self.ident, depth: self.depth, options: self.options })
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        self.string(visitor, "identifier")
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        skip(self.input, &self.ident)?;
+        visitor.visit_unit()
+    }
+}
+
+struct SliceSeqAccess<'a, 'de> {
+    input: &'a mut &'de [u8],
+    item: TagIdent,
+    remaining: u32,
+    depth: usize,
+    options: &'a SerdeOptions,
+}
+
+impl SliceSeqAccess<'_, '_> {
+    // Skip any items the visitor left unread.
+    fn finish(&mut self) -> NBTResult<()> {
+        for _ in 0..self.remaining {
+            skip(self.input, &self.item)?;
+        }
+        self.remaining = 0;
+        Ok(())
+    }
+}
+
+impl<'a, 'de> SeqAccess<'de> for SliceSeqAccess<'a, 'de> {
+    type Error = NBTError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<<T as DeserializeSeed<'de>>::Value>, Self::Error> where
+        T: DeserializeSeed<'de> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let item = SliceDeserializer { input: &mut *self.input, ident: self.item.clone(), depth: self.depth, options: self.options };
+        Ok(Some(seed.deserialize(item)?))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        // Capped, so a bogus length can't reserve more than the data holds.
+        Some((self.remaining as usize).min(self.input.len()))
+    }
+}
+
+struct SliceMapAccess<'a, 'de> {
+    input: &'a mut &'de [u8],
+    // The type of the value whose key was just read.
+    value: Option<TagIdent>,
+    done: bool,
+    depth: usize,
+    options: &'a SerdeOptions,
+}
+
+impl SliceMapAccess<'_, '_> {
+    // Skip any elements the visitor left unread.
+    fn finish(&mut self) -> NBTResult<()> {
+        if let Some(ident) = self.value.take() {
+            skip(self.input, &ident)?;
+        }
+        if !self.done {
+            skip_entries(self.input)?;
+            self.done = true;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, 'de> MapAccess<'de> for SliceMapAccess<'a, 'de> {
+    type Error = NBTError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<<K as DeserializeSeed<'de>>::Value>, Self::Error> where
+        K: DeserializeSeed<'de> {
+        if self.done {
+            return Ok(None);
+        }
+        let ident = read_ident(self.input)?;
+        if ident == TagIdent::TAG_End {
+            self.done = true;
+            return Ok(None);
+        }
+        self.value = Some(ident);
+        // The name is encoded as a string payload, so it is read as one.
+        let key = SliceDeserializer { input: &mut *self.input, ident: TagIdent::TAG_String, depth: self.depth, options: self.options };
+        Ok(Some(seed.deserialize(key)?))
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<<V as DeserializeSeed<'de>>::Value, Self::Error> where
+        V: DeserializeSeed<'de> {
+        match self.value.take() {
+            Some(ident) => seed.deserialize(SliceDeserializer { input: &mut *self.input, ident, depth: self.depth, options: self.options }),
+            None => seed.deserialize(NBTDeserializer::new(None, self.options)),
+        }
+    }
+}
+
+struct SliceEnumAccess<'a, 'de> {
+    input: &'a mut &'de [u8],
+    ident: TagIdent,
+    depth: usize,
+    options: &'a SerdeOptions,
+}
+
+impl<'a, 'de> EnumAccess<'de> for SliceEnumAccess<'a, 'de> {
+    type Error = NBTError;
+    type Variant = SliceVariantAccess<'a, 'de>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(<V as DeserializeSeed<'de>>::Value, Self::Variant), Self::Error> where
+        V: DeserializeSeed<'de> {
+        match self.ident {
+            // Unit variants are serialized as their name alone.
+            TagIdent::TAG_String => {
+                let seed = seed.deserialize(SliceDeserializer { input: &mut *self.input, ident: TagIdent::TAG_String, depth: self.depth, options: self.options })?;
+                Ok((seed, SliceVariantAccess { input: self.input, content: None, depth: self.depth, options: self.options }))
+            }
+            // Other variants are a compound, keyed by the name. Any further elements are ignored.
+            TagIdent::TAG_Compound => {
+                if self.depth == MAX_DEPTH {
+                    return Err(NBTError::DepthLimitExceeded { limit: MAX_DEPTH });
+                }
+                let depth = self.depth + 1;
+                let ident = read_ident(self.input)?;
+                if ident == TagIdent::TAG_End {
+                    return Err(NBTError::InvalidType {
+                        found: TagIdent::TAG_End,
+                        expecting: TagIdent::TAG_Compound,
+                        when: "enum map".to_string()
+                    });
+                }
+                let seed = seed.deserialize(SliceDeserializer { input: &mut *self.input, ident: TagIdent::TAG_String, depth, options: self.options })?;
+                Ok((seed, SliceVariantAccess { input: self.input, content: Some(ident), depth, options: self.options }))
+            }
+            found => Err(NBTError::InvalidType {
+                found,
+                expecting: TagIdent::TAG_Compound,
+                when: "enum map".to_string()
+            })
+        }
+    }
+}
+
+// The content of a variant, or `None` for a variant written as its name alone.
+struct SliceVariantAccess<'a, 'de> {
+    input: &'a mut &'de [u8],
+    content: Option<TagIdent>,
+    depth: usize,
+    options: &'a SerdeOptions,
+}
+
+impl<'a, 'de> VariantAccess<'de> for SliceVariantAccess<'a, 'de> {
+    type Error = NBTError;
+
+    // With content, the rest of the compound holding it is skipped once it has been read.
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        if let Some(ident) = self.content {
+            skip(self.input, &ident)?;
+            skip_entries(self.input)?;
+        }
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<<T as DeserializeSeed<'de>>::Value, Self::Error> where
+        T: DeserializeSeed<'de> {
+        match self.content {
+            Some(ident) => {
+                let value = seed.deserialize(SliceDeserializer { input: &mut *self.input, ident, depth: self.depth, options: self.options })?;
+                skip_entries(self.input)?;
+                Ok(value)
+            }
+            None => seed.deserialize(NBTDeserializer::new(None, self.options)),
+        }
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        match self.content {
+            Some(ident) => {
+                let value = SliceDeserializer { input: &mut *self.input, ident, depth: self.depth, options: self.options }.deserialize_tuple(len, visitor)?;
+                skip_entries(self.input)?;
+                Ok(value)
+            }
+            None => NBTDeserializer::new(None, self.options).deserialize_tuple(len, visitor),
+        }
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<<V as Visitor<'de>>::Value, Self::Error> where
+        V: Visitor<'de> {
+        match self.content {
+            Some(ident) => {
+                let value = SliceDeserializer { input: &mut *self.input, ident, depth: self.depth, options: self.options }.deserialize_struct("", fields, visitor)?;
+                skip_entries(self.input)?;
+                Ok(value)
+            }
+            None => NBTDeserializer::new(None, self.options).deserialize_struct("", fields, visitor),
+        }
+    }
+}
@@ -1,10 +1,185 @@
-use std::io::Read;
-use std::collections::HashMap;
+use std::io::{Read, ErrorKind};
+use crate::tags::CompoundMap;
 use crate::{Tag, TagIdent};
-use byteorder::{ReadBytesExt, BE};
+use byteorder::ReadBytesExt;
+use crate::flavor::NbtFlavor;
 use crate::error::{digest_io, NBTResult, NBTError};
+use crate::diagnostics::{Diagnostics, Diagnostic, SUSPICIOUS_LENGTH};
+use crate::options::{ReadOptions, MemoryBudget, StringMode, DEFAULT_MAX_DEPTH};
+use crate::progress::{Progress, ProgressCallback, CountingReader, PROGRESS_INTERVAL};
+use std::mem::size_of;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::HashMap;
+use crate::shared::{SharedTag, Interner};
+
+// State carried through a single read.
+pub(crate) struct ReadContext<'a> {
+    diagnostics: Option<&'a mut Diagnostics>,
+    // Current path, only tracked while collecting diagnostics.
+    path: String,
+    budget: Option<MemoryBudget>,
+    // Bytes charged against the budget so far.
+    used: usize,
+    string_mode: StringMode,
+    lenient_eof: bool,
+    unsigned_byte_arrays: bool,
+    progress: Option<ProgressCallback>,
+    // Compound entries and list elements read so far.
+    entries: u64,
+    // Bytes read so far, shared with the reader returned by `counted`.
+    bytes: Rc<Cell<u64>>,
+    cancel: Option<Arc<AtomicBool>>,
+    // Reused for the bytes of every string, to avoid an allocation per string.
+    scratch: Vec<u8>,
+    // Current nesting of lists and compounds.
+    depth: usize,
+    max_depth: usize,
+    // Shares the strings of `SharedTag`s. Other reads ignore it.
+    interner: Option<Interner>,
+    intern_values: bool,
+}
+
+impl<'a> ReadContext<'a> {
+    pub(crate) fn new(options: &ReadOptions, diagnostics: Option<&'a mut Diagnostics>) -> Self {
+        Self {
+            diagnostics,
+            path: String::new(),
+            budget: options.memory_budget,
+            used: 0,
+            string_mode: options.string_mode,
+            lenient_eof: options.lenient_eof,
+            unsigned_byte_arrays: options.unsigned_byte_arrays,
+            progress: options.progress.clone(),
+            entries: 0,
+            bytes: Rc::new(Cell::new(0)),
+            cancel: options.cancel.clone(),
+            scratch: Vec::new(),
+            depth: 0,
+            max_depth: options.max_depth.unwrap_or(DEFAULT_MAX_DEPTH),
+            interner: options.interner.clone(),
+            intern_values: options.intern_values,
+        }
+    }
+
+    // Wrap a reader so the bytes read through it are counted by this context.
+    pub(crate) fn counted<'r, R: Read + ?Sized>(&self, reader: &'r mut R) -> CountingReader<'r, R> {
+        CountingReader { inner: reader, count: self.bytes.clone() }
+    }
+
+    // Fail if the read has been cancelled.
+    fn check_cancelled(&self) -> NBTResult<()> {
+        match &self.cancel {
+            Some(flag) if flag.load(Ordering::Relaxed) => Err(NBTError::Cancelled),
+            _ => Ok(())
+        }
+    }
+
+    // Count a processed entry, reporting progress every `PROGRESS_INTERVAL` entries.
+    fn tick(&mut self) {
+        self.entries += 1;
+        if self.entries.is_multiple_of(PROGRESS_INTERVAL) {
+            self.report_progress();
+        }
+    }
 
-pub(crate) fn read_ident<R: Read>(reader: &mut R) -> NBTResult<TagIdent> {
+    pub(crate) fn report_progress(&self) {
+        if let Some(callback) = &self.progress {
+            (callback.0)(Progress { bytes: self.bytes.get(), entries: self.entries });
+        }
+    }
+
+    // Complete the read, reporting the final progress and recording the bytes read.
+    pub(crate) fn finish(&mut self) {
+        self.report_progress();
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.stats.bytes += self.bytes.get();
+        }
+    }
+
+    // Record a tag in the statistics.
+    fn record(&mut self, ident: &TagIdent) {
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.stats.record(ident);
+        }
+    }
+
+    // Record the length of a list or array in the statistics.
+    fn record_length(&mut self, ident: &TagIdent, length: u32) {
+        if let Some(diagnostics) = &mut self.diagnostics {
+            let largest = match ident {
+                TagIdent::TAG_List => &mut diagnostics.stats.largest_list,
+                _ => &mut diagnostics.stats.largest_array,
+            };
+            *largest = (*largest).max(length);
+        }
+    }
+
+    // Enter a list or compound, recording the depth reached and failing past the limit.
+    fn descend(&mut self) -> NBTResult<()> {
+        self.depth += 1;
+        if self.depth > self.max_depth {
+            return Err(NBTError::DepthLimitExceeded { limit: self.max_depth });
+        }
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.stats.max_depth = diagnostics.stats.max_depth.max(self.depth);
+        }
+        Ok(())
+    }
+
+    // Charge an allocation against the memory budget.
+    fn charge(&mut self, bytes: usize) -> NBTResult<()> {
+        if let Some(budget) = &self.budget {
+            self.used = self.used.saturating_add(bytes);
+            if self.used > budget.limit {
+                return Err(NBTError::MemoryBudgetExceeded { limit: budget.limit });
+            }
+        }
+        Ok(())
+    }
+
+    // Enter a child element, returning the previous path length to restore with `leave`.
+    fn enter_key(&mut self, key: &str) -> usize {
+        let length = self.path.len();
+        if self.diagnostics.is_some() {
+            if !self.path.is_empty() { self.path.push('.'); }
+            self.path.push_str(key);
+        }
+        length
+    }
+
+    fn enter_index(&mut self, index: u32) -> usize {
+        let length = self.path.len();
+        if self.diagnostics.is_some() {
+            self.path.push_str(&format!("[{}]", index));
+        }
+        length
+    }
+
+    fn leave(&mut self, length: usize) {
+        self.path.truncate(length);
+    }
+
+    fn report<F: FnOnce(String) -> Diagnostic>(&mut self, diagnostic: F) {
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.push(diagnostic(self.path.clone()));
+        }
+    }
+
+    // Read a list/array length, reporting it if it is suspicious.
+    fn read_length<F: NbtFlavor, R: Read + ?Sized>(&mut self, reader: &mut R) -> NBTResult<u32> {
+        self.check_cancelled()?;
+        let length = digest_io(F::read_length(reader))?;
+        if length > SUSPICIOUS_LENGTH {
+            self.report(|path| Diagnostic::SuspiciousLength { path, length });
+        }
+        Ok(length)
+    }
+}
+
+pub(crate) fn read_ident<R: Read + ?Sized>(reader: &mut R) -> NBTResult<TagIdent> {
     let byte = digest_io(reader.read_u8())?;
     match TagIdent::parse(&byte) {
         Some(x) => Ok(x),
@@ -12,52 +187,154 @@ pub(crate) fn read_ident<R: Read>(reader: &mut R) -> NBTResult<TagIdent> {
     }
 }
 
-pub fn read_root<R: Read>(reader: &mut R) -> NBTResult<(String, HashMap<String, Tag>)> {
+pub(crate) fn read_root<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, ctx: &mut ReadContext) -> NBTResult<(String, CompoundMap)> {
     let implicit_ident = read_ident(reader)?;
     if implicit_ident != TagIdent::TAG_Compound {
         return Err(NBTError::InvalidImplicit { found: implicit_ident });
     };
 
-    let name = read_string(reader)?;
+    let name = read_string::<F, R>(reader, ctx)?;
 
-    let compound = read_compound(reader)?;
+    ctx.record(&TagIdent::TAG_Compound);
+    let compound = read_compound::<F, R>(reader, ctx, true)?;
 
     Ok((name, compound))
 
 }
 
-pub(crate) fn read_size<R: Read, S: Into<usize>>(reader: &mut R, size: S) -> NBTResult<Vec<u8>> {
-    let size = size.into();
-    let mut buffer = Vec::with_capacity(size.clone());
-    for _ in 0..size {
-        buffer.push(digest_io(reader.read_u8())?);
+// Read a root compound sent without a name, as in the Java protocol since 1.20.2.
+pub(crate) fn read_nameless_root<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, ctx: &mut ReadContext) -> NBTResult<CompoundMap> {
+    let implicit_ident = read_ident(reader)?;
+    if implicit_ident != TagIdent::TAG_Compound {
+        return Err(NBTError::InvalidImplicit { found: implicit_ident });
+    };
+
+    ctx.record(&TagIdent::TAG_Compound);
+    read_compound::<F, R>(reader, ctx, true)
+}
+
+pub(crate) fn read_named<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, ctx: &mut ReadContext) -> NBTResult<(String, Tag)> {
+    let ident = read_ident(reader)?;
+    if ident == TagIdent::TAG_End {
+        return Err(NBTError::UnexpectedEndTag {});
     }
-    Ok(buffer)
+    let name = read_string::<F, R>(reader, ctx)?;
+    let tag = read_tag::<F, R>(reader, &ident, ctx)?;
+    Ok((name, tag))
 }
 
-pub(crate) fn read_string<R: Read>(reader: &mut R) -> NBTResult<String> {
-    let length = digest_io(reader.read_u16::<BE>())?;
+pub(crate) fn read_string<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, ctx: &mut ReadContext) -> NBTResult<String> {
+    read_string_bytes::<F, R>(reader, ctx)?;
+    decode_scratch::<F>(ctx)
+}
 
-    let buffer = read_size(reader, length)?;
+// Read the bytes of a string into `ctx.scratch`.
+fn read_string_bytes<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, ctx: &mut ReadContext) -> NBTResult<()> {
+    let length = digest_io(F::read_string_length(reader))?;
+    ctx.charge(length)?;
 
-    decode_wonky_string(&buffer)
+    // Read through `take` so a bogus length can't allocate more than the data sent.
+    ctx.scratch.clear();
+    digest_io(Read::take(&mut *reader, length as u64).read_to_end(&mut ctx.scratch))?;
+    if ctx.scratch.len() < length {
+        return digest_io(Err(ErrorKind::UnexpectedEof.into()));
+    }
+    Ok(())
 }
 
-pub(crate) fn read_compound<R: Read>(reader: &mut R) -> NBTResult<HashMap<String, Tag>> {
-    let mut compound = HashMap::new();
+// Decode the string read into `ctx.scratch`.
+fn decode_scratch<F: NbtFlavor>(ctx: &mut ReadContext) -> NBTResult<String> {
+    match ctx.string_mode {
+        StringMode::Flavor => F::decode_string(&ctx.scratch),
+        StringMode::Strict => F::decode_string_strict(&ctx.scratch),
+        StringMode::Utf8 => std::str::from_utf8(&ctx.scratch).map(str::to_string).map_err(|_| NBTError::StringError),
+        StringMode::Lossy => match F::decode_string(&ctx.scratch) {
+            Err(NBTError::StringError) => {
+                ctx.report(|path| Diagnostic::LossyString { path });
+                Ok(F::decode_string_lossy(&ctx.scratch))
+            }
+            result => result
+        }
+    }
+}
+
+pub(crate) fn read_compound<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, ctx: &mut ReadContext, root: bool) -> NBTResult<CompoundMap> {
+    ctx.descend()?;
+    let mut compound = CompoundMap::default();
     loop {
-        let ident = read_ident(reader)?;
+        let ident = match read_ident(reader) {
+            // A truncated document may end where the root's end tag should be.
+            Err(NBTError::IO { error }) if root && ctx.lenient_eof && error.kind() == ErrorKind::UnexpectedEof => {
+                ctx.report(|_| Diagnostic::MissingEndTag);
+                break;
+            }
+            result => result?
+        };
         if ident == TagIdent::TAG_End { break; }
 
-        let name = read_string(reader)?;
-        let payload = read_tag(reader, &ident)?;
+        ctx.check_cancelled()?;
+        let name = read_string::<F, R>(reader, ctx)?;
+        ctx.charge(size_of::<(String, Tag)>())?;
 
-        compound.insert(name, payload);
+        let parent = ctx.enter_key(&name);
+        let payload = read_tag::<F, R>(reader, &ident, ctx)?;
+        ctx.tick();
+
+        if compound.insert(name, payload).is_some() {
+            ctx.report(|path| Diagnostic::DuplicateKey { path });
+        }
+        ctx.leave(parent);
     }
+    ctx.depth -= 1;
     Ok(compound)
 }
 
-pub fn read_tag<R: Read>(reader: &mut R, ident: &TagIdent) -> NBTResult<Tag> {
+pub(crate) fn read_tag<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, ident: &TagIdent, ctx: &mut ReadContext) -> NBTResult<Tag> {
+    if *ident != TagIdent::TAG_End {
+        ctx.record(ident);
+    }
+    // Tags holding others are read in their own functions, keeping the frames of the recursion small
+    // so the depth limit is reached well before the stack runs out.
+    match ident {
+        TagIdent::TAG_List => read_list::<F, R>(reader, ctx),
+        TagIdent::TAG_Compound => Ok(Tag::Compound(read_compound::<F, R>(reader, ctx, false)?)),
+        _ => read_leaf::<F, R>(reader, ident, ctx),
+    }
+}
+
+fn read_list<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, ctx: &mut ReadContext) -> NBTResult<Tag> {
+    // read list type
+    let ident = read_ident(reader)?;
+
+    // read length
+    let length = ctx.read_length::<F, R>(reader)?;
+    ctx.charge((length as usize).saturating_mul(size_of::<Tag>()))?;
+
+    if length == 0 && ident != TagIdent::TAG_End {
+        ctx.report(|path| Diagnostic::TypedEmptyList { path, ident: ident.clone() });
+    }
+
+    ctx.record_length(&TagIdent::TAG_List, length);
+
+    // create empty buffer
+    let mut list = Vec::new();
+
+    // read items
+    ctx.descend()?;
+    for index in 0..length {
+        ctx.check_cancelled()?;
+        let parent = ctx.enter_index(index);
+        list.push(read_tag::<F, R>(reader, &ident, ctx)?);
+        ctx.tick();
+        ctx.leave(parent);
+    }
+    ctx.depth -= 1;
+
+    Ok(Tag::List(list))
+}
+
+// Read a tag that holds no other tags.
+fn read_leaf<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, ident: &TagIdent, ctx: &mut ReadContext) -> NBTResult<Tag> {
     match ident {
         // If we get a end tag, we error.
         TagIdent::TAG_End => Err(NBTError::UnexpectedEndTag {}),
@@ -66,24 +343,36 @@ pub fn read_tag<R: Read>(reader: &mut R, ident: &TagIdent) -> NBTResult<Tag> {
         TagIdent::TAG_Byte => Ok(Tag::Byte(digest_io(reader.read_i8())?)),
 
         // read short (i16)
-        TagIdent::TAG_Short => Ok(Tag::Short(digest_io(reader.read_i16::<BE>())?)),
+        TagIdent::TAG_Short => Ok(Tag::Short(digest_io(F::read_short(reader))?)),
 
         // read int (i32)
-        TagIdent::TAG_Int => Ok(Tag::Int(digest_io(reader.read_i32::<BE>())?)),
+        TagIdent::TAG_Int => Ok(Tag::Int(digest_io(F::read_int(reader))?)),
 
         // read long (i64)
-        TagIdent::TAG_Long => Ok(Tag::Long(digest_io(reader.read_i64::<BE>())?)),
+        TagIdent::TAG_Long => Ok(Tag::Long(digest_io(F::read_long(reader))?)),
 
         // read float (f32)
-        TagIdent::TAG_Float => Ok(Tag::Float(digest_io(reader.read_f32::<BE>())?)),
+        TagIdent::TAG_Float => Ok(Tag::Float(digest_io(F::read_float(reader))?)),
 
         // read double (f64)
-        TagIdent::TAG_Double => Ok(Tag::Double(digest_io(reader.read_f64::<BE>())?)),
+        TagIdent::TAG_Double => Ok(Tag::Double(digest_io(F::read_double(reader))?)),
 
         // read byte array
         TagIdent::TAG_Byte_Array => {
             // get length int
-            let length = digest_io(reader.read_u32::<BE>())?;
+            let length = ctx.read_length::<F, R>(reader)?;
+            ctx.charge((length as usize).saturating_mul(size_of::<i8>()))?;
+            ctx.record_length(ident, length);
+
+            // read the bytes as they are
+            if ctx.unsigned_byte_arrays {
+                let mut array = Vec::new();
+                digest_io(Read::take(&mut *reader, length as u64).read_to_end(&mut array))?;
+                if array.len() < length as usize {
+                    return digest_io(Err(ErrorKind::UnexpectedEof.into()));
+                }
+                return Ok(Tag::UnsignedByteArray(array));
+            }
 
             // empty build array
             let mut array = Vec::new();
@@ -96,62 +385,193 @@ pub fn read_tag<R: Read>(reader: &mut R, ident: &TagIdent) -> NBTResult<Tag> {
         }
 
         // read string
-        TagIdent::TAG_String => Ok(Tag::String(read_string(reader)?)),
-
-        // read list
-        TagIdent::TAG_List => {
-            // read list type
-            let ident = read_ident(reader)?;
-
-            // read length
-            let length = digest_io(reader.read_u32::<BE>())?;
-
-            // create empty buffer
-            let mut list = Vec::new();
+        TagIdent::TAG_String => Ok(Tag::String(read_string::<F, R>(reader, ctx)?)),
 
-            // read items
-            for _ in 0..length {
-                list.push(read_tag(reader, &ident)?);
-            }
-
-            Ok(Tag::List(list))
-        }
 
-        // read compound
-        TagIdent::TAG_Compound => Ok(Tag::Compound(read_compound(reader)?)),
+        // Handled by `read_tag`.
+        TagIdent::TAG_List | TagIdent::TAG_Compound => unreachable!(),
 
         TagIdent::TAG_Int_Array => {
             // get length int
-            let length = digest_io(reader.read_u32::<BE>())?;
+            let length = ctx.read_length::<F, R>(reader)?;
+            ctx.charge((length as usize).saturating_mul(size_of::<i32>()))?;
+            ctx.record_length(ident, length);
 
             // empty build array
             let mut array = Vec::new();
 
             // read items
             for _ in 0..length {
-                array.push(digest_io(reader.read_i32::<BE>())?)
+                array.push(digest_io(F::read_int(reader))?)
             }
             Ok(Tag::IntArray(array))
         }
         TagIdent::TAG_Long_Array => {
             // get length int
-            let length = digest_io(reader.read_u32::<BE>())?;
+            let length = ctx.read_length::<F, R>(reader)?;
+            ctx.charge((length as usize).saturating_mul(size_of::<i64>()))?;
+            ctx.record_length(ident, length);
 
             // empty build array
             let mut array = Vec::new();
 
             // read items
             for _ in 0..length {
-                array.push(digest_io(reader.read_i64::<BE>())?)
+                array.push(digest_io(F::read_long(reader))?)
             }
             Ok(Tag::LongArray(array))
         }
     }
 }
 
+// Read a string as an `Arc<str>`, shared through the interner if there is one and `intern` is set.
+fn read_shared_string<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, ctx: &mut ReadContext, intern: bool) -> NBTResult<Arc<str>> {
+    read_string_bytes::<F, R>(reader, ctx)?;
+
+    // ASCII without nul bytes decodes to itself in every flavor and string mode, so it is shared
+    // straight from the scratch buffer, without decoding it into an allocation first.
+    let decoded;
+    let string = match std::str::from_utf8(&ctx.scratch) {
+        Ok(string) if ctx.scratch.iter().all(|x| matches!(x, 0x01..=0x7F)) => string,
+        _ => {
+            decoded = decode_scratch::<F>(ctx)?;
+            &decoded
+        }
+    };
+    Ok(match &ctx.interner {
+        Some(interner) if intern => interner.intern(string),
+        _ => Arc::from(string),
+    })
+}
+
+// Read a root compound as a `SharedTag`, discarding its name.
+pub(crate) fn read_shared_root<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, ctx: &mut ReadContext) -> NBTResult<SharedTag> {
+    let implicit_ident = read_ident(reader)?;
+    if implicit_ident != TagIdent::TAG_Compound {
+        return Err(NBTError::InvalidImplicit { found: implicit_ident });
+    };
+
+    read_string::<F, R>(reader, ctx)?;
+    ctx.record(&TagIdent::TAG_Compound);
+    read_shared_compound::<F, R>(reader, ctx, true)
+}
+
+// Read a tag straight into a `SharedTag`, sharing keys, and strings if asked, as they are read.
+fn read_shared_tag<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, ident: &TagIdent, ctx: &mut ReadContext) -> NBTResult<SharedTag> {
+    if *ident != TagIdent::TAG_End {
+        ctx.record(ident);
+    }
+    match ident {
+        TagIdent::TAG_List => read_shared_list::<F, R>(reader, ctx),
+        TagIdent::TAG_Compound => read_shared_compound::<F, R>(reader, ctx, false),
+        TagIdent::TAG_String => {
+            let intern = ctx.intern_values;
+            Ok(SharedTag::String(read_shared_string::<F, R>(reader, ctx, intern)?))
+        }
+        _ => read_leaf::<F, R>(reader, ident, ctx).map(SharedTag::from),
+    }
+}
+
+fn read_shared_compound<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, ctx: &mut ReadContext, root: bool) -> NBTResult<SharedTag> {
+    ctx.descend()?;
+    let mut compound = HashMap::new();
+    loop {
+        let ident = match read_ident(reader) {
+            Err(NBTError::IO { error }) if root && ctx.lenient_eof && error.kind() == ErrorKind::UnexpectedEof => {
+                ctx.report(|_| Diagnostic::MissingEndTag);
+                break;
+            }
+            result => result?
+        };
+        if ident == TagIdent::TAG_End { break; }
+
+        ctx.check_cancelled()?;
+        let name = read_shared_string::<F, R>(reader, ctx, true)?;
+        ctx.charge(size_of::<(Arc<str>, SharedTag)>())?;
+
+        let parent = ctx.enter_key(&name);
+        let payload = read_shared_tag::<F, R>(reader, &ident, ctx)?;
+        ctx.tick();
+
+        if compound.insert(name, payload).is_some() {
+            ctx.report(|path| Diagnostic::DuplicateKey { path });
+        }
+        ctx.leave(parent);
+    }
+    ctx.depth -= 1;
+    Ok(SharedTag::Compound(Arc::new(compound)))
+}
+
+fn read_shared_list<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, ctx: &mut ReadContext) -> NBTResult<SharedTag> {
+    let ident = read_ident(reader)?;
+    let length = ctx.read_length::<F, R>(reader)?;
+    ctx.charge((length as usize).saturating_mul(size_of::<SharedTag>()))?;
+
+    if length == 0 && ident != TagIdent::TAG_End {
+        ctx.report(|path| Diagnostic::TypedEmptyList { path, ident: ident.clone() });
+    }
+    ctx.record_length(&TagIdent::TAG_List, length);
+
+    let mut list = Vec::new();
+    ctx.descend()?;
+    for index in 0..length {
+        ctx.check_cancelled()?;
+        let parent = ctx.enter_index(index);
+        list.push(read_shared_tag::<F, R>(reader, &ident, ctx)?);
+        ctx.tick();
+        ctx.leave(parent);
+    }
+    ctx.depth -= 1;
+
+    Ok(SharedTag::List(Arc::new(list)))
+}
+
+// Decode modified UTF-8 only, rejecting the raw nul bytes and four byte sequences of plain UTF-8.
+pub (crate) fn decode_wonky_string_strict(b: &[u8]) -> NBTResult<String> {
+    if b.iter().any(|x| *x == 0 || *x >= 0xF0) {
+        return Err(NBTError::StringError);
+    }
+    decode_wonky_string(b)
+}
+
 pub (crate) fn decode_wonky_string(b: &[u8]) -> NBTResult<String> {
-    match cesu8::from_java_cesu8(&b) {
-        Ok(s) => Ok(s.to_string()),
+    match cesu8::from_java_cesu8(b) {
+        Ok(s) => Ok(s.into_owned()),
         Err(_) => Err(NBTError::StringError)
     }
-}
\ No newline at end of file
+}
+
+// Decode modified UTF-8, replacing invalid sequences and unpaired surrogates with U+FFFD.
+pub (crate) fn decode_wonky_string_lossy(b: &[u8]) -> String {
+    // Decode a single code unit, returning it and its length in bytes.
+    fn unit(b: &[u8]) -> Option<(u32, usize)> {
+        let continuation = |i: usize| b.get(i).filter(|x| *x & 0xC0 == 0x80).map(|x| (*x & 0x3F) as u32);
+        match *b.first()? {
+            x @ 0x01..=0x7F => Some((x as u32, 1)),
+            x @ 0xC0..=0xDF => Some((((x & 0x1F) as u32) << 6 | continuation(1)?, 2)),
+            x @ 0xE0..=0xEF => Some((((x & 0x0F) as u32) << 12 | continuation(1)? << 6 | continuation(2)?, 3)),
+            _ => None
+        }
+    }
+
+    let mut string = String::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        match unit(&b[i..]) {
+            // Surrogate pair, encoded as two three byte sequences.
+            Some((high @ 0xD800..=0xDBFF, 3)) => match unit(&b[i + 3..]) {
+                Some((low @ 0xDC00..=0xDFFF, 3)) => {
+                    string.push(std::char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).unwrap_or('\u{FFFD}'));
+                    i += 6;
+                }
+                _ => { string.push('\u{FFFD}'); i += 3; }
+            },
+            Some((x, length)) => {
+                string.push(std::char::from_u32(x).unwrap_or('\u{FFFD}'));
+                i += length;
+            }
+            None => { string.push('\u{FFFD}'); i += 1; }
+        }
+    }
+    string
+}
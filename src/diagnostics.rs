@@ -0,0 +1,137 @@
+use crate::tags::TagIdent;
+use std::fmt;
+use std::ops::Deref;
+
+/// Lengths above this are reported as suspicious. (16,777,216 elements)
+pub(crate) const SUSPICIOUS_LENGTH: u32 = 1 << 24;
+
+#[cfg_attr(feature="debug", derive(Debug))]
+#[derive(Clone, PartialEq)]
+/// A non-fatal oddity found while reading NBT data.
+///
+/// Paths are written as `key.key[index]`, with the root compound being `""`.
+pub enum Diagnostic {
+    /// A compound contained the same key more than once. The last value was kept.
+    DuplicateKey { path: String },
+    /// A list with no elements declared a type other than `TAG_End`.
+    TypedEmptyList { path: String, ident: TagIdent },
+    /// A list or array declared a length that is negative or unusually large.
+    SuspiciousLength { path: String, length: u32 },
+    /// Bytes were left over after the document was read.
+    TrailingBytes { count: usize },
+    /// A string could not be decoded and was read lossily.
+    /// The path is that of the string value, or of the containing compound for a key.
+    LossyString { path: String },
+    /// The document ended without the end tag closing the root compound.
+    MissingEndTag,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self {
+            Diagnostic::DuplicateKey { path } => write!(f, "Duplicate key at '{}'", path),
+            Diagnostic::TypedEmptyList { path, ident } => write!(f, "Empty list at '{}' has type {} instead of {}", path, ident, TagIdent::TAG_End),
+            Diagnostic::SuspiciousLength { path, length } => write!(f, "Suspicious length of {} at '{}'", length, path),
+            Diagnostic::TrailingBytes { count } => write!(f, "{} trailing bytes after the end of the document", count),
+            Diagnostic::LossyString { path } => write!(f, "Undecodable string at '{}' was replaced lossily", path),
+            Diagnostic::MissingEndTag => write!(f, "Document ended without closing the root compound"),
+        }
+    }
+}
+
+#[cfg_attr(feature="debug", derive(Debug))]
+#[derive(Clone, Default, PartialEq)]
+/// Statistics of the documents read, collected along with diagnostics.
+///
+/// If a sink is used for more than one read, the statistics cover all of them.
+pub struct ReadStats {
+    tags: [u64; 13],
+    /// The deepest nesting of lists and compounds, with the root compound at a depth of 1.
+    pub max_depth: usize,
+    /// The number of bytes read.
+    pub bytes: u64,
+    /// The length of the longest byte, int or long array.
+    pub largest_array: u32,
+    /// The length of the longest list.
+    pub largest_list: u32,
+}
+
+impl ReadStats {
+    /// The number of tags read of the given type.
+    pub fn count(&self, ident: TagIdent) -> u64 {
+        self.tags[ident as usize]
+    }
+
+    /// The total number of tags read.
+    pub fn total(&self) -> u64 {
+        self.tags.iter().sum()
+    }
+
+    pub(crate) fn record(&mut self, ident: &TagIdent) {
+        self.tags[ident.clone() as usize] += 1;
+    }
+}
+
+#[cfg_attr(feature="debug", derive(Debug))]
+#[derive(Clone, Default)]
+/// A sink collecting `Diagnostic`s during a read.
+///
+/// ## Example
+/// ```
+/// # use nbt::{Blob, NBTRead, Diagnostics};
+/// let mut diagnostics = Diagnostics::new();
+/// let data = vec![10, 0, 0, 0, 0xFF];
+///
+/// let blob = Blob::from_bytes_with_diagnostics(data, &mut diagnostics).unwrap();
+///
+/// for diagnostic in diagnostics.iter() {
+///     println!("{}", diagnostic);
+/// }
+/// # assert_eq!(diagnostics.len(), 1);
+/// ```
+pub struct Diagnostics {
+    entries: Vec<Diagnostic>,
+    pub(crate) stats: ReadStats,
+}
+
+impl Diagnostics {
+    /// Create a empty diagnostics sink.
+    pub fn new() -> Diagnostics {
+        Diagnostics { entries: Vec::new(), stats: ReadStats::default() }
+    }
+
+    pub(crate) fn push(&mut self, diagnostic: Diagnostic) {
+        self.entries.push(diagnostic)
+    }
+
+    /// Get the statistics of the reads made with this sink.
+    ///
+    /// ```
+    /// # use nbt::{Blob, NBTRead, Diagnostics, TagIdent};
+    /// let mut diagnostics = Diagnostics::new();
+    /// let data = vec![10, 0, 0, 11, 0, 1, 97, 0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2, 0];
+    ///
+    /// Blob::from_bytes_with_diagnostics(data, &mut diagnostics).unwrap();
+    ///
+    /// let stats = diagnostics.stats();
+    /// assert_eq!(stats.count(TagIdent::TAG_Int_Array), 1);
+    /// assert_eq!(stats.largest_array, 2);
+    /// assert_eq!(stats.bytes, 20);
+    /// ```
+    pub fn stats(&self) -> &ReadStats {
+        &self.stats
+    }
+
+    /// Get the collected diagnostics.
+    pub fn into_vec(self) -> Vec<Diagnostic> {
+        self.entries
+    }
+}
+
+impl Deref for Diagnostics {
+    type Target = Vec<Diagnostic>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entries
+    }
+}
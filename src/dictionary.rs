@@ -0,0 +1,92 @@
+use crate::blob::Blob;
+use crate::file::Compression;
+use crate::error::{NBTResult, digest_io};
+use crate::front::{NBTRead, NBTWrite};
+use std::io::{Read, Write};
+
+#[derive(Clone)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// A zstd dictionary shared between many small payloads, such as the chunks of a region.
+///
+/// Small payloads compress poorly on their own, as each has to describe its own repeated keys and
+/// values. A dictionary trained on a sample of payloads holds what they have in common, so each
+/// payload only needs to encode what is unique to it. The same dictionary must be used to
+/// decompress, so it should be stored alongside the payloads.
+///
+/// ## Example
+/// ```
+/// use nbt::{Blob, ZstdDictionary};
+///
+/// let chunks: Vec<Blob> = (0..200).map(|x| {
+///     let mut blob = Blob::new();
+///     blob.insert("xPos", x);
+///     blob.insert("Status", "minecraft:full");
+///     blob
+/// }).collect();
+///
+/// let dictionary = ZstdDictionary::train_blobs(&chunks, 4096).unwrap();
+/// let compressed = dictionary.compress_blob(&chunks[7]).unwrap();
+/// let decompressed = dictionary.decompress_blob(&compressed).unwrap();
+/// assert_eq!(decompressed.get::<i32>("xPos"), Some(&7));
+/// ```
+pub struct ZstdDictionary {
+    data: Vec<u8>,
+    level: i32,
+}
+
+impl ZstdDictionary {
+    /// Use a previously trained dictionary.
+    pub fn from_bytes(data: Vec<u8>) -> ZstdDictionary {
+        ZstdDictionary { data, level: 0 }
+    }
+
+    /// Train a dictionary of at most `max_size` bytes from sample payloads.
+    ///
+    /// Training needs a reasonable number of samples, typically a hundred or more, and fails with
+    /// `NBTError::IO` if there are too few.
+    pub fn train<S: AsRef<[u8]>>(samples: &[S], max_size: usize) -> NBTResult<ZstdDictionary> {
+        digest_io(zstd::dict::from_samples(samples, max_size)).map(ZstdDictionary::from_bytes)
+    }
+
+    /// Train a dictionary of at most `max_size` bytes from the encoded form of sample blobs.
+    pub fn train_blobs(samples: &[Blob], max_size: usize) -> NBTResult<ZstdDictionary> {
+        let samples = samples.iter().map(|x| x.bytes()).collect::<NBTResult<Vec<Vec<u8>>>>()?;
+        ZstdDictionary::train(&samples, max_size)
+    }
+
+    /// Set the compression level, from 1 to 22. 0 uses zstd's default.
+    pub fn level(mut self, level: i32) -> ZstdDictionary {
+        self.level = level;
+        self
+    }
+
+    /// The bytes of the dictionary, for storing alongside the payloads.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Compress a payload with the dictionary.
+    pub fn compress(&self, data: &[u8]) -> NBTResult<Vec<u8>> {
+        let mut encoder = digest_io(zstd::stream::Encoder::with_dictionary(Vec::new(), self.level, &self.data))?;
+        digest_io(encoder.write_all(data))?;
+        digest_io(encoder.finish())
+    }
+
+    /// Decompress a payload compressed with the dictionary.
+    pub fn decompress(&self, data: &[u8]) -> NBTResult<Vec<u8>> {
+        let mut buffer = Vec::new();
+        let mut decoder = digest_io(zstd::stream::Decoder::with_dictionary(data, &self.data))?;
+        digest_io(decoder.read_to_end(&mut buffer))?;
+        Ok(buffer)
+    }
+
+    /// Encode and compress a blob with the dictionary.
+    pub fn compress_blob(&self, blob: &Blob) -> NBTResult<Vec<u8>> {
+        self.compress(&blob.bytes()?)
+    }
+
+    /// Decompress and read a blob compressed with the dictionary.
+    pub fn decompress_blob(&self, data: &[u8]) -> NBTResult<Blob> {
+        Ok(Blob::from_bytes(self.decompress(data)?)?.with_compression(Some(Compression::Zstd)))
+    }
+}
@@ -0,0 +1,64 @@
+use crate::blob::Blob;
+use crate::snbt::write_snbt;
+use crate::tags::Tag;
+use std::fmt;
+
+impl Tag {
+    /// Format the tree with one line per value, as `path = value`, for diffing.
+    ///
+    /// Values are written as SNBT and compound keys are sorted, so equal trees always produce the
+    /// same output. Lists and compounds are only given a line of their own when they are empty.
+    ///
+    /// ```
+    /// # use nbt::Tag;
+    /// let player = Tag::compound_from_iter(vec![
+    ///     ("Pos", Tag::List(vec![Tag::Double(12.5), Tag::Double(64.0)])),
+    ///     ("Name", Tag::String("Steve".to_string())),
+    /// ]);
+    ///
+    /// assert_eq!(player.dump_flat(), "Name = \"Steve\"\nPos[0] = 12.5d\nPos[1] = 64.0d\n");
+    /// ```
+    pub fn dump_flat(&self) -> String {
+        let mut out = String::new();
+        // Writing to a String cannot fail.
+        let _ = dump_flat(&mut out, "", self);
+        out
+    }
+}
+
+// Write the lines for a tag, prefixing each path with the path of the tag itself.
+fn dump_flat(out: &mut String, prefix: &str, tag: &Tag) -> fmt::Result {
+    for (path, tag) in tag.iter_recursive() {
+        match tag {
+            Tag::List(list) if !list.is_empty() => continue,
+            Tag::Compound(map) if !map.is_empty() => continue,
+            _ => {}
+        }
+        let path = path.to_string();
+        out.push_str(prefix);
+        if !prefix.is_empty() && !path.is_empty() && !path.starts_with('[') {
+            out.push('.');
+        }
+        out.push_str(&path);
+        out.push_str(" = ");
+        write_snbt(out, tag)?;
+        out.push('\n');
+    }
+    Ok(())
+}
+
+impl Blob {
+    /// Format the blob with one line per value. See [`Tag::dump_flat`].
+    ///
+    /// The name of the root compound is not included.
+    pub fn dump_flat(&self) -> String {
+        let mut keys: Vec<&String> = self.elements.keys().collect();
+        keys.sort();
+
+        let mut out = String::new();
+        for key in keys {
+            let _ = dump_flat(&mut out, key, &self.elements[key]);
+        }
+        out
+    }
+}
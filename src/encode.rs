@@ -1,83 +1,99 @@
 use crate::tags::{Tag, TagIdent};
 use crate::error::{NBTResult, NBTError, digest_io};
 
-use byteorder::{BigEndian as BE, WriteBytesExt};
+use byteorder::WriteBytesExt;
+use crate::flavor::NbtFlavor;
 use std::io::Write;
-use std::collections::HashMap;
-
+use crate::tags::CompoundMap;
+use crate::options::WriteOptions;
+use crate::normalize::coerce_list;
+
+
+// Array items converted per write.
+const ARRAY_CHUNK: usize = 1024;
+
+// Write the items of an array a chunk at a time, converting each chunk into a buffer so that large
+// arrays are written with a few large writes instead of one per item.
+fn write_array<W: Write + ?Sized, T: Copy>(writer: &mut W, items: &[T], write: impl Fn(&mut Vec<u8>, T) -> std::io::Result<()>) -> NBTResult<()> {
+    let mut buffer = Vec::with_capacity(items.len().min(ARRAY_CHUNK) * std::mem::size_of::<T>());
+    for chunk in items.chunks(ARRAY_CHUNK) {
+        buffer.clear();
+        for item in chunk {
+            digest_io(write(&mut buffer, *item))?;
+        }
+        digest_io(writer.write_all(&buffer))?;
+    }
+    Ok(())
+}
 
-pub(crate) fn write_tag<W: Write>(writer: &mut W, tag: &Tag) -> NBTResult<()>  {
+pub(crate) fn write_tag<F: NbtFlavor, W: Write + ?Sized>(writer: &mut W, tag: &Tag, options: &WriteOptions) -> NBTResult<()>  {
     match tag {
         // Writing a Byte (i8)
         Tag::Byte(byte) => digest_io(writer.write_i8(*byte)),
 
         // Writing a Short (i16)
-        Tag::Short(short) => digest_io(writer.write_i16::<BE>(*short)),
+        Tag::Short(short) => digest_io(F::write_short(writer, *short)),
 
         // Writing a Int (i32)
-        Tag::Int(int) => digest_io(writer.write_i32::<BE>(*int)),
+        Tag::Int(int) => digest_io(F::write_int(writer, *int)),
 
         // Writing a Long(i64)
-        Tag::Long(long) => digest_io(writer.write_i64::<BE>(*long)),
+        Tag::Long(long) => digest_io(F::write_long(writer, *long)),
 
         // Writing a Float (f32)
-        Tag::Float(float) => digest_io(writer.write_f32::<BE>(*float)),
+        Tag::Float(float) => digest_io(F::write_float(writer, *float)),
 
         // Writing a Double (f64)
-        Tag::Double(double) => digest_io(writer.write_f64::<BE>(*double)),
+        Tag::Double(double) => digest_io(F::write_double(writer, *double)),
 
-        // Writing an array of bytes (Vec<i8>)
-        Tag::ByteArray(bytes) => {
-            // Write length as a unsigned int. (4bytes)
-            digest_io(writer.write_u32::<BE>(bytes.len() as u32))?;
+        // Writing an array of bytes (Vec<i8> or Vec<u8>), which is written as is.
+        Tag::ByteArray(_) | Tag::UnsignedByteArray(_) => {
+            let bytes = tag.byte_array_as_u8().unwrap_or_default();
 
-            // Write items of array.
-            for byte in bytes {
-                digest_io(writer.write_i8(*byte))?;
-            }
-            Ok(())
+            // Write length as a unsigned int. (4bytes)
+            digest_io(F::write_length(writer, bytes.len() as u32))?;
+            digest_io(writer.write_all(bytes))
         }
 
         // Write a string of utf-8 chars
-        Tag::String(string) => write_string(writer, &string),
+        Tag::String(string) => write_string::<F, W>(writer, string),
 
         Tag::List(list) => {
             // Check the list is valid (all items are of the same type) and return the type prefix.
-            let list_type = ensure_list_integrity(&list)?;
+            let list_type = match ensure_list_integrity(list) {
+                Err(NBTError::InvalidList { .. }) if options.mixed_lists => {
+                    return write_tag::<F, W>(writer, &Tag::List(coerce_list(list)), options);
+                }
+                result => result?
+            };
 
             // Write type prefix.
             digest_io(writer.write_u8(list_type as u8))?;
 
             // Write List length
-            digest_io(writer.write_u32::<BE>(list.len() as u32))?;
+            digest_io(F::write_length(writer, list.len() as u32))?;
 
             // Write items (without prefix)
             for item in list {
-                write_tag(writer, &item)?;
+                write_tag::<F, W>(writer, item, options)?;
             }
 
             Ok(())
         }
-        Tag::Compound(compound) => write_compound(writer, compound),
+        Tag::Compound(compound) => write_compound::<F, W>(writer, compound, options),
         Tag::IntArray(array) => {
             // Write length as a unsigned int. (4bytes)
-            digest_io(writer.write_u32::<BE>(array.len() as u32))?;
+            digest_io(F::write_length(writer, array.len() as u32))?;
 
             // Write items of array.
-            for int in array {
-                digest_io(writer.write_i32::<BE>(*int))?;
-            }
-            Ok(())
+            write_array(writer, array, |buffer, x| F::write_int(buffer, x))
         }
         Tag::LongArray(array) => {
             // Write length as a unsigned int. (4bytes)
-            digest_io(writer.write_u32::<BE>(array.len() as u32))?;
+            digest_io(F::write_length(writer, array.len() as u32))?;
 
             // Write items of array.
-            for long in array {
-                digest_io(writer.write_i64::<BE>(*long))?;
-            }
-            Ok(())
+            write_array(writer, array, |buffer, x| F::write_long(buffer, x))
         }
     }
 }
@@ -85,15 +101,11 @@ pub(crate) fn write_tag<W: Write>(writer: &mut W, tag: &Tag) -> NBTResult<()>  {
 
 // Function checks through items in a list to check if they are of the same type.
 pub(crate) fn ensure_list_integrity(list: &Vec<Tag>) -> NBTResult<TagIdent> {
-    // If list is empty, then type is TAG_End
-    if list.len() == 0 {
-        return Ok(TagIdent::TAG_End);
-    }
-
-    // Get first type.
-    // Should be safe to unwrap here as we know there will be at least one element in the list.
-    // We have ownership so it will never happen.
-    let tag = list.get(0).unwrap().ident();
+    // Get first type. If list is empty, then type is TAG_End
+    let tag = match list.first() {
+        Some(first) => first.ident(),
+        None => return Ok(TagIdent::TAG_End)
+    };
 
     // Loop through items
     for item in list {
@@ -109,44 +121,67 @@ pub(crate) fn ensure_list_integrity(list: &Vec<Tag>) -> NBTResult<TagIdent> {
 
 // String writer.
 // Strings are written the same way multiple times so this function exists.
-pub(crate) fn write_string<W: Write>(writer: &mut W, string: &str) -> NBTResult<()> {
-    // Get the UTF-8 bytes of the string
-    let bytes = encode_wonky_string(string);
+pub(crate) fn write_string<F: NbtFlavor, W: Write + ?Sized>(writer: &mut W, string: &str) -> NBTResult<()> {
+    // Get the encoded bytes of the string
+    let bytes = F::encode_string(string);
 
     // Write length of string
-    digest_io(writer.write_u16::<BE>(bytes.len() as u16))?;
+    F::write_string_length(writer, bytes.len())?;
 
     // Write the string.
     digest_io(writer.write_all(&bytes))
 }
 
 // Function for writing a root compound (implicit compound)
-pub(crate) fn write_root<W: Write>(writer: &mut W, name: &str, elements: &HashMap<String, Tag>) -> NBTResult<()> {
+pub(crate) fn write_root<F: NbtFlavor, W: Write + ?Sized>(writer: &mut W, name: &str, elements: &CompoundMap, options: &WriteOptions) -> NBTResult<()> {
     // Write implicit compound ident prefix.
     digest_io(writer.write_u8(TagIdent::TAG_Compound as u8))?;
 
     // Write root compound name
-    write_string(writer, &name)?;
+    write_string::<F, W>(writer, name)?;
 
     // Write elements
-    write_compound(writer, elements)
+    write_compound::<F, W>(writer, elements, options)
+}
+
+// Function for writing a root compound without a name, as in the Java protocol since 1.20.2.
+pub(crate) fn write_nameless_root<F: NbtFlavor, W: Write + ?Sized>(writer: &mut W, elements: &CompoundMap, options: &WriteOptions) -> NBTResult<()> {
+    digest_io(writer.write_u8(TagIdent::TAG_Compound as u8))?;
+    write_compound::<F, W>(writer, elements, options)
+}
+
+// Function for writing a single named tag: its type, name and payload.
+pub(crate) fn write_named<F: NbtFlavor, W: Write + ?Sized>(writer: &mut W, name: &str, tag: &Tag, options: &WriteOptions) -> NBTResult<()> {
+    digest_io(writer.write_u8(tag.ident() as u8))?;
+    write_string::<F, W>(writer, name)?;
+    write_tag::<F, W>(writer, tag, options)
+}
+
+pub(crate) fn write_compound<F: NbtFlavor, W: Write + ?Sized>(writer: &mut W, compound: &CompoundMap, options: &WriteOptions) -> NBTResult<()> {
+    if options.sorted_keys {
+        let mut elements: Vec<(&String, &Tag)> = compound.iter().collect();
+        elements.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        write_elements::<F, W, _>(writer, elements, options)
+    } else {
+        write_elements::<F, W, _>(writer, compound, options)
+    }
 }
 
-pub(crate) fn write_compound<W: Write>(writer: &mut W, compound: &HashMap<String, Tag>) -> NBTResult<()> {
+fn write_elements<'a, F: NbtFlavor, W: Write + ?Sized, I: IntoIterator<Item = (&'a String, &'a Tag)>>(writer: &mut W, elements: I, options: &WriteOptions) -> NBTResult<()> {
     // Write items of compound
-    for (name, payload) in compound {
+    for (name, payload) in elements {
         // Write element tag
         digest_io(writer.write_u8(payload.ident() as u8))?;
 
         // Write element name
-        write_string(writer, &name)?;
+        write_string::<F, W>(writer, name)?;
 
         // write payload
-        write_tag(writer, payload)?;
+        write_tag::<F, W>(writer, payload, options)?;
     }
     digest_io(writer.write_u8(TagIdent::TAG_End as u8))
 }
 
 pub (crate) fn encode_wonky_string(s: &str) -> Vec<u8> {
-    cesu8::to_java_cesu8(&s).to_vec()
+    cesu8::to_java_cesu8(s).to_vec()
 }
\ No newline at end of file
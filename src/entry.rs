@@ -0,0 +1,128 @@
+use crate::tags::Tag;
+use crate::util::ToTag;
+#[cfg(not(feature="preserve_order"))]
+use std::collections::hash_map;
+#[cfg(feature="preserve_order")]
+use crate::ordered as hash_map;
+
+/// A view into a single element of a compound, which may be present or not.
+///
+/// Created by `Blob::entry` and `Compound::entry`. Values are inserted through `ToTag`, so
+/// anything that can be inserted into a blob can be inserted through an entry.
+///
+/// ## Example
+/// ```
+/// # use nbt::{Blob, Tag};
+/// let mut blob = Blob::new();
+///
+/// // Count deaths, starting from zero.
+/// for _ in 0..3 {
+///     blob.entry("deaths")
+///         .and_modify(|x| if let Tag::Int(x) = x { *x += 1 })
+///         .or_insert(1);
+/// }
+/// assert_eq!(blob.get::<i32>("deaths"), Some(&3));
+/// ```
+pub enum Entry<'a> {
+    /// An element that is present.
+    Occupied(OccupiedEntry<'a>),
+    /// An element that is missing.
+    Vacant(VacantEntry<'a>),
+}
+
+/// An element of a compound that is present. Part of `Entry`.
+pub struct OccupiedEntry<'a> {
+    inner: hash_map::OccupiedEntry<'a, String, Tag>,
+}
+
+/// An element of a compound that is missing. Part of `Entry`.
+pub struct VacantEntry<'a> {
+    inner: hash_map::VacantEntry<'a, String, Tag>,
+}
+
+impl<'a> Entry<'a> {
+    pub(crate) fn new(entry: hash_map::Entry<'a, String, Tag>) -> Entry<'a> {
+        match entry {
+            hash_map::Entry::Occupied(inner) => Entry::Occupied(OccupiedEntry { inner }),
+            hash_map::Entry::Vacant(inner) => Entry::Vacant(VacantEntry { inner }),
+        }
+    }
+
+    /// The key of the element.
+    pub fn key(&self) -> &str {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Insert `payload` if the element is missing, and return the element.
+    pub fn or_insert<P: ToTag>(self, payload: P) -> &'a mut Tag {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(payload),
+        }
+    }
+
+    /// Insert the result of `default` if the element is missing, and return the element.
+    ///
+    /// `default` is only called if the element is missing.
+    pub fn or_insert_with<P: ToTag, F: FnOnce() -> P>(self, default: F) -> &'a mut Tag {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Modify the element if it is present.
+    pub fn and_modify<F: FnOnce(&mut Tag)>(mut self, f: F) -> Entry<'a> {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a> OccupiedEntry<'a> {
+    /// The key of the element.
+    pub fn key(&self) -> &str {
+        self.inner.key()
+    }
+
+    /// Borrow the element.
+    pub fn get(&self) -> &Tag {
+        self.inner.get()
+    }
+
+    /// Mutably borrow the element.
+    pub fn get_mut(&mut self) -> &mut Tag {
+        self.inner.get_mut()
+    }
+
+    /// Mutably borrow the element for as long as the compound.
+    pub fn into_mut(self) -> &'a mut Tag {
+        self.inner.into_mut()
+    }
+
+    /// Replace the element, returning the old one.
+    pub fn insert<P: ToTag>(&mut self, payload: P) -> Tag {
+        self.inner.insert(payload.into_tag())
+    }
+
+    /// Remove the element from the compound, returning it.
+    pub fn remove(self) -> Tag {
+        self.inner.remove()
+    }
+}
+
+impl<'a> VacantEntry<'a> {
+    /// The key of the element.
+    pub fn key(&self) -> &str {
+        self.inner.key()
+    }
+
+    /// Insert the element, and return it.
+    pub fn insert<P: ToTag>(self, payload: P) -> &'a mut Tag {
+        self.inner.insert(payload.into_tag())
+    }
+}
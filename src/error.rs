@@ -12,7 +12,26 @@ pub enum NBTError {
     UnserializableType { type_name: String },
     InvalidType { found: TagIdent, expecting: TagIdent, when: String },
     InvalidChar,
-    NoData { when: String }
+    NoData { when: String },
+    RoundtripMismatch { path: String },
+    RegionChunkTooLarge { x: i32, z: i32 },
+    DuplicateRegionChunk { x: i32, z: i32 },
+    MemoryBudgetExceeded { limit: usize },
+    MissingKey { key: String },
+    UnknownKey { key: String },
+    NoMigration { from: i32, to: i32 },
+    Cancelled,
+    IndexOutOfBounds { index: usize, length: usize },
+    StringTooLong { length: usize },
+    InvalidBase64,
+    BufferTooSmall { capacity: usize },
+    UnexpectedRootName { expected: String, found: String },
+    DepthLimitExceeded { limit: usize },
+    LengthLimitExceeded { length: usize, limit: usize },
+    UnsignedOutOfRange { value: i128 },
+    InvalidPath { path: String },
+    InvalidSnbt { message: String, line: usize, column: usize, offset: usize },
+    UnsupportedCompression { compression: crate::Compression },
 }
 pub type NBTResult<T> = Result<T, NBTError>;
 
@@ -33,29 +52,56 @@ impl fmt::Display for NBTError {
             NBTError::InvalidList { found, expecting } => f.write_str(&format!("Invalid List. Was expecting type {} but found {}", expecting, found)),
             NBTError::InvalidTag { found } => f.write_str(&format!("Invalid Tag Identifier with value {:02X}", found)),
             NBTError::InvalidImplicit { found } => f.write_str(&format!("NBT blob does not start with a compound tag. Found {} tag", found)),
-            NBTError::StringError  => f.write_str(&format!("An error occurred while parsing a UTF-8/CESU8 string")),
+            NBTError::StringError  => f.write_str("An error occurred while parsing a UTF-8/CESU8 string"),
             NBTError::UnexpectedEndTag => f.write_str(&format!("An Unexpected {} was read.", TagIdent::TAG_End)),
             NBTError::Custom(e) => f.write_str(e),
             NBTError::UnserializableType {type_name} => f.write_str(&format!("The type '{}' cannot be serialized into NBT", type_name)),
             NBTError::InvalidType { found, expecting, when } => f.write_str(&format!("Found tag {}, was expecting {} when deserializing {}", found, expecting, when)),
             NBTError::InvalidChar => f.write_str(&format!("Failed to deserialize char, length of {} was not 1", TagIdent::TAG_String)),
             NBTError::NoData {when} => f.write_str(&format!("A value was required when deserializing {}, but none was given.", when)),
+            NBTError::RoundtripMismatch { path } => f.write_str(&format!("Round trip diverged at '{}'", path)),
+            NBTError::RegionChunkTooLarge { x, z } => f.write_str(&format!("Chunk ({}, {}) is larger than 255 sectors", x, z)),
+            NBTError::DuplicateRegionChunk { x, z } => f.write_str(&format!("Chunk ({}, {}) was given more than once", x, z)),
+            NBTError::MemoryBudgetExceeded { limit } => f.write_str(&format!("Reading exceeded the memory budget of {} bytes", limit)),
+            NBTError::MissingKey { key } => f.write_str(&format!("No element with key '{}'", key)),
+            NBTError::UnknownKey { key } => f.write_str(&format!("Key '{}' is not declared in the schema", key)),
+            NBTError::NoMigration { from, to } => f.write_str(&format!("No migration from data version {} towards {}", from, to)),
+            NBTError::Cancelled => f.write_str("The read was cancelled"),
+            NBTError::StringTooLong { length } => f.write_str(&format!("String of {} bytes is too long to be encoded", length)),
+            NBTError::InvalidBase64 => f.write_str("Data is not valid base64"),
+            NBTError::BufferTooSmall { capacity } => f.write_str(&format!("Buffer of {} bytes is too small for the encoded data", capacity)),
+            NBTError::UnexpectedRootName { expected, found } => f.write_str(&format!("Expected a root named '{}' but found '{}'", expected, found)),
+            NBTError::DepthLimitExceeded { limit } => f.write_str(&format!("Lists and compounds are nested deeper than the limit of {}", limit)),
+            NBTError::LengthLimitExceeded { length, limit } => f.write_str(&format!("Length of {} exceeds the limit of {}", length, limit)),
+            NBTError::UnsignedOutOfRange { value } => f.write_str(&format!("{} is out of range for a u64 stored as a long", value)),
+            NBTError::InvalidPath { path } => f.write_str(&format!("'{}' is not a valid path", path)),
+            NBTError::InvalidSnbt { message, line, column, .. } => f.write_str(&format!("Invalid SNBT: {} at line {}, column {}", message, line, column)),
+            NBTError::UnsupportedCompression { compression } => f.write_str(&format!("{} compression is not supported here", compression)),
+            NBTError::IndexOutOfBounds { index, length } => f.write_str(&format!("Index {} is out of bounds for a list of length {}", index, length)),
         }
     }
 }
 
 impl std::error::Error for NBTError {}
 
-#[cfg(feature= "serde")]
-use serde::{ser::Error as SerializeError, de::Error as DeserializeError};
+impl From<std::io::Error> for NBTError {
+    fn from(error: std::io::Error) -> Self {
+        NBTError::IO { error }
+    }
+}
+
+#[cfg(feature= "serde-ser")]
+use serde::ser::Error as SerializeError;
+#[cfg(feature= "serde-de")]
+use serde::de::Error as DeserializeError;
 
-#[cfg(feature= "serde")]
+#[cfg(feature= "serde-ser")]
 impl SerializeError for NBTError {
     fn custom<T>(msg: T) -> Self where T: fmt::Display {
         Self::Custom(msg.to_string())
     }
 }
-#[cfg(feature= "serde")]
+#[cfg(feature= "serde-de")]
 impl DeserializeError for NBTError {
     fn custom<T>(msg: T) -> Self where T: fmt::Display {
         Self::Custom(msg.to_string())
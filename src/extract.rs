@@ -0,0 +1,114 @@
+use crate::tags::{Tag, TagIdent};
+use crate::error::{NBTResult, NBTError, digest_io};
+use crate::decode::{read_ident, read_string, read_tag, ReadContext};
+use crate::flavor::{NbtFlavor, JavaFlavor};
+use crate::options::ReadOptions;
+use crate::path::{parse_pattern, PathPattern, PathSegment};
+use crate::validate::{Validator, ValidateLimits};
+use std::io::Read;
+
+/// Read the tags at a path from a document, without building the rest of it.
+///
+/// The path is written as in `NbtPath`, such as `Level.Sections[0].Palette`, and may use `[*]` to
+/// match every item of a list. Only the matching tags are built, while everything else is skipped
+/// over as it is streamed, so pulling one field out of a chunk costs little more than reading its
+/// bytes. The whole document is read, leaving the reader at its end.
+///
+/// Matches are returned in the order they appear. A malformed path fails with
+/// `NBTError::InvalidPath`.
+///
+/// ## Example
+/// ```
+/// use nbt::{extract_path, nbt, NBTWrite, Tag};
+///
+/// let chunk = nbt!(Blob {
+///     "Level": {
+///         "Sections": [
+///             { "Y": 0i8, "Palette": ["minecraft:bedrock"] },
+///             { "Y": 1i8, "Palette": ["minecraft:stone", "minecraft:air"] },
+///         ],
+///         "Heightmap": vec![64; 256],
+///     },
+/// });
+/// let bytes = chunk.bytes().unwrap();
+///
+/// let heights = extract_path(&mut &bytes[..], "Level.Sections[*].Y").unwrap();
+/// assert_eq!(heights, vec![Tag::Byte(0), Tag::Byte(1)]);
+///
+/// let palette = extract_path(&mut &bytes[..], "Level.Sections[1].Palette[0]").unwrap();
+/// assert_eq!(palette, vec![Tag::String("minecraft:stone".to_string())]);
+/// ```
+pub fn extract_path<R: Read + ?Sized>(reader: &mut R, path: &str) -> NBTResult<Vec<Tag>> {
+    extract_path_flavor::<JavaFlavor, R>(reader, path)
+}
+
+/// Read the tags at a path from a document of the given flavor, without building the rest of it.
+/// See `extract_path`.
+pub fn extract_path_flavor<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, path: &str) -> NBTResult<Vec<Tag>> {
+    let pattern = match parse_pattern(path) {
+        Some(pattern) => pattern,
+        None => return Err(NBTError::InvalidPath { path: path.to_string() }),
+    };
+
+    let options = ReadOptions::default();
+    let limits = ValidateLimits::default();
+    let mut extractor = Extractor { ctx: ReadContext::new(&options, None), skip: Validator::new(&limits), found: Vec::new() };
+
+    match read_ident(reader)? {
+        TagIdent::TAG_Compound => {}
+        found => return Err(NBTError::InvalidImplicit { found }),
+    }
+    read_string::<F, R>(reader, &mut extractor.ctx)?;
+    extractor.payload::<F, R>(reader, &TagIdent::TAG_Compound, &pattern)?;
+    Ok(extractor.found)
+}
+
+struct Extractor<'a> {
+    // Used to build the matching tags.
+    ctx: ReadContext<'a>,
+    // Used to skip everything else.
+    skip: Validator<'a>,
+    found: Vec<Tag>,
+}
+
+impl Extractor<'_> {
+    // Read a payload, collecting the tags within it matched by `pattern`.
+    fn payload<F: NbtFlavor, R: Read + ?Sized>(&mut self, reader: &mut R, ident: &TagIdent, pattern: &[PathPattern]) -> NBTResult<()> {
+        let (step, rest) = match pattern.split_first() {
+            Some(x) => x,
+            None => {
+                let tag = read_tag::<F, R>(reader, ident, &mut self.ctx)?;
+                self.found.push(tag);
+                return Ok(());
+            }
+        };
+
+        match ident {
+            TagIdent::TAG_Compound => loop {
+                let ident = read_ident(reader)?;
+                if ident == TagIdent::TAG_End {
+                    break;
+                }
+                let key = read_string::<F, R>(reader, &mut self.ctx)?;
+                match step {
+                    PathPattern::Segment(PathSegment::Key(x)) if *x == key => self.payload::<F, R>(reader, &ident, rest)?,
+                    _ => self.skip.payload::<F, R>(reader, &ident)?,
+                }
+            },
+            TagIdent::TAG_List => {
+                let item = read_ident(reader)?;
+                let length = digest_io(F::read_length(reader))?;
+                for i in 0..length as usize {
+                    match step {
+                        PathPattern::Segment(PathSegment::Index(x)) if *x == i => self.payload::<F, R>(reader, &item, rest)?,
+                        PathPattern::AnyIndex => self.payload::<F, R>(reader, &item, rest)?,
+                        _ => self.skip.payload::<F, R>(reader, &item)?,
+                    }
+                }
+            }
+            // The path continues past a tag that holds no others, so nothing here matches.
+            _ => self.skip.payload::<F, R>(reader, ident)?,
+        }
+        Ok(())
+    }
+}
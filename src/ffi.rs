@@ -0,0 +1,111 @@
+//! # C FFI
+//! A C ABI over opaque tag handles, for linking from C, C++, C# and other languages.
+//!
+//! Build a shared or static library with
+//! `cargo rustc --release --features ffi --crate-type cdylib` (or `staticlib`).
+//! The matching declarations are in `include/nbt.h`.
+//!
+//! Every handle returned by `nbt_parse` or `nbt_get_path` is owned by the caller and must be
+//! released with `nbt_free`. Strings returned by `nbt_to_snbt` must be released with
+//! `nbt_string_free`. Failures are signalled by returning a null pointer.
+
+use crate::blob::Blob;
+use crate::front::NBTRead;
+use crate::path::{parse_path, lookup};
+use crate::snbt::to_string;
+use crate::tags::Tag;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+use std::slice;
+
+/// An opaque handle to a tag.
+pub struct NbtHandle {
+    tag: Tag,
+}
+
+fn into_handle(tag: Tag) -> *mut NbtHandle {
+    Box::into_raw(Box::new(NbtHandle { tag }))
+}
+
+/// Parse an uncompressed NBT document, returning a handle to its root compound.
+///
+/// Returns null if `data` is null or the document cannot be parsed.
+///
+/// # Safety
+/// `data` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn nbt_parse(data: *const u8, len: usize) -> *mut NbtHandle {
+    if data.is_null() {
+        return ptr::null_mut();
+    }
+    match Blob::from_bytes(slice::from_raw_parts(data, len)) {
+        Ok(blob) => into_handle(blob.compound()),
+        Err(_) => ptr::null_mut()
+    }
+}
+
+/// Look up a nested tag by a path such as `Level.Sections[0].Y`, returning a handle to a copy of it.
+///
+/// Returns null if the path is malformed or does not exist.
+///
+/// # Safety
+/// `handle` must be a live handle and `path` a nul-terminated string, or null.
+#[no_mangle]
+pub unsafe extern "C" fn nbt_get_path(handle: *const NbtHandle, path: *const c_char) -> *mut NbtHandle {
+    if handle.is_null() || path.is_null() {
+        return ptr::null_mut();
+    }
+    let segments = match CStr::from_ptr(path).to_str().ok().and_then(parse_path) {
+        Some(segments) => segments,
+        None => return ptr::null_mut()
+    };
+    let found = match &(*handle).tag {
+        _ if segments.is_empty() => Some(&(*handle).tag),
+        Tag::Compound(compound) => lookup(compound, &segments),
+        _ => None
+    };
+    match found {
+        Some(tag) => into_handle(tag.clone()),
+        None => ptr::null_mut()
+    }
+}
+
+/// Format a tag as SNBT, returning a nul-terminated string.
+///
+/// Returns null if the handle is null or the output contains a nul character.
+///
+/// # Safety
+/// `handle` must be a live handle, or null.
+#[no_mangle]
+pub unsafe extern "C" fn nbt_to_snbt(handle: *const NbtHandle) -> *mut c_char {
+    if handle.is_null() {
+        return ptr::null_mut();
+    }
+    match CString::new(to_string(&(*handle).tag)) {
+        Ok(string) => string.into_raw(),
+        Err(_) => ptr::null_mut()
+    }
+}
+
+/// Release a handle. Null is ignored.
+///
+/// # Safety
+/// `handle` must have been returned by this library and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn nbt_free(handle: *mut NbtHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Release a string returned by `nbt_to_snbt`. Null is ignored.
+///
+/// # Safety
+/// `string` must have been returned by `nbt_to_snbt` and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn nbt_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
@@ -0,0 +1,142 @@
+use crate::blob::Blob;
+use crate::error::{NBTResult, digest_io};
+use crate::flavor::{Dialect, JavaFlavor, BedrockFlavor, NbtFlavor};
+use crate::front::{NBTRead, NBTWrite};
+use crate::options::ReadOptions;
+#[cfg(feature="compression")]
+use crate::compression::{compress, decompress, detect};
+use std::io::Cursor;
+use std::path::Path;
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// A compression format used to wrap NBT data.
+///
+/// Compressing and decompressing need the `compression` feature. The type is present without it,
+/// so that `NBTError::UnsupportedCompression` does not depend on features.
+pub enum Compression {
+    /// Gzip, used by `level.dat` and player data files.
+    Gzip,
+    /// Zlib, used by region file chunks.
+    Zlib,
+    /// Raw DEFLATE, without a gzip or zlib header, as emitted by some tools.
+    Deflate,
+    /// Zstandard, used by some world storage backends. See `ZstdDictionary` for small payloads.
+    ///
+    /// Requires the `zstd` feature. Without it, the variant is still present so that matches don't
+    /// depend on features, but compressing or decompressing with it fails with
+    /// `NBTError::UnsupportedCompression`.
+    Zstd,
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compression::Gzip => f.write_str("Gzip"),
+            Compression::Zlib => f.write_str("Zlib"),
+            Compression::Deflate => f.write_str("Deflate"),
+            Compression::Zstd => f.write_str("Zstd"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature="debug", derive(Debug))]
+#[non_exhaustive]
+/// The format a blob was read in: its dialect, and the compression of the file it came from.
+pub struct SourceFormat {
+    /// The dialect of the data.
+    pub dialect: Dialect,
+    /// The compression of the data, or `None` if it was not compressed.
+    pub compression: Option<Compression>,
+}
+
+impl SourceFormat {
+    /// Create a format for uncompressed data of a dialect.
+    pub fn new(dialect: Dialect) -> SourceFormat {
+        SourceFormat { dialect, compression: None }
+    }
+
+    /// Set the compression of the format.
+    pub fn compression(mut self, compression: Option<Compression>) -> SourceFormat {
+        self.compression = compression;
+        self
+    }
+}
+
+// Read a document of a flavor, if it reads cleanly and spans all of the data.
+fn read_exact<F: NbtFlavor>(data: &[u8]) -> Option<Blob> {
+    let mut cursor = Cursor::new(data);
+    let blob = Blob::read_flavor_with::<F, _>(&mut cursor, &ReadOptions::default(), None).ok()?;
+    if cursor.position() as usize != data.len() {
+        return None;
+    }
+    Some(blob)
+}
+
+impl Blob {
+    /// Read a blob from a file, detecting its format.
+    ///
+    /// With the `compression` feature, gzip, zlib and zstd compressed files are detected from their
+    /// header, and raw DEFLATE, which has none, is tried for files that read as neither dialect. The data is read as Java NBT, or as Bedrock NBT if that fails or leaves bytes over.
+    /// The detected format is recorded in `source`.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use nbt::Blob;
+    /// let mut blob = Blob::from_file("level.dat").unwrap();
+    /// blob.insert("edited", 1i8);
+    /// blob.save_same_format("level.dat").unwrap();
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P) -> NBTResult<Blob> {
+        let data = digest_io(std::fs::read(path))?;
+
+        #[cfg(feature="compression")]
+        let mut compression = detect(&data);
+        #[cfg(not(feature="compression"))]
+        let compression: Option<Compression> = None;
+        #[cfg(feature="compression")]
+        let data = match compression {
+            Some(compression) => decompress(&data, compression)?,
+            None => data,
+        };
+
+        let read = |data: &[u8]| read_exact::<JavaFlavor>(data).or_else(|| read_exact::<BedrockFlavor>(data));
+        #[allow(unused_mut)]
+        let mut exact = read(&data);
+        #[cfg(feature="compression")]
+        if exact.is_none() && compression.is_none() {
+            if let Some(blob) = decompress(&data, Compression::Deflate).ok().and_then(|x| read(&x)) {
+                exact = Some(blob);
+                compression = Some(Compression::Deflate);
+            }
+        }
+
+        // Fall back to a plain read for its error, or to accept trailing bytes.
+        let blob = match exact {
+            Some(blob) => blob,
+            None => Blob::from_bytes(&data)?,
+        };
+        Ok(blob.with_compression(compression))
+    }
+
+    /// Write the blob to a file in the format it was read in.
+    ///
+    /// Blobs without a recorded `source` are written as uncompressed Java NBT.
+    pub fn save_same_format<P: AsRef<Path>>(&self, path: P) -> NBTResult<()> {
+        let format = self.source.unwrap_or_else(|| SourceFormat::new(Dialect::Java));
+        let mut data = Vec::new();
+        match format.dialect {
+            Dialect::Java => self.write_flavor::<JavaFlavor, _>(&mut data)?,
+            Dialect::Bedrock => self.write_flavor::<BedrockFlavor, _>(&mut data)?,
+        }
+
+        #[cfg(feature="compression")]
+        let data = match format.compression {
+            Some(compression) => compress(&data, compression)?,
+            None => data,
+        };
+        digest_io(std::fs::write(path, data))
+    }
+}
@@ -0,0 +1,233 @@
+use crate::decode::{decode_wonky_string, decode_wonky_string_lossy, decode_wonky_string_strict};
+use crate::encode::encode_wonky_string;
+use crate::varint::{read_var_u32, write_var_u32, read_var_i32, write_var_i32, read_var_i64, write_var_i64};
+use crate::error::{NBTResult, NBTError, digest_io};
+use byteorder::{ReadBytesExt, WriteBytesExt, BigEndian, LittleEndian};
+use std::io::{Read, Write, Result, Error, ErrorKind};
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// A built-in NBT dialect, identifying one of the flavors at runtime.
+pub enum Dialect {
+    /// `JavaFlavor`.
+    Java,
+    /// `BedrockFlavor`.
+    Bedrock,
+}
+
+/// The byte-level encoding of a NBT dialect.
+///
+/// The structure of NBT is the same in every dialect, but the encoding of numbers, lengths and
+/// strings differs. Reading and writing is generic over a flavor, so a custom dialect only needs
+/// to implement this trait.
+///
+/// The default methods read and write lengths as ints.
+///
+/// ## Example
+/// ```
+/// use nbt::{Blob, NBTRead, NBTWrite, BedrockFlavor};
+///
+/// let mut blob = Blob::new();
+/// blob.insert("version", 1);
+///
+/// let mut buffer = Vec::new();
+/// blob.write_flavor::<BedrockFlavor, _>(&mut buffer).unwrap();
+/// assert_eq!(buffer, vec![10, 0, 0, 3, 7, 0, 118, 101, 114, 115, 105, 111, 110, 1, 0, 0, 0, 0]);
+///
+/// let read = Blob::read_flavor::<BedrockFlavor, _>(&mut &buffer[..]).unwrap();
+/// assert_eq!(read.get::<i32>("version"), Some(&1));
+/// ```
+pub trait NbtFlavor {
+    /// The built-in dialect this flavor implements, recorded on blobs read with it.
+    ///
+    /// This is `None` for custom flavors.
+    const DIALECT: Option<Dialect> = None;
+
+    /// Whether ints and longs are always 4 and 8 bytes long, so arrays of them can be skipped by
+    /// their length alone.
+    const FIXED_WIDTH: bool = true;
+
+    fn read_short<R: Read + ?Sized>(reader: &mut R) -> Result<i16>;
+    fn read_int<R: Read + ?Sized>(reader: &mut R) -> Result<i32>;
+    fn read_long<R: Read + ?Sized>(reader: &mut R) -> Result<i64>;
+    fn read_float<R: Read + ?Sized>(reader: &mut R) -> Result<f32>;
+    fn read_double<R: Read + ?Sized>(reader: &mut R) -> Result<f64>;
+
+    fn write_short<W: Write + ?Sized>(writer: &mut W, value: i16) -> Result<()>;
+    fn write_int<W: Write + ?Sized>(writer: &mut W, value: i32) -> Result<()>;
+    fn write_long<W: Write + ?Sized>(writer: &mut W, value: i64) -> Result<()>;
+    fn write_float<W: Write + ?Sized>(writer: &mut W, value: f32) -> Result<()>;
+    fn write_double<W: Write + ?Sized>(writer: &mut W, value: f64) -> Result<()>;
+
+    /// Read the length of a list or array.
+    fn read_length<R: Read + ?Sized>(reader: &mut R) -> Result<u32> {
+        Self::read_int(reader).map(|x| x as u32)
+    }
+
+    /// Write the length of a list or array.
+    fn write_length<W: Write + ?Sized>(writer: &mut W, length: u32) -> Result<()> {
+        Self::write_int(writer, length as i32)
+    }
+
+    /// Read the length of a string, in bytes.
+    fn read_string_length<R: Read + ?Sized>(reader: &mut R) -> Result<usize>;
+
+    /// Write the length of a string, in bytes.
+    ///
+    /// Fails with `NBTError::StringTooLong` if the length cannot be represented.
+    fn write_string_length<W: Write + ?Sized>(writer: &mut W, length: usize) -> NBTResult<()>;
+
+    /// Decode the bytes of a string.
+    fn decode_string(bytes: &[u8]) -> NBTResult<String>;
+
+    /// Decode the bytes of a string, replacing anything undecodable with U+FFFD.
+    fn decode_string_lossy(bytes: &[u8]) -> String;
+
+    /// Decode the bytes of a string, only accepting the exact encoding of the dialect.
+    ///
+    /// The default is the same as `decode_string`.
+    fn decode_string_strict(bytes: &[u8]) -> NBTResult<String> {
+        Self::decode_string(bytes)
+    }
+
+    /// Encode a string into bytes.
+    fn encode_string(string: &str) -> Vec<u8>;
+}
+
+/// Java Edition NBT: big endian, with modified UTF-8 strings.
+///
+/// This is the flavor used by default.
+pub struct JavaFlavor;
+
+impl NbtFlavor for JavaFlavor {
+    const DIALECT: Option<Dialect> = Some(Dialect::Java);
+
+    fn read_short<R: Read + ?Sized>(reader: &mut R) -> Result<i16> { reader.read_i16::<BigEndian>() }
+    fn read_int<R: Read + ?Sized>(reader: &mut R) -> Result<i32> { reader.read_i32::<BigEndian>() }
+    fn read_long<R: Read + ?Sized>(reader: &mut R) -> Result<i64> { reader.read_i64::<BigEndian>() }
+    fn read_float<R: Read + ?Sized>(reader: &mut R) -> Result<f32> { reader.read_f32::<BigEndian>() }
+    fn read_double<R: Read + ?Sized>(reader: &mut R) -> Result<f64> { reader.read_f64::<BigEndian>() }
+
+    fn write_short<W: Write + ?Sized>(writer: &mut W, value: i16) -> Result<()> { writer.write_i16::<BigEndian>(value) }
+    fn write_int<W: Write + ?Sized>(writer: &mut W, value: i32) -> Result<()> { writer.write_i32::<BigEndian>(value) }
+    fn write_long<W: Write + ?Sized>(writer: &mut W, value: i64) -> Result<()> { writer.write_i64::<BigEndian>(value) }
+    fn write_float<W: Write + ?Sized>(writer: &mut W, value: f32) -> Result<()> { writer.write_f32::<BigEndian>(value) }
+    fn write_double<W: Write + ?Sized>(writer: &mut W, value: f64) -> Result<()> { writer.write_f64::<BigEndian>(value) }
+
+    fn read_string_length<R: Read + ?Sized>(reader: &mut R) -> Result<usize> {
+        reader.read_u16::<BigEndian>().map(|x| x as usize)
+    }
+
+    fn write_string_length<W: Write + ?Sized>(writer: &mut W, length: usize) -> NBTResult<()> {
+        if length > u16::MAX as usize {
+            return Err(NBTError::StringTooLong { length });
+        }
+        digest_io(writer.write_u16::<BigEndian>(length as u16))
+    }
+
+    fn decode_string(bytes: &[u8]) -> NBTResult<String> { decode_wonky_string(bytes) }
+    fn decode_string_lossy(bytes: &[u8]) -> String { decode_wonky_string_lossy(bytes) }
+    fn decode_string_strict(bytes: &[u8]) -> NBTResult<String> { decode_wonky_string_strict(bytes) }
+    fn encode_string(string: &str) -> Vec<u8> { encode_wonky_string(string) }
+}
+
+/// Bedrock Edition NBT, as stored on disk: little endian, with UTF-8 strings.
+pub struct BedrockFlavor;
+
+impl NbtFlavor for BedrockFlavor {
+    const DIALECT: Option<Dialect> = Some(Dialect::Bedrock);
+
+    fn read_short<R: Read + ?Sized>(reader: &mut R) -> Result<i16> { reader.read_i16::<LittleEndian>() }
+    fn read_int<R: Read + ?Sized>(reader: &mut R) -> Result<i32> { reader.read_i32::<LittleEndian>() }
+    fn read_long<R: Read + ?Sized>(reader: &mut R) -> Result<i64> { reader.read_i64::<LittleEndian>() }
+    fn read_float<R: Read + ?Sized>(reader: &mut R) -> Result<f32> { reader.read_f32::<LittleEndian>() }
+    fn read_double<R: Read + ?Sized>(reader: &mut R) -> Result<f64> { reader.read_f64::<LittleEndian>() }
+
+    fn write_short<W: Write + ?Sized>(writer: &mut W, value: i16) -> Result<()> { writer.write_i16::<LittleEndian>(value) }
+    fn write_int<W: Write + ?Sized>(writer: &mut W, value: i32) -> Result<()> { writer.write_i32::<LittleEndian>(value) }
+    fn write_long<W: Write + ?Sized>(writer: &mut W, value: i64) -> Result<()> { writer.write_i64::<LittleEndian>(value) }
+    fn write_float<W: Write + ?Sized>(writer: &mut W, value: f32) -> Result<()> { writer.write_f32::<LittleEndian>(value) }
+    fn write_double<W: Write + ?Sized>(writer: &mut W, value: f64) -> Result<()> { writer.write_f64::<LittleEndian>(value) }
+
+    fn read_string_length<R: Read + ?Sized>(reader: &mut R) -> Result<usize> {
+        reader.read_u16::<LittleEndian>().map(|x| x as usize)
+    }
+
+    fn write_string_length<W: Write + ?Sized>(writer: &mut W, length: usize) -> NBTResult<()> {
+        if length > u16::MAX as usize {
+            return Err(NBTError::StringTooLong { length });
+        }
+        digest_io(writer.write_u16::<LittleEndian>(length as u16))
+    }
+
+    fn decode_string(bytes: &[u8]) -> NBTResult<String> {
+        String::from_utf8(bytes.to_vec()).map_err(|_| NBTError::StringError)
+    }
+    fn decode_string_lossy(bytes: &[u8]) -> String { String::from_utf8_lossy(bytes).into_owned() }
+    fn encode_string(string: &str) -> Vec<u8> { string.as_bytes().to_vec() }
+}
+
+/// Bedrock Edition NBT, as sent over the network in packets.
+///
+/// Shorts, floats and doubles are little endian, as in `BedrockFlavor`. Ints, longs and the lengths
+/// of lists and arrays are zigzag encoded VarInts, and strings are UTF-8 with an unsigned VarInt
+/// length.
+///
+/// ## Example
+/// ```
+/// use nbt::{Blob, Tag, NBTRead, NBTWrite, NetworkLittleEndian};
+///
+/// let mut buffer = Vec::new();
+/// Tag::String("a".repeat(200)).write_flavor::<NetworkLittleEndian, _>(&mut buffer).unwrap();
+/// assert_eq!(&buffer[..2], &[0xc8, 0x01]);
+///
+/// let mut blob = Blob::new();
+/// blob.insert("x", -1);
+///
+/// let mut buffer = Vec::new();
+/// blob.write_flavor::<NetworkLittleEndian, _>(&mut buffer).unwrap();
+/// assert_eq!(buffer, vec![10, 0, 3, 1, 120, 1, 0]);
+///
+/// let read = Blob::read_flavor::<NetworkLittleEndian, _>(&mut &buffer[..]).unwrap();
+/// assert_eq!(read.get::<i32>("x"), Some(&-1));
+/// ```
+pub struct NetworkLittleEndian;
+
+impl NbtFlavor for NetworkLittleEndian {
+    const FIXED_WIDTH: bool = false;
+
+    fn read_short<R: Read + ?Sized>(reader: &mut R) -> Result<i16> { reader.read_i16::<LittleEndian>() }
+    fn read_int<R: Read + ?Sized>(reader: &mut R) -> Result<i32> { read_var_i32(reader) }
+    fn read_long<R: Read + ?Sized>(reader: &mut R) -> Result<i64> { read_var_i64(reader) }
+    fn read_float<R: Read + ?Sized>(reader: &mut R) -> Result<f32> { reader.read_f32::<LittleEndian>() }
+    fn read_double<R: Read + ?Sized>(reader: &mut R) -> Result<f64> { reader.read_f64::<LittleEndian>() }
+
+    fn write_short<W: Write + ?Sized>(writer: &mut W, value: i16) -> Result<()> { writer.write_i16::<LittleEndian>(value) }
+    fn write_int<W: Write + ?Sized>(writer: &mut W, value: i32) -> Result<()> { write_var_i32(writer, value) }
+    fn write_long<W: Write + ?Sized>(writer: &mut W, value: i64) -> Result<()> { write_var_i64(writer, value) }
+    fn write_float<W: Write + ?Sized>(writer: &mut W, value: f32) -> Result<()> { writer.write_f32::<LittleEndian>(value) }
+    fn write_double<W: Write + ?Sized>(writer: &mut W, value: f64) -> Result<()> { writer.write_f64::<LittleEndian>(value) }
+
+    // Lengths are signed VarInts, so a negative one is invalid rather than huge.
+    fn read_length<R: Read + ?Sized>(reader: &mut R) -> Result<u32> {
+        match read_var_i32(reader)? {
+            length if length < 0 => Err(Error::new(ErrorKind::InvalidData, "negative length")),
+            length => Ok(length as u32)
+        }
+    }
+
+    fn read_string_length<R: Read + ?Sized>(reader: &mut R) -> Result<usize> {
+        read_var_u32(reader).map(|x| x as usize)
+    }
+
+    fn write_string_length<W: Write + ?Sized>(writer: &mut W, length: usize) -> NBTResult<()> {
+        if length > u32::MAX as usize {
+            return Err(NBTError::StringTooLong { length });
+        }
+        digest_io(write_var_u32(writer, length as u32))
+    }
+
+    fn decode_string(bytes: &[u8]) -> NBTResult<String> { BedrockFlavor::decode_string(bytes) }
+    fn decode_string_lossy(bytes: &[u8]) -> String { BedrockFlavor::decode_string_lossy(bytes) }
+    fn encode_string(string: &str) -> Vec<u8> { BedrockFlavor::encode_string(string) }
+}
@@ -1,39 +1,145 @@
-use std::io::{Write, Read, Cursor};
+use std::io::{Write, Read, Seek, SeekFrom, Cursor, ErrorKind};
 use crate::error::{NBTResult, NBTError};
 use crate::tags::Tag;
-use crate::encode::{write_tag, write_root};
+use crate::encode::{write_tag, write_root, write_named, write_nameless_root};
 use crate::blob::Blob;
-use crate::decode::{read_tag, read_ident, read_root};
-use crate::TagIdent;
+use crate::compound::Compound;
+use crate::shared::SharedTag;
+use crate::tags::CompoundMap;
+use crate::decode::{read_tag, read_ident, read_root, read_named, read_nameless_root, read_shared_root, ReadContext};
+use crate::diagnostics::{Diagnostics, Diagnostic};
+use crate::options::{ReadOptions, WriteOptions};
+use crate::counter::ByteCounter;
+use crate::flavor::{NbtFlavor, JavaFlavor};
+use crate::file::SourceFormat;
+
+#[cfg(feature="bytes")]
+use bytes::{Buf, BufMut};
+#[cfg(feature="checksum")]
+use crate::checksum::{Digest, DigestWriter};
 
-#[cfg(feature="serde")]
+#[cfg(feature="serde-ser")]
 use serde::Serialize;
-#[cfg(feature="serde")]
+#[cfg(feature="serde-de")]
 use serde::de::DeserializeOwned;
-#[cfg(feature="serde")]
+#[cfg(feature="serde-de")]
 use crate::de::NBTDeserializer;
-#[cfg(feature="serde")]
+#[cfg(feature="serde-ser")]
 use crate::ser::NBTSerializer;
+#[cfg(feature="serde-ser")]
+use crate::TagIdent;
+#[cfg(any(feature="serde-ser", feature="serde-de"))]
+use crate::options::SerdeOptions;
+#[cfg(feature="serde-ser")]
+use crate::options::RootHandling;
 
 /// A trait supporting encoding of NBT Tags/Blobs into bytes.
+///
+/// Writers may be unsized, so a `&mut dyn Write` can be passed across a dynamic boundary.
+/// ```
+/// use nbt::{Tag, NBTWrite};
+/// use std::io::Write;
+///
+/// fn save(tag: &Tag, writer: &mut dyn Write) {
+///     tag.write(writer).unwrap();
+/// }
+///
+/// let mut buffer = Vec::new();
+/// save(&Tag::Int(42), &mut buffer);
+/// assert_eq!(buffer, vec![0, 0, 0, 42]);
+/// ```
 pub trait NBTWrite {
-    fn write<W: Write>(&self, writer: &mut W) -> NBTResult<()>;
+    /// Write in the given dialect with the given options.
+    fn write_flavor_with<F: NbtFlavor, W: Write + ?Sized>(&self, writer: &mut W, options: &WriteOptions) -> NBTResult<()>;
+
+    /// Write in the given dialect.
+    fn write_flavor<F: NbtFlavor, W: Write + ?Sized>(&self, writer: &mut W) -> NBTResult<()> {
+        self.write_flavor_with::<F, W>(writer, &WriteOptions::default())
+    }
+
+    /// Write in the Java dialect with the given options.
+    fn write_with<W: Write + ?Sized>(&self, writer: &mut W, options: &WriteOptions) -> NBTResult<()> {
+        self.write_flavor_with::<JavaFlavor, W>(writer, options)
+    }
+
+    /// Write in the Java dialect.
+    fn write<W: Write + ?Sized>(&self, writer: &mut W) -> NBTResult<()> {
+        self.write_with(writer, &WriteOptions::default())
+    }
 
     fn bytes(&self) -> NBTResult<Vec<u8>> {
         let mut buffer = Vec::new();
         self.write(&mut buffer)?;
         Ok(buffer)
     }
+
+    /// Write into a preallocated buffer without allocating, returning the number of bytes written.
+    ///
+    /// Fails with `NBTError::BufferTooSmall` if the value does not fit, in which case the buffer
+    /// holds a partial write.
+    ///
+    /// ```
+    /// use nbt::{Tag, NBTWrite, NBTError};
+    ///
+    /// let mut buffer = [0u8; 8];
+    /// assert_eq!(Tag::Int(42).encode_to_slice(&mut buffer).unwrap(), 4);
+    /// assert_eq!(buffer[..4], [0, 0, 0, 42]);
+    ///
+    /// let long = Tag::Long(42);
+    /// assert!(matches!(long.encode_to_slice(&mut buffer[..4]), Err(NBTError::BufferTooSmall { capacity: 4 })));
+    /// ```
+    fn encode_to_slice(&self, buffer: &mut [u8]) -> NBTResult<usize> {
+        let capacity = buffer.len();
+        let mut remaining = buffer;
+        match self.write(&mut remaining) {
+            Ok(()) => Ok(capacity - remaining.len()),
+            Err(NBTError::IO { error }) if error.kind() == ErrorKind::WriteZero => Err(NBTError::BufferTooSmall { capacity }),
+            Err(error) => Err(error)
+        }
+    }
+
+    /// Write to a writer, returning the number of bytes written.
+    fn write_counted<W: Write + ?Sized>(&self, writer: &mut W) -> NBTResult<usize> {
+        let mut counter = ByteCounter::new(writer);
+        self.write(&mut counter)?;
+        Ok(counter.count())
+    }
+
+    #[cfg(feature="checksum")]
+    /// Output to a Vec, along with the checksums of the bytes.
+    fn bytes_with_digest(&self) -> NBTResult<(Vec<u8>, Digest)> {
+        let mut writer = DigestWriter::new(Vec::new());
+        self.write(&mut writer)?;
+        Ok(writer.finish())
+    }
+
+    #[cfg(feature="bytes")]
+    /// Write into a `bytes::BufMut`, such as a `BytesMut` packet buffer.
+    fn write_buf<B: BufMut>(&self, buf: &mut B) -> NBTResult<()> {
+        self.write(&mut BufMut::writer(buf))
+    }
 }
 
 impl NBTWrite for Tag {
-    fn write<W: Write>(&self, writer: &mut W) -> NBTResult<()> {
-        write_tag(writer, &self)
+    fn write_flavor_with<F: NbtFlavor, W: Write + ?Sized>(&self, writer: &mut W, options: &WriteOptions) -> NBTResult<()> {
+        write_tag::<F, W>(writer, self, options)
     }
 }
 impl NBTWrite for Blob {
-    fn write<W: Write>(&self, writer: &mut W) -> NBTResult<()> {
-        write_root(writer, &self.root, &self.elements)
+    fn write_flavor_with<F: NbtFlavor, W: Write + ?Sized>(&self, writer: &mut W, options: &WriteOptions) -> NBTResult<()> {
+        write_root::<F, W>(writer, &self.root, &self.elements, options)
+    }
+}
+/// Written as a document with an empty root name.
+impl NBTWrite for CompoundMap {
+    fn write_flavor_with<F: NbtFlavor, W: Write + ?Sized>(&self, writer: &mut W, options: &WriteOptions) -> NBTResult<()> {
+        write_root::<F, W>(writer, "", self, options)
+    }
+}
+/// Written as a document with an empty root name.
+impl NBTWrite for Compound {
+    fn write_flavor_with<F: NbtFlavor, W: Write + ?Sized>(&self, writer: &mut W, options: &WriteOptions) -> NBTResult<()> {
+        write_root::<F, W>(writer, "", &self.elements, options)
     }
 }
 
@@ -43,31 +149,264 @@ impl NBTWrite for Blob {
 /// - `read` for reading from a readable source or buffer
 /// - `from_bytes` for reading from a array of bytes
 ///
+/// Both have a `_with_options` counterpart taking `ReadOptions`, a `_with_diagnostics`
+/// counterpart that records non-fatal oddities and statistics into a `Diagnostics` sink, and a `_with`
+/// counterpart taking both.
+///
+/// Readers may be unsized, so a `&mut dyn Read` can be passed across a dynamic boundary.
+/// ```
+/// use nbt::{Tag, NBTRead};
+/// use std::io::Read;
+///
+/// fn load(reader: &mut dyn Read) -> Tag {
+///     Tag::read(reader).unwrap()
+/// }
 ///
+/// assert_eq!(load(&mut &[3, 0, 0, 0, 42][..]), Tag::Int(42));
+/// ```
 pub trait NBTRead: Sized {
+    /// Function for reading from a buffer in the given dialect with the given options, optionally
+    /// collecting diagnostics.
+    fn read_flavor_with<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, options: &ReadOptions, diagnostics: Option<&mut Diagnostics>) -> NBTResult<Self>;
+
+    /// Function for reading from a buffer with the given options, optionally collecting diagnostics.
+    fn read_with<R: Read + ?Sized>(reader: &mut R, options: &ReadOptions, diagnostics: Option<&mut Diagnostics>) -> NBTResult<Self> {
+        Self::read_flavor_with::<JavaFlavor, R>(reader, options, diagnostics)
+    }
+
     /// Function for reading from a buffer.
-    fn read<R: Read>(reader: &mut R) -> NBTResult<Self>;
+    fn read<R: Read + ?Sized>(reader: &mut R) -> NBTResult<Self> {
+        Self::read_with(reader, &ReadOptions::default(), None)
+    }
+
+    /// Function for reading from a buffer in the given dialect.
+    fn read_flavor<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R) -> NBTResult<Self> {
+        Self::read_flavor_with::<F, R>(reader, &ReadOptions::default(), None)
+    }
+
+    /// Function for reading from a buffer with the given options.
+    fn read_with_options<R: Read + ?Sized>(reader: &mut R, options: &ReadOptions) -> NBTResult<Self> {
+        Self::read_with(reader, options, None)
+    }
+
+    /// Function for reading from a buffer, collecting diagnostics.
+    fn read_with_diagnostics<R: Read + ?Sized>(reader: &mut R, diagnostics: &mut Diagnostics) -> NBTResult<Self> {
+        Self::read_with(reader, &ReadOptions::default(), Some(diagnostics))
+    }
+
+    /// Function for reading from a buffer, returning the number of bytes read along with the value.
+    ///
+    /// The reader is left positioned directly after the value, so it can carry on with following data.
+    fn read_counted<R: Read + ?Sized>(reader: &mut R) -> NBTResult<(Self, usize)> {
+        let mut counter = ByteCounter::new(reader);
+        let value = Self::read(&mut counter)?;
+        Ok((value, counter.count()))
+    }
+
+    /// Function for reading from a seekable buffer, rewinding it to where it started if the read fails.
+    ///
+    /// This allows a caller to retry the same data in a different format without buffering it.
+    ///
+    /// ```
+    /// use nbt::{Blob, Tag, NBTRead};
+    /// use std::io::Cursor;
+    ///
+    /// // A bare tag, which is not a valid blob.
+    /// let mut cursor = Cursor::new(vec![3, 0, 0, 0, 42]);
+    ///
+    /// assert!(Blob::read_or_rewind(&mut cursor).is_err());
+    /// assert_eq!(Tag::read(&mut cursor).unwrap(), Tag::Int(42));
+    /// ```
+    fn read_or_rewind<R: Read + Seek + ?Sized>(reader: &mut R) -> NBTResult<Self> {
+        Self::read_or_rewind_with(reader, &ReadOptions::default(), None)
+    }
+
+    /// Function for reading from a seekable buffer with the given options, optionally collecting
+    /// diagnostics, rewinding it to where it started if the read fails.
+    fn read_or_rewind_with<R: Read + Seek + ?Sized>(reader: &mut R, options: &ReadOptions, diagnostics: Option<&mut Diagnostics>) -> NBTResult<Self> {
+        let start = reader.stream_position()?;
+        match Self::read_with(reader, options, diagnostics) {
+            Ok(value) => Ok(value),
+            Err(error) => {
+                reader.seek(SeekFrom::Start(start))?;
+                Err(error)
+            }
+        }
+    }
 
     /// Function for reading from a byte array.
     fn from_bytes<B: AsRef<[u8]>>(data: B) -> NBTResult<Self> {
-        Self::read(&mut Cursor::new(data.as_ref().to_vec()))
+        Self::from_bytes_with(data, &ReadOptions::default(), None)
+    }
+
+    /// Function for reading from a byte array with the given options.
+    fn from_bytes_with_options<B: AsRef<[u8]>>(data: B, options: &ReadOptions) -> NBTResult<Self> {
+        Self::from_bytes_with(data, options, None)
+    }
+
+    /// Function for reading from a byte array, collecting diagnostics.
+    ///
+    /// Unlike `read_with_diagnostics`, this also reports any trailing bytes left after the document.
+    fn from_bytes_with_diagnostics<B: AsRef<[u8]>>(data: B, diagnostics: &mut Diagnostics) -> NBTResult<Self> {
+        Self::from_bytes_with(data, &ReadOptions::default(), Some(diagnostics))
+    }
+
+    /// Function for reading from a byte array with the given options, optionally collecting diagnostics.
+    fn from_bytes_with<B: AsRef<[u8]>>(data: B, options: &ReadOptions, mut diagnostics: Option<&mut Diagnostics>) -> NBTResult<Self> {
+        let data = data.as_ref();
+        let mut cursor = Cursor::new(data);
+        let value = Self::read_with(&mut cursor, options, diagnostics.as_deref_mut())?;
+
+        if let Some(diagnostics) = diagnostics {
+            let count = data.len() - cursor.position() as usize;
+            if count > 0 {
+                diagnostics.push(Diagnostic::TrailingBytes { count });
+            }
+        }
+        Ok(value)
+    }
+
+    #[cfg(feature="bytes")]
+    /// Function for reading from a `bytes::Buf`, advancing it past the read data.
+    ///
+    /// ```
+    /// use bytes::{BytesMut, BufMut};
+    /// use nbt::{Blob, NBTRead, NBTWrite};
+    ///
+    /// let mut blob = Blob::new();
+    /// blob.insert("id", 42);
+    ///
+    /// let mut buf = BytesMut::new();
+    /// blob.write_buf(&mut buf).unwrap();
+    /// buf.put_u8(0xFF);
+    ///
+    /// let mut bytes = buf.freeze();
+    /// assert_eq!(Blob::read_buf(&mut bytes).unwrap().get::<i32>("id"), Some(&42));
+    /// assert_eq!(bytes.len(), 1);
+    /// ```
+    fn read_buf<B: Buf>(buf: &mut B) -> NBTResult<Self> {
+        Self::read(&mut Buf::reader(buf))
     }
 }
 
 impl NBTRead for Tag {
-    fn read<R: Read>(reader: &mut R) -> NBTResult<Self> {
+    fn read_flavor_with<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, options: &ReadOptions, diagnostics: Option<&mut Diagnostics>) -> NBTResult<Self> {
+        let ctx = &mut ReadContext::new(options, diagnostics);
+        let reader = &mut ctx.counted(reader);
         let ident = read_ident(reader)?;
-        read_tag(reader, &ident)
+        let tag = read_tag::<F, _>(reader, &ident, ctx)?;
+        ctx.finish();
+        Ok(tag)
     }
 }
 impl NBTRead for Blob {
-    fn read<R: Read>(reader: &mut R) -> NBTResult<Self> {
-        let (name, elements) = read_root(reader)?;
-        Ok(Self { root: name, elements })
+    fn read_flavor_with<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, options: &ReadOptions, diagnostics: Option<&mut Diagnostics>) -> NBTResult<Self> {
+        let ctx = &mut ReadContext::new(options, diagnostics);
+        let (name, elements) = read_root::<F, _>(&mut ctx.counted(reader), ctx)?;
+        ctx.finish();
+        Ok(Self { root: name, elements, source: F::DIALECT.map(SourceFormat::new) })
+    }
+}
+/// Read from a document, discarding the root name.
+impl NBTRead for CompoundMap {
+    fn read_flavor_with<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, options: &ReadOptions, diagnostics: Option<&mut Diagnostics>) -> NBTResult<Self> {
+        Ok(Blob::read_flavor_with::<F, R>(reader, options, diagnostics)?.elements)
+    }
+}
+/// Read from a document, discarding the root name.
+impl NBTRead for Compound {
+    fn read_flavor_with<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, options: &ReadOptions, diagnostics: Option<&mut Diagnostics>) -> NBTResult<Self> {
+        Ok(Compound { elements: CompoundMap::read_flavor_with::<F, R>(reader, options, diagnostics)? })
+    }
+}
+/// Read from a document as a compound, discarding the root name. Keys are shared through the
+/// interner of the options as they are read, if there is one.
+impl NBTRead for SharedTag {
+    fn read_flavor_with<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, options: &ReadOptions, diagnostics: Option<&mut Diagnostics>) -> NBTResult<Self> {
+        let ctx = &mut ReadContext::new(options, diagnostics);
+        let tag = read_shared_root::<F, _>(&mut ctx.counted(reader), ctx)?;
+        ctx.finish();
+        Ok(tag)
+    }
+}
+
+impl Tag {
+    /// Read a single named tag, returning its name along with it.
+    ///
+    /// A named tag is a type, a name and a payload. This is the form of every entry within a
+    /// compound, and of the root of a document, which `Blob` reads when the root is a compound.
+    ///
+    /// ```
+    /// use nbt::Tag;
+    ///
+    /// let mut buffer = Vec::new();
+    /// Tag::Int(42).write_named(&mut buffer, "answer").unwrap();
+    /// # assert_eq!(buffer, vec![3, 0, 6, 97, 110, 115, 119, 101, 114, 0, 0, 0, 42]);
+    ///
+    /// let (name, tag) = Tag::read_named(&mut &buffer[..]).unwrap();
+    /// assert_eq!(name, "answer");
+    /// assert_eq!(tag, Tag::Int(42));
+    /// ```
+    pub fn read_named<R: Read + ?Sized>(reader: &mut R) -> NBTResult<(String, Tag)> {
+        Tag::read_named_with(reader, &ReadOptions::default(), None)
+    }
+
+    /// Read a single named tag with the given options, optionally collecting diagnostics.
+    pub fn read_named_with<R: Read + ?Sized>(reader: &mut R, options: &ReadOptions, diagnostics: Option<&mut Diagnostics>) -> NBTResult<(String, Tag)> {
+        let ctx = &mut ReadContext::new(options, diagnostics);
+        let named = read_named::<JavaFlavor, _>(&mut ctx.counted(reader), ctx)?;
+        ctx.finish();
+        Ok(named)
+    }
+
+    /// Write the tag as a single named tag.
+    pub fn write_named<W: Write + ?Sized>(&self, writer: &mut W, name: &str) -> NBTResult<()> {
+        write_named::<JavaFlavor, W>(writer, name, self, &WriteOptions::default())
     }
 }
 
-#[cfg(feature= "serde")]
+impl Blob {
+    /// Read a document sent over the network by Minecraft 1.20.2 or later, whose root compound has
+    /// no name. The blob's root name is left empty.
+    ///
+    /// ```
+    /// use nbt::Blob;
+    ///
+    /// let mut blob = Blob::new();
+    /// blob.insert("a", 1i8);
+    ///
+    /// let mut buffer = Vec::new();
+    /// blob.write_network(&mut buffer).unwrap();
+    /// assert_eq!(buffer, vec![10, 1, 0, 1, 97, 1, 0]);
+    ///
+    /// let read = Blob::read_network(&mut &buffer[..]).unwrap();
+    /// assert_eq!(read.get::<i8>("a"), Some(&1));
+    /// ```
+    pub fn read_network<R: Read + ?Sized>(reader: &mut R) -> NBTResult<Blob> {
+        Blob::read_network_with(reader, &ReadOptions::default(), None)
+    }
+
+    /// Read a document without a root name with the given options, optionally collecting
+    /// diagnostics. See `read_network`.
+    pub fn read_network_with<R: Read + ?Sized>(reader: &mut R, options: &ReadOptions, diagnostics: Option<&mut Diagnostics>) -> NBTResult<Blob> {
+        let ctx = &mut ReadContext::new(options, diagnostics);
+        let elements = read_nameless_root::<JavaFlavor, _>(&mut ctx.counted(reader), ctx)?;
+        ctx.finish();
+        Ok(Blob { root: String::new(), elements, source: JavaFlavor::DIALECT.map(SourceFormat::new) })
+    }
+
+    /// Write the blob as sent over the network by Minecraft 1.20.2 or later, without its root name.
+    pub fn write_network<W: Write + ?Sized>(&self, writer: &mut W) -> NBTResult<()> {
+        self.write_network_with(writer, &WriteOptions::default())
+    }
+
+    /// Write the blob without its root name, with the given options. See `write_network`.
+    pub fn write_network_with<W: Write + ?Sized>(&self, writer: &mut W, options: &WriteOptions) -> NBTResult<()> {
+        write_nameless_root::<JavaFlavor, W>(writer, &self.elements, options)
+    }
+}
+
+#[cfg(feature= "serde-ser")]
 /// Encode a Serde serializable value into a NBT Tag.
 ///
 /// ### Example
@@ -80,7 +419,13 @@ impl NBTRead for Blob {
 /// # assert_eq!(tag, Tag::List(vec![Tag::Byte(127), Tag::Byte(42), Tag::Byte(10)]));
 /// ```
 pub fn encode_tag<T: Serialize>(o: &T) -> NBTResult<Option<Tag>> {
-    o.serialize(NBTSerializer)
+    encode_tag_with(o, &SerdeOptions::default())
+}
+
+#[cfg(feature= "serde-ser")]
+/// Encode a Serde serializable value into a NBT Tag with the given options.
+pub fn encode_tag_with<T: Serialize>(o: &T, options: &SerdeOptions) -> NBTResult<Option<Tag>> {
+    o.serialize(NBTSerializer::new(options))
 }
 
 /// Encode a Serde serializable value into a NBT Blob with a given root name.
@@ -88,7 +433,7 @@ pub fn encode_tag<T: Serialize>(o: &T) -> NBTResult<Option<Tag>> {
 /// ### Example
 /// ```
 /// use nbt::{encode_tag, encode_named, Tag};
-/// use std::collections::HashMap;
+/// use nbt::CompoundMap;
 /// use serde::Serialize;
 ///
 /// // Define a Serializable Struct
@@ -104,25 +449,78 @@ pub fn encode_tag<T: Serialize>(o: &T) -> NBTResult<Option<Tag>> {
 /// // Encode a NBT blob with name "hello_world"
 /// let tag = encode_named(&example, "hello_world").unwrap();
 ///
-/// # let mut test = HashMap::new();
+/// # let mut test = CompoundMap::default();
 /// # test.insert("name".to_string(), Tag::String("Bananrama".to_string()));
 /// # assert_eq!(tag.compound(), Tag::Compound(test));
 /// ```
 ///
-#[cfg(feature= "serde")]
+#[cfg(feature= "serde-ser")]
 pub fn encode_named<T: Serialize>(o: &T, name: &str) -> NBTResult<Blob> {
-    match encode_tag(o)? {
-        Some(tag) => if let Tag::Compound(map) = tag {
-            Ok(Blob { elements: map, root: name.to_string() })
-        } else {
-            Err(NBTError::InvalidImplicit { found: tag.ident() })
+    encode_named_with(o, name, &SerdeOptions::default())
+}
+
+#[cfg(feature= "serde-ser")]
+/// Encode a Serde serializable value into a NBT Blob with a given root name and the given options.
+///
+/// Values that don't serialize to a compound are handled according to `RootHandling`.
+///
+/// ### Example
+/// ```
+/// use nbt::{encode_named_with, SerdeOptions, RootHandling, Tag};
+///
+/// let options = SerdeOptions::new().root_handling(RootHandling::Wrap("scores".to_string()));
+/// let blob = encode_named_with(&vec![1, 2, 3], "", &options).unwrap();
+///
+/// assert_eq!(blob.get::<Tag>("scores"), Some(&Tag::List(vec![Tag::Int(1), Tag::Int(2), Tag::Int(3)])));
+/// ```
+pub fn encode_named_with<T: Serialize>(o: &T, name: &str, options: &SerdeOptions) -> NBTResult<Blob> {
+    let elements = match (encode_tag_with(o, options)?, &options.root_handling) {
+        (Some(Tag::Compound(map)), _) => map,
+        (Some(tag), RootHandling::Wrap(key)) => {
+            let mut map = CompoundMap::default();
+            map.insert(key.clone(), tag);
+            map
         },
+        (None, RootHandling::Wrap(_)) => CompoundMap::default(),
+        (Some(tag), RootHandling::Error) => return Err(NBTError::InvalidImplicit { found: tag.ident() }),
         // Not sure about this
+        (None, RootHandling::Error) => return Err(NBTError::InvalidImplicit { found: TagIdent::TAG_End }),
+    };
+    Ok(Blob { elements, root: name.to_string(), source: None })
+}
+
+#[cfg(feature= "serde-ser")]
+/// Encode a Serde serializable value as a bare tag: its type followed by its payload, with no name.
+///
+/// This is how tags are sent over the network since Minecraft 1.20.2, and is read back by
+/// `Tag::read`. Unlike `encode`, the value doesn't have to serialize to a compound.
+///
+/// ### Example
+/// ```
+/// use nbt::{encode_bare, Tag, NBTRead};
+///
+/// let bytes = encode_bare(&vec![1i8, 2]).unwrap();
+/// assert_eq!(bytes, vec![9, 1, 0, 0, 0, 2, 1, 2]);
+/// assert_eq!(Tag::from_bytes(&bytes).unwrap(), Tag::List(vec![Tag::Byte(1), Tag::Byte(2)]));
+/// ```
+pub fn encode_bare<T: Serialize>(o: &T) -> NBTResult<Vec<u8>> {
+    encode_bare_with(o, &SerdeOptions::default())
+}
+
+#[cfg(feature= "serde-ser")]
+/// Encode a Serde serializable value as a bare tag with the given options.
+pub fn encode_bare_with<T: Serialize>(o: &T, options: &SerdeOptions) -> NBTResult<Vec<u8>> {
+    match encode_tag_with(o, options)? {
+        Some(tag) => {
+            let mut bytes = vec![tag.ident() as u8];
+            tag.write(&mut bytes)?;
+            Ok(bytes)
+        },
         None => Err(NBTError::InvalidImplicit { found: TagIdent::TAG_End })
     }
 }
 
-#[cfg(feature= "serde")]
+#[cfg(feature= "serde-ser")]
 /// Encode a Serde serializable value into a NBT Blob with a empty root name.
 ///
 /// Encode a Serde serializable value into a NBT Blob with a given root name.
@@ -131,7 +529,7 @@ pub fn encode_named<T: Serialize>(o: &T, name: &str) -> NBTResult<Blob> {
 /// ### Example
 /// ```
 /// use nbt::{encode_tag, encode, Tag};
-/// use std::collections::HashMap;
+/// use nbt::CompoundMap;
 /// use serde::Serialize;
 ///
 /// // Define a Serializable Struct
@@ -151,7 +549,7 @@ pub fn encode_named<T: Serialize>(o: &T, name: &str) -> NBTResult<Blob> {
 /// // Encode a NBT blob with name "example"
 /// let tag = encode(&example).unwrap();
 ///
-/// # let mut test = HashMap::new();
+/// # let mut test = CompoundMap::default();
 /// # test.insert("foo".to_string(), Tag::String("Hello World!".to_string()));
 /// # test.insert("bar".to_string(), Tag::Byte(42));
 /// # test.insert("baz".to_string(), Tag::Short(25565));
@@ -162,7 +560,33 @@ pub fn encode<T: Serialize>(o: &T) -> NBTResult<Blob> {
     encode_named(o, "")
 }
 
-#[cfg(feature= "serde")]
+#[cfg(feature= "serde-ser")]
+/// Encode a Serde serializable value into a NBT Blob with a empty root name and the given options.
+///
+/// ### Example
+/// ```
+/// # #[cfg(all(feature = "serde-de", feature = "serde_unsigned"))] {
+/// use nbt::{encode_with, decode_with, SerdeOptions, UnsignedOverflow, Tag};
+/// use serde::{Serialize, Deserialize};
+///
+/// #[derive(Serialize, Deserialize, PartialEq, Debug)]
+/// pub struct Seed {
+///     value: u64
+/// }
+///
+/// let options = SerdeOptions::new().unsigned_overflow(UnsignedOverflow::String);
+/// let seed = Seed { value: u64::MAX };
+///
+/// let blob = encode_with(&seed, &options).unwrap();
+/// assert_eq!(blob.get::<String>("value").unwrap(), "18446744073709551615");
+/// assert_eq!(decode_with::<Seed>(blob, &options).unwrap(), seed);
+/// # }
+/// ```
+pub fn encode_with<T: Serialize>(o: &T, options: &SerdeOptions) -> NBTResult<Blob> {
+    encode_named_with(o, "", options)
+}
+
+#[cfg(feature= "serde-de")]
 /// Decode a NBT Tag into a Serde deserializable value.
 ///
 /// ### Example
@@ -178,11 +602,17 @@ pub fn encode<T: Serialize>(o: &T) -> NBTResult<Blob> {
 /// assert_eq!(list, vec![127, 42]);
 /// ```
 pub fn decode_tag<T: DeserializeOwned>(tag: Tag) -> NBTResult<T> {
-    T::deserialize(NBTDeserializer::some(tag))
+    decode_tag_with(tag, &SerdeOptions::default())
+}
+
+#[cfg(feature= "serde-de")]
+/// Decode a NBT Tag into a Serde deserializable value with the given options.
+pub fn decode_tag_with<T: DeserializeOwned>(tag: Tag, options: &SerdeOptions) -> NBTResult<T> {
+    T::deserialize(NBTDeserializer::some(tag, options))
 }
 
 
-#[cfg(feature= "serde")]
+#[cfg(feature= "serde-de")]
 /// Decode a NBT Blob into a Serde deserializable value.
 ///
 /// ### Example
@@ -206,10 +636,16 @@ pub fn decode_tag<T: DeserializeOwned>(tag: Tag) -> NBTResult<T> {
 /// assert_eq!(data, Example { foo: "bar".to_string() });
 /// ```
 pub fn decode<T: DeserializeOwned>(tag: Blob) -> NBTResult<T> {
-    T::deserialize(NBTDeserializer::some(Tag::Compound(tag.elements)))
+    decode_with(tag, &SerdeOptions::default())
+}
+
+#[cfg(feature= "serde-de")]
+/// Decode a NBT Blob into a Serde deserializable value with the given options.
+pub fn decode_with<T: DeserializeOwned>(tag: Blob, options: &SerdeOptions) -> NBTResult<T> {
+    decode_tag_with(Tag::Compound(tag.elements), options)
 }
 
-#[cfg(feature= "serde")]
+#[cfg(feature= "serde-de")]
 /// Decode a NBT Blob into a Serde deserializable value and the given root name.
 ///
 /// ### Example
@@ -234,6 +670,35 @@ pub fn decode<T: DeserializeOwned>(tag: Blob) -> NBTResult<T> {
 /// assert_eq!(root, "baz".to_string());
 /// ```
 pub fn decode_named<T: DeserializeOwned>(tag: Blob) -> NBTResult<(String, T)> {
-    Ok((tag.root.clone(), T::deserialize(NBTDeserializer::some(Tag::Compound(tag.elements)))?))
+    Ok((tag.root.clone(), decode_with(tag, &SerdeOptions::default())?))
 }
 
+#[cfg(feature= "serde-de")]
+/// Decode a NBT Blob into a Serde deserializable value, checking its root name.
+///
+/// Fails with `NBTError::UnexpectedRootName` if the root name is not `expected`.
+///
+/// ### Example
+/// ```
+/// use nbt::{Blob, NBTError, decode_expect_named};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, PartialEq, Debug)]
+/// pub struct Example {
+///     foo: String
+/// }
+///
+/// let mut blob = Blob::create("baz");
+/// blob.insert("foo", "bar");
+///
+/// assert!(matches!(decode_expect_named::<Example>(blob.clone(), "qux"), Err(NBTError::UnexpectedRootName { .. })));
+///
+/// let data: Example = decode_expect_named(blob, "baz").unwrap();
+/// assert_eq!(data, Example { foo: "bar".to_string() });
+/// ```
+pub fn decode_expect_named<T: DeserializeOwned>(tag: Blob, expected: &str) -> NBTResult<T> {
+    if tag.root != expected {
+        return Err(NBTError::UnexpectedRootName { expected: expected.to_string(), found: tag.root });
+    }
+    decode(tag)
+}
@@ -0,0 +1,159 @@
+use crate::tags::{Tag, TagIdent, CompoundMap};
+use crate::blob::Blob;
+
+// Characters used for generated strings, including some that are encoded specially in
+// modified UTF-8: the nul character and characters outside the basic multilingual plane.
+const CHARACTERS: &[char] = &['a', 'b', 'c', 'x', 'y', 'z', 'A', 'Z', '0', '9', '_', ' ', '"', '\0', 'é', 'ß', '字', '😀'];
+
+const TYPES: &[TagIdent] = &[
+    TagIdent::TAG_Byte, TagIdent::TAG_Short, TagIdent::TAG_Int, TagIdent::TAG_Long,
+    TagIdent::TAG_Float, TagIdent::TAG_Double, TagIdent::TAG_Byte_Array, TagIdent::TAG_String,
+    TagIdent::TAG_List, TagIdent::TAG_Compound, TagIdent::TAG_Int_Array, TagIdent::TAG_Long_Array,
+];
+
+#[derive(Clone)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// A generator of pseudo-random tags, for testing.
+///
+/// The same seed and settings always produce the same sequence of tags, so failures found with a
+/// generator can be reproduced.
+///
+/// ## Example
+/// ```
+/// use nbt::{TagGenerator, TagIdent};
+///
+/// let generator = TagGenerator::new(42)
+///     .max_depth(3)
+///     .allowed(&[TagIdent::TAG_Int, TagIdent::TAG_String, TagIdent::TAG_Compound]);
+///
+/// let blob = generator.clone().generate_blob();
+/// assert_eq!(blob.compound(), generator.clone().generate_blob().compound());
+/// ```
+pub struct TagGenerator {
+    state: u64,
+    max_depth: usize,
+    max_length: usize,
+    allowed: Vec<TagIdent>,
+    unsigned_byte_arrays: bool,
+}
+
+impl TagGenerator {
+    /// Create a generator from a seed, allowing every type, up to 4 levels deep and 8 elements long.
+    pub fn new(seed: u64) -> TagGenerator {
+        TagGenerator { state: seed, max_depth: 4, max_length: 8, allowed: TYPES.to_vec(), unsigned_byte_arrays: false }
+    }
+
+    /// Limit how deeply lists and compounds are nested.
+    pub fn max_depth(mut self, depth: usize) -> TagGenerator {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Limit the number of elements in lists, arrays and compounds, and the length of strings.
+    pub fn max_length(mut self, length: usize) -> TagGenerator {
+        self.max_length = length;
+        self
+    }
+
+    /// Only generate tags of the given types. `TAG_End` is ignored.
+    ///
+    /// The root of a blob is always a compound. Lists and compounds beyond the maximum depth are
+    /// left empty if no other type is allowed.
+    pub fn allowed(mut self, types: &[TagIdent]) -> TagGenerator {
+        self.allowed = types.iter().filter(|x| **x != TagIdent::TAG_End).cloned().collect();
+        self
+    }
+
+    /// Generate byte arrays as `Tag::UnsignedByteArray` instead of `Tag::ByteArray`.
+    ///
+    /// The bytes generated are the same either way.
+    pub fn unsigned_byte_arrays(mut self, unsigned: bool) -> TagGenerator {
+        self.unsigned_byte_arrays = unsigned;
+        self
+    }
+
+    // SplitMix64.
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // A number in `0..bound`.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn length(&mut self) -> usize {
+        self.below(self.max_length + 1)
+    }
+
+    // Pick an allowed type, only picking lists and compounds above the maximum depth if possible.
+    fn pick_type(&mut self, depth: usize) -> TagIdent {
+        let leaves: Vec<TagIdent> = self.allowed.iter()
+            .filter(|x| depth < self.max_depth || !matches!(x, TagIdent::TAG_List | TagIdent::TAG_Compound))
+            .cloned()
+            .collect();
+        let choices = if leaves.is_empty() { self.allowed.clone() } else { leaves };
+        if choices.is_empty() {
+            return TagIdent::TAG_Compound;
+        }
+        choices[self.below(choices.len())].clone()
+    }
+
+    fn string(&mut self) -> String {
+        let length = self.length();
+        (0..length).map(|_| CHARACTERS[self.below(CHARACTERS.len())]).collect()
+    }
+
+    fn compound(&mut self, depth: usize) -> CompoundMap {
+        let mut map = CompoundMap::default();
+        if depth > self.max_depth { return map; }
+        for _ in 0..self.length() {
+            let key = self.string();
+            let ident = self.pick_type(depth + 1);
+            let value = self.tag_of(&ident, depth + 1);
+            map.insert(key, value);
+        }
+        map
+    }
+
+    fn tag_of(&mut self, ident: &TagIdent, depth: usize) -> Tag {
+        match ident {
+            TagIdent::TAG_Byte => Tag::Byte(self.next() as i8),
+            TagIdent::TAG_Short => Tag::Short(self.next() as i16),
+            TagIdent::TAG_Int => Tag::Int(self.next() as i32),
+            TagIdent::TAG_Long => Tag::Long(self.next() as i64),
+            TagIdent::TAG_Float => Tag::Float(f32::from_bits(self.next() as u32)),
+            TagIdent::TAG_Double => Tag::Double(f64::from_bits(self.next())),
+            TagIdent::TAG_Byte_Array => {
+                let bytes = Tag::ByteArray((0..self.length()).map(|_| self.next() as i8).collect());
+                bytes.with_unsigned_bytes(self.unsigned_byte_arrays)
+            }
+            TagIdent::TAG_String => Tag::String(self.string()),
+            TagIdent::TAG_List => {
+                if depth > self.max_depth { return Tag::List(Vec::new()); }
+                let ident = self.pick_type(depth + 1);
+                Tag::List((0..self.length()).map(|_| self.tag_of(&ident, depth + 1)).collect())
+            }
+            TagIdent::TAG_Int_Array => Tag::IntArray((0..self.length()).map(|_| self.next() as i32).collect()),
+            TagIdent::TAG_Long_Array => Tag::LongArray((0..self.length()).map(|_| self.next() as i64).collect()),
+            _ => Tag::Compound(self.compound(depth)),
+        }
+    }
+
+    /// Generate the next tag.
+    pub fn generate(&mut self) -> Tag {
+        let ident = self.pick_type(0);
+        self.tag_of(&ident, 0)
+    }
+
+    /// Generate the next blob, with a generated root name.
+    pub fn generate_blob(&mut self) -> Blob {
+        let root = self.string();
+        let elements = self.compound(0);
+        Blob { root, elements, source: None }
+    }
+}
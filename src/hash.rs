@@ -0,0 +1,115 @@
+use crate::tags::Tag;
+use crate::blob::Blob;
+use crate::tags::CompoundMap;
+
+const FNV64_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV64_PRIME: u64 = 0x0000_0100_0000_01b3;
+const FNV128_OFFSET: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+const FNV128_PRIME: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013b;
+
+// A sink for bytes, and a function feeding bytes into one.
+type Sink<'a> = &'a mut dyn FnMut(&[u8]);
+type Input<'a> = &'a dyn Fn(Sink);
+
+// Feed a canonical byte representation of a tag into `out`.
+// Compound keys are sorted so that the result does not depend on iteration order.
+fn feed(tag: &Tag, out: Sink) {
+    out(&[tag.ident() as u8]);
+    match tag {
+        Tag::Byte(v) => out(&v.to_be_bytes()),
+        Tag::Short(v) => out(&v.to_be_bytes()),
+        Tag::Int(v) => out(&v.to_be_bytes()),
+        Tag::Long(v) => out(&v.to_be_bytes()),
+        Tag::Float(v) => out(&v.to_bits().to_be_bytes()),
+        Tag::Double(v) => out(&v.to_bits().to_be_bytes()),
+        Tag::ByteArray(array) => {
+            out(&(array.len() as u32).to_be_bytes());
+            array.iter().for_each(|v| out(&v.to_be_bytes()));
+        }
+        Tag::UnsignedByteArray(array) => {
+            out(&(array.len() as u32).to_be_bytes());
+            out(array);
+        }
+        Tag::String(string) => feed_str(string, out),
+        Tag::List(list) => {
+            out(&(list.len() as u32).to_be_bytes());
+            list.iter().for_each(|v| feed(v, out));
+        }
+        Tag::Compound(map) => feed_compound(map, out),
+        Tag::IntArray(array) => {
+            out(&(array.len() as u32).to_be_bytes());
+            array.iter().for_each(|v| out(&v.to_be_bytes()));
+        }
+        Tag::LongArray(array) => {
+            out(&(array.len() as u32).to_be_bytes());
+            array.iter().for_each(|v| out(&v.to_be_bytes()));
+        }
+    }
+}
+
+fn feed_str(string: &str, out: Sink) {
+    out(&(string.len() as u32).to_be_bytes());
+    out(string.as_bytes());
+}
+
+fn feed_compound(map: &CompoundMap, out: Sink) {
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+
+    out(&(keys.len() as u32).to_be_bytes());
+    for key in keys {
+        feed_str(key, out);
+        feed(&map[key], out);
+    }
+}
+
+fn fnv64(input: Input) -> u64 {
+    let mut hash = FNV64_OFFSET;
+    input(&mut |bytes| for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV64_PRIME);
+    });
+    hash
+}
+
+fn fnv128(input: Input) -> u128 {
+    let mut hash = FNV128_OFFSET;
+    input(&mut |bytes| for byte in bytes {
+        hash ^= *byte as u128;
+        hash = hash.wrapping_mul(FNV128_PRIME);
+    });
+    hash
+}
+
+impl Tag {
+    /// A stable 64-bit hash of the tag's contents.
+    ///
+    /// The hash is independent of compound iteration order and is the same across runs and platforms,
+    /// making it suitable for deduplication, caching and change detection. Floats are hashed by their bits.
+    /// ```
+    /// # use nbt::Tag;
+    /// let a = Tag::compound_from_iter(vec![("x", 1), ("y", 2)]);
+    /// let b = Tag::compound_from_iter(vec![("y", 2), ("x", 1)]);
+    /// assert_eq!(a.content_hash(), b.content_hash());
+    /// ```
+    pub fn content_hash(&self) -> u64 {
+        fnv64(&|out| feed(self, out))
+    }
+
+    /// A stable 128-bit hash of the tag's contents. See [`content_hash`](Tag::content_hash).
+    pub fn content_hash_128(&self) -> u128 {
+        fnv128(&|out| feed(self, out))
+    }
+}
+
+impl Blob {
+    /// A stable 64-bit hash of the blob's root name and contents. See [`Tag::content_hash`].
+    pub fn content_hash(&self) -> u64 {
+        fnv64(&|out| { feed_str(&self.root, out); feed_compound(&self.elements, out) })
+    }
+
+    /// A stable 128-bit hash of the blob's root name and contents. See [`Tag::content_hash`].
+    pub fn content_hash_128(&self) -> u128 {
+        fnv128(&|out| { feed_str(&self.root, out); feed_compound(&self.elements, out) })
+    }
+}
@@ -0,0 +1,188 @@
+// Hex dumps of encoded documents, annotated with where each tag starts.
+
+use crate::blob::Blob;
+use crate::error::NBTResult;
+use crate::flavor::{NbtFlavor, JavaFlavor};
+use crate::front::NBTWrite;
+use crate::tags::{Tag, TagIdent};
+use std::fmt::Write;
+
+impl Tag {
+    /// Encode the tag in the Java dialect, and format the bytes as a hex dump annotated with the
+    /// start of every tag. As with `bytes`, the dump holds the payload without a type or name.
+    ///
+    /// See `Blob::hexdump`.
+    pub fn hexdump(&self) -> NBTResult<String> {
+        let bytes = self.bytes()?;
+        let mut walker = Walker { bytes: &bytes, pos: 0, marks: Vec::new() };
+        let label = format!("{}{}", self.ident(), walker.summary(&self.ident()));
+        walker.mark(0, 0, label);
+        walker.payload(&self.ident(), 0);
+        Ok(walker.render())
+    }
+}
+
+impl Blob {
+    /// Encode the blob in the Java dialect, and format the bytes as a hex dump annotated with the
+    /// start of every tag.
+    ///
+    /// Each tag starts a new row, with its offset, its bytes, and a comment naming the tag,
+    /// indented by its depth. Long tags continue over further rows of 16 bytes.
+    /// ```
+    /// # use nbt::Blob;
+    /// let mut blob = Blob::create("hello world");
+    /// blob.insert("name", "Bananrama");
+    ///
+    /// assert_eq!(blob.hexdump().unwrap(), concat!(
+    ///     "00000000  0a 00 0b 68 65 6c 6c 6f 20 77 6f 72 6c 64        // TAG_Compound 'hello world'\n",
+    ///     "0000000e  08 00 04 6e 61 6d 65 00 09 42 61 6e 61 6e 72 61  //   TAG_String 'name'\n",
+    ///     "0000001e  6d 61\n",
+    ///     "00000020  00                                               //   TAG_End\n",
+    /// ));
+    /// ```
+    pub fn hexdump(&self) -> NBTResult<String> {
+        let bytes = self.bytes()?;
+        let mut walker = Walker { bytes: &bytes, pos: 0, marks: Vec::new() };
+        walker.entry(0);
+        Ok(walker.render())
+    }
+}
+
+// Finds where each tag of an encoded document starts.
+struct Walker<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    // The offset, depth and description of the start of each tag.
+    marks: Vec<(usize, usize, String)>,
+}
+
+impl<'a> Walker<'a> {
+    fn mark(&mut self, pos: usize, depth: usize, label: String) {
+        self.marks.push((pos, depth, label));
+    }
+
+    fn take(&mut self, length: usize) -> Option<&'a [u8]> {
+        let bytes = self.bytes.get(self.pos..self.pos.checked_add(length)?)?;
+        self.pos += length;
+        Some(bytes)
+    }
+
+    fn ident(&mut self) -> Option<TagIdent> {
+        TagIdent::parse(&self.take(1)?[0])
+    }
+
+    fn length(&mut self) -> Option<usize> {
+        let bytes = self.take(4)?;
+        Some(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]).max(0) as usize)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let bytes = self.take(2)?;
+        let length = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+        Some(JavaFlavor::decode_string_lossy(self.take(length)?))
+    }
+
+    // Describe the length of a payload starting at the current offset, without reading it.
+    fn summary(&self, ident: &TagIdent) -> String {
+        let rest = &self.bytes[self.pos.min(self.bytes.len())..];
+        let length = |at: usize| rest.get(at..at + 4).map(|x| i32::from_be_bytes([x[0], x[1], x[2], x[3]]));
+        match ident {
+            TagIdent::TAG_List => match (rest.first().and_then(TagIdent::parse), length(1)) {
+                (Some(item), Some(length)) => format!(" of {} {}", length, item),
+                _ => String::new(),
+            },
+            TagIdent::TAG_Byte_Array | TagIdent::TAG_Int_Array | TagIdent::TAG_Long_Array => match length(0) {
+                Some(length) => format!(" of {}", length),
+                None => String::new(),
+            },
+            _ => String::new(),
+        }
+    }
+
+    // Read a named tag, returning `false` at the end of a compound.
+    fn entry(&mut self, depth: usize) -> Option<bool> {
+        let start = self.pos;
+        let ident = self.ident()?;
+        if ident == TagIdent::TAG_End {
+            self.mark(start, depth, ident.to_string());
+            return Some(false);
+        }
+        let name = self.string()?;
+        let label = format!("{} '{}'{}", ident, name, self.summary(&ident));
+        self.mark(start, depth, label);
+        self.payload(&ident, depth)?;
+        Some(true)
+    }
+
+    // Read a payload, marking the tags within it. Stops at the first malformed byte.
+    fn payload(&mut self, ident: &TagIdent, depth: usize) -> Option<()> {
+        match ident {
+            TagIdent::TAG_End => {}
+            TagIdent::TAG_Byte => { self.take(1)?; }
+            TagIdent::TAG_Short => { self.take(2)?; }
+            TagIdent::TAG_Int | TagIdent::TAG_Float => { self.take(4)?; }
+            TagIdent::TAG_Long | TagIdent::TAG_Double => { self.take(8)?; }
+            TagIdent::TAG_String => { self.string()?; }
+            TagIdent::TAG_Byte_Array => {
+                let length = self.length()?;
+                self.take(length)?;
+            }
+            TagIdent::TAG_Int_Array => {
+                let length = self.length()?;
+                self.take(length.checked_mul(4)?)?;
+            }
+            TagIdent::TAG_Long_Array => {
+                let length = self.length()?;
+                self.take(length.checked_mul(8)?)?;
+            }
+            TagIdent::TAG_List => {
+                let item = self.ident()?;
+                let length = self.length()?;
+                for i in 0..length {
+                    let label = format!("[{}] {}{}", i, item, self.summary(&item));
+                    self.mark(self.pos, depth + 1, label);
+                    self.payload(&item, depth + 1)?;
+                }
+            }
+            TagIdent::TAG_Compound => {
+                while self.entry(depth + 1)? {}
+            }
+        }
+        Some(())
+    }
+
+    // Format the bytes as rows, starting a new row at each mark. The first mark is always at the
+    // start, and the last runs to the end, so bytes after anything malformed are still shown.
+    fn render(&self) -> String {
+        let mut out = String::new();
+        for (i, (start, depth, label)) in self.marks.iter().enumerate() {
+            let end = match self.marks.get(i + 1) {
+                Some((next, _, _)) => *next,
+                None => self.bytes.len(),
+            };
+            let segment = &self.bytes[*start..end];
+
+            let mut offset = *start;
+            let mut rows = segment.chunks(16);
+            let first = rows.next().unwrap_or(&[]);
+            let _ = writeln!(out, "{:08x}  {:<49}// {:indent$}{}", offset, hex(first), "", label, indent = depth * 2);
+            offset += first.len();
+            for row in rows {
+                let _ = writeln!(out, "{:08x}  {}", offset, hex(row));
+                offset += row.len();
+            }
+        }
+        out
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 3);
+    for (i, byte) in bytes.iter().enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+        let _ = write!(out, "{:02x}", byte);
+    }
+    out
+}
@@ -0,0 +1,103 @@
+use crate::path::{NbtPath, PathSegment};
+use crate::tags::Tag;
+
+impl Tag {
+    /// Iterate over this tag and every tag nested within it, depth first.
+    ///
+    /// Each tag is yielded before its children, along with its path relative to this tag.
+    /// Compound keys are visited in sorted order, as compounds do not preserve insertion order.
+    ///
+    /// ```
+    /// # use nbt::Tag;
+    /// let item = |id: &str, count: i8| Tag::compound_from_iter(vec![
+    ///     ("id", Tag::String(id.to_string())),
+    ///     ("Count", Tag::Byte(count)),
+    /// ]);
+    /// let inventory = Tag::List(vec![item("minecraft:stone", 64), item("minecraft:dirt", 12)]);
+    ///
+    /// let total: i64 = inventory.iter_recursive()
+    ///     .filter(|(path, _)| path.to_string().ends_with(".Count"))
+    ///     .filter_map(|(_, tag)| if let Tag::Byte(x) = tag { Some(*x as i64) } else { None })
+    ///     .sum();
+    ///
+    /// assert_eq!(total, 76);
+    /// ```
+    pub fn iter_recursive(&self) -> RecursiveIter<'_> {
+        RecursiveIter { stack: vec![(NbtPath::new(), self)] }
+    }
+
+    /// Mutably iterate over every tag nested within this tag that is not a list or compound, depth first.
+    ///
+    /// Lists and compounds themselves are not yielded, as their children are borrowed instead.
+    /// Compound keys are visited in sorted order.
+    ///
+    /// ```
+    /// # use nbt::Tag;
+    /// let mut pos = Tag::List(vec![Tag::Double(1.0), Tag::Double(2.0), Tag::Double(3.0)]);
+    ///
+    /// for (_, tag) in pos.iter_recursive_mut() {
+    ///     if let Tag::Double(x) = tag { *x *= 2.0; }
+    /// }
+    ///
+    /// assert_eq!(pos, Tag::List(vec![Tag::Double(2.0), Tag::Double(4.0), Tag::Double(6.0)]));
+    /// ```
+    pub fn iter_recursive_mut(&mut self) -> RecursiveIterMut<'_> {
+        RecursiveIterMut { stack: vec![(NbtPath::new(), self)] }
+    }
+}
+
+/// A depth-first iterator over a tree of tags. See [`Tag::iter_recursive`].
+pub struct RecursiveIter<'a> {
+    stack: Vec<(NbtPath, &'a Tag)>,
+}
+
+impl<'a> Iterator for RecursiveIter<'a> {
+    type Item = (NbtPath, &'a Tag);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (path, tag) = self.stack.pop()?;
+        // Children are pushed in reverse so they are popped in order.
+        match tag {
+            Tag::Compound(map) => {
+                let mut entries: Vec<(&String, &Tag)> = map.iter().collect();
+                entries.sort_by(|a, b| b.0.cmp(a.0));
+                for (key, value) in entries {
+                    self.stack.push((path.join(PathSegment::Key(key.clone())), value));
+                }
+            }
+            Tag::List(list) => for (index, value) in list.iter().enumerate().rev() {
+                self.stack.push((path.join(PathSegment::Index(index)), value));
+            },
+            _ => {}
+        }
+        Some((path, tag))
+    }
+}
+
+/// A depth-first mutable iterator over the leaves of a tree of tags. See [`Tag::iter_recursive_mut`].
+pub struct RecursiveIterMut<'a> {
+    stack: Vec<(NbtPath, &'a mut Tag)>,
+}
+
+impl<'a> Iterator for RecursiveIterMut<'a> {
+    type Item = (NbtPath, &'a mut Tag);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (path, tag) = self.stack.pop()?;
+            match tag {
+                Tag::Compound(map) => {
+                    let mut entries: Vec<(&String, &mut Tag)> = map.iter_mut().collect();
+                    entries.sort_by(|a, b| b.0.cmp(a.0));
+                    for (key, value) in entries {
+                        self.stack.push((path.join(PathSegment::Key(key.clone())), value));
+                    }
+                }
+                Tag::List(list) => for (index, value) in list.iter_mut().enumerate().rev() {
+                    self.stack.push((path.join(PathSegment::Index(index)), value));
+                },
+                tag => return Some((path, tag)),
+            }
+        }
+    }
+}
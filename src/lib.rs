@@ -11,14 +11,37 @@
 //! - Support for Serialisation and Deserialization with the [Serde](https://serde.rs) framework.
 //! - Ability to create partial or complete documents through the `Tag` and `Blob` objects.
 //! - Ability to read/write from a socket or buffer.
+//! - Optional diagnostics collection, reporting non-fatal oddities found while reading.
+//! - Java (big endian), Bedrock (little endian) and Bedrock network (VarInt) dialects, pluggable through the `NbtFlavor` trait.
+//! - Opt-in compact storage for long-lived numeric lists with `PackedList`. (`Tag::List` itself still holds `Tag`s)
 //!
 //! ### Cargo Features
-//! - `serde`             (default) includes Serde serialisation and deserialization support.
+//! - `serde`             (default) includes Serde serialisation and deserialization support. Enables `serde-ser` and `serde-de`.
+//! - `serde-ser`         Serde serialisation support only.
+//! - `serde-de`          Serde deserialization support only.
 //! - `serde_boolean`     (default) converts booleans to bytes during serialisation and deserialization.
-//! - `serde_unsigned`    converts unsigned to their signed counterparts during serialisation and deserialization.
+//! - `serde_unsigned`    converts unsigned to their signed counterparts during serialisation and deserialization. (see `SerdeOptions` for large `u64`s)
 //! - `debug`             (default) debug for tags and blobs
+//! - `cli`               `nbt-dump` binary for printing NBT files as a tree, or as SNBT with `--snbt`.
+//! - `bytes`             read/write over `bytes::Buf` and `bytes::BufMut`.
+//! - `codec`             `tokio_util` codec for framing blobs.
+//! - `async`             reading and writing blobs over `tokio` async IO, and the `from_async_reader`/`to_async_writer` Serde functions.
+//! - `region`            parallel region (`.mca`) file writing with `rayon`.
+//! - `rayon`             parallel iteration over compounds. (enabled by `region`)
+//! - `codegen`           generation of Rust structs from sample documents.
+//! - `checksum`          CRC32 and SHA-256 checksums computed while writing.
+//! - `vecmath`           conversions between vector lists and `[f64; 3]`/`glam` vectors.
+//! - `ffi`               C ABI over opaque tag handles. (see `include/nbt.h`)
+//! - `test_vectors`      the reference `hello_world.nbt` and `bigtest.nbt` documents, and a synthetic Bedrock `level.dat`.
+//! - `ahash`             hash compound keys with `ahash` instead of SipHash.
+//! - `preserve_order`    keep compound elements in the order they were inserted or read, so documents round-trip byte for byte.
+//! - `macros`            the `snbt!` macro, parsing SNBT at compile time.
+//! - `base64`            base64 encoding of compressed blobs, for sharing as text.
+//! - `zstd`              zstd compression, with dictionaries shared between small payloads.
+//! - `text`              conversion of chat components between JSON and NBT.
+//! - `hexdump`           `Tag::hexdump` and `Blob::hexdump`, annotated hex dumps of encoded documents for debugging.
 //! - `arrays`            utils for writing byte, int and long arrays. (dev branch)
-//! - `compression`       gzip and DEFLATE support. (dev branch)
+//! - `compression`       gzip and zlib support.
 //!
 //! ### Operation
 //! This crate has two seperate operations that allow data to be mutated.
@@ -26,6 +49,7 @@
 //! 2. Encoding/Decoding - `Tags/Blobs` `<--Serde-->` `Structs`
 //!
 //! ## Quick Start
+//! The common traits and types can be imported at once with `use nbt::prelude::*;`.
 //!
 //! #### Tags
 //! One way of creating partial NBT objects is with tags.
@@ -42,11 +66,14 @@
 //! let list = Tag::List(vec![Tag::Byte(1), Tag::Byte(2), Tag::Byte(3)]);
 //!
 //! // An example of a compound
-//! use std::collections::HashMap;
-//! let mut map = HashMap::<String, Tag>::new();
+//! use nbt::CompoundMap;
+//! let mut map = CompoundMap::default();
 //! map.insert("age".to_string(), Tag::Byte(18));
 //! map.insert("id".to_string(), Tag::Int(69420));
 //! let compound = Tag::Compound(map);
+//!
+//! // The same compound, written with the `nbt!` macro
+//! let compound = nbt::nbt!({ "age": 18i8, "id": 69420 });
 //! ```
 //! #### Blobs
 //! Blobs allow for you to create full NBT objects/documents.
@@ -108,13 +135,16 @@
 //!
 //! ### Serde
 //! This library has full serde serialisation and deserialization support for all types in the
-//! [serde data model](https://serde.rs/data-model.html) except for u8 (byte)arrays.
+//! [serde data model](https://serde.rs/data-model.html). Bytes, such as `serde_bytes::ByteBuf`,
+//! are stored as byte arrays. Other sequences are stored as lists, unless wrapped in
+//! [`IntArray`](crate::IntArray) or [`LongArray`](crate::LongArray).
 //!
 //! Serde support requires the `with_serde` cargo feature, which is enabled by default.
 //!
 //! #### Encoding
 //! Here is an basic example for encoding between a struct and bytes
 //! ```rust
+//! # #[cfg(feature = "serde-ser")] {
 //! use serde::Serialize;
 //! use nbt::{encode, NBTWrite};
 //!
@@ -136,11 +166,13 @@
 //! let bytes = blob.bytes().unwrap();
 //!
 //! assert_eq!(bytes, vec![10, 0, 0, 8, 0, 4, 110, 97, 109, 101, 0, 10, 68, 105, 110, 110, 101, 114, 98, 111, 110, 101, 0])
+//! # }
 //! ```
 //!
 //! #### Decoding
 //! Here is the reverse operation for the above example
 //! ```
+//! # #[cfg(feature = "serde-de")] {
 //! use serde::Deserialize;
 //! use nbt::{Blob, NBTRead, decode};
 //!
@@ -162,6 +194,7 @@
 //! assert_eq!(hello, HelloWorld {
 //!     name: "Dinnerbone".to_string()
 //! });
+//! # }
 //! ```
 //!
 //! #### Serde Functions
@@ -170,7 +203,16 @@
 //! - `TAG --> SERDE` [`decode_tag(...)`](crate::decode_tag)
 //! - `TAG <-- SERDE` [`encode_tag(...)`](crate::encode_tag)
 //! - `BLOB --> SERDE + NAME` [`decode_named(...)`](crate::decode_named)
+//! - `BLOB --> SERDE`, checking the name [`decode_expect_named(...)`](crate::decode_expect_named)
 //! - `BLOB <-- SERDE + NAME` [`encode_named(...)`](crate::encode_named)
+//! - `BYTES <-- SERDE`, as a bare tag [`encode_bare(...)`](crate::encode_bare)
+//! - `BYTES --> SERDE`, borrowing strings from the bytes [`from_slice(...)`](crate::from_slice)
+//!
+//! A blob's root must be a compound, so encoding a `Vec` or a number into one fails unless
+//! [`RootHandling::Wrap`](crate::RootHandling) is set. `encode_bare` accepts any value.
+//!
+//! `encode`, `encode_named`, `encode_tag`, `encode_bare`, `decode`, `decode_tag` and `from_slice` each have a `_with` variant
+//! taking [`SerdeOptions`](crate::SerdeOptions).
 
 pub(crate) mod tags;
 pub(crate) mod error;
@@ -180,25 +222,145 @@ pub(crate) mod decode;
 pub(crate) mod front;
 pub(crate) mod util;
 pub(crate) mod compound;
+pub(crate) mod entry;
+pub(crate) mod diagnostics;
+pub(crate) mod verify;
+pub(crate) mod validate;
+pub(crate) mod extract;
+pub(crate) mod options;
+pub(crate) mod progress;
+pub(crate) mod path;
+pub(crate) mod normalize;
+pub(crate) mod hash;
+pub(crate) mod shared;
+pub(crate) mod transform;
+pub(crate) mod size;
+pub(crate) mod schema;
+pub(crate) mod migrate;
+pub(crate) mod iter;
+pub(crate) mod counter;
+pub mod snbt;
+pub(crate) mod dump;
+mod literal;
+pub(crate) mod flavor;
+pub(crate) mod varint;
+pub(crate) mod order;
+pub(crate) mod generator;
+pub(crate) mod file;
+pub(crate) mod packed;
+pub(crate) mod array;
+pub mod raw;
+pub mod prelude;
 // pub(crate) mod map;
 
 pub use util::{FromTag, ToTag};
 pub use front::{NBTWrite, NBTRead};
-pub use tags::{TagIdent, Tag};
-pub use blob::Blob;
+pub use tags::{TagIdent, Tag, CompoundMap, CompoundHasher};
+pub use blob::{Blob, MergeStrategy};
 pub use compound::Compound;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+pub use diagnostics::{Diagnostic, Diagnostics, ReadStats};
+pub use verify::verify;
+pub use validate::{validate, validate_flavor, ValidateLimits};
+pub use extract::{extract_path, extract_path_flavor};
+pub use path::{NbtPath, PathSegment};
+pub use iter::{RecursiveIter, RecursiveIterMut};
+pub use counter::ByteCounter;
+pub use generator::TagGenerator;
+pub use flavor::{NbtFlavor, JavaFlavor, BedrockFlavor, NetworkLittleEndian, Dialect};
+pub use file::SourceFormat;
+pub use progress::{Progress, ProgressWriter};
+pub use options::{ReadOptions, WriteOptions, MemoryBudget, StringMode};
+pub use normalize::NormalizeOptions;
+pub use shared::{SharedTag, Interner};
+pub use packed::PackedList;
+pub use array::{IntArray, LongArray};
+pub use schema::{BlobSchema, BoundBlob};
+pub use migrate::{Migrations, DATA_VERSION};
+pub use error::{NBTError, NBTResult};
 
 
 #[cfg(test)]
 pub mod tests;
 
-#[cfg(feature= "serde")]
+#[cfg(feature= "compression")]
+pub mod compression;
+pub use file::Compression;
+
+#[cfg(feature= "base64")]
+mod b64;
+
+#[cfg(feature= "text")]
+pub mod text;
+
+#[cfg(feature= "hexdump")]
+mod hexdump;
+
+#[cfg(feature= "preserve_order")]
+mod ordered;
+#[cfg(feature= "preserve_order")]
+pub use ordered::OrderedMap;
+
+#[cfg(feature= "zstd")]
+mod dictionary;
+#[cfg(feature= "zstd")]
+pub use dictionary::ZstdDictionary;
+
+#[cfg(feature= "region")]
+pub mod region;
+
+#[cfg(feature= "codegen")]
+pub mod codegen;
+
+#[cfg(feature= "checksum")]
+mod checksum;
+#[cfg(feature= "checksum")]
+pub use checksum::{Digest, DigestWriter};
+
+#[cfg(feature= "vecmath")]
+pub mod vecmath;
+
+#[cfg(feature= "ffi")]
+pub mod ffi;
+
+#[cfg(feature= "test_vectors")]
+pub mod test_vectors;
+
+#[cfg(feature= "macros")]
+mod macros;
+#[cfg(feature= "macros")]
+#[doc(hidden)]
+pub use macros::__snbt;
+
+#[cfg(feature= "codec")]
+mod codec;
+#[cfg(feature= "codec")]
+pub use codec::NBTCodec;
+
+#[cfg(feature= "async")]
+mod async_io;
+#[cfg(all(feature= "async", feature= "serde-de"))]
+pub use async_io::from_async_reader;
+#[cfg(all(feature= "async", feature= "serde-ser"))]
+pub use async_io::to_async_writer;
+
+#[cfg(feature= "serde-ser")]
 mod ser;
-#[cfg(feature= "serde")]
+#[cfg(feature= "serde-de")]
 mod de;
+#[cfg(feature= "serde-de")]
+mod de_slice;
+#[cfg(any(feature= "serde-ser", feature= "serde-de"))]
+pub mod transcode;
 
 // mod list;
 
 
-#[cfg(feature= "serde")]
-pub use front::{encode, encode_named, encode_tag, decode, decode_named, decode_tag};
\ No newline at end of file
+#[cfg(feature= "serde-ser")]
+pub use front::{encode, encode_named, encode_tag, encode_bare, encode_with, encode_named_with, encode_tag_with, encode_bare_with};
+#[cfg(feature= "serde-de")]
+pub use front::{decode, decode_named, decode_expect_named, decode_tag, decode_with, decode_tag_with};
+#[cfg(feature= "serde-de")]
+pub use de_slice::{from_slice, from_slice_with};
+#[cfg(any(feature= "serde-ser", feature= "serde-de"))]
+pub use options::{SerdeOptions, UnsignedOverflow, RootHandling};
\ No newline at end of file
@@ -0,0 +1,92 @@
+// The `nbt!` macro, building tags from JSON-like literals.
+
+/// Create a `Tag` or `Blob` from a JSON-like literal.
+///
+/// Braces create compounds, and brackets create lists. Any other value is converted with `ToTag`,
+/// so its type picks the tag type: `20.0f32` is a float, `1.0` a double, `1` an int and `1i8` a
+/// byte. Keys are any single token convertible into a `String`, such as a string literal or a
+/// variable, and values may be any expression.
+///
+/// Prefix a compound with `Blob` to create a blob with an empty root name, or `Blob(name)` to name
+/// its root.
+///
+/// Lists are not checked, so a list holding more than one type fails when it is written.
+///
+/// ## Example
+/// ```
+/// use nbt::{nbt, Tag};
+///
+/// let health = 20.0f32;
+/// let player = nbt!({
+///     "name": "Steve",
+///     "Health": health,
+///     "Pos": [1.0, 64.0, -3.5],
+///     "Inventory": [
+///         { "id": "minecraft:stone", "Count": 1i8 },
+///     ],
+///     "Scores": vec![1, 2, 3],
+/// });
+///
+/// assert_eq!(player["Health"], Tag::Float(20.0));
+/// assert_eq!(player["Pos"][2], Tag::Double(-3.5));
+/// assert_eq!(player["Inventory"][0]["Count"], Tag::Byte(1));
+/// assert_eq!(player["Scores"], Tag::IntArray(vec![1, 2, 3]));
+///
+/// let blob = nbt!(Blob("hello world") { "name": "Bananrama" });
+/// assert_eq!(blob.root, "hello world");
+/// assert_eq!(blob.get::<String>("name").unwrap(), "Bananrama");
+/// ```
+#[macro_export]
+macro_rules! nbt {
+    (Blob { $($tt:tt)* }) => {
+        $crate::nbt!(Blob("") { $($tt)* })
+    };
+    (Blob($root:expr) { $($tt:tt)* }) => {{
+        #[allow(unused_mut)]
+        let mut blob = $crate::Blob::create(::std::convert::AsRef::<str>::as_ref(&$root));
+        $crate::nbt!(@compound blob.elements, $($tt)*);
+        blob
+    }};
+    ({ $($tt:tt)* }) => {{
+        #[allow(unused_mut)]
+        let mut elements = $crate::CompoundMap::default();
+        $crate::nbt!(@compound elements, $($tt)*);
+        $crate::Tag::Compound(elements)
+    }};
+    ([ $($tt:tt)* ]) => {
+        $crate::Tag::List($crate::nbt!(@list [] $($tt)*))
+    };
+
+    // Insert the elements of a compound into `$map`, one at a time.
+    (@compound $map:expr, ) => {};
+    (@compound $map:expr, $key:tt : { $($value:tt)* } $(, $($rest:tt)*)?) => {
+        $map.insert(::std::convert::Into::<::std::string::String>::into($key), $crate::nbt!({ $($value)* }));
+        $crate::nbt!(@compound $map, $($($rest)*)?);
+    };
+    (@compound $map:expr, $key:tt : [ $($value:tt)* ] $(, $($rest:tt)*)?) => {
+        $map.insert(::std::convert::Into::<::std::string::String>::into($key), $crate::nbt!([ $($value)* ]));
+        $crate::nbt!(@compound $map, $($($rest)*)?);
+    };
+    (@compound $map:expr, $key:tt : $value:expr $(, $($rest:tt)*)?) => {
+        $map.insert(::std::convert::Into::<::std::string::String>::into($key), $crate::ToTag::into_tag($value));
+        $crate::nbt!(@compound $map, $($($rest)*)?);
+    };
+
+    // Collect the items of a list, then create the vec at once.
+    (@list [$($items:expr,)*]) => {
+        ::std::vec![$($items,)*]
+    };
+    (@list [$($items:expr,)*] { $($item:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::nbt!(@list [$($items,)* $crate::nbt!({ $($item)* }),] $($($rest)*)?)
+    };
+    (@list [$($items:expr,)*] [ $($item:tt)* ] $(, $($rest:tt)*)?) => {
+        $crate::nbt!(@list [$($items,)* $crate::nbt!([ $($item)* ]),] $($($rest)*)?)
+    };
+    (@list [$($items:expr,)*] $item:expr $(, $($rest:tt)*)?) => {
+        $crate::nbt!(@list [$($items,)* $crate::ToTag::into_tag($item),] $($($rest)*)?)
+    };
+
+    ($value:expr) => {
+        $crate::ToTag::into_tag($value)
+    };
+}
@@ -0,0 +1,26 @@
+#[doc(hidden)]
+pub use luna_nbt_macros::snbt as __snbt;
+
+/// Create a `Tag` from stringified NBT (SNBT), parsed at compile time.
+///
+/// The SNBT is the text form used by Minecraft commands, so values can be pasted from the game.
+/// Invalid SNBT, or a list holding more than one type, is a compile error.
+///
+/// Requires the `macros` cargo feature.
+///
+/// ## Example
+/// ```
+/// use nbt::{snbt, Tag};
+///
+/// let item = snbt!(r#"{id:"minecraft:stone",Count:1b,tag:{Damage:0}}"#);
+///
+/// assert_eq!(item.ident(), nbt::TagIdent::TAG_Compound);
+/// assert_eq!(snbt!("[I; 1, 2, 3]"), Tag::IntArray(vec![1, 2, 3]));
+/// assert_eq!(snbt!("1.5f"), Tag::Float(1.5));
+/// ```
+#[macro_export]
+macro_rules! snbt {
+    ($snbt:literal) => {
+        $crate::__snbt!($crate, $snbt)
+    };
+}
@@ -0,0 +1,77 @@
+use crate::blob::Blob;
+use crate::error::{NBTResult, NBTError};
+
+/// The key of the data version within a document.
+pub const DATA_VERSION: &str = "DataVersion";
+
+type Step = Box<dyn Fn(&mut Blob) + Send + Sync>;
+
+#[derive(Default)]
+/// A registry of upgrade steps between data versions.
+///
+/// Each step upgrades a document from one `DataVersion` to a later one, and `migrate_to` chains
+/// them together to reach a target version.
+///
+/// ## Example
+/// ```
+/// # use nbt::{Blob, Migrations};
+/// let mut migrations = Migrations::new();
+/// migrations.register(100, 200, |blob| {
+///     if let Some(name) = blob.elements.remove("Name") {
+///         blob.insert("CustomName", name);
+///     }
+/// });
+/// migrations.register(200, 300, |blob| { blob.insert("Upgraded", 1i8); });
+///
+/// let mut blob = Blob::new();
+/// blob.insert("DataVersion", 100);
+/// blob.insert("Name", "Steve");
+///
+/// assert_eq!(migrations.migrate_to(&mut blob, 300).unwrap(), 300);
+/// assert_eq!(blob.get::<String>("CustomName").unwrap(), "Steve");
+/// assert_eq!(blob.get::<i32>("DataVersion"), Some(&300));
+/// ```
+pub struct Migrations {
+    steps: Vec<(i32, i32, Step)>
+}
+
+impl Migrations {
+    /// Create a empty registry.
+    pub fn new() -> Migrations {
+        Migrations::default()
+    }
+
+    /// Register a step upgrading documents from version `from` to version `to`.
+    ///
+    /// The step applies to any document with a version in `from..to`. The `DataVersion` is set to `to`
+    /// after the step runs.
+    pub fn register<F: Fn(&mut Blob) + Send + Sync + 'static>(&mut self, from: i32, to: i32, step: F) -> &mut Migrations {
+        self.steps.push((from, to, Box::new(step)));
+        self
+    }
+
+    /// Upgrade a document to the target version, returning the version reached.
+    ///
+    /// At each version the applicable step with the smallest `to` is run. Returns
+    /// `NBTError::NoMigration` if the target cannot be reached, in which case the document is left
+    /// at the last version reached.
+    pub fn migrate_to(&self, blob: &mut Blob, target: i32) -> NBTResult<i32> {
+        let mut version = *blob.try_get::<i32>(DATA_VERSION)?;
+
+        while version < target {
+            let step = self.steps.iter()
+                .filter(|(from, to, _)| *from <= version && version < *to && *to <= target)
+                .min_by_key(|(_, to, _)| *to);
+
+            match step {
+                Some((_, to, step)) => {
+                    step(blob);
+                    version = *to;
+                    blob.insert(DATA_VERSION, version);
+                }
+                None => return Err(NBTError::NoMigration { from: version, to: target })
+            }
+        }
+        Ok(version)
+    }
+}
@@ -0,0 +1,162 @@
+use crate::tags::{Tag, TagIdent, CompoundMap};
+use crate::encode::ensure_list_integrity;
+
+#[derive(Clone, Default)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// Options for `Tag::normalize`.
+pub struct NormalizeOptions {
+    pub(crate) lists_to_arrays: bool,
+    pub(crate) canonical_floats: bool,
+    pub(crate) mixed_lists: bool,
+}
+
+impl NormalizeOptions {
+    /// Create options that change nothing.
+    pub fn new() -> NormalizeOptions {
+        NormalizeOptions::default()
+    }
+
+    /// Convert non-empty lists of bytes, ints or longs into their array tags.
+    pub fn lists_to_arrays(mut self, enabled: bool) -> NormalizeOptions {
+        self.lists_to_arrays = enabled;
+        self
+    }
+
+    /// Collapse `-0.0` into `0.0` and every NaN into the standard NaN.
+    pub fn canonical_floats(mut self, enabled: bool) -> NormalizeOptions {
+        self.canonical_floats = enabled;
+        self
+    }
+
+    /// Make lists holding more than one type of tag valid.
+    ///
+    /// Lists of numbers are converted to the widest type among them, and any other list has each of
+    /// its items wrapped in a compound under the key `""`. Lists read from NBT data always hold a
+    /// single type, so this is for lists built in memory or transcoded from other formats.
+    ///
+    /// ```
+    /// # use nbt::{Tag, NormalizeOptions};
+    /// let options = NormalizeOptions::new().mixed_lists(true);
+    ///
+    /// let mut list = Tag::List(vec![Tag::Byte(1), Tag::Float(0.5)]);
+    /// list.normalize(&options);
+    /// assert_eq!(list, Tag::List(vec![Tag::Float(1.0), Tag::Float(0.5)]));
+    /// ```
+    pub fn mixed_lists(mut self, enabled: bool) -> NormalizeOptions {
+        self.mixed_lists = enabled;
+        self
+    }
+}
+
+impl Tag {
+    /// Convert the tag, and every tag within it, into a canonical form for hashing and comparison.
+    ///
+    /// Compounds are unordered maps, so key order is not part of the in-memory form. Unsigned byte
+    /// arrays always become `ByteArray`, as both are the same `TAG_Byte_Array`.
+    ///
+    /// ```
+    /// # use nbt::{Tag, NormalizeOptions};
+    /// let options = NormalizeOptions::new().lists_to_arrays(true).canonical_floats(true);
+    ///
+    /// let mut list = Tag::List(vec![Tag::Int(1), Tag::Int(2)]);
+    /// list.normalize(&options);
+    /// assert_eq!(list, Tag::IntArray(vec![1, 2]));
+    ///
+    /// let mut zero = Tag::Double(-0.0);
+    /// zero.normalize(&options);
+    /// assert!(matches!(zero, Tag::Double(x) if x.is_sign_positive()));
+    /// ```
+    pub fn normalize(&mut self, options: &NormalizeOptions) {
+        match self {
+            Tag::Float(x) if options.canonical_floats => {
+                if x.is_nan() { *x = f32::NAN } else if *x == 0.0 { *x = 0.0 }
+            }
+            Tag::Double(x) if options.canonical_floats => {
+                if x.is_nan() { *x = f64::NAN } else if *x == 0.0 { *x = 0.0 }
+            }
+            Tag::UnsignedByteArray(bytes) => {
+                *self = Tag::UnsignedByteArray(std::mem::take(bytes)).with_unsigned_bytes(false);
+            }
+            Tag::Compound(map) => {
+                for value in map.values_mut() {
+                    value.normalize(options);
+                }
+            }
+            Tag::List(list) => {
+                for item in list.iter_mut() {
+                    item.normalize(options);
+                }
+                if options.mixed_lists && ensure_list_integrity(list).is_err() {
+                    *list = coerce_list(list);
+                }
+                if options.lists_to_arrays {
+                    if let Some(array) = list_to_array(list) {
+                        *self = array;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Convert a homogeneous list of bytes, ints or longs into an array tag.
+fn list_to_array(list: &[Tag]) -> Option<Tag> {
+    match list.first()? {
+        Tag::Byte(_) => list.iter().map(|x| if let Tag::Byte(v) = x { Some(*v) } else { None }).collect::<Option<Vec<i8>>>().map(Tag::ByteArray),
+        Tag::Int(_) => list.iter().map(|x| if let Tag::Int(v) = x { Some(*v) } else { None }).collect::<Option<Vec<i32>>>().map(Tag::IntArray),
+        Tag::Long(_) => list.iter().map(|x| if let Tag::Long(v) = x { Some(*v) } else { None }).collect::<Option<Vec<i64>>>().map(Tag::LongArray),
+        _ => None
+    }
+}
+
+// Convert a list holding more than one type into a list of one type.
+// Numbers are widened to the widest type present, with ints and longs taking floats to doubles
+// so their precision is kept. Anything else is wrapped in a compound with the key "".
+pub(crate) fn coerce_list(list: &[Tag]) -> Vec<Tag> {
+    let mut widest = TagIdent::TAG_Byte;
+    let mut has_float = false;
+    let mut has_wide_int = false;
+    for item in list {
+        let ident = item.ident();
+        match ident {
+            TagIdent::TAG_Byte | TagIdent::TAG_Short => {}
+            TagIdent::TAG_Int | TagIdent::TAG_Long => has_wide_int = true,
+            TagIdent::TAG_Float | TagIdent::TAG_Double => has_float = true,
+            _ => return list.iter().map(wrap).collect()
+        }
+        if ident > widest {
+            widest = ident;
+        }
+    }
+    if has_float && has_wide_int {
+        widest = TagIdent::TAG_Double;
+    }
+    list.iter().map(|item| widen(item, &widest)).collect()
+}
+
+fn wrap(item: &Tag) -> Tag {
+    let mut map = CompoundMap::default();
+    map.insert(String::new(), item.clone());
+    Tag::Compound(map)
+}
+
+// Convert a number into the given, wider, type.
+fn widen(item: &Tag, ident: &TagIdent) -> Tag {
+    let (int, float) = match item {
+        Tag::Byte(x) => (*x as i64, *x as f64),
+        Tag::Short(x) => (*x as i64, *x as f64),
+        Tag::Int(x) => (*x as i64, *x as f64),
+        Tag::Long(x) => (*x, *x as f64),
+        Tag::Float(x) => (0, *x as f64),
+        Tag::Double(x) => (0, *x),
+        _ => unreachable!()
+    };
+    match ident {
+        TagIdent::TAG_Short => Tag::Short(int as i16),
+        TagIdent::TAG_Int => Tag::Int(int as i32),
+        TagIdent::TAG_Long => Tag::Long(int),
+        TagIdent::TAG_Float => Tag::Float(float as f32),
+        _ => Tag::Double(float)
+    }
+}
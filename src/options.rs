@@ -0,0 +1,410 @@
+#[cfg(all(feature="serde_unsigned", any(feature="serde-ser", feature="serde-de")))]
+use crate::{Tag, NBTError, NBTResult};
+#[cfg(all(feature="serde_unsigned", feature="serde-de"))]
+use crate::TagIdent;
+#[cfg(all(feature="serde_unsigned", feature="serde-de"))]
+use std::convert::TryInto;
+use crate::progress::{Progress, ProgressCallback};
+use crate::shared::Interner;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// A cap on the memory allocated while reading a single document.
+///
+/// Strings, arrays, lists and compound entries are charged against the budget before they are
+/// allocated, and the read fails with `NBTError::MemoryBudgetExceeded` once it is used up.
+/// The accounting is an estimate of heap usage, not an exact measurement.
+pub struct MemoryBudget {
+    pub(crate) limit: usize
+}
+
+impl MemoryBudget {
+    /// Create a budget of `limit` bytes.
+    pub fn new(limit: usize) -> MemoryBudget {
+        MemoryBudget { limit }
+    }
+
+    /// The limit of the budget in bytes.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// How the bytes of strings are checked and decoded while reading.
+pub enum StringMode {
+    /// Decode strings as the flavor does, failing with `NBTError::StringError` if they cannot be.
+    /// The default.
+    ///
+    /// `JavaFlavor` accepts plain UTF-8 as well as modified UTF-8, as long as a string does not mix them.
+    #[default]
+    Flavor,
+    /// Only accept strings in the exact encoding of the flavor.
+    ///
+    /// For `JavaFlavor` this is modified UTF-8, so raw nul bytes and four byte UTF-8 sequences,
+    /// as written by non-Java tools, are rejected.
+    Strict,
+    /// Decode strings as standard UTF-8, whatever the flavor.
+    ///
+    /// Surrogate pairs, as Java writes characters outside the basic multilingual plane, are rejected.
+    Utf8,
+    /// Decode strings as the flavor does, replacing anything undecodable with U+FFFD.
+    ///
+    /// Affected strings are reported as `Diagnostic::LossyString` when collecting diagnostics.
+    Lossy,
+}
+
+// How deeply lists and compounds may nest when reading, unless `ReadOptions::max_depth` is set.
+pub(crate) const DEFAULT_MAX_DEPTH: usize = 512;
+
+#[derive(Clone, Default)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// Options controlling how NBT data is read.
+///
+/// ## Example
+/// ```
+/// # use nbt::{Blob, NBTRead, ReadOptions, MemoryBudget};
+/// let options = ReadOptions::new()
+///     .memory_budget(MemoryBudget::new(1024 * 1024));
+///
+/// let data = vec![10, 0, 0, 0];
+/// let blob = Blob::from_bytes_with_options(data, &options).unwrap();
+/// ```
+pub struct ReadOptions {
+    pub(crate) memory_budget: Option<MemoryBudget>,
+    pub(crate) string_mode: StringMode,
+    pub(crate) lenient_eof: bool,
+    pub(crate) unsigned_byte_arrays: bool,
+    pub(crate) progress: Option<ProgressCallback>,
+    pub(crate) cancel: Option<Arc<AtomicBool>>,
+    pub(crate) interner: Option<Interner>,
+    pub(crate) intern_values: bool,
+    pub(crate) max_depth: Option<usize>,
+}
+
+impl ReadOptions {
+    /// Create the default options.
+    pub fn new() -> ReadOptions {
+        ReadOptions::default()
+    }
+
+    /// Limit the memory allocated while reading.
+    pub fn memory_budget(mut self, budget: MemoryBudget) -> ReadOptions {
+        self.memory_budget = Some(budget);
+        self
+    }
+
+    /// Replace undecodable string data with U+FFFD instead of failing with `NBTError::StringError`.
+    ///
+    /// Affected strings are reported as `Diagnostic::LossyString` when collecting diagnostics.
+    /// This is the same as `string_mode(StringMode::Lossy)`, and disabling it restores the default mode.
+    pub fn lossy_strings(mut self, enabled: bool) -> ReadOptions {
+        self.string_mode = if enabled { StringMode::Lossy } else { StringMode::Flavor };
+        self
+    }
+
+    /// Choose how the bytes of strings are checked and decoded. See `StringMode`.
+    ///
+    /// ## Example
+    /// ```
+    /// # use nbt::{Blob, NBTRead, ReadOptions, StringMode};
+    /// // A string holding a nul byte, which Java would encode as two bytes.
+    /// let data = vec![10, 0, 0, 8, 0, 1, 97, 0, 1, 0, 0];
+    ///
+    /// assert!(Blob::from_bytes(&data).is_ok());
+    /// assert!(Blob::from_bytes_with_options(&data, &ReadOptions::new().string_mode(StringMode::Strict)).is_err());
+    /// ```
+    pub fn string_mode(mut self, mode: StringMode) -> ReadOptions {
+        self.string_mode = mode;
+        self
+    }
+
+    /// Accept the end of the data in place of the end tag closing the root compound, instead of
+    /// failing with `NBTError::IO`.
+    ///
+    /// This salvages documents truncated by a single byte. The missing tag is reported as
+    /// `Diagnostic::MissingEndTag` when collecting diagnostics.
+    ///
+    /// ## Example
+    /// ```
+    /// # use nbt::{Blob, NBTRead, ReadOptions};
+    /// let options = ReadOptions::new().lenient_eof(true);
+    ///
+    /// let data = vec![10, 0, 0, 1, 0, 1, 97, 42];
+    /// let blob = Blob::from_bytes_with_options(data, &options).unwrap();
+    /// assert_eq!(blob.get::<i8>("a"), Some(&42));
+    /// ```
+    pub fn lenient_eof(mut self, enabled: bool) -> ReadOptions {
+        self.lenient_eof = enabled;
+        self
+    }
+
+    /// Read byte arrays as `Tag::UnsignedByteArray` instead of `Tag::ByteArray`.
+    ///
+    /// The bytes are read in one go, and can be used as raw data without converting them.
+    ///
+    /// ## Example
+    /// ```
+    /// # use nbt::{Blob, NBTRead, ReadOptions};
+    /// let options = ReadOptions::new().unsigned_byte_arrays(true);
+    ///
+    /// let data = vec![10, 0, 0, 7, 0, 1, 97, 0, 0, 0, 2, 255, 1, 0];
+    /// let blob = Blob::from_bytes_with_options(data, &options).unwrap();
+    /// assert_eq!(blob.compound_ref()["a"].byte_array_as_u8(), Some(&[255u8, 1][..]));
+    /// ```
+    pub fn unsigned_byte_arrays(mut self, enabled: bool) -> ReadOptions {
+        self.unsigned_byte_arrays = enabled;
+        self
+    }
+
+    /// Call `callback` periodically with the progress of the read, and once more when it completes.
+    ///
+    /// ## Example
+    /// ```
+    /// # use nbt::{Blob, NBTRead, ReadOptions};
+    /// let options = ReadOptions::new()
+    ///     .progress(|progress| println!("{} bytes read", progress.bytes));
+    ///
+    /// let data = vec![10, 0, 0, 0];
+    /// let blob = Blob::from_bytes_with_options(data, &options).unwrap();
+    /// ```
+    pub fn progress<F: Fn(Progress) + Send + Sync + 'static>(mut self, callback: F) -> ReadOptions {
+        self.progress = Some(ProgressCallback(Arc::new(callback)));
+        self
+    }
+
+    /// Abort the read with `NBTError::Cancelled` once `flag` is set.
+    ///
+    /// The flag is checked before every compound entry, list element and array, so a read can be
+    /// cancelled from another thread while it is in progress.
+    ///
+    /// ## Example
+    /// ```
+    /// # use nbt::{Blob, NBTRead, NBTError, ReadOptions};
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    ///
+    /// let cancel = Arc::new(AtomicBool::new(false));
+    /// let options = ReadOptions::new().cancel_flag(cancel.clone());
+    ///
+    /// cancel.store(true, Ordering::Relaxed);
+    /// let data = vec![10, 0, 0, 1, 0, 1, 97, 42, 0];
+    /// assert!(matches!(Blob::from_bytes_with_options(data, &options), Err(NBTError::Cancelled)));
+    /// ```
+    pub fn cancel_flag(mut self, flag: Arc<AtomicBool>) -> ReadOptions {
+        self.cancel = Some(flag);
+        self
+    }
+
+    /// Fail with `NBTError::DepthLimitExceeded` if lists and compounds nest deeper than `depth`,
+    /// rather than the default of 512.
+    ///
+    /// Reading recurses for each level, so the limit keeps hostile input from overflowing the stack.
+    ///
+    /// ## Example
+    /// ```
+    /// # use nbt::{Blob, NBTRead, NBTError, ReadOptions};
+    /// let options = ReadOptions::new().max_depth(1);
+    ///
+    /// // A compound holding an empty compound.
+    /// let data = vec![10, 0, 0, 10, 0, 1, 97, 0, 0];
+    /// assert!(Blob::from_bytes(&data).is_ok());
+    /// assert!(matches!(Blob::from_bytes_with_options(&data, &options), Err(NBTError::DepthLimitExceeded { limit: 1 })));
+    /// ```
+    pub fn max_depth(mut self, depth: usize) -> ReadOptions {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Share the compound keys of `SharedTag`s read with these options through `interner`.
+    ///
+    /// Store a clone of the same interner in the options of every read whose results are kept, so
+    /// identical keys are only stored once. Keys are looked up as they are read, so a key already
+    /// in the interner costs no allocation.
+    ///
+    /// Only reads of `SharedTag` use the interner. `Blob`, `Tag` and the other types hold their keys
+    /// as `String`s, so reading them ignores it.
+    pub fn interner(mut self, interner: Interner) -> ReadOptions {
+        self.interner = Some(interner);
+        self
+    }
+
+    /// Share string values through the interner as well as compound keys. Off by default, as
+    /// values are far less often repeated than keys.
+    pub fn intern_values(mut self, enabled: bool) -> ReadOptions {
+        self.intern_values = enabled;
+        self
+    }
+}
+
+#[derive(Clone, Default)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// Options controlling how NBT data is written.
+///
+/// ## Example
+/// ```
+/// # use nbt::{Tag, NBTWrite, WriteOptions};
+/// let options = WriteOptions::new().mixed_lists(true);
+///
+/// let list = Tag::List(vec![Tag::Byte(1), Tag::Int(2)]);
+/// let mut buffer = Vec::new();
+/// list.write_with(&mut buffer, &options).unwrap();
+/// # assert_eq!(buffer, vec![3, 0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2]);
+/// ```
+pub struct WriteOptions {
+    pub(crate) mixed_lists: bool,
+    pub(crate) sorted_keys: bool,
+}
+
+impl WriteOptions {
+    /// Create the default options.
+    pub fn new() -> WriteOptions {
+        WriteOptions::default()
+    }
+
+    /// Write lists holding more than one type of tag instead of failing with `NBTError::InvalidList`.
+    ///
+    /// Lists of numbers are written as a list of the widest type among them, and any other list
+    /// has each of its items wrapped in a compound under the key `""`.
+    /// The same conversion is available in memory through `NormalizeOptions::mixed_lists`.
+    pub fn mixed_lists(mut self, enabled: bool) -> WriteOptions {
+        self.mixed_lists = enabled;
+        self
+    }
+
+    /// Write the elements of every compound in order of their keys, compared by bytes, rather than
+    /// in the order the compound iterates them.
+    ///
+    /// Equal blobs then always produce identical bytes, as needed for content hashing and cache
+    /// keys. Sorting costs a vec of references per compound written.
+    /// ```
+    /// # use nbt::{Blob, NBTWrite, WriteOptions};
+    /// let options = WriteOptions::new().sorted_keys(true);
+    ///
+    /// let mut a = Blob::new();
+    /// a.insert("x", 1);
+    /// a.insert("y", 2);
+    /// let mut b = Blob::new();
+    /// b.insert("y", 2);
+    /// b.insert("x", 1);
+    ///
+    /// let (mut left, mut right) = (Vec::new(), Vec::new());
+    /// a.write_with(&mut left, &options).unwrap();
+    /// b.write_with(&mut right, &options).unwrap();
+    /// assert_eq!(left, right);
+    /// ```
+    pub fn sorted_keys(mut self, enabled: bool) -> WriteOptions {
+        self.sorted_keys = enabled;
+        self
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature="debug", derive(Debug))]
+#[cfg(any(feature="serde-ser", feature="serde-de"))]
+/// How a `u64` above `i64::MAX` is stored when the `serde_unsigned` feature is enabled.
+///
+/// Smaller values are always stored as a `TAG_Long`. Decoding accepts what the same policy encodes.
+pub enum UnsignedOverflow {
+    /// Wrap around into a negative long, and back again when decoding. The default.
+    #[default]
+    Wrap,
+    /// Fail with `NBTError::UnsignedOutOfRange`. Negative longs are rejected when decoding.
+    Error,
+    /// Store `i64::MAX`. Negative longs are rejected when decoding.
+    Saturate,
+    /// Store the decimal value as a `TAG_String`.
+    String,
+    /// Store the value as a `TAG_Byte_Array` of 8 big endian bytes.
+    ByteArray,
+}
+
+#[cfg(all(feature="serde_unsigned", feature="serde-ser"))]
+impl UnsignedOverflow {
+    pub(crate) fn encode(self, value: u64) -> NBTResult<Tag> {
+        if value <= i64::MAX as u64 {
+            return Ok(Tag::Long(value as i64));
+        }
+        match self {
+            UnsignedOverflow::Wrap => Ok(Tag::Long(value as i64)),
+            UnsignedOverflow::Error => Err(NBTError::UnsignedOutOfRange { value: value as i128 }),
+            UnsignedOverflow::Saturate => Ok(Tag::Long(i64::MAX)),
+            UnsignedOverflow::String => Ok(Tag::String(value.to_string())),
+            UnsignedOverflow::ByteArray => Ok(Tag::ByteArray(value.to_be_bytes().iter().map(|x| *x as i8).collect())),
+        }
+    }
+}
+
+#[cfg(all(feature="serde_unsigned", feature="serde-de"))]
+impl UnsignedOverflow {
+    pub(crate) fn decode(self, tag: Tag) -> NBTResult<u64> {
+        match (self, tag) {
+            (UnsignedOverflow::Wrap, Tag::Long(x)) => Ok(x as u64),
+            (_, Tag::Long(x)) if x < 0 => Err(NBTError::UnsignedOutOfRange { value: x as i128 }),
+            (_, Tag::Long(x)) => Ok(x as u64),
+            (UnsignedOverflow::String, Tag::String(x)) => x.parse()
+                .map_err(|_| NBTError::Custom(format!("Could not parse '{}' as a u64", x))),
+            (UnsignedOverflow::ByteArray, tag @ Tag::ByteArray(_)) | (UnsignedOverflow::ByteArray, tag @ Tag::UnsignedByteArray(_)) => {
+                let bytes = tag.byte_array_as_u8().unwrap_or_default();
+                let bytes: [u8; 8] = bytes.try_into()
+                    .map_err(|_| NBTError::Custom(format!("Expected 8 bytes for a u64, found {}", bytes.len())))?;
+                Ok(u64::from_be_bytes(bytes))
+            }
+            (_, tag) => Err(NBTError::InvalidType { found: tag.ident(), expecting: TagIdent::TAG_Long, when: "u64".to_string() }),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Default)]
+#[cfg_attr(feature="debug", derive(Debug))]
+#[cfg(any(feature="serde-ser", feature="serde-de"))]
+/// What `encode_named` does with a value that doesn't serialize to a compound, such as a `Vec` or
+/// a number.
+///
+/// To send such a value as a bare tag, as done over the network, use `encode_bare` instead.
+pub enum RootHandling {
+    /// Return `NBTError::InvalidImplicit`. The default.
+    #[default]
+    Error,
+    /// Store the value in a compound under the given key. A value that serializes to nothing,
+    /// such as `None`, gives an empty compound.
+    Wrap(String),
+}
+
+#[derive(Clone, Default)]
+#[cfg_attr(feature="debug", derive(Debug))]
+#[cfg(any(feature="serde-ser", feature="serde-de"))]
+/// Options controlling how values are encoded to and decoded from tags with Serde.
+///
+/// ## Example
+/// ```
+/// # use nbt::{SerdeOptions, UnsignedOverflow};
+/// let options = SerdeOptions::new().unsigned_overflow(UnsignedOverflow::String);
+/// ```
+pub struct SerdeOptions {
+    pub(crate) unsigned_overflow: UnsignedOverflow,
+    pub(crate) root_handling: RootHandling,
+}
+
+#[cfg(any(feature="serde-ser", feature="serde-de"))]
+impl SerdeOptions {
+    /// Create the default options.
+    pub fn new() -> SerdeOptions {
+        SerdeOptions::default()
+    }
+
+    /// Choose how a `u64` above `i64::MAX` is stored. Only used with the `serde_unsigned` feature.
+    pub fn unsigned_overflow(mut self, policy: UnsignedOverflow) -> SerdeOptions {
+        self.unsigned_overflow = policy;
+        self
+    }
+
+    /// Choose what `encode_named` does with a value that doesn't serialize to a compound.
+    pub fn root_handling(mut self, handling: RootHandling) -> SerdeOptions {
+        self.root_handling = handling;
+        self
+    }
+}
@@ -0,0 +1,85 @@
+use crate::tags::{Tag, CompoundMap};
+use std::cmp::Ordering;
+
+/// Tags are equal when they are of the same type and hold the same value.
+///
+/// Floats are compared by their bits, so `NaN` equals itself and `0.0` does not equal `-0.0`.
+impl PartialEq for Tag {
+    fn eq(&self, other: &Tag) -> bool {
+        // Signed and unsigned byte arrays hold the same bytes either way.
+        if let (Some(a), Some(b)) = (self.byte_array_as_i8(), other.byte_array_as_i8()) {
+            return a == b;
+        }
+        match (self, other) {
+            (Tag::Byte(a), Tag::Byte(b)) => a == b,
+            (Tag::Short(a), Tag::Short(b)) => a == b,
+            (Tag::Int(a), Tag::Int(b)) => a == b,
+            (Tag::Long(a), Tag::Long(b)) => a == b,
+            (Tag::Float(a), Tag::Float(b)) => a.to_bits() == b.to_bits(),
+            (Tag::Double(a), Tag::Double(b)) => a.to_bits() == b.to_bits(),
+            (Tag::String(a), Tag::String(b)) => a == b,
+            (Tag::List(a), Tag::List(b)) => a == b,
+            (Tag::Compound(a), Tag::Compound(b)) => a == b,
+            (Tag::IntArray(a), Tag::IntArray(b)) => a == b,
+            (Tag::LongArray(a), Tag::LongArray(b)) => a == b,
+            _ => false
+        }
+    }
+}
+
+impl Eq for Tag {}
+
+impl PartialOrd for Tag {
+    fn partial_cmp(&self, other: &Tag) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Tags are ordered by their type id, then by their value.
+///
+/// - Numbers are ordered numerically. Floats use the IEEE-754 total order, so `-0.0` comes before
+///   `0.0`, and negative and positive `NaN` come before and after every other value.
+/// - Strings are ordered by their UTF-8 bytes.
+/// - Lists and arrays are ordered lexicographically by their items.
+/// - Compounds are ordered lexicographically by their entries, sorted by key.
+///
+/// ```
+/// use nbt::Tag;
+/// use std::collections::BTreeSet;
+///
+/// let mut list = vec![Tag::Double(1.5), Tag::Int(7), Tag::Byte(3), Tag::Int(-2)];
+/// list.sort();
+/// assert_eq!(list, vec![Tag::Byte(3), Tag::Int(-2), Tag::Int(7), Tag::Double(1.5)]);
+///
+/// let set: BTreeSet<Tag> = vec![Tag::Float(f32::NAN), Tag::Float(f32::NAN)].into_iter().collect();
+/// assert_eq!(set.len(), 1);
+/// ```
+impl Ord for Tag {
+    fn cmp(&self, other: &Tag) -> Ordering {
+        if let (Some(a), Some(b)) = (self.byte_array_as_i8(), other.byte_array_as_i8()) {
+            return a.cmp(b);
+        }
+        match (self, other) {
+            (Tag::Byte(a), Tag::Byte(b)) => a.cmp(b),
+            (Tag::Short(a), Tag::Short(b)) => a.cmp(b),
+            (Tag::Int(a), Tag::Int(b)) => a.cmp(b),
+            (Tag::Long(a), Tag::Long(b)) => a.cmp(b),
+            (Tag::Float(a), Tag::Float(b)) => a.total_cmp(b),
+            (Tag::Double(a), Tag::Double(b)) => a.total_cmp(b),
+            (Tag::String(a), Tag::String(b)) => a.cmp(b),
+            (Tag::List(a), Tag::List(b)) => a.cmp(b),
+            (Tag::Compound(a), Tag::Compound(b)) => compound_cmp(a, b),
+            (Tag::IntArray(a), Tag::IntArray(b)) => a.cmp(b),
+            (Tag::LongArray(a), Tag::LongArray(b)) => a.cmp(b),
+            (a, b) => (a.ident() as u8).cmp(&(b.ident() as u8))
+        }
+    }
+}
+
+fn compound_cmp(a: &CompoundMap, b: &CompoundMap) -> Ordering {
+    let mut a: Vec<(&String, &Tag)> = a.iter().collect();
+    let mut b: Vec<(&String, &Tag)> = b.iter().collect();
+    a.sort_by_key(|(key, _)| *key);
+    b.sort_by_key(|(key, _)| *key);
+    a.cmp(&b)
+}
@@ -0,0 +1,454 @@
+// A map remembering the order its keys were inserted in, backing compounds with the
+// `preserve_order` feature.
+
+use crate::tags::CompoundHasher;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::ops::{Index, IndexMut};
+use std::{fmt, slice, vec};
+#[cfg(feature="rayon")]
+use rayon::prelude::*;
+
+/// A map that iterates in the order its keys were first inserted.
+///
+/// With the `preserve_order` feature, `CompoundMap` is an `OrderedMap`, so documents are written
+/// with their elements in the order they were read. It has the same methods as the `HashMap`
+/// used otherwise, so code works with or without the feature.
+///
+/// Replacing the value of an existing key keeps its position, and removing a key shifts the
+/// following keys down, so both preserve the order of everything else. Keys are stored twice,
+/// once in order and once for lookup.
+///
+/// ## Example
+/// ```
+/// use nbt::OrderedMap;
+///
+/// let mut map = OrderedMap::new();
+/// map.insert("z", 1);
+/// map.insert("a", 2);
+/// map.insert("m", 3);
+/// map.remove("a");
+///
+/// assert_eq!(map.keys().collect::<Vec<_>>(), vec![&"z", &"m"]);
+/// ```
+#[derive(Clone)]
+pub struct OrderedMap<K, V> {
+    entries: Vec<(K, V)>,
+    // The position of each key within `entries`.
+    indices: HashMap<K, usize, CompoundHasher>,
+}
+
+impl<K, V> OrderedMap<K, V> {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        OrderedMap { entries: Vec::new(), indices: HashMap::default() }
+    }
+
+    /// Create an empty map with space for at least `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        OrderedMap::with_capacity_and_hasher(capacity, Default::default())
+    }
+
+    /// Create an empty map with space for at least `capacity` elements, using the given hasher for
+    /// lookups.
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: CompoundHasher) -> Self {
+        OrderedMap { entries: Vec::with_capacity(capacity), indices: HashMap::with_capacity_and_hasher(capacity, hasher) }
+    }
+
+    /// The number of elements the map can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity().min(self.indices.capacity())
+    }
+
+    /// The number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the map has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Remove every element.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.indices.clear();
+    }
+
+    /// Iterate over the elements, in order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter { inner: self.entries.iter() }
+    }
+
+    /// Iterate over the elements with mutable values, in order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut { inner: self.entries.iter_mut() }
+    }
+
+    /// Iterate over the keys, in order.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.entries.iter() }
+    }
+
+    /// Iterate over the values, in order.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.entries.iter() }
+    }
+
+    /// Iterate over mutable values, in order.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut { inner: self.entries.iter_mut() }
+    }
+
+    /// Take ownership of the keys, in order.
+    pub fn into_keys(self) -> impl Iterator<Item = K> {
+        self.entries.into_iter().map(|(key, _)| key)
+    }
+
+    /// Take ownership of the values, in order.
+    pub fn into_values(self) -> impl Iterator<Item = V> {
+        self.entries.into_iter().map(|(_, value)| value)
+    }
+
+    /// Remove every element, returning them in order.
+    pub fn drain(&mut self) -> vec::Drain<'_, (K, V)> {
+        self.indices.clear();
+        self.entries.drain(..)
+    }
+
+    /// Get an element by its position in the map.
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.entries.get(index).map(|(key, value)| (key, value))
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> OrderedMap<K, V> {
+    /// Reserve space for at least `additional` more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        self.entries.reserve(additional);
+        self.indices.reserve(additional);
+    }
+
+    /// Shrink the map to hold only its elements.
+    pub fn shrink_to_fit(&mut self) {
+        self.entries.shrink_to_fit();
+        self.indices.shrink_to_fit();
+    }
+
+    /// Get the value of a key.
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V> where K: Borrow<Q> {
+        self.indices.get(key).map(|index| &self.entries[*index].1)
+    }
+
+    /// Get the key and value of a key.
+    pub fn get_key_value<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<(&K, &V)> where K: Borrow<Q> {
+        self.indices.get(key).map(|index| {
+            let (key, value) = &self.entries[*index];
+            (key, value)
+        })
+    }
+
+    /// Mutably get the value of a key.
+    pub fn get_mut<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&mut V> where K: Borrow<Q> {
+        match self.indices.get(key) {
+            Some(index) => Some(&mut self.entries[*index].1),
+            None => None,
+        }
+    }
+
+    /// The position of a key in the map.
+    pub fn get_index_of<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<usize> where K: Borrow<Q> {
+        self.indices.get(key).copied()
+    }
+
+    /// Whether the map has a key.
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool where K: Borrow<Q> {
+        self.indices.contains_key(key)
+    }
+
+    /// Insert a value, returning the previous value of the key.
+    ///
+    /// A new key is added at the end, while an existing key keeps its position.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.indices.get(&key) {
+            Some(index) => Some(std::mem::replace(&mut self.entries[*index].1, value)),
+            None => {
+                self.indices.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Remove a key, returning its value. The keys after it are shifted down to keep their order.
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V> where K: Borrow<Q> {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Remove a key, returning it and its value. The keys after it are shifted down to keep their
+    /// order.
+    pub fn remove_entry<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<(K, V)> where K: Borrow<Q> {
+        let index = self.indices.remove(key)?;
+        Some(self.remove_index(index))
+    }
+
+    // Remove the element at a position whose key has already been removed from `indices`.
+    fn remove_index(&mut self, index: usize) -> (K, V) {
+        let entry = self.entries.remove(index);
+        for (key, _) in &self.entries[index..] {
+            if let Some(position) = self.indices.get_mut(key) {
+                *position -= 1;
+            }
+        }
+        entry
+    }
+
+    /// Keep only the elements for which `keep` returns true, preserving their order.
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut keep: F) {
+        let before = self.entries.len();
+        self.entries.retain_mut(|(key, value)| keep(key, value));
+        if self.entries.len() != before {
+            self.reindex();
+        }
+    }
+
+    /// Sort the elements by key.
+    pub fn sort_keys(&mut self) where K: Ord {
+        self.entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        self.reindex();
+    }
+
+    // Rebuild `indices` after moving elements.
+    fn reindex(&mut self) {
+        self.indices.clear();
+        for (index, (key, _)) in self.entries.iter().enumerate() {
+            self.indices.insert(key.clone(), index);
+        }
+    }
+
+    /// Get the element of a key, for in-place insertion or modification.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.indices.get(&key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { index: *index, map: self }),
+            None => Entry::Vacant(VacantEntry { key, map: self }),
+        }
+    }
+}
+
+#[cfg(feature="rayon")]
+impl<K: Sync + Send, V: Sync + Send> OrderedMap<K, V> {
+    /// Iterate over the elements in parallel.
+    pub fn par_iter(&self) -> impl IndexedParallelIterator<Item = (&K, &V)> {
+        self.entries.par_iter().map(|(key, value)| (key, value))
+    }
+
+    /// Iterate over the elements with mutable values in parallel.
+    pub fn par_iter_mut(&mut self) -> impl IndexedParallelIterator<Item = (&K, &mut V)> {
+        self.entries.par_iter_mut().map(|(key, value)| (&*key, value))
+    }
+}
+
+impl<K, V> Default for OrderedMap<K, V> {
+    fn default() -> Self {
+        OrderedMap::new()
+    }
+}
+
+/// Maps are equal when they hold the same keys and values, in any order.
+impl<K: Hash + Eq + Clone, V: PartialEq> PartialEq for OrderedMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Eq> Eq for OrderedMap<K, V> {}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for OrderedMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, Q: Hash + Eq + ?Sized> Index<&Q> for OrderedMap<K, V> where K: Borrow<Q> {
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, Q: Hash + Eq + ?Sized> IndexMut<&Q> for OrderedMap<K, V> where K: Borrow<Q> {
+    fn index_mut(&mut self, key: &Q) -> &mut V {
+        self.get_mut(key).expect("no entry found for key")
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Extend<(K, V)> for OrderedMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> FromIterator<(K, V)> for OrderedMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = OrderedMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V> IntoIterator for OrderedMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a OrderedMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a mut OrderedMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+macro_rules! ordered_iter {
+    ($name:ident, $inner:ident, $item:ty, |$entry:pat_param| $map:expr) => {
+        /// An iterator over the elements of an `OrderedMap`, in order.
+        pub struct $name<'a, K, V> {
+            inner: slice::$inner<'a, (K, V)>,
+        }
+
+        impl<'a, K, V> Iterator for $name<'a, K, V> {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                self.inner.next().map(|$entry| $map)
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.inner.size_hint()
+            }
+        }
+
+        impl<'a, K, V> DoubleEndedIterator for $name<'a, K, V> {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.inner.next_back().map(|$entry| $map)
+            }
+        }
+
+        impl<'a, K, V> ExactSizeIterator for $name<'a, K, V> {}
+    };
+}
+
+ordered_iter!(Iter, Iter, (&'a K, &'a V), |(key, value)| (key, value));
+ordered_iter!(IterMut, IterMut, (&'a K, &'a mut V), |(key, value)| (&*key, value));
+ordered_iter!(Keys, Iter, &'a K, |(key, _)| key);
+ordered_iter!(Values, Iter, &'a V, |(_, value)| value);
+ordered_iter!(ValuesMut, IterMut, &'a mut V, |(_, value)| value);
+
+/// An element of an `OrderedMap`, which may be present or not.
+pub enum Entry<'a, K, V> {
+    /// An element that is present.
+    Occupied(OccupiedEntry<'a, K, V>),
+    /// An element that is missing.
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+impl<'a, K: Hash + Eq + Clone, V> Entry<'a, K, V> {
+    /// Insert `default` if the element is missing, and return the element.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Insert the result of `default` if the element is missing, and return the element.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+}
+
+/// An element of an `OrderedMap` that is present.
+pub struct OccupiedEntry<'a, K, V> {
+    map: &'a mut OrderedMap<K, V>,
+    index: usize,
+}
+
+impl<'a, K: Hash + Eq + Clone, V> OccupiedEntry<'a, K, V> {
+    /// The key of the element.
+    pub fn key(&self) -> &K {
+        &self.map.entries[self.index].0
+    }
+
+    /// Borrow the element.
+    pub fn get(&self) -> &V {
+        &self.map.entries[self.index].1
+    }
+
+    /// Mutably borrow the element.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.entries[self.index].1
+    }
+
+    /// Mutably borrow the element for as long as the map.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.entries[self.index].1
+    }
+
+    /// Replace the element, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Remove the element from the map, returning it. The keys after it are shifted down.
+    pub fn remove(self) -> V {
+        let key = self.map.entries[self.index].0.clone();
+        self.map.indices.remove(&key);
+        self.map.remove_index(self.index).1
+    }
+}
+
+/// An element of an `OrderedMap` that is missing.
+pub struct VacantEntry<'a, K, V> {
+    map: &'a mut OrderedMap<K, V>,
+    key: K,
+}
+
+impl<'a, K: Hash + Eq + Clone, V> VacantEntry<'a, K, V> {
+    /// The key of the element.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Insert the element at the end of the map, and return it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let index = self.map.entries.len();
+        self.map.indices.insert(self.key.clone(), index);
+        self.map.entries.push((self.key, value));
+        &mut self.map.entries[index].1
+    }
+}
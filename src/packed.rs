@@ -0,0 +1,133 @@
+use crate::tags::{Tag, TagIdent};
+use crate::util::ToTag;
+
+#[derive(Clone)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// A list stored compactly, with homogeneous numeric lists held as plain vectors.
+///
+/// A `Tag::List` stores every element as a full `Tag`, so a list of doubles takes four times the
+/// memory of the doubles themselves. Packing it stores the numbers directly, which adds up for the
+/// position, motion and rotation lists found on every entity. Lists of other types are kept as is.
+///
+/// This is an opt-in container, not the storage behind `Tag::List`. `Tag::List` is a public
+/// `Vec<Tag>`, and reading a document still produces one. Pack lists you keep around for a long
+/// time, such as the entities of loaded chunks, and convert back with `into_tag` or `into_tags`
+/// when writing them out.
+///
+/// ## Example
+/// ```
+/// use nbt::{Tag, PackedList, ToTag};
+///
+/// let motion = vec![Tag::Double(0.0), Tag::Double(-0.08), Tag::Double(0.0)];
+/// let packed = PackedList::from(motion.clone());
+/// assert!(matches!(packed, PackedList::Double(_)));
+/// assert_eq!(packed.get(1), Some(Tag::Double(-0.08)));
+///
+/// assert_eq!(packed.into_tag(), Tag::List(motion));
+/// ```
+pub enum PackedList {
+    /// A list of `TAG_Byte` elements.
+    Byte(Vec<i8>),
+    /// A list of `TAG_Short` elements.
+    Short(Vec<i16>),
+    /// A list of `TAG_Int` elements.
+    Int(Vec<i32>),
+    /// A list of `TAG_Long` elements.
+    Long(Vec<i64>),
+    /// A list of `TAG_Float` elements.
+    Float(Vec<f32>),
+    /// A list of `TAG_Double` elements.
+    Double(Vec<f64>),
+    /// An empty list, or a list of non-numeric elements.
+    Tags(Vec<Tag>),
+}
+
+// Collect the payloads of a list whose elements are all of one variant.
+macro_rules! pack {
+    ($list:expr, $variant:ident) => {
+        $list.iter().map(|x| if let Tag::$variant(v) = x { Some(*v) } else { None }).collect::<Option<Vec<_>>>().map(PackedList::$variant)
+    };
+}
+
+impl PackedList {
+    /// The number of elements in the list.
+    pub fn len(&self) -> usize {
+        match self {
+            PackedList::Byte(x) => x.len(),
+            PackedList::Short(x) => x.len(),
+            PackedList::Int(x) => x.len(),
+            PackedList::Long(x) => x.len(),
+            PackedList::Float(x) => x.len(),
+            PackedList::Double(x) => x.len(),
+            PackedList::Tags(x) => x.len(),
+        }
+    }
+
+    /// Whether the list has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The type of the elements, or `TAG_End` if the list is empty.
+    pub fn element_ident(&self) -> TagIdent {
+        match self {
+            PackedList::Byte(_) => TagIdent::TAG_Byte,
+            PackedList::Short(_) => TagIdent::TAG_Short,
+            PackedList::Int(_) => TagIdent::TAG_Int,
+            PackedList::Long(_) => TagIdent::TAG_Long,
+            PackedList::Float(_) => TagIdent::TAG_Float,
+            PackedList::Double(_) => TagIdent::TAG_Double,
+            PackedList::Tags(x) => x.first().map(Tag::ident).unwrap_or(TagIdent::TAG_End),
+        }
+    }
+
+    /// Get an element of the list as a tag.
+    ///
+    /// Elements of packed lists are copied out, and others are cloned.
+    pub fn get(&self, index: usize) -> Option<Tag> {
+        match self {
+            PackedList::Byte(x) => x.get(index).copied().map(Tag::Byte),
+            PackedList::Short(x) => x.get(index).copied().map(Tag::Short),
+            PackedList::Int(x) => x.get(index).copied().map(Tag::Int),
+            PackedList::Long(x) => x.get(index).copied().map(Tag::Long),
+            PackedList::Float(x) => x.get(index).copied().map(Tag::Float),
+            PackedList::Double(x) => x.get(index).copied().map(Tag::Double),
+            PackedList::Tags(x) => x.get(index).cloned(),
+        }
+    }
+
+    /// Unpack the list into its elements as tags.
+    pub fn into_tags(self) -> Vec<Tag> {
+        match self {
+            PackedList::Byte(x) => x.into_iter().map(Tag::Byte).collect(),
+            PackedList::Short(x) => x.into_iter().map(Tag::Short).collect(),
+            PackedList::Int(x) => x.into_iter().map(Tag::Int).collect(),
+            PackedList::Long(x) => x.into_iter().map(Tag::Long).collect(),
+            PackedList::Float(x) => x.into_iter().map(Tag::Float).collect(),
+            PackedList::Double(x) => x.into_iter().map(Tag::Double).collect(),
+            PackedList::Tags(x) => x,
+        }
+    }
+}
+
+/// Pack a list, storing the elements directly if they are all of one numeric type.
+impl From<Vec<Tag>> for PackedList {
+    fn from(list: Vec<Tag>) -> Self {
+        let packed = match list.first() {
+            Some(Tag::Byte(_)) => pack!(list, Byte),
+            Some(Tag::Short(_)) => pack!(list, Short),
+            Some(Tag::Int(_)) => pack!(list, Int),
+            Some(Tag::Long(_)) => pack!(list, Long),
+            Some(Tag::Float(_)) => pack!(list, Float),
+            Some(Tag::Double(_)) => pack!(list, Double),
+            _ => None
+        };
+        packed.unwrap_or(PackedList::Tags(list))
+    }
+}
+
+impl ToTag for PackedList {
+    fn into_tag(self) -> Tag {
+        Tag::List(self.into_tags())
+    }
+}
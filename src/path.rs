@@ -0,0 +1,142 @@
+// Paths into a NBT tree, written as `key.key[index]`.
+// This is the same form used when reporting diagnostics and divergences.
+
+use crate::tags::Tag;
+use crate::tags::CompoundMap;
+use std::fmt;
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// A single step of a `NbtPath`.
+pub enum PathSegment {
+    /// A key of a compound.
+    Key(String),
+    /// An index of a list.
+    Index(usize),
+}
+
+#[derive(Clone, PartialEq, Default)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// A path to a tag within a tree, displayed as `key.key[index]`.
+///
+/// The empty path refers to the tree itself.
+pub struct NbtPath {
+    segments: Vec<PathSegment>
+}
+
+impl NbtPath {
+    /// Create an empty path.
+    pub fn new() -> NbtPath {
+        NbtPath::default()
+    }
+
+    /// Parse a path such as `Level.Sections[0].Y`, returning `None` if it is malformed.
+    pub fn parse(path: &str) -> Option<NbtPath> {
+        parse_path(path).map(|segments| NbtPath { segments })
+    }
+
+    /// The segments of the path, from the root.
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Create a child path.
+    pub fn join(&self, segment: PathSegment) -> NbtPath {
+        let mut segments = self.segments.clone();
+        segments.push(segment);
+        NbtPath { segments }
+    }
+}
+
+impl fmt::Display for NbtPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) if i == 0 => f.write_str(key)?,
+                PathSegment::Key(key) => write!(f, ".{}", key)?,
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// Parse a path into segments, returning `None` if it is malformed.
+pub(crate) fn parse_path(path: &str) -> Option<Vec<PathSegment>> {
+    parse_pattern(path)?.into_iter().map(|step| match step {
+        PathPattern::Segment(segment) => Some(segment),
+        PathPattern::AnyIndex => None,
+    }).collect()
+}
+
+// A step of a path that may match more than one tag, where `[*]` matches every item of a list.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature="debug", derive(Debug))]
+pub(crate) enum PathPattern {
+    Segment(PathSegment),
+    AnyIndex,
+}
+
+// Parse a path that may hold `[*]` into steps, returning `None` if it is malformed.
+pub(crate) fn parse_pattern(path: &str) -> Option<Vec<PathPattern>> {
+    let mut segments = Vec::new();
+    let mut chars = path.chars().peekable();
+    let mut key = String::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                if key.is_empty() && !matches!(segments.last(), Some(PathPattern::Segment(PathSegment::Index(_))) | Some(PathPattern::AnyIndex)) {
+                    return None;
+                }
+                if !key.is_empty() {
+                    segments.push(PathPattern::Segment(PathSegment::Key(std::mem::take(&mut key))));
+                }
+                // A separator must be followed by a key.
+                if !matches!(chars.peek(), Some(c) if *c != '.' && *c != '[') {
+                    return None;
+                }
+            }
+            '[' => {
+                if !key.is_empty() {
+                    segments.push(PathPattern::Segment(PathSegment::Key(std::mem::take(&mut key))));
+                }
+                let mut index = String::new();
+                loop {
+                    match chars.next()? {
+                        ']' => break,
+                        c => index.push(c),
+                    }
+                }
+                if index == "*" {
+                    segments.push(PathPattern::AnyIndex);
+                } else {
+                    segments.push(PathPattern::Segment(PathSegment::Index(index.parse().ok()?)));
+                }
+            }
+            ']' => return None,
+            c => key.push(c),
+        }
+    }
+    if !key.is_empty() {
+        segments.push(PathPattern::Segment(PathSegment::Key(key)));
+    }
+    Some(segments)
+}
+
+// Follow a path from a root compound.
+pub(crate) fn lookup<'a>(root: &'a CompoundMap, segments: &[PathSegment]) -> Option<&'a Tag> {
+    let (first, rest) = segments.split_first()?;
+    let mut tag = match first {
+        PathSegment::Key(key) => root.get(key)?,
+        PathSegment::Index(_) => return None,
+    };
+    for segment in rest {
+        tag = match (tag, segment) {
+            (Tag::Compound(map), PathSegment::Key(key)) => map.get(key)?,
+            (Tag::List(list), PathSegment::Index(index)) => list.get(*index)?,
+            _ => return None,
+        };
+    }
+    Some(tag)
+}
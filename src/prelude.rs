@@ -0,0 +1,27 @@
+//! # Prelude
+//! The traits and types used by almost every user of the crate.
+//!
+//! ```
+//! use nbt::prelude::*;
+//!
+//! let mut blob = Blob::new();
+//! blob.insert("DataVersion", 2586);
+//! let bytes = blob.bytes().unwrap();
+//! assert_eq!(Blob::from_bytes(bytes).unwrap().get::<i32>("DataVersion"), Some(&2586));
+//! ```
+
+pub use crate::front::{NBTRead, NBTWrite};
+pub use crate::util::{ToTag, FromTag};
+pub use crate::tags::{Tag, TagIdent, CompoundMap};
+pub use crate::blob::Blob;
+pub use crate::compound::Compound;
+pub use crate::options::{ReadOptions, WriteOptions};
+pub use crate::error::{NBTError, NBTResult};
+
+#[cfg(feature= "compression")]
+pub use crate::compression::Compression;
+
+#[cfg(feature= "serde-ser")]
+pub use crate::front::{encode, encode_named, encode_tag};
+#[cfg(feature= "serde-de")]
+pub use crate::front::{decode, decode_named, decode_tag};
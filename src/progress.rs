@@ -0,0 +1,90 @@
+use std::cell::Cell;
+use std::io::{Read, Write, Result};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::fmt;
+
+/// Entries are reported at most once per this many, to keep the callback off the hot path.
+pub(crate) const PROGRESS_INTERVAL: u64 = 4096;
+
+#[derive(Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// The progress of a read or write, passed to progress callbacks.
+pub struct Progress {
+    /// Bytes read or written so far.
+    pub bytes: u64,
+    /// Entries processed so far. For reads these are compound entries and list elements,
+    /// for region files these are chunks. Writers that do not count entries report 0.
+    pub entries: u64,
+}
+
+// A shareable progress callback. Wrapped so that `ReadOptions` can stay `Clone` and `Debug`.
+#[derive(Clone)]
+pub(crate) struct ProgressCallback(pub(crate) Arc<dyn Fn(Progress) + Send + Sync>);
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+// A reader adapter counting the bytes read through it into a shared cell.
+pub(crate) struct CountingReader<'r, R: Read + ?Sized> {
+    pub(crate) inner: &'r mut R,
+    pub(crate) count: Rc<Cell<u64>>,
+}
+
+impl<'r, R: Read + ?Sized> Read for CountingReader<'r, R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count.set(self.count.get() + read as u64);
+        Ok(read)
+    }
+}
+
+/// A writer adapter reporting the number of bytes written through it.
+///
+/// ## Example
+/// ```
+/// use nbt::{Blob, NBTWrite, ProgressWriter};
+///
+/// let mut blob = Blob::new();
+/// blob.insert("name", "Bananrama");
+///
+/// let mut written = 0;
+/// let mut writer = ProgressWriter::new(Vec::new(), |progress| written = progress.bytes);
+/// blob.write(&mut writer).unwrap();
+/// let bytes = writer.into_inner();
+///
+/// assert_eq!(written, bytes.len() as u64);
+/// ```
+pub struct ProgressWriter<W: Write, F: FnMut(Progress)> {
+    inner: W,
+    callback: F,
+    bytes: u64,
+}
+
+impl<W: Write, F: FnMut(Progress)> ProgressWriter<W, F> {
+    /// Wrap a writer, calling `callback` after every write.
+    pub fn new(inner: W, callback: F) -> ProgressWriter<W, F> {
+        ProgressWriter { inner, callback, bytes: 0 }
+    }
+
+    /// Unwrap the writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write, F: FnMut(Progress)> Write for ProgressWriter<W, F> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes += written as u64;
+        (self.callback)(Progress { bytes: self.bytes, entries: 0 });
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
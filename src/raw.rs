@@ -0,0 +1,70 @@
+//! # Raw Tags
+//! Low-level reading and writing of the parts of NBT data, for embedding tags in custom framing.
+//!
+//! Unlike [`NBTRead`](crate::NBTRead) and [`NBTWrite`](crate::NBTWrite), which handle complete
+//! values, these functions handle each part separately: a type id, a string, or a payload
+//! without its type id.
+//!
+//! ## Example
+//! ```
+//! use nbt::{Tag, TagIdent};
+//! use nbt::raw::{read_ident, read_tag, write_ident, write_tag};
+//!
+//! // A custom frame holding a type id and a payload, without a name.
+//! let tag = Tag::List(vec![Tag::Int(1), Tag::Int(2)]);
+//! let mut frame = Vec::new();
+//! write_ident(&mut frame, tag.ident()).unwrap();
+//! write_tag(&mut frame, &tag).unwrap();
+//!
+//! let mut reader = &frame[..];
+//! let ident = read_ident(&mut reader).unwrap();
+//! assert_eq!(ident, TagIdent::TAG_List);
+//! assert_eq!(read_tag(&mut reader, &ident).unwrap(), tag);
+//! ```
+
+use crate::decode::{self, ReadContext};
+use crate::diagnostics::Diagnostics;
+use crate::encode;
+use crate::error::{NBTResult, digest_io};
+use crate::flavor::JavaFlavor;
+use crate::options::{ReadOptions, WriteOptions};
+use crate::tags::{Tag, TagIdent};
+use byteorder::WriteBytesExt;
+use std::io::{Read, Write};
+
+/// Read a type id.
+///
+/// Fails with `NBTError::InvalidTag` if the byte is not a known type.
+pub fn read_ident<R: Read + ?Sized>(reader: &mut R) -> NBTResult<TagIdent> {
+    decode::read_ident(reader)
+}
+
+/// Write a type id.
+pub fn write_ident<W: Write + ?Sized>(writer: &mut W, ident: TagIdent) -> NBTResult<()> {
+    digest_io(writer.write_u8(ident as u8))
+}
+
+/// Read the payload of a tag of the given type.
+pub fn read_tag<R: Read + ?Sized>(reader: &mut R, ident: &TagIdent) -> NBTResult<Tag> {
+    read_tag_with(reader, ident, &ReadOptions::default(), None)
+}
+
+/// Read the payload of a tag of the given type with the given options, optionally collecting diagnostics.
+pub fn read_tag_with<R: Read + ?Sized>(reader: &mut R, ident: &TagIdent, options: &ReadOptions, diagnostics: Option<&mut Diagnostics>) -> NBTResult<Tag> {
+    decode::read_tag::<JavaFlavor, _>(reader, ident, &mut ReadContext::new(options, diagnostics))
+}
+
+/// Write the payload of a tag, without its type id.
+pub fn write_tag<W: Write + ?Sized>(writer: &mut W, tag: &Tag) -> NBTResult<()> {
+    encode::write_tag::<JavaFlavor, _>(writer, tag, &WriteOptions::default())
+}
+
+/// Read a length-prefixed, modified UTF-8 string, as used for names and string payloads.
+pub fn read_string<R: Read + ?Sized>(reader: &mut R) -> NBTResult<String> {
+    decode::read_string::<JavaFlavor, _>(reader, &mut ReadContext::new(&ReadOptions::default(), None))
+}
+
+/// Write a length-prefixed, modified UTF-8 string.
+pub fn write_string<W: Write + ?Sized>(writer: &mut W, string: &str) -> NBTResult<()> {
+    encode::write_string::<JavaFlavor, _>(writer, string)
+}
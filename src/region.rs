@@ -0,0 +1,145 @@
+//! # Region Files
+//! Writing of Anvil region (`.mca`) files.
+//!
+//! Chunks are serialised and compressed in parallel using `rayon`, after which the sectors are
+//! laid out in order on a single thread.
+//!
+//! ## Example
+//! ```
+//! use nbt::{Blob, Compression};
+//! use nbt::region::{RegionChunk, write_region};
+//!
+//! let chunks: Vec<RegionChunk> = (0..4).map(|x| {
+//!     let mut blob = Blob::new();
+//!     blob.insert("xPos", x);
+//!     RegionChunk::new(x, 0, blob)
+//! }).collect();
+//!
+//! let mut buffer = Vec::new();
+//! write_region(&mut buffer, &chunks, Compression::Zlib).unwrap();
+//! # assert_eq!(buffer.len() % 4096, 0);
+//! ```
+
+use crate::blob::Blob;
+use crate::compression::{Compression, compress};
+use crate::error::{NBTResult, NBTError, digest_io};
+use crate::front::NBTWrite;
+use crate::progress::Progress;
+use byteorder::{BigEndian as BE, WriteBytesExt};
+use rayon::prelude::*;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The size of a region file sector, in bytes.
+pub const SECTOR_SIZE: usize = 4096;
+
+/// A chunk to be written into a region file.
+#[derive(Clone)]
+#[cfg_attr(feature="debug", derive(Debug))]
+pub struct RegionChunk {
+    /// The chunk X coordinate. World coordinates are accepted and wrapped into the region.
+    pub x: i32,
+    /// The chunk Z coordinate. World coordinates are accepted and wrapped into the region.
+    pub z: i32,
+    /// The last modification time, in seconds since the epoch.
+    pub timestamp: u32,
+    /// The chunk data.
+    pub blob: Blob,
+}
+
+impl RegionChunk {
+    /// Create a chunk with a timestamp of 0.
+    pub fn new(x: i32, z: i32, blob: Blob) -> RegionChunk {
+        RegionChunk { x, z, timestamp: 0, blob }
+    }
+
+    // Index of the chunk within the region header.
+    fn index(&self) -> usize {
+        (self.x.rem_euclid(32) + self.z.rem_euclid(32) * 32) as usize
+    }
+}
+
+// The compression type byte stored before each chunk.
+// Region files have no type for raw DEFLATE.
+fn compression_id(compression: Compression) -> NBTResult<u8> {
+    match compression {
+        Compression::Gzip => Ok(1),
+        Compression::Zlib => Ok(2),
+        _ => Err(NBTError::UnsupportedCompression { compression }),
+    }
+}
+
+/// Write a region file containing the given chunks.
+///
+/// Chunks larger than 255 sectors (~1MiB) cannot be stored and return `NBTError::RegionChunkTooLarge`.
+/// Raw DEFLATE and zstd cannot be stored either, and return `NBTError::UnsupportedCompression`.
+pub fn write_region<W: Write>(writer: &mut W, chunks: &[RegionChunk], compression: Compression) -> NBTResult<()> {
+    write_region_with_progress(writer, chunks, compression, |_| ())
+}
+
+/// Write a region file, calling `callback` as each chunk is encoded and once more as each chunk
+/// is written out.
+///
+/// `Progress::entries` counts the chunks encoded, and `Progress::bytes` the bytes written.
+/// The callback may be called from multiple threads while encoding.
+pub fn write_region_with_progress<W, F>(writer: &mut W, chunks: &[RegionChunk], compression: Compression, callback: F) -> NBTResult<()>
+    where W: Write, F: Fn(Progress) + Sync {
+    let id = compression_id(compression)?;
+
+    // Serialise and compress in parallel.
+    let encoded_chunks = AtomicU64::new(0);
+    let encoded = chunks.par_iter()
+        .map(|chunk| {
+            let data = compress(&chunk.blob.bytes()?, compression)?;
+            let entries = encoded_chunks.fetch_add(1, Ordering::Relaxed) + 1;
+            callback(Progress { bytes: 0, entries });
+            Ok(data)
+        })
+        .collect::<NBTResult<Vec<Vec<u8>>>>()?;
+    let entries = encoded.len() as u64;
+
+    // Lay out the sectors, after the two header sectors.
+    let mut locations = [0u32; 1024];
+    let mut timestamps = [0u32; 1024];
+    let mut sector = 2;
+
+    for (chunk, data) in chunks.iter().zip(encoded.iter()) {
+        let index = chunk.index();
+        if locations[index] != 0 {
+            return Err(NBTError::DuplicateRegionChunk { x: chunk.x, z: chunk.z });
+        }
+
+        // Length prefix + compression byte + data
+        let sectors = (data.len() + 5).div_ceil(SECTOR_SIZE);
+        if sectors > 255 {
+            return Err(NBTError::RegionChunkTooLarge { x: chunk.x, z: chunk.z });
+        }
+
+        locations[index] = ((sector as u32) << 8) | sectors as u32;
+        timestamps[index] = chunk.timestamp;
+        sector += sectors;
+    }
+
+    // Write the header.
+    for location in locations.iter() {
+        digest_io(writer.write_u32::<BE>(*location))?;
+    }
+    for timestamp in timestamps.iter() {
+        digest_io(writer.write_u32::<BE>(*timestamp))?;
+    }
+
+    // Write the chunks, padded to the sector size.
+    let mut bytes = (SECTOR_SIZE * 2) as u64;
+    for data in encoded.iter() {
+        digest_io(writer.write_u32::<BE>(data.len() as u32 + 1))?;
+        digest_io(writer.write_u8(id))?;
+        digest_io(writer.write_all(data))?;
+
+        let padding = (SECTOR_SIZE - (data.len() + 5) % SECTOR_SIZE) % SECTOR_SIZE;
+        digest_io(writer.write_all(&vec![0; padding]))?;
+
+        bytes += (data.len() + 5 + padding) as u64;
+        callback(Progress { bytes, entries });
+    }
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use crate::blob::Blob;
+use crate::tags::{Tag, TagIdent};
+use crate::util::ToTag;
+use crate::error::{NBTResult, NBTError};
+use std::collections::HashMap;
+use std::ops::Deref;
+
+#[derive(Clone, Default)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// The declared types of the keys of a `BoundBlob`.
+pub struct BlobSchema {
+    fields: HashMap<String, TagIdent>,
+    allow_unknown: bool,
+}
+
+impl BlobSchema {
+    /// Create a empty schema, which rejects unknown keys.
+    pub fn new() -> BlobSchema {
+        BlobSchema::default()
+    }
+
+    /// Declare the type of a key.
+    pub fn field(mut self, name: &str, ident: TagIdent) -> BlobSchema {
+        self.fields.insert(name.to_string(), ident);
+        self
+    }
+
+    /// Allow keys that are not declared, with any type.
+    pub fn allow_unknown(mut self, allow: bool) -> BlobSchema {
+        self.allow_unknown = allow;
+        self
+    }
+
+    /// Check a element against the schema.
+    pub fn validate(&self, name: &str, tag: &Tag) -> NBTResult<()> {
+        match self.fields.get(name) {
+            Some(expecting) if *expecting != tag.ident() => Err(NBTError::InvalidType {
+                found: tag.ident(),
+                expecting: expecting.clone(),
+                when: format!("key '{}'", name)
+            }),
+            None if !self.allow_unknown => Err(NBTError::UnknownKey { key: name.to_string() }),
+            _ => Ok(())
+        }
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// A `Blob` bound to a schema, rejecting inserts that violate it.
+///
+/// Dereferences to the inner `Blob` for reading.
+///
+/// ## Example
+/// ```
+/// # use nbt::{BoundBlob, BlobSchema, TagIdent};
+/// let schema = BlobSchema::new()
+///     .field("name", TagIdent::TAG_String)
+///     .field("age", TagIdent::TAG_Byte);
+///
+/// let mut blob = BoundBlob::new(schema, "player");
+/// blob.insert("name", "Steve").unwrap();
+///
+/// assert!(blob.insert("age", 18i32).is_err());
+/// assert!(blob.insert("health", 20.0f32).is_err());
+/// assert_eq!(blob.get::<String>("name").unwrap(), "Steve");
+/// ```
+pub struct BoundBlob {
+    schema: BlobSchema,
+    blob: Blob,
+}
+
+impl BoundBlob {
+    /// Create a empty `BoundBlob` with a given root compound name.
+    pub fn new(schema: BlobSchema, root: &str) -> BoundBlob {
+        BoundBlob { schema, blob: Blob::create(root) }
+    }
+
+    /// Bind a existing `Blob` to a schema, checking its elements.
+    pub fn bind(schema: BlobSchema, blob: Blob) -> NBTResult<BoundBlob> {
+        for (name, tag) in blob.elements.iter() {
+            schema.validate(name, tag)?;
+        }
+        Ok(BoundBlob { schema, blob })
+    }
+
+    /// Insert a element into the root compound, if it matches the schema.
+    pub fn insert<P: ToTag>(&mut self, name: &str, payload: P) -> NBTResult<Option<Tag>> {
+        let tag = payload.into_tag();
+        self.schema.validate(name, &tag)?;
+        Ok(self.blob.insert(name, tag))
+    }
+
+    /// Remove a element from the root compound.
+    pub fn remove(&mut self, name: &str) -> Option<Tag> {
+        self.blob.elements.remove(name)
+    }
+
+    /// The schema the blob is bound to.
+    pub fn schema(&self) -> &BlobSchema {
+        &self.schema
+    }
+
+    /// Unbind the `Blob` from its schema.
+    pub fn into_blob(self) -> Blob {
+        self.blob
+    }
+}
+
+impl Deref for BoundBlob {
+    type Target = Blob;
+
+    fn deref(&self) -> &Self::Target {
+        &self.blob
+    }
+}
@@ -1,23 +1,30 @@
 use serde::{Serializer, Serialize};
-use crate::Tag;
+use crate::{Tag, TagIdent};
+use crate::array::{INT_ARRAY, LONG_ARRAY};
 use crate::error::NBTError;
-use std::collections::HashMap;
+use crate::tags::CompoundMap;
+use crate::options::SerdeOptions;
 use std::fmt::Display;
 use serde::ser::{SerializeSeq, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant, SerializeMap, SerializeStruct, SerializeStructVariant};
 
-pub struct NBTSerializer;
+pub struct NBTSerializer<'a> {
+    options: &'a SerdeOptions,
+}
+impl<'a> NBTSerializer<'a> {
+    pub fn new(options: &'a SerdeOptions) -> Self { NBTSerializer { options } }
+}
 
 #[allow(unused_variables)]
-impl Serializer for NBTSerializer {
+impl<'a> Serializer for NBTSerializer<'a> {
     type Ok = Option<Tag>;
     type Error = NBTError;
-    type SerializeSeq = NBTSeqSerializer;
-    type SerializeTuple = NBTSeqSerializer;
-    type SerializeTupleStruct = NBTSeqSerializer;
-    type SerializeTupleVariant = NBTVariantSeqSerializer;
-    type SerializeMap = NBTMapSerializer;
-    type SerializeStruct = NBTStructSerializer;
-    type SerializeStructVariant = NBTVariantStructSerializer;
+    type SerializeSeq = NBTSeqSerializer<'a>;
+    type SerializeTuple = NBTSeqSerializer<'a>;
+    type SerializeTupleStruct = NBTSeqSerializer<'a>;
+    type SerializeTupleVariant = NBTVariantSeqSerializer<'a>;
+    type SerializeMap = NBTMapSerializer<'a>;
+    type SerializeStruct = NBTStructSerializer<'a>;
+    type SerializeStructVariant = NBTVariantStructSerializer<'a>;
 
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
@@ -70,38 +77,37 @@ impl Serializer for NBTSerializer {
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
         #[cfg(feature="serde_unsigned")]
-            return Ok(Some(Tag::Long(v as i64)));
+            return self.options.unsigned_overflow.encode(v).map(Some);
 
         #[cfg(not(feature="serde_unsigned"))]
             return Err(NBTError::UnserializableType {type_name: "i64".to_string()})
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
-        return Ok(Some(Tag::Float(v)));
+        Ok(Some(Tag::Float(v)))
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        return Ok(Some(Tag::Double(v)));
+        Ok(Some(Tag::Double(v)))
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
-        return Ok(Some(Tag::String(v.to_string())))
+        Ok(Some(Tag::String(v.to_string())))
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
-        return Ok(Some(Tag::String(v.to_string())))
+        Ok(Some(Tag::String(v.to_string())))
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        return Err(NBTError::UnserializableType {type_name: "bytes".to_string()})
+        Ok(Some(Tag::UnsignedByteArray(v.to_vec()).with_unsigned_bytes(false)))
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
         Ok(None)
     }
 
-    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> where
-        T: Serialize {
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
         value.serialize(self)
     }
 
@@ -117,67 +123,69 @@ impl Serializer for NBTSerializer {
         Ok(Some(Tag::String(variant.to_string())))
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(self, _name: &'static str, value: &T) -> Result<Self::Ok, Self::Error> where
-        T: Serialize {
-        value.serialize(Self)
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, name: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
+        let tag = value.serialize(self)?;
+        match name {
+            INT_ARRAY => tag.map(|x| into_array(x, TagIdent::TAG_Int_Array)).transpose(),
+            LONG_ARRAY => tag.map(|x| into_array(x, TagIdent::TAG_Long_Array)).transpose(),
+            _ => Ok(tag)
+        }
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(self, _name: &'static str, _variant_index: u32, variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error> where
-        T: Serialize {
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _name: &'static str, _variant_index: u32, variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
         match Serialize::serialize(value, self)? {
-            Some(x) => Ok(Some(external(&variant, x))),
+            Some(x) => Ok(Some(external(variant, x))),
             None => Ok(None)
         }
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
-        Ok(NBTSeqSerializer::new())
+        Ok(NBTSeqSerializer::new(self.options))
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
-        Ok(NBTSeqSerializer::new())
+        Ok(NBTSeqSerializer::new(self.options))
     }
 
     fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, Self::Error> {
-        Ok(NBTSeqSerializer::new())
+        Ok(NBTSeqSerializer::new(self.options))
     }
 
     fn serialize_tuple_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str, _len: usize) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        Ok(NBTVariantSeqSerializer::new(variant))
+        Ok(NBTVariantSeqSerializer::new(variant, self.options))
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        Ok(NBTMapSerializer::new())
+        Ok(NBTMapSerializer::new(self.options))
     }
 
     fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, Self::Error> {
-        Ok(NBTStructSerializer::new())
+        Ok(NBTStructSerializer::new(self.options))
     }
 
     fn serialize_struct_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str, _len: usize) -> Result<Self::SerializeStructVariant, Self::Error> {
-        Ok(NBTVariantStructSerializer::new(variant))
+        Ok(NBTVariantStructSerializer::new(variant, self.options))
     }
 
-    fn collect_str<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error> where
-        T: Display {
+    fn collect_str<T: ?Sized + Display>(self, value: &T) -> Result<Self::Ok, Self::Error> {
         Ok(Some(Tag::String(value.to_string())))
     }
 }
 
-pub struct NBTSeqSerializer {
+pub struct NBTSeqSerializer<'a> {
     elements: Vec<Tag>,
+    options: &'a SerdeOptions,
 }
-impl NBTSeqSerializer {
-    pub fn new() -> Self { NBTSeqSerializer { elements: Vec::new() } }
+impl<'a> NBTSeqSerializer<'a> {
+    pub fn new(options: &'a SerdeOptions) -> Self { NBTSeqSerializer { elements: Vec::new(), options } }
 }
 
-impl SerializeSeq for NBTSeqSerializer {
+impl<'a> SerializeSeq for NBTSeqSerializer<'a> {
     type Ok = Option<Tag>;
     type Error = NBTError;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> where
-        T: Serialize {
-        if let Some(value) = value.serialize(NBTSerializer)? {
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        if let Some(value) = value.serialize(NBTSerializer::new(self.options))? {
             self.elements.push(value);
         };
         Ok(())
@@ -187,12 +195,12 @@ impl SerializeSeq for NBTSeqSerializer {
         Ok(Some(Tag::List(self.elements)))
     }
 }
-impl SerializeTuple for NBTSeqSerializer {
+impl<'a> SerializeTuple for NBTSeqSerializer<'a> {
     type Ok = Option<Tag>;
     type Error = NBTError;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> where T: Serialize {
-        if let Some(value) = value.serialize(NBTSerializer)? {
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        if let Some(value) = value.serialize(NBTSerializer::new(self.options))? {
             self.elements.push(value);
         };
         Ok(())
@@ -203,12 +211,12 @@ impl SerializeTuple for NBTSeqSerializer {
     }
 }
 
-impl SerializeTupleStruct for NBTSeqSerializer {
+impl<'a> SerializeTupleStruct for NBTSeqSerializer<'a> {
     type Ok = Option<Tag>;
     type Error = NBTError;
 
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> where T: Serialize {
-        if let Some(value) = value.serialize(NBTSerializer)? {
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        if let Some(value) = value.serialize(NBTSerializer::new(self.options))? {
             self.elements.push(value);
         };
         Ok(())
@@ -219,24 +227,26 @@ impl SerializeTupleStruct for NBTSeqSerializer {
     }
 }
 
-pub struct NBTVariantSeqSerializer {
+pub struct NBTVariantSeqSerializer<'a> {
     variant: String,
-    elements: Vec<Tag>
+    elements: Vec<Tag>,
+    options: &'a SerdeOptions,
 }
-impl NBTVariantSeqSerializer {
-    pub fn new(variant: &str) -> Self {
+impl<'a> NBTVariantSeqSerializer<'a> {
+    pub fn new(variant: &str, options: &'a SerdeOptions) -> Self {
         Self {
             variant: variant.to_string(),
-            elements: Vec::new()
+            elements: Vec::new(),
+            options,
         }
     }
 }
-impl SerializeTupleVariant for NBTVariantSeqSerializer {
+impl<'a> SerializeTupleVariant for NBTVariantSeqSerializer<'a> {
     type Ok = Option<Tag>;
     type Error = NBTError;
 
-    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> where T: Serialize {
-        if let Some(value) = value.serialize(NBTSerializer)? {
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        if let Some(value) = value.serialize(NBTSerializer::new(self.options))? {
             self.elements.push(value);
         };
         Ok(())
@@ -247,36 +257,52 @@ impl SerializeTupleVariant for NBTVariantSeqSerializer {
     }
 }
 
+// Convert a list of ints or longs, serialized from an `IntArray` or `LongArray`, into the array tag.
+fn into_array(tag: Tag, ident: TagIdent) -> Result<Tag, NBTError> {
+    let items = match tag {
+        Tag::List(items) => items,
+        tag => return Err(NBTError::InvalidType { found: tag.ident(), expecting: ident, when: "array".to_string() })
+    };
+    let mismatch = |x: &Tag| NBTError::InvalidList { found: x.ident(), expecting: ident.clone() };
+    Ok(match ident {
+        TagIdent::TAG_Int_Array => Tag::IntArray(items.into_iter()
+            .map(|x| if let Tag::Int(x) = x { Ok(x) } else { Err(mismatch(&x)) })
+            .collect::<Result<_, _>>()?),
+        _ => Tag::LongArray(items.into_iter()
+            .map(|x| if let Tag::Long(x) = x { Ok(x) } else { Err(mismatch(&x)) })
+            .collect::<Result<_, _>>()?),
+    })
+}
+
 pub fn external(name: &str, value: Tag) -> Tag {
-    let mut map = HashMap::new();
+    let mut map = CompoundMap::default();
     map.insert(name.to_string(), value);
     Tag::Compound(map)
 }
 
-pub struct NBTMapSerializer {
-    map: HashMap<String, Tag>,
-    key: Option<String>
+pub struct NBTMapSerializer<'a> {
+    map: CompoundMap,
+    key: Option<String>,
+    options: &'a SerdeOptions,
 }
-impl NBTMapSerializer {
-    pub fn new() -> Self { Self { map:HashMap::new(), key:None }}
+impl<'a> NBTMapSerializer<'a> {
+    pub fn new(options: &'a SerdeOptions) -> Self { Self { map:CompoundMap::default(), key:None, options }}
 }
 
-impl SerializeMap for NBTMapSerializer {
+impl<'a> SerializeMap for NBTMapSerializer<'a> {
     type Ok = Option<Tag>;
     type Error = NBTError;
 
-    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> where
-        T: Serialize {
-        if let Some(Tag::String(key)) = key.serialize(NBTSerializer)? {
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        if let Some(Tag::String(key)) = key.serialize(NBTSerializer::new(self.options))? {
             self.key = Some(key);
         };
         Ok(())
     }
 
-    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> where
-        T: Serialize {
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
         if let Some(key) = &self.key {
-            if let Some(v) = value.serialize(NBTSerializer)? {
+            if let Some(v) = value.serialize(NBTSerializer::new(self.options))? {
                 self.map.insert(key.clone(), v);
             }
         };
@@ -290,19 +316,19 @@ impl SerializeMap for NBTMapSerializer {
 }
 
 
-pub struct NBTStructSerializer {
-    map: HashMap<String, Tag>
+pub struct NBTStructSerializer<'a> {
+    map: CompoundMap,
+    options: &'a SerdeOptions,
 }
-impl NBTStructSerializer {
-    pub fn new() -> Self { Self { map:HashMap::new() }}
+impl<'a> NBTStructSerializer<'a> {
+    pub fn new(options: &'a SerdeOptions) -> Self { Self { map:CompoundMap::default(), options }}
 }
-impl SerializeStruct for NBTStructSerializer {
+impl<'a> SerializeStruct for NBTStructSerializer<'a> {
     type Ok = Option<Tag>;
     type Error = NBTError;
 
-    fn serialize_field<T: ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error> where
-        T: Serialize {
-        if let Some(v) = value.serialize(NBTSerializer)? {
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error> {
+        if let Some(v) = value.serialize(NBTSerializer::new(self.options))? {
             self.map.insert(key.to_string(), v);
         };
         Ok(())
@@ -313,20 +339,20 @@ impl SerializeStruct for NBTStructSerializer {
     }
 }
 
-pub struct NBTVariantStructSerializer {
-    map: HashMap<String, Tag>,
-    variant: String
+pub struct NBTVariantStructSerializer<'a> {
+    map: CompoundMap,
+    variant: String,
+    options: &'a SerdeOptions,
 }
-impl NBTVariantStructSerializer {
-    pub fn new(variant: &str) -> Self { Self { map:HashMap::new(), variant: variant.to_string() }}
+impl<'a> NBTVariantStructSerializer<'a> {
+    pub fn new(variant: &str, options: &'a SerdeOptions) -> Self { Self { map:CompoundMap::default(), variant: variant.to_string(), options }}
 }
-impl SerializeStructVariant for NBTVariantStructSerializer {
+impl<'a> SerializeStructVariant for NBTVariantStructSerializer<'a> {
     type Ok = Option<Tag>;
     type Error = NBTError;
 
-    fn serialize_field<T: ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error> where
-        T: Serialize {
-        if let Some(v) = value.serialize(NBTSerializer)? {
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error> {
+        if let Some(v) = value.serialize(NBTSerializer::new(self.options))? {
             self.map.insert(key.to_string(), v);
         };
         Ok(())
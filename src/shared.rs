@@ -0,0 +1,230 @@
+use crate::tags::{Tag, TagIdent};
+use crate::util::ToTag;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+#[derive(PartialEq, Clone)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// A NBT Tag whose compounds and lists are shared between clones.
+///
+/// Cloning a `SharedTag` is cheap, as compounds and lists are reference counted.
+/// They are only copied when mutated while shared, so many near-identical trees
+/// (such as an undo history) can be kept in memory without paying for full copies.
+///
+/// Compound keys and strings are reference counted too. Documents read with an `Interner` in their
+/// `ReadOptions` share identical keys, and optionally strings, with every other document read with
+/// the same interner.
+///
+/// ## Example
+/// ```
+/// use nbt::{Tag, SharedTag};
+///
+/// let original = SharedTag::from(Tag::compound_from_iter(vec![("x", 1), ("y", 2)]));
+/// let mut edited = original.clone();
+///
+/// // Only `edited` is copied.
+/// edited.compound_mut().unwrap().insert("x".into(), SharedTag::Int(5));
+///
+/// assert_eq!(original.get("x"), Some(&SharedTag::Int(1)));
+/// assert_eq!(edited.get("x"), Some(&SharedTag::Int(5)));
+/// ```
+pub enum SharedTag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(Arc<str>),
+    List(Arc<Vec<SharedTag>>),
+    Compound(Arc<HashMap<Arc<str>, SharedTag>>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>)
+}
+
+impl SharedTag {
+    /// The `TagIdent` representation of a tag.
+    pub fn ident(&self) -> TagIdent {
+        match &self {
+            SharedTag::Byte(_) => TagIdent::TAG_Byte,
+            SharedTag::Short(_) => TagIdent::TAG_Short,
+            SharedTag::Int(_) => TagIdent::TAG_Int,
+            SharedTag::Long(_) => TagIdent::TAG_Long,
+            SharedTag::Float(_) => TagIdent::TAG_Float,
+            SharedTag::Double(_) => TagIdent::TAG_Double,
+            SharedTag::ByteArray(_) => TagIdent::TAG_Byte_Array,
+            SharedTag::String(_) => TagIdent::TAG_String,
+            SharedTag::List(_) => TagIdent::TAG_List,
+            SharedTag::Compound(_) => TagIdent::TAG_Compound,
+            SharedTag::IntArray(_) => TagIdent::TAG_Int_Array,
+            SharedTag::LongArray(_) => TagIdent::TAG_Long_Array,
+        }
+    }
+
+    /// Get a element of a compound.
+    pub fn get(&self, key: &str) -> Option<&SharedTag> {
+        match self {
+            SharedTag::Compound(map) => map.get(key),
+            _ => None
+        }
+    }
+
+    /// Mutably borrow a compound, copying it first if it is shared.
+    pub fn compound_mut(&mut self) -> Option<&mut HashMap<Arc<str>, SharedTag>> {
+        match self {
+            SharedTag::Compound(map) => Some(Arc::make_mut(map)),
+            _ => None
+        }
+    }
+
+    /// Mutably borrow a list, copying it first if it is shared.
+    pub fn list_mut(&mut self) -> Option<&mut Vec<SharedTag>> {
+        match self {
+            SharedTag::List(list) => Some(Arc::make_mut(list)),
+            _ => None
+        }
+    }
+
+    /// Whether two tags share the same compound or list.
+    pub fn ptr_eq(&self, other: &SharedTag) -> bool {
+        match (self, other) {
+            (SharedTag::Compound(a), SharedTag::Compound(b)) => Arc::ptr_eq(a, b),
+            (SharedTag::List(a), SharedTag::List(b)) => Arc::ptr_eq(a, b),
+            _ => false
+        }
+    }
+
+    /// Convert into a plain `Tag`, copying any shared data.
+    pub fn to_tag(&self) -> Tag {
+        match self {
+            SharedTag::Byte(v) => Tag::Byte(*v),
+            SharedTag::Short(v) => Tag::Short(*v),
+            SharedTag::Int(v) => Tag::Int(*v),
+            SharedTag::Long(v) => Tag::Long(*v),
+            SharedTag::Float(v) => Tag::Float(*v),
+            SharedTag::Double(v) => Tag::Double(*v),
+            SharedTag::ByteArray(v) => Tag::ByteArray(v.clone()),
+            SharedTag::String(v) => Tag::String(v.to_string()),
+            SharedTag::List(list) => Tag::List(list.iter().map(|x| x.to_tag()).collect()),
+            SharedTag::Compound(map) => Tag::Compound(map.iter().map(|(k, v)| (k.to_string(), v.to_tag())).collect()),
+            SharedTag::IntArray(v) => Tag::IntArray(v.clone()),
+            SharedTag::LongArray(v) => Tag::LongArray(v.clone()),
+        }
+    }
+}
+
+impl From<Tag> for SharedTag {
+    fn from(tag: Tag) -> Self {
+        SharedTag::convert(tag, &mut |x| Arc::from(x), false)
+    }
+}
+
+impl SharedTag {
+    /// Convert a plain `Tag`, sharing its compound keys, and its strings if `values` is set, with
+    /// everything else interned by `interner`.
+    pub fn from_interned(tag: Tag, interner: &Interner, values: bool) -> SharedTag {
+        let mut strings = interner.lock();
+        SharedTag::convert(tag, &mut |x| intern(&mut strings, &x), values)
+    }
+
+    // Convert a tag, turning keys (and strings if `values` is set) into `Arc<str>`s with `share`.
+    fn convert(tag: Tag, share: &mut dyn FnMut(String) -> Arc<str>, values: bool) -> SharedTag {
+        match tag {
+            Tag::Byte(v) => SharedTag::Byte(v),
+            Tag::Short(v) => SharedTag::Short(v),
+            Tag::Int(v) => SharedTag::Int(v),
+            Tag::Long(v) => SharedTag::Long(v),
+            Tag::Float(v) => SharedTag::Float(v),
+            Tag::Double(v) => SharedTag::Double(v),
+            Tag::ByteArray(v) => SharedTag::ByteArray(v),
+            tag @ Tag::UnsignedByteArray(_) => SharedTag::convert(tag.with_unsigned_bytes(false), share, values),
+            Tag::String(v) if values => SharedTag::String(share(v)),
+            Tag::String(v) => SharedTag::String(Arc::from(v)),
+            Tag::List(list) => SharedTag::List(Arc::new(list.into_iter().map(|x| SharedTag::convert(x, share, values)).collect())),
+            Tag::Compound(map) => SharedTag::Compound(Arc::new(map.into_iter().map(|(k, v)| (share(k), SharedTag::convert(v, share, values))).collect())),
+            Tag::IntArray(v) => SharedTag::IntArray(v),
+            Tag::LongArray(v) => SharedTag::LongArray(v),
+        }
+    }
+}
+
+// Find the shared copy of a string, adding it if this is the first time it is seen.
+fn intern(strings: &mut HashSet<Arc<str>>, string: &str) -> Arc<str> {
+    match strings.get(string) {
+        Some(shared) => shared.clone(),
+        None => {
+            let shared: Arc<str> = Arc::from(string);
+            strings.insert(shared.clone());
+            shared
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// A set of strings shared between the `SharedTag`s read with it.
+///
+/// Clones of an interner share the same set, so one can be stored in the `ReadOptions` of every
+/// read in a session. Strings stay in the set until `shrink` is called, even once no tag uses them.
+///
+/// ## Example
+/// ```
+/// use nbt::{Blob, SharedTag, Interner, NBTRead, NBTWrite, ReadOptions};
+/// use std::sync::Arc;
+///
+/// let mut blob = Blob::new();
+/// blob.insert("DataVersion", 3465);
+/// let data = blob.bytes().unwrap();
+///
+/// let interner = Interner::new();
+/// let options = ReadOptions::new().interner(interner.clone());
+///
+/// let a = SharedTag::from_bytes_with_options(&data, &options).unwrap();
+/// let b = SharedTag::from_bytes_with_options(&data, &options).unwrap();
+///
+/// // Both documents use the same key.
+/// let key = |tag: &SharedTag| match tag {
+///     SharedTag::Compound(map) => map.keys().next().unwrap().clone(),
+///     _ => unreachable!(),
+/// };
+/// assert!(Arc::ptr_eq(&key(&a), &key(&b)));
+/// assert_eq!(interner.len(), 1);
+/// ```
+pub struct Interner {
+    strings: Arc<Mutex<HashSet<Arc<str>>>>,
+}
+
+impl Interner {
+    /// Create an empty interner.
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashSet<Arc<str>>> {
+        // The set is always left valid, so a panic while it was locked doesn't matter.
+        self.strings.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Get the shared copy of a string, adding it to the interner if needed.
+    pub fn intern(&self, string: &str) -> Arc<str> {
+        intern(&mut self.lock(), string)
+    }
+
+    /// The number of distinct strings interned.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no strings have been interned.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Forget the strings no longer used outside of the interner.
+    pub fn shrink(&self) {
+        self.lock().retain(|x| Arc::strong_count(x) > 1);
+    }
+}
+
+impl ToTag for SharedTag { fn into_tag(self) -> Tag { self.to_tag() } }
@@ -0,0 +1,57 @@
+use crate::tags::Tag;
+use crate::blob::Blob;
+use crate::packed::PackedList;
+use crate::tags::CompoundMap;
+use std::mem::size_of;
+
+fn compound_heap_size(map: &CompoundMap) -> usize {
+    // Each bucket holds an entry and a control byte.
+    let buckets = map.capacity() * (size_of::<(String, Tag)>() + 1);
+    buckets + map.iter().map(|(k, v)| k.capacity() + v.estimate_heap_size()).sum::<usize>()
+}
+
+impl Tag {
+    /// Estimate the number of bytes the tag has allocated on the heap.
+    ///
+    /// This includes spare capacity, and the tag's children, but not the size of the tag itself.
+    /// The hash map overhead of compounds is approximated.
+    /// ```
+    /// # use nbt::Tag;
+    /// assert_eq!(Tag::Int(42).estimate_heap_size(), 0);
+    /// assert!(Tag::LongArray(vec![0; 256]).estimate_heap_size() >= 256 * 8);
+    /// ```
+    pub fn estimate_heap_size(&self) -> usize {
+        match self {
+            Tag::ByteArray(array) => array.capacity() * size_of::<i8>(),
+            Tag::UnsignedByteArray(array) => array.capacity() * size_of::<u8>(),
+            Tag::String(string) => string.capacity(),
+            Tag::List(list) => list.capacity() * size_of::<Tag>() + list.iter().map(|x| x.estimate_heap_size()).sum::<usize>(),
+            Tag::Compound(map) => compound_heap_size(map),
+            Tag::IntArray(array) => array.capacity() * size_of::<i32>(),
+            Tag::LongArray(array) => array.capacity() * size_of::<i64>(),
+            _ => 0
+        }
+    }
+}
+
+impl Blob {
+    /// Estimate the number of bytes the blob has allocated on the heap. See [`Tag::estimate_heap_size`].
+    pub fn estimate_heap_size(&self) -> usize {
+        self.root.capacity() + compound_heap_size(&self.elements)
+    }
+}
+
+impl PackedList {
+    /// Estimate the number of bytes the list has allocated on the heap. See [`Tag::estimate_heap_size`].
+    pub fn estimate_heap_size(&self) -> usize {
+        match self {
+            PackedList::Byte(x) => x.capacity() * size_of::<i8>(),
+            PackedList::Short(x) => x.capacity() * size_of::<i16>(),
+            PackedList::Int(x) => x.capacity() * size_of::<i32>(),
+            PackedList::Long(x) => x.capacity() * size_of::<i64>(),
+            PackedList::Float(x) => x.capacity() * size_of::<f32>(),
+            PackedList::Double(x) => x.capacity() * size_of::<f64>(),
+            PackedList::Tags(x) => x.capacity() * size_of::<Tag>() + x.iter().map(|x| x.estimate_heap_size()).sum::<usize>(),
+        }
+    }
+}
@@ -0,0 +1,641 @@
+//! # SNBT
+//! Formatting and parsing of stringified NBT (SNBT), the text form used by Minecraft commands.
+//!
+//! Compound keys are sorted so the output does not depend on hash order.
+//! The same output is available through `Tag::to_snbt`, `Blob::to_snbt` and `Display` for `Tag`.
+//!
+//! Floats and doubles are written in the shortest form that parses back to the same bits, except
+//! for NaN. SNBT has no literal for it, so it is written as `NaNf` or `NaNd`, as Minecraft's own
+//! writer does. This parser reads that back as the standard NaN, losing any payload bits, and
+//! Minecraft's parser reads it as the string `"NaNf"`. Documents holding NaN payloads that need
+//! to survive should be kept as binary NBT instead.
+//!
+//! ## Example
+//! ```
+//! use nbt::{snbt, Tag};
+//!
+//! let tag = Tag::compound_from_iter(vec![("id", Tag::String("minecraft:stone".to_string())), ("Count", Tag::Byte(1))]);
+//! assert_eq!(snbt::to_string(&tag), r#"{Count:1b,id:"minecraft:stone"}"#);
+//! ```
+
+use crate::tags::{Tag, TagIdent, CompoundMap};
+use crate::blob::Blob;
+use crate::error::{NBTResult, NBTError};
+use crate::path::{parse_path, PathSegment};
+use std::collections::HashSet;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+const INDENT: &str = "    ";
+
+// Keys and strings made only of these characters can be written without quotes.
+fn is_bare(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+'))
+}
+
+pub(crate) fn write_quoted<W: Write>(out: &mut W, s: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        if c == '"' || c == '\\' {
+            out.write_char('\\')?;
+        }
+        out.write_char(c)?;
+    }
+    out.write_char('"')
+}
+
+// Start a new line at the given depth when pretty printing.
+fn newline<W: Write>(out: &mut W, depth: Option<usize>) -> fmt::Result {
+    if let Some(depth) = depth {
+        out.write_char('\n')?;
+        for _ in 0..depth {
+            out.write_str(INDENT)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_array<W: Write, T: fmt::Display>(out: &mut W, prefix: &str, suffix: &str, items: &[T], depth: Option<usize>) -> fmt::Result {
+    let separator = if depth.is_some() { ", " } else { "," };
+    write!(out, "[{};", prefix)?;
+    for (i, item) in items.iter().enumerate() {
+        out.write_str(if i > 0 { separator } else if depth.is_some() { " " } else { "" })?;
+        write!(out, "{}{}", item, suffix)?;
+    }
+    out.write_char(']')
+}
+
+fn write_compound<W: Write>(out: &mut W, compound: &CompoundMap, depth: Option<usize>) -> fmt::Result {
+    if compound.is_empty() {
+        return out.write_str("{}");
+    }
+    let mut entries: Vec<(&String, &Tag)> = compound.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let inner = depth.map(|x| x + 1);
+    out.write_char('{')?;
+    for (i, (key, value)) in entries.into_iter().enumerate() {
+        if i > 0 { out.write_char(',')?; }
+        newline(out, inner)?;
+        if is_bare(key) { out.write_str(key)? } else { write_quoted(out, key)? }
+        out.write_str(if depth.is_some() { ": " } else { ":" })?;
+        write_tag(out, value, inner)?;
+    }
+    newline(out, depth)?;
+    out.write_char('}')
+}
+
+fn write_list<W: Write>(out: &mut W, list: &[Tag], depth: Option<usize>) -> fmt::Result {
+    if list.is_empty() {
+        return out.write_str("[]");
+    }
+    let inner = depth.map(|x| x + 1);
+    out.write_char('[')?;
+    for (i, item) in list.iter().enumerate() {
+        if i > 0 { out.write_char(',')?; }
+        newline(out, inner)?;
+        write_tag(out, item, inner)?;
+    }
+    newline(out, depth)?;
+    out.write_char(']')
+}
+
+// Write a float in the shortest form that parses back to the same bits, followed by its suffix.
+// Infinities have no literal, so they are written as a number too large for the type, which
+// parses back to infinity. NaN is written as `NaN`, which does not keep its payload (see the module docs).
+fn write_float<W: Write, T: fmt::Debug + Into<f64> + Copy>(out: &mut W, value: T, overflow: &str, suffix: char) -> fmt::Result {
+    let wide: f64 = value.into();
+    if wide.is_nan() {
+        out.write_str("NaN")?;
+    } else if wide.is_infinite() {
+        if wide < 0.0 { out.write_char('-')?; }
+        out.write_str(overflow)?;
+    } else {
+        write!(out, "{:?}", value)?;
+    }
+    out.write_char(suffix)
+}
+
+// Write a tag, pretty printed at the given depth, or compactly if there is none.
+fn write_tag<W: Write>(out: &mut W, tag: &Tag, depth: Option<usize>) -> fmt::Result {
+    match tag {
+        Tag::Byte(x) => write!(out, "{}b", x),
+        Tag::Short(x) => write!(out, "{}s", x),
+        Tag::Int(x) => write!(out, "{}", x),
+        Tag::Long(x) => write!(out, "{}L", x),
+        Tag::Float(x) => write_float(out, *x, "1e39", 'f'),
+        Tag::Double(x) => write_float(out, *x, "1e309", 'd'),
+        Tag::String(x) => write_quoted(out, x),
+        Tag::ByteArray(x) => write_array(out, "B", "b", x, depth),
+        Tag::UnsignedByteArray(_) => write_array(out, "B", "b", tag.byte_array_as_i8().unwrap_or_default(), depth),
+        Tag::IntArray(x) => write_array(out, "I", "", x, depth),
+        Tag::LongArray(x) => write_array(out, "L", "L", x, depth),
+        Tag::List(list) => write_list(out, list, depth),
+        Tag::Compound(compound) => write_compound(out, compound, depth),
+    }
+}
+
+// Write a tag as compact SNBT.
+pub(crate) fn write_snbt<W: Write>(out: &mut W, tag: &Tag) -> fmt::Result {
+    write_tag(out, tag, None)
+}
+
+// Write a compound as SNBT, pretty printed if `pretty` is set.
+pub(crate) fn write_snbt_compound<W: Write>(out: &mut W, compound: &CompoundMap, pretty: bool) -> fmt::Result {
+    write_compound(out, compound, if pretty { Some(0) } else { None })
+}
+
+/// Format a tag as compact SNBT.
+pub fn to_string(tag: &Tag) -> String {
+    let mut out = String::new();
+    // Writing to a string cannot fail.
+    let _ = write_snbt(&mut out, tag);
+    out
+}
+
+/// Format a tag as SNBT, with each list element and compound entry on its own line.
+pub fn to_string_pretty(tag: &Tag) -> String {
+    let mut out = String::new();
+    let _ = write_snbt_pretty(&mut out, tag);
+    out
+}
+
+// Write a tag as SNBT, with each list element and compound entry on its own line.
+pub(crate) fn write_snbt_pretty<W: Write>(out: &mut W, tag: &Tag) -> fmt::Result {
+    write_tag(out, tag, Some(0))
+}
+
+impl Tag {
+    /// Format the tag as compact SNBT, suitable for Minecraft commands.
+    ///
+    /// ```
+    /// # use nbt::Tag;
+    /// let tag = Tag::compound_from_iter(vec![("Count", Tag::Byte(1)), ("Damage", Tag::Short(3))]);
+    /// assert_eq!(tag.to_snbt(), "{Count:1b,Damage:3s}");
+    /// assert_eq!(Tag::List(vec![Tag::Long(1), Tag::Long(2)]).to_snbt(), "[1L,2L]");
+    /// ```
+    pub fn to_snbt(&self) -> String {
+        to_string(self)
+    }
+
+    /// Format the tag as SNBT, with each list element and compound entry on its own line.
+    pub fn to_snbt_pretty(&self) -> String {
+        to_string_pretty(self)
+    }
+
+    /// Parse SNBT into a tag. See `snbt::from_str`.
+    pub fn from_snbt(input: &str) -> NBTResult<Tag> {
+        from_str(input)
+    }
+}
+
+/// Tags are parsed from SNBT. See `snbt::from_str`.
+impl FromStr for Tag {
+    type Err = NBTError;
+
+    fn from_str(input: &str) -> NBTResult<Tag> {
+        from_str(input)
+    }
+}
+
+impl Blob {
+    /// Format the root compound as compact SNBT. The root name is not included.
+    ///
+    /// ```
+    /// # use nbt::Blob;
+    /// let mut blob = Blob::create("Data");
+    /// blob.insert("LevelName", "My World");
+    /// blob.insert("rainTime", 0.5_f32);
+    /// assert_eq!(blob.to_snbt(), r#"{LevelName:"My World",rainTime:0.5f}"#);
+    /// ```
+    pub fn to_snbt(&self) -> String {
+        let mut out = String::new();
+        let _ = write_snbt_compound(&mut out, &self.elements, false);
+        out
+    }
+
+    /// Format the root compound as SNBT, with each list element and compound entry on its own line.
+    pub fn to_snbt_pretty(&self) -> String {
+        let mut out = String::new();
+        let _ = write_snbt_compound(&mut out, &self.elements, true);
+        out
+    }
+}
+
+/// Tags are displayed as SNBT, such as `{Count:1b,id:"minecraft:stone"}`.
+///
+/// The alternate form (`{:#}`) puts each list element and compound entry on its own line.
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write_snbt_pretty(f, self)
+        } else {
+            write_snbt(f, self)
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// A range of bytes within SNBT source.
+pub struct Span {
+    /// The offset of the first byte.
+    pub start: usize,
+    /// The offset after the last byte.
+    pub end: usize,
+}
+
+impl Span {
+    /// The line and column of the start of the span within `source`, counted from 1. Columns count
+    /// characters rather than bytes.
+    pub fn line_column(&self, source: &str) -> (usize, usize) {
+        line_column(source, self.start)
+    }
+}
+
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    (line, column)
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// A tag parsed from SNBT, with the span of source it was parsed from. See `parse_spanned`.
+pub struct SpannedTag {
+    /// The span of the whole value, including any brackets.
+    pub span: Span,
+    /// The value, with the spans of the tags within it.
+    pub value: SpannedValue,
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// The value of a `SpannedTag`.
+pub enum SpannedValue {
+    /// A tag that holds no other tags, including arrays.
+    Leaf(Tag),
+    /// A list, with the span of each item.
+    List(Vec<SpannedTag>),
+    /// A compound, with the span of each key and value, in the order they were written.
+    Compound(Vec<SpannedEntry>),
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// An element of a compound parsed from SNBT.
+pub struct SpannedEntry {
+    pub key: String,
+    /// The span of the key, including any quotes.
+    pub key_span: Span,
+    pub value: SpannedTag,
+}
+
+impl SpannedTag {
+    /// The type of the tag.
+    pub fn ident(&self) -> TagIdent {
+        match &self.value {
+            SpannedValue::Leaf(tag) => tag.ident(),
+            SpannedValue::List(_) => TagIdent::TAG_List,
+            SpannedValue::Compound(_) => TagIdent::TAG_Compound,
+        }
+    }
+
+    /// Find the span of the tag at a path, such as `Level.Sections[0]`. See `NbtPath`.
+    pub fn span_of(&self, path: &str) -> Option<Span> {
+        let mut tag = self;
+        for segment in parse_path(path)? {
+            tag = match (&tag.value, segment) {
+                (SpannedValue::Compound(entries), PathSegment::Key(key)) => &entries.iter().find(|x| x.key == key)?.value,
+                (SpannedValue::List(items), PathSegment::Index(index)) => items.get(index)?,
+                _ => return None,
+            };
+        }
+        Some(tag.span)
+    }
+
+    /// Drop the spans, leaving the tag.
+    pub fn into_tag(self) -> Tag {
+        match self.value {
+            SpannedValue::Leaf(tag) => tag,
+            SpannedValue::List(items) => Tag::List(items.into_iter().map(SpannedTag::into_tag).collect()),
+            SpannedValue::Compound(entries) => Tag::Compound(entries.into_iter().map(|x| (x.key, x.value.into_tag())).collect()),
+        }
+    }
+}
+
+/// Parse SNBT into a tag.
+///
+/// Numbers take the type of their suffix, as written by `to_string`, and unquoted words that are
+/// not numbers or booleans are strings. Invalid SNBT, a list holding more than one type, or a
+/// repeated key fails with `NBTError::InvalidSnbt`, holding the line and column of the problem.
+///
+/// ## Example
+/// ```
+/// use nbt::{snbt, Tag, NBTError};
+///
+/// let tag = snbt::from_str(r#"{id:"minecraft:stone",Count:1b}"#).unwrap();
+/// assert_eq!(tag["Count"], Tag::Byte(1));
+///
+/// let error = snbt::from_str("{\n    Count: 1b,\n    Damage: 3s\n    id: stone\n}").unwrap_err();
+/// assert!(matches!(error, NBTError::InvalidSnbt { line: 3, column: 15, .. }));
+/// assert_eq!(error.to_string(), "Invalid SNBT: expected ',' or '}' at line 3, column 15");
+/// ```
+pub fn from_str(input: &str) -> NBTResult<Tag> {
+    parse_spanned(input).map(SpannedTag::into_tag)
+}
+
+/// Parse SNBT into a tag, keeping the span of every tag within it, to point at values in
+/// messages about the source. See `from_str`.
+///
+/// ## Example
+/// ```
+/// use nbt::snbt;
+///
+/// let source = "{\n    port: 25565,\n    motd: \"Hello\"\n}";
+/// let tag = snbt::parse_spanned(source).unwrap();
+///
+/// let span = tag.span_of("motd").unwrap();
+/// assert_eq!(&source[span.start..span.end], "\"Hello\"");
+/// assert_eq!(span.line_column(source), (3, 11));
+/// ```
+pub fn parse_spanned(input: &str) -> NBTResult<SpannedTag> {
+    let mut parser = Parser { input, position: 0, depth: 0 };
+    let tag = parser.value()?;
+    parser.skip_whitespace();
+    if parser.position < input.len() {
+        return parser.error("unexpected trailing characters");
+    }
+    Ok(tag)
+}
+
+// Characters that may appear in unquoted keys and strings.
+fn is_bare_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
+
+// How deeply lists and compounds may nest, as with `ValidateLimits`, so hostile input can't
+// overflow the stack.
+const MAX_DEPTH: usize = 512;
+
+// Parses SNBT, borrowing the source. Mirrors the parser behind the `snbt!` macro, which can't share
+// this code as the macros crate is a dependency of this one. The `snbt_macro_parity` test compares them.
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, message: &str) -> NBTResult<T> {
+        self.error_at(self.position, message)
+    }
+
+    fn error_at<T>(&self, offset: usize, message: &str) -> NBTResult<T> {
+        let (line, column) = line_column(self.input, offset);
+        Err(NBTError::InvalidSnbt { message: message.to_string(), line, column, offset })
+    }
+
+    fn span(&self, start: usize) -> Span {
+        Span { start, end: self.position }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() { break; }
+            self.position += c.len_utf8();
+        }
+    }
+
+    // Consume `c` if it is next, returning whether it was.
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn value(&mut self) -> NBTResult<SpannedTag> {
+        self.skip_whitespace();
+        let start = self.position;
+        let value = match self.peek() {
+            Some('{') | Some('[') => {
+                if self.depth == MAX_DEPTH {
+                    return self.error(&format!("nested more than {} deep", MAX_DEPTH));
+                }
+                self.depth += 1;
+                let value = if self.peek() == Some('{') { self.compound()? } else { self.list()? };
+                self.depth -= 1;
+                value
+            }
+            Some('"') | Some('\'') => SpannedValue::Leaf(Tag::String(self.quoted()?)),
+            Some(c) if is_bare_char(c) => SpannedValue::Leaf(parse_word(self.bare())),
+            Some(_) => return self.error("unexpected character"),
+            None => return self.error("unexpected end of input"),
+        };
+        Ok(SpannedTag { span: self.span(start), value })
+    }
+
+    fn bare(&mut self) -> &'a str {
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            if !is_bare_char(c) { break; }
+            self.position += 1;
+        }
+        &self.input[start..self.position]
+    }
+
+    fn quoted(&mut self) -> NBTResult<String> {
+        let start = self.position;
+        let quote = self.peek().unwrap_or('"');
+        self.position += 1;
+        let mut out = String::new();
+        loop {
+            match self.peek() {
+                Some('\\') => {
+                    self.position += 1;
+                    match self.peek() {
+                        Some(c) if c == '\\' || c == '"' || c == '\'' => {
+                            out.push(c);
+                            self.position += 1;
+                        }
+                        _ => return self.error("invalid escape"),
+                    }
+                }
+                Some(c) if c == quote => {
+                    self.position += 1;
+                    return Ok(out);
+                }
+                Some(c) => {
+                    out.push(c);
+                    self.position += c.len_utf8();
+                }
+                None => return self.error_at(start, "unterminated string"),
+            }
+        }
+    }
+
+    fn key(&mut self) -> NBTResult<(String, Span)> {
+        self.skip_whitespace();
+        let start = self.position;
+        let key = match self.peek() {
+            Some('"') | Some('\'') => self.quoted()?,
+            Some(c) if is_bare_char(c) => self.bare().to_string(),
+            _ => return self.error("expected a key"),
+        };
+        Ok((key, self.span(start)))
+    }
+
+    fn compound(&mut self) -> NBTResult<SpannedValue> {
+        self.position += 1;
+        let mut entries: Vec<SpannedEntry> = Vec::new();
+        let mut keys = HashSet::new();
+        if self.eat('}') {
+            return Ok(SpannedValue::Compound(entries));
+        }
+        loop {
+            let (key, key_span) = self.key()?;
+            if !self.eat(':') {
+                return self.error("expected ':'");
+            }
+            let value = self.value()?;
+            if !keys.insert(key.clone()) {
+                return self.error_at(key_span.start, &format!("duplicate key '{}'", key));
+            }
+            entries.push(SpannedEntry { key, key_span, value });
+            let end = self.position;
+            if self.eat('}') {
+                return Ok(SpannedValue::Compound(entries));
+            }
+            if !self.eat(',') {
+                return self.error_at(end, "expected ',' or '}'");
+            }
+        }
+    }
+
+    fn list(&mut self) -> NBTResult<SpannedValue> {
+        self.position += 1;
+
+        // An array starts with its type and a semicolon.
+        let rest = &self.input[self.position..];
+        let prefix = rest.trim_start();
+        let skipped = rest.len() - prefix.len();
+        if prefix.len() >= 2 && prefix.as_bytes()[1] == b';' {
+            let kind = prefix.as_bytes()[0];
+            if matches!(kind, b'B' | b'I' | b'L') {
+                self.position += skipped + 2;
+                return self.array(kind).map(SpannedValue::Leaf);
+            }
+        }
+
+        let mut items: Vec<SpannedTag> = Vec::new();
+        if self.eat(']') {
+            return Ok(SpannedValue::List(items));
+        }
+        loop {
+            let item = self.value()?;
+            if let Some(first) = items.first() {
+                if first.ident() != item.ident() {
+                    return self.error_at(item.span.start, &format!("expected a {} like the first item of the list, found a {}", first.ident(), item.ident()));
+                }
+            }
+            items.push(item);
+            let end = self.position;
+            if self.eat(']') {
+                return Ok(SpannedValue::List(items));
+            }
+            if !self.eat(',') {
+                return self.error_at(end, "expected ',' or ']'");
+            }
+        }
+    }
+
+    fn array(&mut self, kind: u8) -> NBTResult<Tag> {
+        let mut items: Vec<i64> = Vec::new();
+        if !self.eat(']') {
+            loop {
+                self.skip_whitespace();
+                let start = self.position;
+                let item = match (kind, parse_word(self.bare())) {
+                    (b'B', Tag::Byte(x)) => x as i64,
+                    (b'I', Tag::Int(x)) => x as i64,
+                    (b'L', Tag::Long(x)) => x,
+                    _ => return self.error_at(start, "array items must all be of the array's type"),
+                };
+                items.push(item);
+                let end = self.position;
+                if self.eat(']') { break; }
+                if !self.eat(',') {
+                    return self.error_at(end, "expected ',' or ']'");
+                }
+            }
+        }
+        Ok(match kind {
+            b'B' => Tag::ByteArray(items.into_iter().map(|x| x as i8).collect()),
+            b'I' => Tag::IntArray(items.into_iter().map(|x| x as i32).collect()),
+            _ => Tag::LongArray(items),
+        })
+    }
+}
+
+// Whether a word is a number, ignoring any suffix.
+// Besides decimal numbers, `NaN` is accepted for a suffixed float, as written by the formatter.
+fn is_number(word: &str) -> bool {
+    let digits = word.strip_prefix(['-', '+']).unwrap_or(word);
+    if digits == "NaN" {
+        return true;
+    }
+    let mut seen_digit = false;
+    let mut seen_exponent = false;
+    let mut previous = ' ';
+    for c in digits.chars() {
+        match c {
+            '0'..='9' => seen_digit = true,
+            '.' if !seen_exponent => {}
+            'e' | 'E' if seen_digit && !seen_exponent => seen_exponent = true,
+            '-' | '+' if previous == 'e' || previous == 'E' => {}
+            _ => return false,
+        }
+        previous = c;
+    }
+    seen_digit && (previous.is_ascii_digit() || previous == '.')
+}
+
+// Parse an unquoted word as a number or boolean, falling back to a string.
+fn parse_word(word: &str) -> Tag {
+    match word {
+        "true" => return Tag::Byte(1),
+        "false" => return Tag::Byte(0),
+        _ => {}
+    }
+
+    let (body, suffix) = match word.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() && word != "NaN" => (&word[..i], Some(c.to_ascii_lowercase())),
+        _ => (word, None),
+    };
+
+    if is_number(body) {
+        let parsed = match suffix {
+            Some('b') => body.parse().ok().map(Tag::Byte),
+            Some('s') => body.parse().ok().map(Tag::Short),
+            Some('l') => body.parse().ok().map(Tag::Long),
+            Some('f') => body.parse().ok().map(Tag::Float),
+            Some('d') => body.parse().ok().map(Tag::Double),
+            None if body.contains(['.', 'e', 'E']) => body.parse().ok().map(Tag::Double),
+            None => body.parse().ok().map(Tag::Int),
+            _ => None,
+        };
+        if let Some(tag) = parsed {
+            return tag;
+        }
+    }
+    Tag::String(word.to_string())
+}
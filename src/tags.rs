@@ -1,5 +1,12 @@
+#[cfg(not(feature="preserve_order"))]
 use std::collections::HashMap;
 use std::fmt;
+use std::ops::{Index, IndexMut};
+#[cfg(feature="debug")]
+use crate::snbt::{write_snbt, write_snbt_pretty};
+use crate::util::{ToTag, FromTag};
+use crate::error::{NBTResult, NBTError};
+use crate::encode::ensure_list_integrity;
 
 #[repr(u8)]
 #[derive(Clone, PartialOrd, PartialEq)]
@@ -84,6 +91,20 @@ impl TagIdent {
     }
 }
 
+#[cfg(feature="debug")]
+/// Tags are debug formatted as SNBT, such as `{id:"minecraft:stone",Count:1b}`.
+///
+/// The alternate form (`{:#?}`) puts each list element and compound entry on its own line.
+impl fmt::Debug for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write_snbt_pretty(f, self)
+        } else {
+            write_snbt(f, self)
+        }
+    }
+}
+
 impl fmt::Display for TagIdent {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self {
@@ -105,9 +126,39 @@ impl fmt::Display for TagIdent {
 }
 
 
-#[derive(PartialEq, Clone)]
-#[cfg_attr(feature="debug", derive(Debug))]
+#[cfg(not(feature="ahash"))]
+/// The hasher used by compounds. Enable the `ahash` feature to use `ahash` instead of SipHash.
+pub type CompoundHasher = std::collections::hash_map::RandomState;
+#[cfg(feature="ahash")]
+/// The hasher used by compounds. Disable the `ahash` feature to use the standard SipHash.
+pub type CompoundHasher = ahash::RandomState;
+
+#[cfg(not(feature="preserve_order"))]
+/// The map backing a compound.
+///
+/// As the hasher depends on the `ahash` feature, create maps with `CompoundMap::default()` or
+/// `collect()` rather than `HashMap::new()`. Enable the `preserve_order` feature to keep elements
+/// in the order they were inserted.
+pub type CompoundMap = HashMap<String, Tag, CompoundHasher>;
+#[cfg(feature="preserve_order")]
+/// The map backing a compound, keeping elements in the order they were inserted.
+///
+/// Disable the `preserve_order` feature to use a `HashMap` instead.
+pub type CompoundMap = crate::ordered::OrderedMap<String, Tag>;
+
+#[derive(Clone)]
 /// A NBT Tag, representing the 13 datatypes supported by the format.
+///
+/// Tags implement `Eq` and `Ord`, so they can be sorted and kept in sets. To make that sound,
+/// `Float` and `Double` are compared by their bits rather than by IEEE-754 rules: `NaN` equals
+/// itself, and `0.0` does not equal `-0.0`. Compare the values themselves for IEEE-754 equality.
+///
+/// ```
+/// use nbt::Tag;
+///
+/// assert_eq!(Tag::Double(f64::NAN), Tag::Double(f64::NAN));
+/// assert_ne!(Tag::Double(0.0), Tag::Double(-0.0));
+/// ```
 pub enum Tag {
     Byte(i8),
     Short(i16),
@@ -118,9 +169,15 @@ pub enum Tag {
     ByteArray(Vec<i8>),
     String(String),
     List(Vec<Tag>),
-    Compound(HashMap<String, Tag>),
+    Compound(CompoundMap),
     IntArray(Vec<i32>),
-    LongArray(Vec<i64>)
+    LongArray(Vec<i64>),
+    /// A byte array stored as unsigned bytes.
+    ///
+    /// This is written and read as a `TAG_Byte_Array`, and is equal to the `ByteArray` holding the
+    /// same bytes. Reads produce it in place of `ByteArray` with `ReadOptions::unsigned_byte_arrays`.
+    /// With Serde, it is serialized as signed bytes, the same as `ByteArray`.
+    UnsignedByteArray(Vec<u8>)
 }
 
 impl Tag {
@@ -138,7 +195,8 @@ impl Tag {
             Tag::List(_) => 9,
             Tag::Compound(_) => 10,
             Tag::IntArray(_) => 11,
-            Tag::LongArray(_) => 12
+            Tag::LongArray(_) => 12,
+            Tag::UnsignedByteArray(_) => 7
         }
     }
 
@@ -158,6 +216,489 @@ impl Tag {
             Tag::Compound(_) => TagIdent::TAG_Compound,
             Tag::IntArray(_) => TagIdent::TAG_Int_Array,
             Tag::LongArray(_) => TagIdent::TAG_Long_Array,
+            Tag::UnsignedByteArray(_) => TagIdent::TAG_Byte_Array,
+        }
+    }
+
+    /// The type of the items of a list or array, or `None` for other tags.
+    ///
+    /// An empty list has items of type `TAG_End`, as it is written. A list holding more than one type
+    /// of tag, which cannot be written, gives the type of its first item.
+    ///
+    /// ```
+    /// # use nbt::{Tag, TagIdent};
+    /// // UUIDs were once stored as a list of ints, and are now an int array.
+    /// let legacy = Tag::List(vec![Tag::Int(1), Tag::Int(2), Tag::Int(3), Tag::Int(4)]);
+    /// let modern = Tag::IntArray(vec![1, 2, 3, 4]);
+    ///
+    /// assert_eq!(legacy.list_element_type(), Some(TagIdent::TAG_Int));
+    /// assert_eq!(modern.list_element_type(), Some(TagIdent::TAG_Int));
+    /// assert_eq!(Tag::List(vec![]).list_element_type(), Some(TagIdent::TAG_End));
+    /// assert_eq!(Tag::Int(1).list_element_type(), None);
+    /// ```
+    pub fn list_element_type(&self) -> Option<TagIdent> {
+        match self {
+            Tag::List(list) => Some(list.first().map_or(TagIdent::TAG_End, Tag::ident)),
+            Tag::ByteArray(_) | Tag::UnsignedByteArray(_) => Some(TagIdent::TAG_Byte),
+            Tag::IntArray(_) => Some(TagIdent::TAG_Int),
+            Tag::LongArray(_) => Some(TagIdent::TAG_Long),
+            _ => None
+        }
+    }
+
+    /// Create a byte tag holding a boolean, as 1 for true and 0 for false.
+    pub fn from_bool(value: bool) -> Tag {
+        Tag::Byte(value as i8)
+    }
+
+    /// Get a byte tag as a boolean.
+    ///
+    /// Returns `None` if the tag is not a byte, or if it is neither 0 nor 1.
+    /// ```
+    /// # use nbt::Tag;
+    /// assert_eq!(Tag::Byte(1).as_bool(), Some(true));
+    /// assert_eq!(Tag::Byte(2).as_bool(), None);
+    /// assert_eq!(Tag::Byte(2).as_bool_lenient(), Some(true));
+    /// ```
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Tag::Byte(0) => Some(false),
+            Tag::Byte(1) => Some(true),
+            _ => None
+        }
+    }
+
+    /// Get a byte tag as a boolean, treating any nonzero value as true.
+    ///
+    /// Returns `None` if the tag is not a byte.
+    pub fn as_bool_lenient(&self) -> Option<bool> {
+        match self {
+            Tag::Byte(x) => Some(*x != 0),
+            _ => None
+        }
+    }
+
+    /// Get an element of a list.
+    ///
+    /// Returns `NBTError::InvalidType` if the tag is not a list, and `NBTError::IndexOutOfBounds`
+    /// if there is no element at the index.
+    /// ```
+    /// # use nbt::{Tag, NBTError};
+    /// let list = Tag::List(vec![Tag::Int(1), Tag::Int(2)]);
+    ///
+    /// assert_eq!(list.get_index(1).unwrap(), &Tag::Int(2));
+    /// assert!(matches!(list.get_index(2), Err(NBTError::IndexOutOfBounds { index: 2, length: 2 })));
+    /// assert_eq!(list.get_index_as::<i32>(0).unwrap(), &1);
+    /// assert!(list.get_index_as::<i8>(0).is_err());
+    /// ```
+    pub fn get_index(&self, index: usize) -> NBTResult<&Tag> {
+        match self {
+            Tag::List(list) => list.get(index).ok_or(NBTError::IndexOutOfBounds { index, length: list.len() }),
+            tag => Err(NBTError::InvalidType { found: tag.ident(), expecting: TagIdent::TAG_List, when: format!("index {}", index) })
+        }
+    }
+
+    /// Get an element of a list, with a given type.
+    ///
+    /// As [`get_index`](Tag::get_index), also returning `NBTError::InvalidType` if the element is of the wrong type.
+    pub fn get_index_as<T: FromTag>(&self, index: usize) -> NBTResult<&T> {
+        let tag = self.get_index(index)?;
+        T::from_borrowed_tag(tag).ok_or_else(|| match T::expected_ident() {
+            Some(expecting) => NBTError::InvalidType { found: tag.ident(), expecting, when: format!("index {}", index) },
+            None => NBTError::Custom(format!("Could not convert {} at index {}", tag.ident(), index))
+        })
+    }
+
+    /// Create a compound tag from an iterator of names and payloads.
+    ///
+    /// The payloads take a `Tag` or any type that implements `ToTag`
+    /// ```
+    /// # use nbt::Tag;
+    /// let scores = vec![("Steve", 10), ("Alex", 12)];
+    /// let compound = Tag::compound_from_iter(scores);
+    /// # assert_eq!(compound.ident(), nbt::TagIdent::TAG_Compound);
+    /// ```
+    pub fn compound_from_iter<K: Into<String>, P: ToTag, I: IntoIterator<Item = (K, P)>>(iter: I) -> Tag {
+        Tag::Compound(iter.into_iter().map(|(k, v)| (k.into(), v.into_tag())).collect())
+    }
+
+    /// Create a list tag from an iterator of payloads.
+    ///
+    /// Returns `NBTError::InvalidList` if the payloads are not all of the same type.
+    /// ```
+    /// # use nbt::Tag;
+    /// let list = Tag::list_from_iter((1..=3).map(|x: i32| x * 2)).unwrap();
+    /// assert_eq!(list, Tag::List(vec![Tag::Int(2), Tag::Int(4), Tag::Int(6)]));
+    ///
+    /// assert!(Tag::list_from_iter(vec![Tag::Int(1), Tag::Byte(2)]).is_err());
+    /// ```
+    pub fn list_from_iter<P: ToTag, I: IntoIterator<Item = P>>(iter: I) -> NBTResult<Tag> {
+        Tag::try_list(iter.into_iter().map(|x| x.into_tag()).collect())
+    }
+
+    /// Create a list tag, checking that the items are all of the same type.
+    ///
+    /// Returns `NBTError::InvalidList` if they are not, rather than failing later when the list is written.
+    /// ```
+    /// # use nbt::{Tag, NBTError};
+    /// let list = Tag::try_list(vec![Tag::Int(1), Tag::Int(2)]).unwrap();
+    /// assert_eq!(list, Tag::List(vec![Tag::Int(1), Tag::Int(2)]));
+    ///
+    /// assert!(matches!(Tag::try_list(vec![Tag::Int(1), Tag::Byte(2)]), Err(NBTError::InvalidList { .. })));
+    /// ```
+    pub fn try_list(items: Vec<Tag>) -> NBTResult<Tag> {
+        ensure_list_integrity(&items)?;
+        Ok(Tag::List(items))
+    }
+
+    /// Create a list tag without checking the types of the items.
+    ///
+    /// This is the same as `Tag::List(items)`. A list with mixed types fails with
+    /// `NBTError::InvalidList` when written.
+    pub fn list_unchecked(items: Vec<Tag>) -> Tag {
+        Tag::List(items)
+    }
+
+    /// Create a byte array tag from unsigned bytes, without copying.
+    pub fn byte_array_from_u8(bytes: Vec<u8>) -> Tag {
+        Tag::ByteArray(cast_vec(bytes))
+    }
+
+    /// View a byte array as unsigned bytes, without copying.
+    ///
+    /// Returns `None` if the tag is not a byte array.
+    /// ```
+    /// # use nbt::Tag;
+    /// let tag = Tag::ByteArray(vec![-1, 0, 1]);
+    /// assert_eq!(tag.byte_array_as_u8(), Some(&[255u8, 0, 1][..]));
+    /// ```
+    pub fn byte_array_as_u8(&self) -> Option<&[u8]> {
+        match self {
+            // SAFETY: i8 and u8 have the same size and alignment, and every bit pattern is valid for both.
+            Tag::ByteArray(bytes) => Some(unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const u8, bytes.len()) }),
+            Tag::UnsignedByteArray(bytes) => Some(bytes),
+            _ => None
+        }
+    }
+
+    /// Mutably view a byte array as unsigned bytes, without copying.
+    ///
+    /// Returns `None` if the tag is not a byte array.
+    pub fn byte_array_as_u8_mut(&mut self) -> Option<&mut [u8]> {
+        match self {
+            // SAFETY: as above.
+            Tag::ByteArray(bytes) => Some(unsafe { std::slice::from_raw_parts_mut(bytes.as_mut_ptr() as *mut u8, bytes.len()) }),
+            Tag::UnsignedByteArray(bytes) => Some(bytes),
+            _ => None
+        }
+    }
+
+    /// View a byte array as signed bytes, without copying.
+    ///
+    /// Returns `None` if the tag is not a byte array.
+    /// ```
+    /// # use nbt::Tag;
+    /// let tag = Tag::UnsignedByteArray(vec![255, 0, 1]);
+    /// assert_eq!(tag.byte_array_as_i8(), Some(&[-1i8, 0, 1][..]));
+    /// ```
+    pub fn byte_array_as_i8(&self) -> Option<&[i8]> {
+        match self {
+            Tag::ByteArray(bytes) => Some(bytes),
+            // SAFETY: as above.
+            Tag::UnsignedByteArray(bytes) => Some(unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const i8, bytes.len()) }),
+            _ => None
+        }
+    }
+
+    /// Consume a byte array into unsigned bytes, without copying.
+    ///
+    /// Returns `None` if the tag is not a byte array.
+    pub fn into_u8_vec(self) -> Option<Vec<u8>> {
+        match self {
+            Tag::ByteArray(bytes) => Some(cast_vec(bytes)),
+            Tag::UnsignedByteArray(bytes) => Some(bytes),
+            _ => None
+        }
+    }
+
+    /// Convert a byte array between its signed and unsigned storage, without copying.
+    ///
+    /// A `ByteArray` becomes an `UnsignedByteArray` if `unsigned` is set, and the reverse if not.
+    /// Other tags are returned unchanged.
+    /// ```
+    /// # use nbt::Tag;
+    /// let tag = Tag::ByteArray(vec![-1, 0, 1]).with_unsigned_bytes(true);
+    /// assert!(matches!(&tag, Tag::UnsignedByteArray(bytes) if bytes == &[255, 0, 1]));
+    /// ```
+    pub fn with_unsigned_bytes(self, unsigned: bool) -> Tag {
+        match self {
+            Tag::ByteArray(bytes) if unsigned => Tag::UnsignedByteArray(cast_vec(bytes)),
+            Tag::UnsignedByteArray(bytes) if !unsigned => Tag::ByteArray(cast_vec(bytes)),
+            tag => tag
+        }
+    }
+
+    /// Iterate over a list of a given type.
+    ///
+    /// Returns `None` if the tag is not a list, or if any of its elements are not of the type.
+    /// ```
+    /// # use nbt::Tag;
+    /// let list = Tag::List(vec![Tag::Int(1), Tag::Int(2)]);
+    /// let sum: i32 = list.list_of::<i32>().unwrap().sum();
+    /// # assert_eq!(sum, 3);
+    ///
+    /// assert!(list.list_of::<i8>().is_none());
+    /// ```
+    pub fn list_of<'a, T: FromTag + 'a>(&'a self) -> Option<impl Iterator<Item = &'a T>> {
+        match self {
+            Tag::List(list) if list.iter().all(|x| T::from_borrowed_tag(x).is_some()) => {
+                Some(list.iter().filter_map(T::from_borrowed_tag))
+            }
+            _ => None
+        }
+    }
+
+    /// Mutably iterate over a list of a given type.
+    ///
+    /// Returns `None` if the tag is not a list, or if any of its elements are not of the type.
+    pub fn list_of_mut<'a, T: FromTag + 'a>(&'a mut self) -> Option<impl Iterator<Item = &'a mut T>> {
+        match self {
+            Tag::List(list) if list.iter().all(|x| T::from_borrowed_tag(x).is_some()) => {
+                Some(list.iter_mut().filter_map(T::from_borrowed_tag_mut))
+            }
+            _ => None
+        }
+    }
+
+    /// Consume a list of a given type.
+    ///
+    /// Returns `None` if the tag is not a list, or if any of its elements are not of the type.
+    pub fn into_list_of<T: FromTag>(self) -> Option<impl Iterator<Item = T>> {
+        match self {
+            Tag::List(list) if list.iter().all(|x| T::from_borrowed_tag(x).is_some()) => {
+                Some(list.into_iter().filter_map(T::from_tag))
+            }
+            _ => None
         }
     }
-}
\ No newline at end of file
+}
+
+macro_rules! number_accessor {
+    ($name: ident, $variant: ident, $type: ty, $kind: literal) => {
+        #[doc = concat!("Get the value of a ", $kind, " tag, or `None` for any other tag.")]
+        pub fn $name(&self) -> Option<$type> {
+            match self {
+                Tag::$variant(x) => Some(*x),
+                _ => None
+            }
+        }
+    };
+}
+
+macro_rules! type_predicate {
+    ($name: ident, $ident: ident, $kind: literal) => {
+        #[doc = concat!("Whether the tag is a ", $kind, ".")]
+        pub fn $name(&self) -> bool {
+            self.ident() == TagIdent::$ident
+        }
+    };
+}
+
+// Accessors for the value of a tag of a given type, returning `None` for any other type.
+impl Tag {
+    number_accessor!(as_i8, Byte, i8, "byte");
+    number_accessor!(as_i16, Short, i16, "short");
+    number_accessor!(as_i32, Int, i32, "int");
+    number_accessor!(as_i64, Long, i64, "long");
+    number_accessor!(as_f32, Float, f32, "float");
+    number_accessor!(as_f64, Double, f64, "double");
+
+    /// Borrow the value of a string tag.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(x) => Some(x),
+            _ => None
+        }
+    }
+
+    /// Borrow the items of a list.
+    pub fn as_list(&self) -> Option<&Vec<Tag>> {
+        match self {
+            Tag::List(x) => Some(x),
+            _ => None
+        }
+    }
+
+    /// Mutably borrow the items of a list.
+    pub fn as_list_mut(&mut self) -> Option<&mut Vec<Tag>> {
+        match self {
+            Tag::List(x) => Some(x),
+            _ => None
+        }
+    }
+
+    /// Borrow the elements of a compound.
+    ///
+    /// Like the other accessors, this returns `None` for any other type of tag. Numbers are not
+    /// converted between types, so `as_i64` of an int is `None`.
+    /// ```
+    /// # use nbt::Tag;
+    /// let tag = Tag::compound_from_iter(vec![("name", Tag::String("Steve".to_string())), ("xp", Tag::Int(30))]);
+    ///
+    /// let compound = tag.as_compound().unwrap();
+    /// assert_eq!(compound["name"].as_str(), Some("Steve"));
+    /// assert_eq!(compound["xp"].as_i32(), Some(30));
+    /// assert_eq!(compound["xp"].as_i64(), None);
+    /// assert!(compound["xp"].is_int());
+    /// ```
+    pub fn as_compound(&self) -> Option<&CompoundMap> {
+        match self {
+            Tag::Compound(x) => Some(x),
+            _ => None
+        }
+    }
+
+    /// Mutably borrow the elements of a compound.
+    pub fn as_compound_mut(&mut self) -> Option<&mut CompoundMap> {
+        match self {
+            Tag::Compound(x) => Some(x),
+            _ => None
+        }
+    }
+
+    /// Borrow the items of an int array.
+    pub fn as_int_array(&self) -> Option<&[i32]> {
+        match self {
+            Tag::IntArray(x) => Some(x),
+            _ => None
+        }
+    }
+
+    /// Borrow the items of a long array.
+    pub fn as_long_array(&self) -> Option<&[i64]> {
+        match self {
+            Tag::LongArray(x) => Some(x),
+            _ => None
+        }
+    }
+
+    type_predicate!(is_byte, TAG_Byte, "byte");
+    type_predicate!(is_short, TAG_Short, "short");
+    type_predicate!(is_int, TAG_Int, "int");
+    type_predicate!(is_long, TAG_Long, "long");
+    type_predicate!(is_float, TAG_Float, "float");
+    type_predicate!(is_double, TAG_Double, "double");
+    type_predicate!(is_byte_array, TAG_Byte_Array, "byte array, signed or unsigned");
+    type_predicate!(is_string, TAG_String, "string");
+    type_predicate!(is_list, TAG_List, "list");
+    type_predicate!(is_compound, TAG_Compound, "compound");
+    type_predicate!(is_int_array, TAG_Int_Array, "int array");
+    type_predicate!(is_long_array, TAG_Long_Array, "long array");
+}
+
+impl Tag {
+    /// Look up a nested tag by a path of compound keys and list indices separated by `/`, such as
+    /// `Level/Sections/0/Palette`. A leading `/` is ignored, and the empty path is the tag itself.
+    ///
+    /// Returns `None` if any step of the path is missing, or indexes a tag that is neither a
+    /// compound nor a list. Numeric steps are keys when applied to compounds.
+    /// ```
+    /// # use nbt::Tag;
+    /// let section = Tag::compound_from_iter(vec![("Y", Tag::Byte(4))]);
+    /// let level = Tag::compound_from_iter(vec![("Sections", Tag::List(vec![section]))]);
+    /// let chunk = Tag::compound_from_iter(vec![("Level", level)]);
+    ///
+    /// assert_eq!(chunk.pointer("Level/Sections/0/Y"), Some(&Tag::Byte(4)));
+    /// assert_eq!(chunk.pointer("Level/Sections/1/Y"), None);
+    /// assert_eq!(chunk["Level"]["Sections"][0]["Y"], Tag::Byte(4));
+    /// ```
+    pub fn pointer(&self, path: &str) -> Option<&Tag> {
+        let mut tag = self;
+        for step in pointer_steps(path) {
+            tag = match tag {
+                Tag::Compound(map) => map.get(step)?,
+                Tag::List(list) => list.get(step.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(tag)
+    }
+
+    /// Mutably look up a nested tag by a path separated by `/`. See `pointer`.
+    pub fn pointer_mut(&mut self, path: &str) -> Option<&mut Tag> {
+        let mut tag = self;
+        for step in pointer_steps(path) {
+            tag = match tag {
+                Tag::Compound(map) => map.get_mut(step)?,
+                Tag::List(list) => list.get_mut(step.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(tag)
+    }
+}
+
+// Split a pointer into its steps, ignoring a leading separator.
+fn pointer_steps(path: &str) -> impl Iterator<Item = &str> {
+    let path = path.strip_prefix('/').unwrap_or(path);
+    path.split('/').filter(move |_| !path.is_empty())
+}
+
+/// Index the elements of a compound by key.
+///
+/// Panics if the tag is not a compound or has no such element. Use `Tag::pointer` or
+/// `as_compound` to look up elements that may be missing.
+impl Index<&str> for Tag {
+    type Output = Tag;
+
+    fn index(&self, key: &str) -> &Tag {
+        match self {
+            Tag::Compound(map) => map.get(key).unwrap_or_else(|| panic!("no element named '{}' in compound", key)),
+            _ => panic!("cannot index a {} by key '{}'", self.ident(), key),
+        }
+    }
+}
+
+impl IndexMut<&str> for Tag {
+    fn index_mut(&mut self, key: &str) -> &mut Tag {
+        match self {
+            Tag::Compound(map) => map.get_mut(key).unwrap_or_else(|| panic!("no element named '{}' in compound", key)),
+            _ => panic!("cannot index a {} by key '{}'", self.ident(), key),
+        }
+    }
+}
+
+/// Index the items of a list.
+///
+/// Panics if the tag is not a list or the index is out of bounds.
+impl Index<usize> for Tag {
+    type Output = Tag;
+
+    fn index(&self, index: usize) -> &Tag {
+        match self {
+            Tag::List(list) => &list[index],
+            _ => panic!("cannot index a {} by index {}", self.ident(), index),
+        }
+    }
+}
+
+impl IndexMut<usize> for Tag {
+    fn index_mut(&mut self, index: usize) -> &mut Tag {
+        match self {
+            Tag::List(list) => &mut list[index],
+            _ => panic!("cannot index a {} by index {}", self.ident(), index),
+        }
+    }
+}
+
+// Reinterpret a vec of bytes between their signed and unsigned forms.
+fn cast_vec<A: Byte, B: Byte>(vec: Vec<A>) -> Vec<B> {
+    let mut vec = std::mem::ManuallyDrop::new(vec);
+    // SAFETY: `Byte` is only implemented for i8 and u8, which have the same size and alignment,
+    // and every bit pattern is valid for both. The original vec is never dropped.
+    unsafe { Vec::from_raw_parts(vec.as_mut_ptr() as *mut B, vec.len(), vec.capacity()) }
+}
+
+trait Byte {}
+impl Byte for i8 {}
+impl Byte for u8 {}
@@ -0,0 +1,103 @@
+//! # Test Vectors
+//! The reference `hello_world.nbt` and `bigtest.nbt` documents, and a synthetic Bedrock `level.dat`,
+//! with their expected contents.
+//!
+//! All documents are uncompressed. Downstream crates can use them to check their integration
+//! against known-good data. The Bedrock `level.dat` was not saved by the game, so it only checks
+//! the dialect, not compatibility with real worlds.
+//!
+//! ## Example
+//! ```
+//! use nbt::{Blob, NBTRead};
+//! use nbt::test_vectors::{BIGTEST, bigtest};
+//!
+//! let blob = Blob::from_bytes(BIGTEST).unwrap();
+//! assert_eq!(blob.compound(), bigtest().compound());
+//! ```
+
+use crate::blob::Blob;
+use crate::tags::Tag;
+
+/// The bytes of `hello_world.nbt`.
+pub const HELLO_WORLD: &[u8] = include_bytes!("test_vectors/hello_world.nbt");
+
+/// The bytes of `bigtest.nbt`, uncompressed.
+pub const BIGTEST: &[u8] = include_bytes!("test_vectors/bigtest.nbt");
+
+/// The bytes of a synthetic Bedrock `level.dat`.
+///
+/// The file is laid out as the game writes it: a little endian int holding the storage version,
+/// another holding the length of the NBT, then the NBT in the `BedrockFlavor` dialect.
+/// It is not a save from the game. It was assembled by hand with a subset of the fields the game
+/// writes, including floats, a list of ints and a name outside the basic multilingual plane, which
+/// Java would encode differently.
+/// ```
+/// use nbt::{Blob, NBTRead, BedrockFlavor};
+/// use nbt::test_vectors::{BEDROCK_LEVEL_DAT, bedrock_level};
+///
+/// let blob = Blob::read_flavor::<BedrockFlavor, _>(&mut &BEDROCK_LEVEL_DAT[8..]).unwrap();
+/// assert_eq!(blob.compound(), bedrock_level().compound());
+/// ```
+pub const BEDROCK_LEVEL_DAT: &[u8] = include_bytes!("test_vectors/bedrock_level.dat");
+
+/// The expected contents of `hello_world.nbt`.
+pub fn hello_world() -> Blob {
+    let mut blob = Blob::create("hello world");
+    blob.insert("name", "Bananrama");
+    blob
+}
+
+/// The expected contents of `bigtest.nbt`.
+pub fn bigtest() -> Blob {
+    let food = |name: &str, value: f32| Tag::compound_from_iter(vec![
+        ("name", Tag::String(name.to_string())),
+        ("value", Tag::Float(value)),
+    ]);
+    let entry = |index: i32| Tag::compound_from_iter(vec![
+        ("name", Tag::String(format!("Compound tag #{}", index))),
+        ("created-on", Tag::Long(1264099775885)),
+    ]);
+
+    let mut blob = Blob::create("Level");
+    blob.insert("nested compound test", Tag::compound_from_iter(vec![
+        ("egg", food("Eggbert", 0.5)),
+        ("ham", food("Hampus", 0.75)),
+    ]));
+    blob.insert("intTest", i32::MAX);
+    blob.insert("byteTest", i8::MAX);
+    blob.insert("stringTest", "HELLO WORLD THIS IS A TEST STRING ÅÄÖ!");
+    blob.insert("listTest (long)", Tag::List((11..16).map(Tag::Long).collect()));
+    blob.insert("doubleTest", 0.493_128_713_218_231_5_f64);
+    blob.insert("floatTest", 0.498_231_47_f32);
+    blob.insert("longTest", i64::MAX);
+    blob.insert("listTest (compound)", Tag::List((0..2).map(entry).collect()));
+    blob.insert(
+        "byteArrayTest (the first 1000 values of (n*n*255+n*7)%100, starting with n=0 (0, 62, 34, 16, 8, ...))",
+        (0..1000).map(|n: i32| ((n * n * 255 + n * 7) % 100) as i8).collect::<Vec<i8>>(),
+    );
+    blob.insert("shortTest", i16::MAX);
+    blob
+}
+
+/// The expected contents of `BEDROCK_LEVEL_DAT`, after its header.
+pub fn bedrock_level() -> Blob {
+    let mut blob = Blob::new();
+    blob.insert("StorageVersion", 10);
+    blob.insert("LevelName", "Bedrock level é 😀");
+    blob.insert("RandomSeed", -6_582_916_932_012_556_379_i64);
+    blob.insert("SpawnX", 0);
+    blob.insert("SpawnY", 32767);
+    blob.insert("SpawnZ", 4);
+    blob.insert("rainLevel", 0.3_f32);
+    blob.insert("lightningLevel", 1.0_f32);
+    blob.insert("currentTick", 2_147_483_648_123_i64);
+    blob.insert("commandsEnabled", 1_i8);
+    blob.insert("abilities", Tag::compound_from_iter(vec![
+        ("flySpeed", Tag::Float(0.05)),
+        ("walkSpeed", Tag::Float(0.1)),
+        ("mayfly", Tag::Byte(0)),
+    ]));
+    blob.insert("lastOpenedWithVersion", Tag::List(vec![1, 20, 80, 5, 0].into_iter().map(Tag::Int).collect()));
+    blob.insert("BiomeOverride", "");
+    blob
+}
@@ -14,3 +14,2619 @@ fn blob_example() {
 
     println!("{:?}", decoded)
 }
+
+#[test]
+fn diagnostics_collection() {
+    use crate::{Diagnostics, Diagnostic, TagIdent};
+
+    let data = vec![
+        10, 0, 0,                               // root compound ""
+        1, 0, 1, 97, 1,                         // byte "a" = 1
+        1, 0, 1, 97, 2,                         // byte "a" = 2 (duplicate)
+        9, 0, 1, 98, 3, 0, 0, 0, 0,             // list "b" of TAG_Int, empty
+        0,                                      // end
+        0, 0                                    // trailing
+    ];
+
+    let mut diagnostics = Diagnostics::new();
+    let blob = Blob::from_bytes_with_diagnostics(&data, &mut diagnostics).unwrap();
+
+    assert_eq!(blob.get::<i8>("a"), Some(&2));
+    assert_eq!(diagnostics.into_vec(), vec![
+        Diagnostic::DuplicateKey { path: "a".to_string() },
+        Diagnostic::TypedEmptyList { path: "b".to_string(), ident: TagIdent::TAG_Int },
+        Diagnostic::TrailingBytes { count: 2 },
+    ]);
+
+    // Reading without a sink still succeeds.
+    assert!(Blob::from_bytes(&data).is_ok());
+}
+
+#[test]
+fn roundtrip_divergence() {
+    use crate::Tag;
+    use crate::verify::divergence;
+    use crate::CompoundMap;
+
+    let mut inner = CompoundMap::default();
+    inner.insert("x".to_string(), Tag::List(vec![Tag::Int(1), Tag::Int(2)]));
+    let a = Tag::Compound(inner.clone());
+
+    inner.insert("x".to_string(), Tag::List(vec![Tag::Int(1), Tag::Int(3)]));
+    let b = Tag::Compound(inner);
+
+    assert_eq!(divergence(&a, &a, ""), None);
+    assert_eq!(divergence(&a, &b, ""), Some("x[1]".to_string()));
+    assert_eq!(divergence(&Tag::Float(f32::NAN), &Tag::Float(f32::NAN), ""), None);
+
+    let mut blob = Blob::create("root");
+    blob.insert("nan", f64::NAN);
+    blob.insert("list", Tag::List(vec![Tag::Byte(1)]));
+    assert!(blob.verify_roundtrip().is_ok());
+}
+
+#[cfg(feature = "codec")]
+#[test]
+fn codec_partial_frames() {
+    use crate::{NBTCodec, NBTError, Compression};
+    use bytes::BytesMut;
+    use tokio_util::codec::{Decoder, Encoder};
+
+    for mut codec in [NBTCodec::new(), NBTCodec::compressed(Compression::Gzip)] {
+        let mut blob = Blob::create("frame");
+        blob.insert("name", "Bananrama");
+
+        let mut encoded = BytesMut::new();
+        codec.encode(blob.clone(), &mut encoded).unwrap();
+        codec.encode(blob, &mut encoded).unwrap();
+
+        // Feed the frames in one byte at a time.
+        let mut buffer = BytesMut::new();
+        let mut decoded = Vec::new();
+        for byte in encoded.iter() {
+            buffer.extend_from_slice(&[*byte]);
+            if let Some(frame) = codec.decode(&mut buffer).unwrap() {
+                decoded.push(frame);
+            }
+        }
+
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[1].root, "frame");
+        assert!(buffer.is_empty());
+    }
+
+    // Frames over the limit are rejected once the limit is passed, without reserving their length.
+    let mut codec = NBTCodec::compressed(Compression::Gzip);
+    let mut buffer = BytesMut::from(&[0xFF, 0xFF, 0xFF, 0xFF][..]);
+    assert!(matches!(codec.decode(&mut buffer), Err(NBTError::LengthLimitExceeded { length: 0xFFFF_FFFF, limit: 0x80_0000 })));
+    assert!(buffer.capacity() < 1024);
+
+    let mut codec = NBTCodec::new().max_frame_length(16);
+    let mut blob = Blob::new();
+    blob.insert("name", "a name longer than the limit");
+    let bytes = blob.bytes().unwrap();
+    let mut buffer = BytesMut::from(&bytes[..16]);
+    assert!(matches!(codec.decode(&mut buffer), Ok(None)));
+    buffer.extend_from_slice(&bytes[16..20]);
+    assert!(matches!(codec.decode(&mut buffer), Err(NBTError::LengthLimitExceeded { length: 20, limit: 16 })));
+}
+
+#[cfg(feature = "region")]
+#[test]
+fn region_layout() {
+    use crate::Compression;
+    use crate::region::{RegionChunk, write_region, SECTOR_SIZE};
+
+    let chunks = vec![
+        RegionChunk::new(0, 0, Blob::new()),
+        RegionChunk::new(-1, 1, Blob::new()),
+    ];
+
+    let mut buffer = Vec::new();
+    write_region(&mut buffer, &chunks, Compression::Zlib).unwrap();
+    assert_eq!(buffer.len(), SECTOR_SIZE * 4);
+
+    // (0, 0) is in sector 2, (31, 1) in sector 3.
+    assert_eq!(&buffer[0..4], &[0, 0, 2, 1]);
+    assert_eq!(&buffer[(31 + 32) * 4..(31 + 32) * 4 + 4], &[0, 0, 3, 1]);
+    assert_eq!(buffer[SECTOR_SIZE * 2 + 4], 2);
+
+    let duplicate = vec![RegionChunk::new(0, 0, Blob::new()), RegionChunk::new(32, 0, Blob::new())];
+    assert!(write_region(&mut Vec::new(), &duplicate, Compression::Zlib).is_err());
+}
+
+#[test]
+fn memory_budget() {
+    use crate::{ReadOptions, MemoryBudget, NBTError};
+
+    let mut blob = Blob::new();
+    blob.insert("data", vec![0i64; 1024]);
+    let data = blob.bytes().unwrap();
+
+    let small = ReadOptions::new().memory_budget(MemoryBudget::new(1024));
+    match Blob::from_bytes_with_options(&data, &small) {
+        Err(NBTError::MemoryBudgetExceeded { limit }) => assert_eq!(limit, 1024),
+        other => panic!("expected budget error, got {:?}", other)
+    }
+
+    let large = ReadOptions::new().memory_budget(MemoryBudget::new(64 * 1024));
+    assert!(Blob::from_bytes_with_options(&data, &large).is_ok());
+
+    // A lying length prefix is refused before anything is allocated.
+    let lying = vec![10, 0, 0, 12, 0, 1, 97, 0x7F, 0xFF, 0xFF, 0xFF];
+    assert!(matches!(Blob::from_bytes_with_options(&lying, &large), Err(NBTError::MemoryBudgetExceeded { .. })));
+}
+
+#[test]
+fn blob_merge_strategies() {
+    use crate::{MergeStrategy, Tag};
+    use crate::CompoundMap;
+
+    let compound = |pairs: &[(&str, i32)]| Tag::Compound(pairs.iter().map(|(k, v)| (k.to_string(), Tag::Int(*v))).collect::<CompoundMap>());
+
+    let mut defaults = Blob::new();
+    defaults.insert("a", 1);
+    defaults.insert("nested", compound(&[("x", 1), ("y", 1)]));
+
+    let mut user = Blob::new();
+    user.insert("a", 2);
+    user.insert("b", 2);
+    user.insert("nested", compound(&[("y", 2)]));
+
+    let mut keep = defaults.clone();
+    keep.merge(user.clone(), MergeStrategy::Keep);
+    assert_eq!(keep.get::<i32>("a"), Some(&1));
+    assert_eq!(keep.get::<i32>("b"), Some(&2));
+
+    let mut overwrite = defaults.clone();
+    overwrite.merge(user.clone(), MergeStrategy::Overwrite);
+    assert_eq!(overwrite.get::<i32>("a"), Some(&2));
+    assert_eq!(overwrite.get::<Tag>("nested"), Some(&compound(&[("y", 2)])));
+
+    let mut deep = defaults;
+    deep.merge(user, MergeStrategy::Deep);
+    assert_eq!(deep.get::<i32>("a"), Some(&2));
+    assert_eq!(deep.get::<Tag>("nested"), Some(&compound(&[("x", 1), ("y", 2)])));
+}
+
+#[test]
+fn blob_extract() {
+    use crate::Tag;
+    use crate::path::{parse_path, PathSegment};
+    use crate::CompoundMap;
+
+    assert_eq!(parse_path("a.b[2].c"), Some(vec![
+        PathSegment::Key("a".to_string()), PathSegment::Key("b".to_string()),
+        PathSegment::Index(2), PathSegment::Key("c".to_string())
+    ]));
+    assert_eq!(parse_path("a..b"), None);
+    assert_eq!(parse_path("a[x]"), None);
+
+    let mut section = CompoundMap::default();
+    section.insert("Y".to_string(), Tag::Byte(4));
+    let mut level = CompoundMap::default();
+    level.insert("Sections".to_string(), Tag::List(vec![Tag::Compound(section.clone())]));
+
+    let mut blob = Blob::new();
+    blob.insert("Level", Tag::Compound(level));
+
+    assert!(blob.extract_blob("Level.Sections[0].Y").is_none());
+    let extracted = blob.extract_blob("Level.Sections[0]").unwrap();
+    assert_eq!(extracted.elements, section);
+    assert_eq!(blob.extract("Level.Sections"), Some(Tag::List(vec![])));
+    assert_eq!(blob.extract("Level.Sections"), None);
+}
+
+#[test]
+fn tag_normalize() {
+    use crate::{Tag, NormalizeOptions};
+    use crate::CompoundMap;
+
+    let mut map = CompoundMap::default();
+    map.insert("bytes".to_string(), Tag::List(vec![Tag::Byte(1), Tag::Byte(2)]));
+    map.insert("mixed".to_string(), Tag::List(vec![Tag::Int(1), Tag::Long(2)]));
+    map.insert("nan".to_string(), Tag::Float(f32::from_bits(0x7fc0_0001)));
+    let mut tag = Tag::Compound(map);
+
+    // Nothing changes with the default options.
+    let original = tag.clone();
+    tag.normalize(&NormalizeOptions::new());
+    assert!(crate::verify::divergence(&tag, &original, "").is_none());
+
+    tag.normalize(&NormalizeOptions::new().lists_to_arrays(true).canonical_floats(true));
+    if let Tag::Compound(map) = tag {
+        assert_eq!(map["bytes"], Tag::ByteArray(vec![1, 2]));
+        assert_eq!(map["mixed"], Tag::List(vec![Tag::Int(1), Tag::Long(2)]));
+        assert!(matches!(map["nan"], Tag::Float(x) if x.to_bits() == f32::NAN.to_bits()));
+    }
+}
+
+#[test]
+fn lossy_strings() {
+    use crate::{ReadOptions, Diagnostics, Diagnostic, NBTError};
+    use crate::decode::decode_wonky_string_lossy;
+
+    // "a", invalid byte, a surrogate pair for U+1F600, an unpaired surrogate and a modified UTF-8 null.
+    let bytes = [0x61, 0xFF, 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80, 0xED, 0xA0, 0xBD, 0xC0, 0x80];
+    assert_eq!(decode_wonky_string_lossy(&bytes), "a\u{FFFD}\u{1F600}\u{FFFD}\0");
+
+    // byte "name" = "a\xFFb"
+    let data = vec![10, 0, 0, 8, 0, 4, 110, 97, 109, 101, 0, 3, 97, 0xFF, 98, 0];
+    assert!(matches!(Blob::from_bytes(&data), Err(NBTError::StringError)));
+
+    let mut diagnostics = Diagnostics::new();
+    let options = ReadOptions::new().lossy_strings(true);
+    let blob = Blob::from_bytes_with(&data, &options, Some(&mut diagnostics)).unwrap();
+
+    assert_eq!(blob.get::<String>("name").unwrap(), "a\u{FFFD}b");
+    assert_eq!(diagnostics.into_vec(), vec![Diagnostic::LossyString { path: "name".to_string() }]);
+}
+
+#[test]
+fn string_modes() {
+    use crate::{Blob, Tag, NBTRead, NBTWrite, ReadOptions, StringMode, BedrockFlavor, NBTError};
+
+    // A blob holding one string, written as Java and as plain UTF-8.
+    let string = "nul\0 and \u{1F600}".to_string();
+    let blob = |payload: &[u8]| {
+        let mut data = vec![10, 0, 0, 8, 0, 1, b's', 0, payload.len() as u8];
+        data.extend_from_slice(payload);
+        data.push(0);
+        data
+    };
+    let java = blob(&Tag::String(string.clone()).bytes().unwrap()[2..]);
+    let mut utf8 = Vec::new();
+    Tag::String(string.clone()).write_flavor::<BedrockFlavor, _>(&mut utf8).unwrap();
+    let utf8 = blob(&utf8[2..]);
+    assert_ne!(java, utf8);
+
+    let read = |data: &[u8], mode| Blob::from_bytes_with_options(data, &ReadOptions::new().string_mode(mode))
+        .map(|x| x.get::<String>("s").cloned());
+
+    // By default Java accepts both, but strict mode only modified UTF-8 and UTF-8 mode only UTF-8.
+    for mode in [StringMode::Flavor, StringMode::Lossy] {
+        assert_eq!(read(&java, mode).unwrap(), Some(string.clone()));
+        assert_eq!(read(&utf8, mode).unwrap(), Some(string.clone()));
+    }
+    assert_eq!(read(&java, StringMode::Strict).unwrap(), Some(string.clone()));
+    assert!(matches!(read(&utf8, StringMode::Strict), Err(NBTError::StringError)));
+    assert_eq!(read(&utf8, StringMode::Utf8).unwrap(), Some(string.clone()));
+    assert!(matches!(read(&java, StringMode::Utf8), Err(NBTError::StringError)));
+
+    // Mixing the encodings in one string fails unless lossy.
+    let mixed = blob(&[0xf0, 0x9f, 0x98, 0x80, 0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80]);
+    assert!(read(&mixed, StringMode::Flavor).is_err());
+    assert_eq!(read(&mixed, StringMode::Lossy).unwrap().map(|x| x.ends_with('\u{1F600}')), Some(true));
+}
+
+#[test]
+fn typed_list_iteration() {
+    use crate::Tag;
+
+    let mut list = Tag::List(vec![Tag::Short(1), Tag::Short(2)]);
+    for item in list.list_of_mut::<i16>().unwrap() {
+        *item *= 10;
+    }
+    assert_eq!(list.list_of::<i16>().unwrap().copied().collect::<Vec<_>>(), vec![10, 20]);
+    assert_eq!(list.clone().into_list_of::<i16>().unwrap().collect::<Vec<_>>(), vec![10, 20]);
+
+    assert!(list.list_of_mut::<i32>().is_none());
+    assert!(Tag::Int(1).list_of::<i32>().is_none());
+    assert_eq!(Tag::List(vec![]).list_of::<String>().unwrap().count(), 0);
+}
+
+#[test]
+fn unsigned_byte_arrays() {
+    use crate::Tag;
+
+    let mut tag = Tag::byte_array_from_u8(vec![0, 127, 128, 255]);
+    assert_eq!(tag, Tag::ByteArray(vec![0, 127, -128, -1]));
+
+    tag.byte_array_as_u8_mut().unwrap()[0] = 200;
+    assert_eq!(tag.byte_array_as_u8(), Some(&[200u8, 127, 128, 255][..]));
+    assert_eq!(tag.into_u8_vec(), Some(vec![200, 127, 128, 255]));
+
+    assert_eq!(Tag::Int(0).byte_array_as_u8(), None);
+}
+
+#[test]
+fn stable_content_hash() {
+    use crate::Tag;
+
+    // Pinned values, the hash must not change between releases.
+    assert_eq!(Tag::Int(1).content_hash(), 0xf16b_3403_a27b_5565);
+    assert_eq!(Blob::new().content_hash(), Blob::new().content_hash());
+
+    let mut a = Blob::create("a");
+    a.insert("x", 1);
+    let mut b = Blob::create("b");
+    b.insert("x", 1);
+    assert_ne!(a.content_hash(), b.content_hash());
+    assert_ne!(Tag::Int(1).content_hash_128(), Tag::Long(1).content_hash_128());
+}
+
+#[test]
+fn shared_tag_copy_on_write() {
+    use crate::{Tag, SharedTag};
+
+    let level = Tag::compound_from_iter(vec![("a", Tag::List(vec![Tag::Int(1)])), ("b", Tag::Int(2))]);
+    let original = SharedTag::from(level.clone());
+
+    let mut edited = original.clone();
+    assert!(edited.ptr_eq(&original));
+
+    // Mutating the copy un-shares the root, but not the untouched list.
+    edited.compound_mut().unwrap().insert("b".into(), SharedTag::Int(3));
+    assert!(!edited.ptr_eq(&original));
+    assert!(edited.get("a").unwrap().ptr_eq(original.get("a").unwrap()));
+
+    assert_eq!(original.to_tag(), level);
+    assert_eq!(edited.get("b"), Some(&SharedTag::Int(3)));
+}
+
+#[test]
+fn interned_shared_tags() {
+    use crate::{Tag, SharedTag, Interner, NBTRead, NBTWrite, ReadOptions};
+    use std::sync::Arc;
+
+    let mut blob = Blob::new();
+    blob.insert("id", "minecraft:stone");
+    blob.insert("tag", Tag::compound_from_iter(vec![("id", Tag::String("minecraft:stone".to_string()))]));
+    let data = blob.bytes().unwrap();
+
+    let key = |tag: &SharedTag, name: &str| match tag {
+        SharedTag::Compound(map) => map.keys().find(|x| &***x == name).unwrap().clone(),
+        _ => unreachable!(),
+    };
+    let string = |tag: &SharedTag| match tag {
+        SharedTag::String(x) => x.clone(),
+        _ => unreachable!(),
+    };
+
+    // Without an interner, nothing is shared.
+    let a = SharedTag::from_bytes(&data).unwrap();
+    let b = SharedTag::from_bytes(&data).unwrap();
+    assert!(!Arc::ptr_eq(&key(&a, "id"), &key(&b, "id")));
+    assert_eq!(a.to_tag(), blob.clone().compound());
+
+    // Keys are shared across documents and nesting, but values only when asked.
+    let interner = Interner::new();
+    let options = ReadOptions::new().interner(interner.clone());
+    let a = SharedTag::from_bytes_with_options(&data, &options).unwrap();
+    let b = SharedTag::from_bytes_with_options(&data, &options).unwrap();
+    assert!(Arc::ptr_eq(&key(&a, "id"), &key(&b, "id")));
+    assert!(Arc::ptr_eq(&key(&a, "id"), &key(a.get("tag").unwrap(), "id")));
+    assert!(!Arc::ptr_eq(&string(a.get("id").unwrap()), &string(b.get("id").unwrap())));
+    assert_eq!(interner.len(), 2);
+    assert_eq!(b.to_tag(), blob.compound());
+
+    let options = options.intern_values(true);
+    let c = SharedTag::from_bytes_with_options(&data, &options).unwrap();
+    let d = SharedTag::from_bytes_with_options(&data, &options).unwrap();
+    assert!(Arc::ptr_eq(&string(c.get("id").unwrap()), &string(d.get("id").unwrap())));
+    assert!(Arc::ptr_eq(&key(&c, "id"), &key(&a, "id")));
+    assert_eq!(interner.len(), 3);
+
+    // Strings are forgotten once no tag uses them.
+    drop((c, d));
+    interner.shrink();
+    assert_eq!(interner.len(), 2);
+    drop((a, b));
+    interner.shrink();
+    assert!(interner.is_empty());
+    assert!(Arc::ptr_eq(&interner.intern("x"), &interner.intern("x")));
+
+    // Keys that need decoding are shared too, and other types leave the interner alone.
+    let mut blob = Blob::new();
+    blob.insert("é\0", Tag::List(vec![Tag::compound_from_iter(vec![("é\0", 1)])]));
+    let data = blob.bytes().unwrap();
+    let interner = Interner::new();
+    let options = ReadOptions::new().interner(interner.clone());
+    let e = SharedTag::from_bytes_with_options(&data, &options).unwrap();
+    let inner = match e.get("é\0").unwrap() {
+        SharedTag::List(list) => list[0].clone(),
+        _ => unreachable!(),
+    };
+    assert!(Arc::ptr_eq(&key(&e, "é\0"), &key(&inner, "é\0")));
+    assert_eq!(e.to_tag(), blob.clone().compound());
+    Blob::from_bytes_with_options(&data, &options).unwrap();
+    assert_eq!(interner.len(), 1);
+}
+
+#[test]
+fn replace_where_paths() {
+    use crate::Tag;
+
+    let inner = Tag::compound_from_iter(vec![("n", Tag::List(vec![Tag::Int(1), Tag::Int(2)]))]);
+    let mut tag = Tag::compound_from_iter(vec![("a", inner)]);
+
+    let mut visited = Vec::new();
+    let count = tag.replace_where(|path, _| { visited.push(path.to_string()); path == "a.n" }, |_| Tag::Int(0));
+
+    assert_eq!(count, 1);
+    assert_eq!(visited, vec!["", "a", "a.n"]);
+    assert_eq!(tag, Tag::compound_from_iter(vec![("a", Tag::compound_from_iter(vec![("n", 0)]))]));
+}
+
+#[cfg(feature = "codegen")]
+#[test]
+fn codegen_nested_and_optional() {
+    use crate::Tag;
+    use crate::codegen::generate;
+
+    let item = |id: &str, count: Option<i8>| {
+        let mut pairs = vec![("id", Tag::String(id.to_string()))];
+        if let Some(count) = count { pairs.push(("Count", Tag::Byte(count))); }
+        Tag::compound_from_iter(pairs)
+    };
+
+    let mut a = Blob::new();
+    a.insert("Inventory", Tag::List(vec![item("minecraft:stone", Some(1)), item("minecraft:air", None)]));
+    a.insert("type", "player");
+    let mut b = Blob::new();
+    b.insert("Inventory", Tag::List(vec![]));
+    b.insert("type", "player");
+    b.insert("2fast", 1.0f32);
+
+    assert_eq!(generate("player data", &[a, b]), "\
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlayerData {
+    #[serde(rename = \"2fast\")]
+    pub _2fast: Option<f32>,
+    #[serde(rename = \"Inventory\")]
+    pub inventory: Vec<Inventory>,
+    #[serde(rename = \"type\")]
+    pub type_: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Inventory {
+    #[serde(rename = \"Count\")]
+    pub count: Option<i8>,
+    pub id: String,
+}
+");
+}
+
+#[test]
+fn heap_size_estimate() {
+    use crate::Tag;
+    use std::mem::size_of;
+
+    let list = Tag::List(vec![Tag::String("abcd".to_string()), Tag::IntArray(vec![1, 2])]);
+    assert_eq!(list.estimate_heap_size(), 2 * size_of::<Tag>() + 4 + 2 * 4);
+
+    let mut blob = Blob::create("root");
+    let empty = blob.estimate_heap_size();
+    blob.insert("list", list);
+    assert!(blob.estimate_heap_size() > empty + 2 * size_of::<Tag>());
+}
+
+#[test]
+fn bound_blob_validation() {
+    use crate::{BoundBlob, BlobSchema, TagIdent, NBTError};
+
+    let schema = BlobSchema::new().field("id", TagIdent::TAG_Int);
+
+    let mut blob = Blob::new();
+    blob.insert("id", 1);
+    let mut bound = BoundBlob::bind(schema.clone(), blob).unwrap();
+
+    match bound.insert("id", "one") {
+        Err(NBTError::InvalidType { found, expecting, .. }) => {
+            assert_eq!(found, TagIdent::TAG_String);
+            assert_eq!(expecting, TagIdent::TAG_Int);
+        }
+        other => panic!("expected type error, got {:?}", other)
+    }
+    assert!(matches!(bound.insert("other", 1), Err(NBTError::UnknownKey { .. })));
+    assert_eq!(bound.get::<i32>("id"), Some(&1));
+
+    let mut open = BoundBlob::new(schema.allow_unknown(true), "");
+    assert!(open.insert("other", 1).is_ok());
+
+    let mut invalid = Blob::new();
+    invalid.insert("id", 1i64);
+    assert!(BoundBlob::bind(BlobSchema::new().field("id", TagIdent::TAG_Int), invalid).is_err());
+}
+
+#[test]
+fn migration_chain() {
+    use crate::{Migrations, NBTError};
+
+    let mut migrations = Migrations::new();
+    migrations
+        .register(1, 5, |blob| { blob.insert("a", 1); })
+        .register(3, 4, |blob| { blob.insert("b", 1); })
+        .register(5, 10, |blob| { blob.insert("c", 1); });
+
+    // Starts inside the first step's range, and takes the shortest hop.
+    let mut blob = Blob::new();
+    blob.insert("DataVersion", 3);
+    assert_eq!(migrations.migrate_to(&mut blob, 5).unwrap(), 5);
+    assert!(blob.get::<i32>("b").is_some());
+
+    // A target that falls between steps cannot be reached.
+    assert!(matches!(migrations.migrate_to(&mut blob, 7), Err(NBTError::NoMigration { from: 5, to: 7 })));
+    assert_eq!(migrations.migrate_to(&mut blob, 10).unwrap(), 10);
+
+    assert!(matches!(migrations.migrate_to(&mut Blob::new(), 10), Err(NBTError::MissingKey { .. })));
+}
+
+#[cfg(feature = "checksum")]
+#[test]
+fn digest_while_writing() {
+    use crate::Tag;
+
+    let (bytes, digest) = Tag::String("abc".to_string()).bytes_with_digest().unwrap();
+    assert_eq!(bytes, vec![0, 3, 97, 98, 99]);
+    assert_eq!(digest.crc32, crc32fast::hash(&bytes));
+
+    let (_, abc) = {
+        let mut writer = crate::DigestWriter::new(Vec::new());
+        std::io::Write::write_all(&mut writer, b"abc").unwrap();
+        writer.finish()
+    };
+    assert_eq!(abc.sha256[..4], [0xba, 0x78, 0x16, 0xbf]);
+}
+
+#[test]
+fn read_progress() {
+    use crate::{Blob, Tag, NBTRead, NBTWrite, ReadOptions, Progress};
+    use std::sync::{Arc, Mutex};
+
+    let mut blob = Blob::new();
+    blob.insert("list", Tag::List((0..10000).map(Tag::Int).collect()));
+    let bytes = blob.bytes().unwrap();
+
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let sink = reports.clone();
+    let options = ReadOptions::new().progress(move |progress| sink.lock().unwrap().push(progress));
+    Blob::from_bytes_with_options(&bytes, &options).unwrap();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 3);
+    assert_eq!(reports[0].entries, 4096);
+    assert_eq!(*reports.last().unwrap(), Progress { bytes: bytes.len() as u64, entries: 10001 });
+}
+
+#[test]
+fn cancel_read() {
+    use crate::{Blob, Tag, NBTRead, NBTWrite, NBTError, ReadOptions};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let mut blob = Blob::new();
+    blob.insert("list", Tag::List((0..10000).map(Tag::Int).collect()));
+    let bytes = blob.bytes().unwrap();
+
+    // Cancel part way through, from the progress callback.
+    let cancel = Arc::new(AtomicBool::new(false));
+    let flag = cancel.clone();
+    let options = ReadOptions::new()
+        .cancel_flag(cancel)
+        .progress(move |_| flag.store(true, Ordering::Relaxed));
+
+    assert!(matches!(Blob::from_bytes_with_options(&bytes, &options), Err(NBTError::Cancelled)));
+}
+
+#[cfg(feature = "vecmath")]
+#[test]
+fn vector_mappings() {
+    use crate::{Tag, ToTag, FromTag};
+    use glam::{DVec3, Vec2};
+
+    let pos = Tag::List(vec![Tag::Double(1.0), Tag::Double(64.0), Tag::Double(-3.5)]);
+    assert_eq!(<[f64; 3]>::from_tag(pos.clone()), Some([1.0, 64.0, -3.5]));
+    assert_eq!(DVec3::from_tag(pos.clone()), Some(DVec3::new(1.0, 64.0, -3.5)));
+    assert_eq!(DVec3::new(1.0, 64.0, -3.5).into_tag(), pos);
+
+    let rotation = Tag::List(vec![Tag::Float(90.0), Tag::Float(0.0)]);
+    assert_eq!(Vec2::from_tag(rotation.clone()), Some(Vec2::new(90.0, 0.0)));
+    assert_eq!(DVec3::from_tag(rotation.clone()), None);
+
+    // Vectors can't be borrowed from a blob, so they are read as copies.
+    let mut blob = crate::Blob::new();
+    blob.insert("Pos", pos.clone());
+    blob.insert("Rotation", rotation);
+    assert_eq!(blob.get::<DVec3>("Pos"), None);
+    assert_eq!(blob.get_owned::<DVec3>("Pos"), Some(DVec3::new(1.0, 64.0, -3.5)));
+    assert_eq!(blob.get_owned::<[f32; 2]>("Rotation"), Some([90.0, 0.0]));
+    assert_eq!(blob.get_owned::<DVec3>("Rotation"), None);
+    assert!(matches!(blob.try_get::<DVec3>("Pos"), Err(crate::NBTError::Custom(_))));
+    assert!(matches!(blob.try_get::<DVec3>("Rotation"), Err(crate::NBTError::InvalidType { .. })));
+
+    #[cfg(feature = "serde")]
+    {
+        let tag = crate::encode_tag(&DVec3::new(1.0, 64.0, -3.5)).unwrap().unwrap();
+        assert_eq!(tag, pos);
+        assert_eq!(crate::decode_tag::<DVec3>(pos).unwrap(), DVec3::new(1.0, 64.0, -3.5));
+    }
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn ffi_roundtrip() {
+    use crate::ffi::*;
+    use std::ffi::{CStr, CString};
+
+    // {"": {"a": 42b, "b": [1, 2]}}
+    let data = [10, 0, 0, 1, 0, 1, 97, 42, 9, 0, 1, 98, 3, 0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2, 0];
+    unsafe {
+        let root = nbt_parse(data.as_ptr(), data.len());
+        assert!(!root.is_null());
+
+        let snbt = nbt_to_snbt(root);
+        assert_eq!(CStr::from_ptr(snbt).to_str().unwrap(), "{a:42b,b:[1,2]}");
+        nbt_string_free(snbt);
+
+        let path = CString::new("b[1]").unwrap();
+        let element = nbt_get_path(root, path.as_ptr());
+        let snbt = nbt_to_snbt(element);
+        assert_eq!(CStr::from_ptr(snbt).to_str().unwrap(), "2");
+        nbt_string_free(snbt);
+        nbt_free(element);
+
+        let missing = CString::new("c").unwrap();
+        assert!(nbt_get_path(root, missing.as_ptr()).is_null());
+        assert!(nbt_parse(data.as_ptr(), 3).is_null());
+        nbt_free(root);
+    }
+}
+
+#[cfg(feature = "test_vectors")]
+#[test]
+fn reference_vectors() {
+    use crate::{Blob, NBTRead, NBTWrite};
+    use crate::test_vectors::*;
+
+    let blob = Blob::from_bytes(HELLO_WORLD).unwrap();
+    assert_eq!(blob.bytes().unwrap(), HELLO_WORLD);
+    assert_eq!(blob.root, hello_world().root);
+
+    let blob = Blob::from_bytes(BIGTEST).unwrap();
+    assert_eq!(blob.root, "Level");
+    assert_eq!(blob.compound(), bigtest().compound());
+}
+
+#[cfg(feature = "test_vectors")]
+#[test]
+fn bedrock_level_dat() {
+    use crate::{Blob, Tag, NBTRead, NBTWrite, BedrockFlavor};
+    use crate::test_vectors::*;
+    use byteorder::{ReadBytesExt, LittleEndian};
+
+    // The header holds the storage version and the length of the rest, both little endian.
+    let mut header = &BEDROCK_LEVEL_DAT[..8];
+    assert_eq!(header.read_i32::<LittleEndian>().unwrap(), 10);
+    assert_eq!(header.read_i32::<LittleEndian>().unwrap() as usize, BEDROCK_LEVEL_DAT.len() - 8);
+
+    let data = &BEDROCK_LEVEL_DAT[8..];
+    let blob = Blob::read_flavor::<BedrockFlavor, _>(&mut &data[..]).unwrap();
+    assert_eq!(blob.get::<f32>("rainLevel").map(|x| x.to_bits()), Some(0.3_f32.to_bits()));
+    assert_eq!(blob.compound_ref(), bedrock_level().compound_ref());
+    assert!(Blob::from_bytes(data).is_err());
+
+    let mut buffer = Vec::new();
+    blob.write_flavor::<BedrockFlavor, _>(&mut buffer).unwrap();
+    assert_eq!(buffer.len(), data.len());
+    assert_eq!(Blob::read_flavor::<BedrockFlavor, _>(&mut &buffer[..]).unwrap().compound_ref(), blob.compound_ref());
+
+    // Floats and doubles are little endian, with their bits kept exactly.
+    for (tag, bytes) in [
+        (Tag::Float(0.3), vec![5, 0x9a, 0x99, 0x99, 0x3e]),
+        (Tag::Double(0.3), vec![6, 0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0xd3, 0x3f]),
+        (Tag::Float(f32::from_bits(0x7fc0_0001)), vec![5, 0x01, 0x00, 0xc0, 0x7f]),
+    ] {
+        let mut buffer = Vec::new();
+        tag.write_flavor::<BedrockFlavor, _>(&mut buffer).unwrap();
+        assert_eq!(buffer, bytes[1..]);
+        assert_eq!(Tag::read_flavor::<BedrockFlavor, _>(&mut &bytes[..]).unwrap().bytes().unwrap(), tag.bytes().unwrap());
+    }
+
+    // Strings are plain UTF-8, so characters outside the basic multilingual plane take four bytes
+    // instead of a six byte surrogate pair.
+    assert!(data.windows(4).any(|x| x == [0xf0, 0x9f, 0x98, 0x80]));
+    let emoji = Tag::String("😀".to_string());
+    let mut buffer = Vec::new();
+    emoji.write_flavor::<BedrockFlavor, _>(&mut buffer).unwrap();
+    assert_eq!(buffer, vec![4, 0, 0xf0, 0x9f, 0x98, 0x80]);
+    assert_eq!(emoji.bytes().unwrap().len(), 8);
+}
+
+#[test]
+fn recursive_iteration() {
+    use crate::{Tag, NbtPath};
+
+    let mut tag = Tag::compound_from_iter(vec![
+        ("b", Tag::List(vec![Tag::Int(1), Tag::Int(2)])),
+        ("a", Tag::compound_from_iter(vec![("c", Tag::Byte(3))])),
+    ]);
+
+    let paths: Vec<String> = tag.iter_recursive().map(|(path, _)| path.to_string()).collect();
+    assert_eq!(paths, vec!["", "a", "a.c", "b", "b[0]", "b[1]"]);
+
+    let leaves: Vec<String> = tag.iter_recursive_mut().map(|(path, _)| path.to_string()).collect();
+    assert_eq!(leaves, vec!["a.c", "b[0]", "b[1]"]);
+
+    assert_eq!(NbtPath::parse("b[1]").unwrap().to_string(), "b[1]");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn transcode_json() {
+    use crate::{Tag, encode_tag, decode_tag};
+
+    let tag = Tag::compound_from_iter(vec![
+        ("id", Tag::String("minecraft:stone".to_string())),
+        ("Pos", Tag::List(vec![Tag::Double(0.5), Tag::Double(64.0)])),
+        ("Count", Tag::Long(64)),
+    ]);
+    let json = serde_json::to_string(&tag).unwrap();
+    assert_eq!(serde_json::from_str::<Tag>(&json).unwrap(), tag);
+
+    // Through the NBT serializer, tags map to themselves.
+    assert_eq!(encode_tag(&Tag::Short(3)).unwrap(), Some(Tag::Short(3)));
+    assert_eq!(decode_tag::<Tag>(tag.clone()).unwrap(), tag);
+
+    // Mixed sequences cannot become lists.
+    assert!(serde_json::from_str::<Tag>("[1, \"a\"]").is_err());
+}
+
+#[test]
+fn strings_of_varying_length() {
+    use crate::{Tag, NBTRead, NBTWrite};
+
+    // Strings share a scratch buffer while reading, so shorter strings must not see stale bytes.
+    let list = Tag::List(vec!["a long string", "", "short", "a"].into_iter().map(|x| Tag::String(x.to_string())).collect());
+    let mut bytes = vec![9];
+    bytes.extend(list.bytes().unwrap());
+    assert_eq!(Tag::from_bytes(bytes).unwrap(), list);
+}
+
+#[test]
+fn counted_read_write() {
+    use crate::{Blob, NBTRead, NBTWrite};
+
+    let mut blob = Blob::new();
+    blob.insert("id", 42);
+
+    let mut packet = Vec::new();
+    let written = blob.write_counted(&mut packet).unwrap();
+    packet.extend_from_slice(&[1, 2, 3]);
+
+    let mut reader = &packet[..];
+    let (read, count) = Blob::read_counted(&mut reader).unwrap();
+    assert_eq!(count, written);
+    assert_eq!(read.get::<i32>("id"), Some(&42));
+    assert_eq!(reader, &[1, 2, 3]);
+}
+
+#[test]
+fn rewind_failed_read() {
+    use crate::{Blob, NBTRead};
+    use std::io::{Cursor, Seek, SeekFrom};
+
+    // Truncated part way through the document.
+    let mut cursor = Cursor::new(vec![0xAA, 10, 0, 0, 1, 0, 1, 97]);
+    cursor.seek(SeekFrom::Start(1)).unwrap();
+
+    assert!(Blob::read_or_rewind(&mut cursor).is_err());
+    assert_eq!(cursor.position(), 1);
+}
+
+#[test]
+fn flat_dump() {
+    use crate::Tag;
+
+    let mut blob = Blob::create("Data");
+    blob.insert("Player", Tag::compound_from_iter(vec![
+        ("Pos", Tag::List(vec![Tag::Double(12.5)])),
+        ("Inventory", Tag::List(vec![])),
+        ("Flags", Tag::ByteArray(vec![1, 0])),
+    ]));
+    blob.insert("Version", 3);
+
+    assert_eq!(blob.dump_flat(), "\
+Player.Flags = [B;1b,0b]
+Player.Inventory = []
+Player.Pos[0] = 12.5d
+Version = 3
+");
+}
+
+#[test]
+fn named_tag_pair() {
+    use crate::{Tag, Blob, NBTRead};
+
+    let list = Tag::List(vec![Tag::Short(1), Tag::Short(2)]);
+    let mut buffer = Vec::new();
+    list.write_named(&mut buffer, "values").unwrap();
+
+    assert_eq!(Tag::read_named(&mut &buffer[..]).unwrap(), ("values".to_string(), list));
+    assert!(Blob::from_bytes(&buffer).is_err());
+    assert!(Tag::read_named(&mut &[0u8][..]).is_err());
+}
+
+#[test]
+fn raw_strings() {
+    use crate::raw::{read_string, write_string};
+
+    let mut buffer = Vec::new();
+    write_string(&mut buffer, "\0nul").unwrap();
+    // Modified UTF-8 encodes nul as two bytes.
+    assert_eq!(buffer, vec![0, 5, 0xC0, 0x80, 110, 117, 108]);
+    assert_eq!(read_string(&mut &buffer[..]).unwrap(), "\0nul");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_does_not_panic() {
+    use crate::{Tag, decode_tag};
+    use serde::Deserialize;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Item {
+        id: String,
+    }
+
+    // Unknown fields are ignored rather than panicking.
+    let tag = Tag::compound_from_iter(vec![
+        ("id", Tag::String("minecraft:stone".to_string())),
+        ("Count", Tag::Byte(1)),
+    ]);
+    assert_eq!(decode_tag::<Item>(tag).unwrap(), Item { id: "minecraft:stone".to_string() });
+
+    // Including nested compounds, lists and arrays.
+    let tag = Tag::compound_from_iter(vec![
+        ("id", Tag::String("minecraft:chest".to_string())),
+        ("UUID", Tag::IntArray(vec![1, 2, 3, 4])),
+        ("Items", Tag::List(vec![Tag::compound_from_iter(vec![("Slot", Tag::Byte(0))])])),
+        ("tag", Tag::compound_from_iter(vec![("display", Tag::compound_from_iter(vec![("Name", Tag::String("{}".to_string()))]))])),
+    ]);
+    assert_eq!(decode_tag::<Item>(tag).unwrap(), Item { id: "minecraft:chest".to_string() });
+
+    // A char is a single character, not a single byte.
+    assert_eq!(decode_tag::<char>(Tag::String("é".to_string())).unwrap(), 'é');
+    assert!(decode_tag::<char>(Tag::String("ab".to_string())).is_err());
+    assert!(decode_tag::<char>(Tag::String(String::new())).is_err());
+}
+
+#[test]
+fn capacity_hints() {
+    use crate::Compound;
+
+    let mut compound = Compound::with_capacity(16);
+    assert!(compound.capacity() >= 16);
+    compound.reserve(100);
+    assert!(compound.capacity() >= 100);
+
+    let mut blob = Blob::new();
+    blob.reserve(50);
+    assert!(blob.capacity() >= 50);
+}
+
+#[test]
+fn borrowed_compound() {
+    use crate::Tag;
+
+    let mut blob = Blob::create("root");
+    blob.compound_mut().insert("a".to_string(), Tag::Int(1));
+    assert_eq!(blob.compound_ref().get("a"), Some(&Tag::Int(1)));
+    assert_eq!(blob.root, "root");
+}
+
+#[test]
+fn boolean_helpers() {
+    use crate::{Compound, Tag};
+
+    let mut compound = Compound::new();
+    compound.insert_bool("flag", false);
+    assert_eq!(compound.elements.get("flag"), Some(&Tag::Byte(0)));
+    assert_eq!(compound.get_bool("flag"), Some(false));
+    assert_eq!(compound.get_bool("missing"), None);
+
+    compound.elements.insert("odd".to_string(), Tag::Byte(-1));
+    assert_eq!(compound.get_bool("odd"), None);
+    assert_eq!(compound.get_bool_lenient("odd"), Some(true));
+    assert_eq!(Tag::Int(1).as_bool_lenient(), None);
+}
+
+#[cfg(feature = "debug")]
+#[test]
+fn snbt_debug() {
+    use crate::Tag;
+
+    let tag = Tag::compound_from_iter(vec![
+        ("id", Tag::String("minecraft:stone".to_string())),
+        ("Count", Tag::Byte(1)),
+        ("Pos", Tag::List(vec![Tag::Double(0.5), Tag::Double(64.0)])),
+        ("Data", Tag::IntArray(vec![1, 2])),
+    ]);
+    assert_eq!(format!("{:?}", tag), r#"{Count:1b,Data:[I;1,2],Pos:[0.5d,64.0d],id:"minecraft:stone"}"#);
+    assert_eq!(format!("{:#?}", Tag::List(vec![tag.clone()])), r#"[
+    {
+        Count: 1b,
+        Data: [I; 1, 2],
+        Pos: [
+            0.5d,
+            64.0d
+        ],
+        id: "minecraft:stone"
+    }
+]"#);
+
+    let mut blob = Blob::create("hello world");
+    blob.insert("name", "Bananrama");
+    assert_eq!(format!("{:?}", blob), r#"Blob("hello world") {name:"Bananrama"}"#);
+}
+
+#[test]
+fn collection_read_write() {
+    use crate::{Compound, CompoundMap, Tag, NBTRead, NBTWrite};
+
+    let mut map = CompoundMap::default();
+    map.insert("a".to_string(), Tag::Byte(42));
+
+    let bytes = map.bytes().unwrap();
+    assert_eq!(bytes, vec![10, 0, 0, 1, 0, 1, 97, 42, 0]);
+    assert_eq!(CompoundMap::from_bytes(&bytes).unwrap(), map);
+
+    let compound = Compound::from_bytes(&bytes).unwrap();
+    assert_eq!(compound.bytes().unwrap(), bytes);
+}
+
+#[test]
+fn checked_list_access() {
+    use crate::{Tag, TagIdent, NBTError};
+
+    let list = Tag::List(vec![Tag::String("a".to_string())]);
+    assert_eq!(list.get_index_as::<String>(0).unwrap(), "a");
+    assert!(matches!(list.get_index_as::<i32>(0), Err(NBTError::InvalidType { found: TagIdent::TAG_String, expecting: TagIdent::TAG_Int, .. })));
+    assert!(matches!(Tag::Int(0).get_index(0), Err(NBTError::InvalidType { found: TagIdent::TAG_Int, expecting: TagIdent::TAG_List, .. })));
+}
+
+#[test]
+fn bedrock_flavor() {
+    use crate::{Blob, Tag, NBTRead, NBTWrite, NBTError, BedrockFlavor};
+
+    let mut blob = Blob::new();
+    blob.insert("value", Tag::Short(0x0102));
+
+    let mut buffer = Vec::new();
+    blob.write_flavor::<BedrockFlavor, _>(&mut buffer).unwrap();
+    assert_eq!(buffer, vec![10, 0, 0, 2, 5, 0, 118, 97, 108, 117, 101, 2, 1, 0]);
+    assert_eq!(Blob::read_flavor::<BedrockFlavor, _>(&mut &buffer[..]).unwrap().get::<i16>("value"), Some(&0x0102));
+
+    let long = Tag::String("a".repeat(u16::MAX as usize + 1));
+    assert!(matches!(long.bytes(), Err(NBTError::StringTooLong { length: 65536 })));
+}
+
+#[test]
+fn lenient_eof() {
+    use crate::{Blob, NBTRead, NBTError, ReadOptions, Diagnostic, Diagnostics};
+
+    let truncated = vec![10, 0, 0, 10, 0, 1, 99, 1, 0, 1, 97, 42, 0];
+    assert!(matches!(Blob::from_bytes(&truncated), Err(NBTError::IO { .. })));
+
+    let options = ReadOptions::new().lenient_eof(true);
+    let mut diagnostics = Diagnostics::new();
+    let blob = Blob::from_bytes_with(&truncated, &options, Some(&mut diagnostics)).unwrap();
+    assert!(blob.compound_ref().contains_key("c"));
+    assert_eq!(diagnostics.into_vec(), vec![Diagnostic::MissingEndTag]);
+
+    // Only the root compound may be left open.
+    assert!(Blob::from_bytes_with_options(&truncated[..12], &options).is_err());
+}
+
+#[test]
+fn mixed_list_recovery() {
+    use crate::{Tag, NBTWrite, NBTError, WriteOptions, NormalizeOptions, CompoundMap};
+
+    let numbers = Tag::List(vec![Tag::Short(1), Tag::Long(2), Tag::Float(0.5)]);
+    assert!(matches!(numbers.bytes(), Err(NBTError::InvalidList { .. })));
+
+    let mut normalized = numbers.clone();
+    normalized.normalize(&NormalizeOptions::new().mixed_lists(true));
+    assert_eq!(normalized, Tag::List(vec![Tag::Double(1.0), Tag::Double(2.0), Tag::Double(0.5)]));
+
+    let mut buffer = Vec::new();
+    numbers.write_with(&mut buffer, &WriteOptions::new().mixed_lists(true)).unwrap();
+    assert_eq!(buffer, normalized.bytes().unwrap());
+
+    let mut mixed = Tag::List(vec![Tag::Int(1), Tag::String("a".to_string())]);
+    mixed.normalize(&NormalizeOptions::new().mixed_lists(true));
+    let wrapped: Vec<Tag> = [Tag::Int(1), Tag::String("a".to_string())].iter().map(|item| {
+        let mut map = CompoundMap::default();
+        map.insert(String::new(), item.clone());
+        Tag::Compound(map)
+    }).collect();
+    assert_eq!(mixed, Tag::List(wrapped));
+}
+
+#[test]
+fn checked_list_constructor() {
+    use crate::{Tag, TagIdent, NBTError};
+
+    assert_eq!(Tag::try_list(Vec::new()).unwrap(), Tag::List(Vec::new()));
+    assert!(matches!(
+        Tag::try_list(vec![Tag::Byte(1), Tag::Byte(2), Tag::Short(3)]),
+        Err(NBTError::InvalidList { found: TagIdent::TAG_Short, expecting: TagIdent::TAG_Byte })
+    ));
+    assert_eq!(Tag::list_unchecked(vec![Tag::Byte(1), Tag::Short(3)]).ident(), TagIdent::TAG_List);
+}
+
+#[test]
+fn tag_total_order() {
+    use crate::{Tag, CompoundMap};
+    use std::collections::BTreeMap;
+
+    assert!(Tag::Double(-0.0) < Tag::Double(0.0));
+    assert_ne!(Tag::Float(-0.0), Tag::Float(0.0));
+    assert_eq!(Tag::Double(f64::NAN), Tag::Double(f64::NAN));
+    assert!(Tag::Long(i64::MAX) < Tag::Float(f32::MIN));
+    assert!(Tag::List(vec![Tag::Int(1)]) < Tag::List(vec![Tag::Int(1), Tag::Int(0)]));
+
+    let compound = |entries: &[(&str, i32)]| Tag::Compound(entries.iter().map(|(k, v)| (k.to_string(), Tag::Int(*v))).collect::<CompoundMap>());
+    assert!(compound(&[("b", 0), ("a", 1)]) < compound(&[("a", 2)]));
+    assert!(compound(&[("a", 1)]) < compound(&[("a", 1), ("b", 0)]));
+
+    let mut map = BTreeMap::new();
+    map.insert(Tag::String("b".to_string()), 2);
+    map.insert(Tag::String("a".to_string()), 1);
+    assert_eq!(map.values().copied().collect::<Vec<_>>(), vec![1, 2]);
+}
+
+#[cfg(feature="debug")]
+#[test]
+fn snbt_exact_floats() {
+    use crate::Tag;
+
+    let floats = [0.1f32, -0.0, 16777216.0, 3.4028235e38, f32::MIN_POSITIVE, 1e-45, f32::INFINITY, f32::NEG_INFINITY];
+    for x in floats.iter() {
+        let snbt = format!("{:?}", Tag::Float(*x));
+        let parsed: f32 = snbt.strip_suffix('f').unwrap().parse().unwrap();
+        assert_eq!(parsed.to_bits(), x.to_bits(), "{}", snbt);
+    }
+
+    let doubles = [0.1f64 + 0.2, -0.0, 1e300, 5e-324, 9007199254740993.0, f64::INFINITY];
+    for x in doubles.iter() {
+        let snbt = format!("{:?}", Tag::Double(*x));
+        let parsed: f64 = snbt.strip_suffix('d').unwrap().parse().unwrap();
+        assert_eq!(parsed.to_bits(), x.to_bits(), "{}", snbt);
+    }
+
+    assert_eq!(format!("{:?}", Tag::Float(f32::NEG_INFINITY)), "-1e39f");
+    assert_eq!(format!("{:?}", Tag::Double(f64::NAN)), "NaNd");
+}
+
+#[test]
+fn dyn_reader_writer() {
+    use crate::{Blob, NBTRead, NBTWrite};
+    use std::io::{Read, Write, Cursor};
+
+    let mut blob = Blob::create("root");
+    blob.insert("id", 42);
+
+    let mut buffer = Vec::new();
+    let writer: &mut dyn Write = &mut buffer;
+    blob.write(writer).unwrap();
+
+    let mut cursor = Cursor::new(buffer);
+    let reader: &mut dyn Read = &mut cursor;
+    let (read, count) = Blob::read_counted(reader).unwrap();
+    assert_eq!(read.root, "root");
+    assert_eq!(read.get::<i32>("id"), Some(&42));
+    assert_eq!(count, cursor.get_ref().len());
+}
+
+#[cfg(feature="macros")]
+#[test]
+fn snbt_macro() {
+    use crate::{snbt, Tag, CompoundMap};
+
+    let tag = snbt!(r#"{
+        id: "minecraft:stone",
+        Count: 1b,
+        'display name': 'it\'s',
+        flags: [true, false],
+        pos: [0.5d, -1e2, 3.0],
+        scale: NaNf,
+        longs: [L; 1L, -2L],
+        plain: bare_word,
+        empty: []
+    }"#);
+
+    let mut map = CompoundMap::default();
+    map.insert("id".to_string(), Tag::String("minecraft:stone".to_string()));
+    map.insert("Count".to_string(), Tag::Byte(1));
+    map.insert("display name".to_string(), Tag::String("it's".to_string()));
+    map.insert("flags".to_string(), Tag::List(vec![Tag::Byte(1), Tag::Byte(0)]));
+    map.insert("pos".to_string(), Tag::List(vec![Tag::Double(0.5), Tag::Double(-100.0), Tag::Double(3.0)]));
+    map.insert("scale".to_string(), Tag::Float(f32::NAN));
+    map.insert("longs".to_string(), Tag::LongArray(vec![1, -2]));
+    map.insert("plain".to_string(), Tag::String("bare_word".to_string()));
+    map.insert("empty".to_string(), Tag::List(Vec::new()));
+    assert_eq!(tag, Tag::Compound(map));
+}
+
+#[cfg(feature="macros")]
+#[test]
+fn snbt_macro_parity() {
+    use crate::snbt;
+
+    // The macro and the runtime parser are separate copies, so check they agree on the edge cases.
+    macro_rules! same {
+        ($($input:literal),* $(,)?) => {
+            $(assert_eq!(snbt!($input), snbt::from_str($input).unwrap(), "{}", $input);)*
+        };
+    }
+    same!(
+        "1b", "-128B", "128b", "2s", "3", "+4", "-5L", "2147483648", "1.5f", "-0.0d", "1e3", "1E-3F",
+        ".5", "5.", "1e", "1.2.3", "e5", "NaN", "NaNf", "-NaNd", "1e39f", "true", "false", "TRUE",
+        "bare_word", "minecraft.stone_1", "\"quoted \\\" string\"", "'single \\' quote'", "\"é 😀\"",
+        "[]", "[ 1 , 2 ]", "[B;]", "[B; 1b, -2b]", "[I;1,2]", "[L; 1L]", "[[1], [2b], []]",
+        "{}", "{ a : 1 , 'b c' : {d: [1.5f]} }", "{\"\": x}",
+    );
+}
+
+#[cfg(feature="base64")]
+#[test]
+fn base64_blobs() {
+    use crate::{Blob, Compression, NBTError};
+
+    let mut blob = Blob::create("item");
+    blob.insert("Count", 64i8);
+
+    for compression in [Compression::Gzip, Compression::Zlib] {
+        let text = blob.to_base64(compression).unwrap();
+        let decoded = Blob::from_base64(&format!("{}\n", text), compression).unwrap();
+        assert_eq!(decoded.root, "item");
+        assert_eq!(decoded.get::<i8>("Count"), Some(&64));
+    }
+    assert!(matches!(Blob::from_base64("not base64!", Compression::Gzip), Err(NBTError::InvalidBase64)));
+}
+
+#[test]
+fn seeded_generator() {
+    use crate::{TagGenerator, TagIdent, Tag, Blob, NBTRead, NBTWrite};
+
+    let mut a = TagGenerator::new(7);
+    let mut b = TagGenerator::new(7);
+    for _ in 0..16 {
+        let tag = a.generate();
+        assert_eq!(tag, b.generate());
+        let mut buffer = vec![tag.ident() as u8];
+        tag.write(&mut buffer).unwrap();
+        assert_eq!(Tag::from_bytes(&buffer).unwrap(), tag);
+        let blob = a.generate_blob();
+        assert_eq!(Blob::from_bytes(blob.bytes().unwrap()).unwrap().compound(), b.generate_blob().compound());
+    }
+    assert_ne!(TagGenerator::new(1).generate_blob().compound(), TagGenerator::new(2).generate_blob().compound());
+
+    let mut bytes_only = TagGenerator::new(3).max_depth(1).allowed(&[TagIdent::TAG_Byte, TagIdent::TAG_List]);
+    for _ in 0..16 {
+        for (_, tag) in bytes_only.generate().iter_recursive() {
+            assert!(matches!(tag, Tag::Byte(_) | Tag::List(_)));
+        }
+    }
+}
+
+#[test]
+fn read_stats() {
+    use crate::{Blob, Tag, TagIdent, NBTRead, NBTWrite, Diagnostics};
+
+    let mut blob = Blob::new();
+    blob.insert("list", Tag::List(vec![Tag::List(vec![Tag::Byte(1), Tag::Byte(2), Tag::Byte(3)])]));
+    blob.insert("bytes", Tag::ByteArray(vec![0; 5]));
+    blob.insert("name", "x");
+    let bytes = blob.bytes().unwrap();
+
+    let mut diagnostics = Diagnostics::new();
+    Blob::from_bytes_with_diagnostics(&bytes, &mut diagnostics).unwrap();
+    let stats = diagnostics.stats();
+    assert_eq!(stats.count(TagIdent::TAG_Compound), 1);
+    assert_eq!(stats.count(TagIdent::TAG_List), 2);
+    assert_eq!(stats.count(TagIdent::TAG_Byte), 3);
+    assert_eq!(stats.total(), 8);
+    assert_eq!(stats.max_depth, 3);
+    assert_eq!(stats.largest_list, 3);
+    assert_eq!(stats.largest_array, 5);
+    assert_eq!(stats.bytes, bytes.len() as u64);
+}
+
+#[test]
+fn encode_to_slice() {
+    use crate::{Blob, NBTWrite, NBTError};
+
+    let mut blob = Blob::new();
+    blob.insert("name", "Bananrama");
+    let bytes = blob.bytes().unwrap();
+
+    let mut buffer = vec![0xFFu8; bytes.len() + 3];
+    assert_eq!(blob.encode_to_slice(&mut buffer).unwrap(), bytes.len());
+    assert_eq!(&buffer[..bytes.len()], &bytes[..]);
+    assert_eq!(blob.encode_to_slice(&mut buffer[..bytes.len()]).unwrap(), bytes.len());
+
+    let small = bytes.len() - 1;
+    assert!(matches!(blob.encode_to_slice(&mut buffer[..small]), Err(NBTError::BufferTooSmall { capacity }) if capacity == small));
+}
+
+#[cfg(feature="serde-de")]
+#[test]
+fn decode_expect_named() {
+    use crate::{Blob, NBTError, decode_expect_named};
+    use std::collections::HashMap;
+
+    let mut blob = Blob::create("Level");
+    blob.insert("version", 3);
+
+    let data: HashMap<String, i32> = decode_expect_named(blob.clone(), "Level").unwrap();
+    assert_eq!(data["version"], 3);
+
+    match decode_expect_named::<HashMap<String, i32>>(blob, "Player") {
+        Err(NBTError::UnexpectedRootName { expected, found }) => {
+            assert_eq!(expected, "Player");
+            assert_eq!(found, "Level");
+        }
+        _ => panic!("expected a root name error")
+    }
+}
+
+#[cfg(feature="compression")]
+#[test]
+fn raw_deflate() {
+    use crate::{Blob, Compression, NBTRead, NBTWrite};
+    use crate::compression::{compress, decompress};
+
+    let mut blob = Blob::new();
+    blob.insert("name", "Bananrama");
+    let bytes = blob.bytes().unwrap();
+
+    let deflated = compress(&bytes, Compression::Deflate).unwrap();
+    assert_ne!(deflated[..2], compress(&bytes, Compression::Zlib).unwrap()[..2]);
+    let inflated = decompress(&deflated, Compression::Deflate).unwrap();
+    assert_eq!(Blob::from_bytes(inflated).unwrap().get::<String>("name").unwrap(), "Bananrama");
+
+    // Data without a header that fails to read as uncompressed is tried as raw DEFLATE, keeping
+    // the first error if that fails too.
+    assert_eq!(crate::compression::detect(&deflated), None);
+    assert_eq!(Blob::read_auto(&deflated[..]).unwrap().get::<String>("name").unwrap(), "Bananrama");
+    assert_eq!(Blob::read_auto(&bytes[..]).unwrap().get::<String>("name").unwrap(), "Bananrama");
+    let tag = compress(&crate::Tag::Int(7).bytes().map(|x| [&[3][..], &x].concat()).unwrap(), Compression::Deflate).unwrap();
+    assert_eq!(crate::Tag::read_auto(&tag[..]).unwrap(), crate::Tag::Int(7));
+    assert!(matches!(Blob::read_auto(&[5, 0, 0][..]), Err(crate::NBTError::InvalidImplicit { .. })));
+
+    let path = std::env::temp_dir().join(format!("luna_nbt_raw_deflate_{}.dat", std::process::id()));
+    std::fs::write(&path, &deflated).unwrap();
+    let read = Blob::from_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    let read = read.unwrap();
+    assert_eq!(read.get::<String>("name").unwrap(), "Bananrama");
+    assert_eq!(read.source().unwrap().compression, Some(Compression::Deflate));
+}
+
+#[cfg(feature="region")]
+#[test]
+fn region_rejects_raw_deflate() {
+    use crate::{Blob, Compression, NBTError};
+    use crate::region::{RegionChunk, write_region};
+
+    let chunks = vec![RegionChunk::new(0, 0, Blob::new())];
+    assert!(matches!(write_region(&mut Vec::new(), &chunks, Compression::Deflate), Err(NBTError::UnsupportedCompression { compression: Compression::Deflate })));
+}
+
+#[cfg(feature="rayon")]
+#[test]
+fn compound_par_iter() {
+    use crate::{Compound, Tag};
+    use rayon::prelude::*;
+
+    let mut compound = Compound::with_capacity(1000);
+    for i in 0..1000 {
+        compound.elements.insert(format!("entry{}", i), Tag::Int(i));
+    }
+
+    compound.par_iter_mut().for_each(|(_, tag)| if let Tag::Int(x) = tag { *x *= 2 });
+    assert_eq!(compound.par_iter().filter(|(_, tag)| matches!(tag, Tag::Int(x) if x % 2 == 0)).count(), 1000);
+    assert_eq!(compound.elements["entry21"], Tag::Int(42));
+}
+
+#[test]
+fn unsigned_byte_array_storage() {
+    use crate::{ReadOptions, Tag};
+
+    let mut blob = Blob::new();
+    blob.insert("signed", vec![-1i8, 0, 1]);
+    blob.insert("unsigned", vec![255u8, 0, 1]);
+    let data = blob.bytes().unwrap();
+
+    let signed = Blob::from_bytes(&data).unwrap();
+    assert_eq!(signed.compound_ref()["signed"], signed.compound_ref()["unsigned"]);
+    assert!(matches!(signed.compound_ref()["unsigned"], Tag::ByteArray(_)));
+
+    let options = ReadOptions::new().unsigned_byte_arrays(true);
+    let unsigned = Blob::from_bytes_with_options(&data, &options).unwrap();
+    assert!(matches!(&unsigned.compound_ref()["signed"], Tag::UnsignedByteArray(x) if x == &[255, 0, 1]));
+    assert_eq!(unsigned.compound_ref(), signed.compound_ref());
+    assert_eq!(Blob::from_bytes(unsigned.bytes().unwrap()).unwrap().compound_ref(), signed.compound_ref());
+
+    // A truncated array still fails.
+    assert!(Blob::from_bytes_with_options(&data[..data.len() - 12], &options).is_err());
+
+    // Equal tags serialize the same, and normalize to the signed form.
+    let mut tag = Tag::UnsignedByteArray(vec![255, 0, 1]);
+    #[cfg(feature="serde-ser")]
+    assert_eq!(serde_json::to_string(&tag).unwrap(), serde_json::to_string(&Tag::ByteArray(vec![-1, 0, 1])).unwrap());
+    tag.normalize(&crate::NormalizeOptions::new());
+    assert!(matches!(&tag, Tag::ByteArray(x) if x == &[-1, 0, 1]));
+
+    let generated = crate::TagGenerator::new(3).unsigned_byte_arrays(true).allowed(&[crate::TagIdent::TAG_Byte_Array]).generate();
+    assert!(matches!(generated, Tag::UnsignedByteArray(_)));
+    assert_eq!(generated, crate::TagGenerator::new(3).allowed(&[crate::TagIdent::TAG_Byte_Array]).generate());
+}
+
+#[test]
+fn save_same_format() {
+    use crate::{BedrockFlavor, Dialect};
+
+    let dir = std::env::temp_dir().join(format!("luna_nbt_save_same_format_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut blob = Blob::create("level");
+    blob.insert("version", 1);
+
+    // Bedrock data is detected, and written back as Bedrock.
+    let path = dir.join("bedrock.dat");
+    let mut data = Vec::new();
+    blob.write_flavor::<BedrockFlavor, _>(&mut data).unwrap();
+    std::fs::write(&path, &data).unwrap();
+
+    let mut read = Blob::from_file(&path).unwrap();
+    assert_eq!(read.source().map(|x| x.dialect), Some(Dialect::Bedrock));
+    read.insert("edited", 1i8);
+    read.save_same_format(&path).unwrap();
+    let read = Blob::read_flavor::<BedrockFlavor, _>(&mut &std::fs::read(&path).unwrap()[..]).unwrap();
+    assert_eq!(read.get::<i8>("edited"), Some(&1));
+
+    // The format can be chosen for blobs built in memory.
+    let mut created = blob.clone();
+    assert!(created.source().is_none());
+    created.set_source(Some(crate::SourceFormat::new(Dialect::Bedrock)));
+    created.save_same_format(&path).unwrap();
+    assert_eq!(Blob::from_file(&path).unwrap().source().map(|x| x.dialect), Some(Dialect::Bedrock));
+
+    // Compressed files stay compressed.
+    #[cfg(feature="compression")]
+    {
+        use crate::Compression;
+        use crate::compression::{compress, decompress};
+
+        let path = dir.join("level.dat");
+        std::fs::write(&path, compress(&blob.bytes().unwrap(), Compression::Gzip).unwrap()).unwrap();
+
+        let read = Blob::from_file(&path).unwrap();
+        assert_eq!(read.source().and_then(|x| x.compression), Some(Compression::Gzip));
+        assert_eq!(read.source().map(|x| x.dialect), Some(Dialect::Java));
+        read.save_same_format(&path).unwrap();
+        let data = decompress(&std::fs::read(&path).unwrap(), Compression::Gzip).unwrap();
+        assert_eq!(Blob::from_bytes(data).unwrap().get::<i32>("version"), Some(&1));
+
+        // As do blobs read from compressed streams.
+        let compressed = compress(&blob.bytes().unwrap(), Compression::Zlib).unwrap();
+        for read in [Blob::read_zlib(&compressed[..]).unwrap(), Blob::read_auto(&compressed[..]).unwrap()] {
+            assert_eq!(read.source().and_then(|x| x.compression), Some(Compression::Zlib));
+        }
+        assert_eq!(Blob::read_auto(&blob.bytes().unwrap()[..]).unwrap().source().and_then(|x| x.compression), None);
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn packed_lists() {
+    use crate::{PackedList, Tag, TagIdent, ToTag};
+
+    let doubles: Vec<Tag> = (0..100).map(|x| Tag::Double(x as f64)).collect();
+    let packed = PackedList::from(doubles.clone());
+    assert!(matches!(packed, PackedList::Double(_)));
+    assert_eq!(packed.len(), 100);
+    assert_eq!(packed.element_ident(), TagIdent::TAG_Double);
+    assert!(packed.estimate_heap_size() * 3 < Tag::List(doubles.clone()).estimate_heap_size());
+    assert_eq!(packed.into_tag(), Tag::List(doubles));
+
+    // Mixed, non-numeric and empty lists are kept as tags.
+    assert!(matches!(PackedList::from(vec![Tag::Int(1), Tag::Long(2)]), PackedList::Tags(_)));
+    assert!(matches!(PackedList::from(vec![Tag::String("a".to_string())]), PackedList::Tags(_)));
+    let empty = PackedList::from(Vec::new());
+    assert!(empty.is_empty());
+    assert_eq!(empty.element_ident(), TagIdent::TAG_End);
+}
+
+#[test]
+fn bulk_array_writes() {
+    use crate::{Tag, BedrockFlavor};
+    use std::io::Write;
+
+    // Counts the writes made to it.
+    struct Writes(Vec<u8>, usize);
+    impl Write for Writes {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.1 += 1;
+            self.0.write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+    }
+
+    let ints = Tag::IntArray((0..10_000).collect());
+    let mut writer = Writes(Vec::new(), 0);
+    ints.write(&mut writer).unwrap();
+    assert!(writer.1 < 20);
+    assert_eq!(&writer.0[..12], &[0, 0, 0x27, 0x10, 0, 0, 0, 0, 0, 0, 0, 1]);
+    assert_eq!(writer.0.len(), 4 + 4 * 10_000);
+    assert_eq!(&writer.0[writer.0.len() - 4..], &[0, 0, 0x27, 0x0f]);
+
+    let longs = Tag::LongArray(vec![1, -1]);
+    let mut buffer = Vec::new();
+    longs.write_flavor::<BedrockFlavor, _>(&mut buffer).unwrap();
+    assert_eq!(buffer, vec![2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 255, 255, 255, 255, 255, 255, 255, 255]);
+}
+
+#[test]
+fn typed_inserts() {
+    use crate::{Compound, NBTError, Tag, TagIdent};
+
+    let mut compound = Compound::new();
+    assert_eq!(compound.insert_typed("Health", 20.0f32).unwrap(), None);
+    assert_eq!(compound.insert_typed("Health", 15.5f32).unwrap(), Some(Tag::Float(20.0)));
+
+    let error = compound.insert_typed("Health", 10.0f64).unwrap_err();
+    assert!(matches!(error, NBTError::InvalidType { found: TagIdent::TAG_Double, expecting: TagIdent::TAG_Float, .. }));
+    assert_eq!(compound.elements["Health"], Tag::Float(15.5));
+
+    // Byte arrays keep their type whichever storage they use.
+    compound.insert_typed("Data", vec![1i8]).unwrap();
+    assert!(compound.insert_typed("Data", vec![255u8]).is_ok());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn unknown_enum_variants() {
+    use crate::{Tag, encode_tag, decode_tag};
+    use serde::{Serialize, Deserialize};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    enum Difficulty {
+        Peaceful,
+        Hard,
+        #[serde(other)]
+        Unknown,
+    }
+
+    // Unit variants round trip through their names.
+    let tag = encode_tag(&Difficulty::Hard).unwrap().unwrap();
+    assert_eq!(tag, Tag::String("Hard".to_string()));
+    assert_eq!(decode_tag::<Difficulty>(tag).unwrap(), Difficulty::Hard);
+
+    // Names from newer versions fall back to the `other` variant, with or without content.
+    assert_eq!(decode_tag::<Difficulty>(Tag::String("Nightmare".to_string())).unwrap(), Difficulty::Unknown);
+    let tagged = Tag::compound_from_iter(vec![("Nightmare", Tag::Int(3))]);
+    assert_eq!(decode_tag::<Difficulty>(tagged).unwrap(), Difficulty::Unknown);
+}
+
+#[cfg(feature="zstd")]
+#[test]
+fn zstd_dictionary() {
+    use crate::{Compression, ZstdDictionary, Tag};
+    use crate::compression::{compress, decompress, detect};
+
+    let chunks: Vec<Blob> = (0..300).map(|x| {
+        let mut blob = Blob::new();
+        blob.insert("xPos", x);
+        blob.insert("zPos", -x);
+        blob.insert("Status", "minecraft:full");
+        blob.insert("Heightmaps", Tag::LongArray(vec![0x0101_0101_0101_0101; 37]));
+        blob
+    }).collect();
+
+    let dictionary = ZstdDictionary::train_blobs(&chunks, 8192).unwrap().level(3);
+    let shared = dictionary.compress_blob(&chunks[42]).unwrap();
+    let alone = compress(&chunks[42].bytes().unwrap(), Compression::Zstd).unwrap();
+    assert!(shared.len() < alone.len());
+
+    // A dictionary restored from its bytes reads the payloads.
+    let restored = ZstdDictionary::from_bytes(dictionary.as_bytes().to_vec());
+    assert_eq!(restored.decompress_blob(&shared).unwrap().get::<i32>("zPos"), Some(&-42));
+
+    assert_eq!(detect(&alone), Some(Compression::Zstd));
+    assert_eq!(decompress(&alone, Compression::Zstd).unwrap(), chunks[42].bytes().unwrap());
+}
+
+#[cfg(feature="text")]
+#[test]
+fn text_components() {
+    use crate::{text, Tag};
+    use serde_json::json;
+
+    // Plain components stay strings, and arrays become a component with extras.
+    assert_eq!(text::from_json(&json!("hi")).unwrap(), Tag::String("hi".to_string()));
+    let tag = text::from_json_str(r#"["a", {"text": "b", "italic": false}, 3]"#).unwrap();
+    assert_eq!(text::to_json(&tag).unwrap(), json!({
+        "text": "a", "extra": [{"text": "b", "italic": false}, {"text": "3"}]
+    }));
+
+    // Other mixed lists are wrapped, and unwrapped again.
+    let tag = text::from_json(&json!({"translate": "x", "with": ["a", "b"], "data": [1, "two"]})).unwrap();
+    assert!(matches!(&tag, Tag::Compound(map) if map["with"] == Tag::List(vec![Tag::String("a".to_string()), Tag::String("b".to_string())])));
+    assert_eq!(text::to_json(&tag).unwrap()["data"], json!([1, "two"]));
+
+    assert!(text::from_json(&json!({"text": null})).is_err());
+}
+
+#[test]
+fn read_depth_limit() {
+    use crate::{Blob, Tag, NBTRead, NBTError, ReadOptions, Diagnostics};
+
+    // A root compound holding `depth - 1` levels of lists, each with one list in it.
+    fn nested(depth: usize) -> Vec<u8> {
+        let mut data = vec![10, 0, 0, 9, 0, 1, 97];
+        for _ in 2..depth {
+            data.extend_from_slice(&[9, 0, 0, 0, 1]);
+        }
+        data.extend_from_slice(&[1, 0, 0, 0, 0, 0]);
+        data
+    }
+
+    assert!(Blob::from_bytes(nested(512)).is_ok());
+    assert!(matches!(Blob::from_bytes(nested(513)), Err(NBTError::DepthLimitExceeded { limit: 512 })));
+
+    // Compounds count the same way, and reading up to the limit fits in a test thread's stack.
+    let mut compounds = vec![10, 0, 0];
+    for _ in 1..512 {
+        compounds.extend_from_slice(&[10, 0, 1, 97]);
+    }
+    compounds.extend(std::iter::repeat_n(0, 512));
+    let mut diagnostics = Diagnostics::new();
+    assert!(Blob::read_with(&mut &compounds[..], &ReadOptions::new(), Some(&mut diagnostics)).is_ok());
+    assert_eq!(diagnostics.stats.max_depth, 512);
+
+    // Hostile input is rejected rather than overflowing the stack.
+    let deep = nested(200_000);
+    assert!(matches!(Blob::from_bytes(&deep), Err(NBTError::DepthLimitExceeded { limit: 512 })));
+    let unnamed = [&[10][..], &deep[3..]].concat();
+    assert!(matches!(Tag::read(&mut &unnamed[..]), Err(NBTError::DepthLimitExceeded { .. })));
+    assert!(matches!(crate::verify(&deep), Err(NBTError::DepthLimitExceeded { .. })));
+
+    let options = ReadOptions::new().max_depth(3);
+    assert!(Blob::from_bytes_with_options(nested(3), &options).is_ok());
+    assert!(matches!(Blob::from_bytes_with_options(nested(4), &options), Err(NBTError::DepthLimitExceeded { limit: 3 })));
+}
+
+#[test]
+fn streaming_validation() {
+    use crate::{validate, ValidateLimits, NBTError, Tag, TagIdent};
+
+    let mut blob = Blob::new();
+    blob.insert("nested", Tag::List(vec![Tag::List(vec![Tag::Int(1)])]));
+    blob.insert("data", Tag::LongArray(vec![1; 100]));
+    let data = blob.bytes().unwrap();
+    let limits = ValidateLimits::new();
+    assert!(validate(&mut &data[..], &limits).is_ok());
+
+    // The root compound and two lists are three levels deep.
+    assert!(validate(&mut &data[..], &ValidateLimits::new().max_depth(3)).is_ok());
+    assert!(matches!(validate(&mut &data[..], &ValidateLimits::new().max_depth(2)), Err(NBTError::DepthLimitExceeded { limit: 2 })));
+
+    // Truncated arrays, unknown ids, non-empty lists of end tags and invalid strings are rejected.
+    assert!(matches!(validate(&mut &data[..data.len() - 20], &limits), Err(NBTError::IO { .. })));
+    assert!(matches!(validate(&mut &[10, 0, 0, 13, 0][..], &limits), Err(NBTError::InvalidTag { found: 13 })));
+    assert!(matches!(validate(&mut &[10, 0, 0, 9, 0, 0, 0, 0, 0, 0, 1, 0][..], &limits), Err(NBTError::UnexpectedEndTag)));
+    assert!(matches!(validate(&mut &[10, 0, 0, 8, 0, 0, 0, 1, 0xff, 0][..], &limits), Err(NBTError::StringError)));
+    assert!(matches!(validate(&mut &[1, 0, 0, 5][..], &limits), Err(NBTError::InvalidImplicit { found: TagIdent::TAG_Byte })));
+}
+
+#[cfg(feature="compression")]
+#[test]
+fn compressed_blobs() {
+    use crate::Compression;
+    use std::fs::File;
+
+    let mut blob = Blob::create("Data");
+    blob.insert("DataVersion", 2586);
+
+    let mut gzip = Vec::new();
+    blob.write_gzip(&mut gzip).unwrap();
+    assert_eq!(Blob::read_gzip(&gzip[..]).unwrap().get::<i32>("DataVersion"), Some(&2586));
+
+    let mut zlib = Vec::new();
+    blob.write_zlib(&mut zlib).unwrap();
+    assert_eq!(zlib[0], 0x78);
+    assert_eq!(Blob::read_zlib(&zlib[..]).unwrap().root, "Data");
+
+    // Reading from a file works directly.
+    let path = std::env::temp_dir().join(format!("luna_nbt_compressed_blobs_{}.dat", std::process::id()));
+    blob.write_compressed(&mut File::create(&path).unwrap(), Compression::Gzip).unwrap();
+    let read = Blob::read_compressed(File::open(&path).unwrap(), Compression::Gzip).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(read.compound_ref(), blob.compound_ref());
+
+    assert!(Blob::read_zlib(&gzip[..]).is_err());
+
+    // Zstd is always a variant, and fails clearly without its feature.
+    #[cfg(not(feature="zstd"))]
+    {
+        use crate::NBTError;
+        assert!(matches!(crate::compression::compress(&[], Compression::Zstd), Err(NBTError::UnsupportedCompression { compression: Compression::Zstd })));
+        assert!(matches!(Blob::read_auto(&[0x28, 0xb5, 0x2f, 0xfd, 0][..]), Err(NBTError::UnsupportedCompression { .. })));
+    }
+}
+
+#[cfg(feature="compression")]
+#[test]
+fn read_auto_detection() {
+    use crate::{Compression, Tag, NBTWrite};
+
+    let mut blob = Blob::create("Data");
+    blob.insert("DataVersion", 2586);
+    let raw = blob.bytes().unwrap();
+
+    for compression in [Compression::Gzip, Compression::Zlib] {
+        let mut buffer = Vec::new();
+        blob.write_compressed(&mut buffer, compression).unwrap();
+        assert_eq!(Blob::read_auto(&buffer[..]).unwrap().compound_ref(), blob.compound_ref());
+    }
+    assert_eq!(Blob::read_auto(&raw[..]).unwrap().root, "Data");
+
+    // Readers handing out one byte at a time still have the whole header sniffed.
+    let compressed = crate::compression::compress(&[3, 0, 0, 0, 7], Compression::Zlib).unwrap();
+    let reader = std::io::Read::chain(&compressed[..1], &compressed[1..]);
+    assert_eq!(Tag::read_auto(reader).unwrap(), Tag::Int(7));
+
+    assert!(Blob::read_auto(&raw[..2]).is_err());
+    assert!(Blob::read_auto(&[][..]).is_err());
+}
+
+#[test]
+fn varint_strings() {
+    use crate::{NetworkLittleEndian, NbtFlavor, NBTError, TagGenerator, Tag};
+    use crate::varint::{read_var_u32, write_var_u32};
+
+    // Lengths around each byte boundary, up to the largest.
+    for length in [0, 1, 127, 128, 16_383, 16_384, 2_097_151, 2_097_152, 268_435_455, 268_435_456, u32::MAX] {
+        let mut buffer = Vec::new();
+        write_var_u32(&mut buffer, length).unwrap();
+        assert_eq!(buffer.len(), 1 + (32 - length.leading_zeros()).saturating_sub(1) as usize / 7);
+        assert_eq!(read_var_u32(&mut &buffer[..]).unwrap(), length);
+        assert_eq!(NetworkLittleEndian::read_string_length(&mut &buffer[..]).unwrap(), length as usize);
+    }
+
+    // Overlong and unterminated lengths fail rather than wrapping.
+    assert!(read_var_u32(&mut &[0xff, 0xff, 0xff, 0xff, 0x1f][..]).is_err());
+    assert!(read_var_u32(&mut &[0x80, 0x80, 0x80, 0x80, 0x80, 0x01][..]).is_err());
+    assert!(read_var_u32(&mut &[0x80][..]).is_err());
+    #[cfg(target_pointer_width = "64")]
+    assert!(matches!(NetworkLittleEndian::write_string_length(&mut Vec::new(), u32::MAX as usize + 1), Err(NBTError::StringTooLong { .. })));
+
+    // Generated documents round trip, and every truncation fails cleanly.
+    let mut generator = TagGenerator::new(7).max_depth(2).max_length(12);
+    for _ in 0..50 {
+        let blob = generator.generate_blob();
+        let mut buffer = Vec::new();
+        blob.write_flavor::<NetworkLittleEndian, _>(&mut buffer).unwrap();
+        let read = Blob::read_flavor::<NetworkLittleEndian, _>(&mut &buffer[..]).unwrap();
+        assert_eq!(read.compound_ref(), blob.compound_ref());
+        for end in (0..buffer.len()).step_by(7) {
+            assert!(Blob::read_flavor::<NetworkLittleEndian, _>(&mut &buffer[..end]).is_err());
+        }
+    }
+    let long = Tag::String("x".repeat(70_000));
+    let mut buffer = Vec::new();
+    long.write_flavor::<NetworkLittleEndian, _>(&mut buffer).unwrap();
+    assert_eq!(&buffer[..3], &[0xf0, 0xa2, 0x04]);
+
+    // A huge claimed length fails once the data runs out, rather than allocating it up front.
+    let huge = [8, 0xff, 0xff, 0xff, 0xff, 0x0f];
+    assert!(matches!(Tag::read_flavor::<NetworkLittleEndian, _>(&mut &huge[..]), Err(NBTError::IO { .. })));
+    let mut document = vec![10, 0, 8, 1, b'x'];
+    document.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 0x0f, b'y']);
+    assert!(matches!(Blob::read_flavor::<NetworkLittleEndian, _>(&mut &document[..]), Err(NBTError::IO { .. })));
+    assert!(matches!(crate::validate_flavor::<NetworkLittleEndian, _>(&mut &document[..], &crate::ValidateLimits::new()), Err(NBTError::IO { .. })));
+}
+
+#[test]
+fn network_varints() {
+    use crate::{NetworkLittleEndian, TagGenerator, Tag, NBTWrite, validate_flavor, ValidateLimits};
+    use crate::varint::{read_var_i32, write_var_i32, read_var_i64, write_var_i64};
+
+    // Small numbers of either sign stay short.
+    for (value, bytes) in [(0, vec![0x00]), (-1, vec![0x01]), (1, vec![0x02]), (-64, vec![0x7f]), (64, vec![0x80, 0x01])] {
+        let mut buffer = Vec::new();
+        write_var_i32(&mut buffer, value).unwrap();
+        assert_eq!(buffer, bytes);
+        assert_eq!(read_var_i32(&mut &buffer[..]).unwrap(), value);
+    }
+    for value in [i32::MIN, i32::MAX] {
+        let mut buffer = Vec::new();
+        write_var_i32(&mut buffer, value).unwrap();
+        assert_eq!(buffer.len(), 5);
+        assert_eq!(read_var_i32(&mut &buffer[..]).unwrap(), value);
+    }
+    for value in [i64::MIN, i64::MAX, -1, 1 << 40] {
+        let mut buffer = Vec::new();
+        write_var_i64(&mut buffer, value).unwrap();
+        assert_eq!(read_var_i64(&mut &buffer[..]).unwrap(), value);
+    }
+    assert!(read_var_i64(&mut &[0xff; 11][..]).is_err());
+
+    // Ints and list lengths are VarInts, while shorts stay fixed.
+    let list = Tag::List(vec![Tag::Int(-2), Tag::Int(300)]);
+    let mut buffer = Vec::new();
+    list.write_flavor::<NetworkLittleEndian, _>(&mut buffer).unwrap();
+    assert_eq!(buffer, vec![3, 4, 3, 0xd8, 0x04]);
+    let mut buffer = Vec::new();
+    Tag::Short(-2).write_flavor::<NetworkLittleEndian, _>(&mut buffer).unwrap();
+    assert_eq!(buffer, vec![0xfe, 0xff]);
+
+    // Negative lengths are rejected.
+    let data = [10, 0, 9, 1, b'l', 3, 0x01, 0];
+    assert!(Blob::read_flavor::<NetworkLittleEndian, _>(&mut &data[..]).is_err());
+    assert!(validate_flavor::<NetworkLittleEndian, _>(&mut &data[..], &ValidateLimits::new()).is_err());
+
+    // Arrays of VarInts are validated item by item.
+    let mut generator = TagGenerator::new(11).max_depth(2).max_length(12);
+    for _ in 0..50 {
+        let mut buffer = Vec::new();
+        generator.generate_blob().write_flavor::<NetworkLittleEndian, _>(&mut buffer).unwrap();
+        assert!(validate_flavor::<NetworkLittleEndian, _>(&mut &buffer[..], &ValidateLimits::new()).is_ok());
+        assert!(validate_flavor::<NetworkLittleEndian, _>(&mut &buffer[..buffer.len() - 1], &ValidateLimits::new()).is_err());
+    }
+}
+
+#[test]
+fn typed_iteration() {
+    use crate::Tag;
+
+    let mut blob = Blob::new();
+    blob.insert("LevelName", "World");
+    blob.insert("generatorName", "default");
+    blob.insert("SpawnX", 12);
+    blob.insert("raining", Tag::Byte(0));
+
+    let mut strings: Vec<(&str, &String)> = blob.iter_of::<String>().collect();
+    strings.sort();
+    assert_eq!(strings, vec![("LevelName", &"World".to_string()), ("generatorName", &"default".to_string())]);
+
+    assert_eq!(blob.iter_of::<i32>().collect::<Vec<_>>(), vec![("SpawnX", &12)]);
+    assert_eq!(blob.iter_of::<i64>().count(), 0);
+    assert_eq!(blob.iter_of::<Tag>().count(), 4);
+}
+
+#[cfg(all(feature="serde", feature="serde_unsigned"))]
+#[test]
+fn unsigned_overflow() {
+    use crate::{encode_tag_with, decode_tag_with, encode_tag, decode_tag, SerdeOptions, UnsignedOverflow, NBTError, Tag};
+
+    let options = |policy| SerdeOptions::new().unsigned_overflow(policy);
+    let huge = i64::MAX as u64 + 2;
+
+    // Values that fit are longs whatever the policy.
+    for policy in [UnsignedOverflow::Wrap, UnsignedOverflow::Error, UnsignedOverflow::Saturate, UnsignedOverflow::String, UnsignedOverflow::ByteArray] {
+        assert_eq!(encode_tag_with(&7u64, &options(policy)).unwrap(), Some(Tag::Long(7)));
+        assert_eq!(decode_tag_with::<u64>(Tag::Long(7), &options(policy)).unwrap(), 7);
+    }
+
+    // The default wraps, as before.
+    assert_eq!(encode_tag(&huge).unwrap(), Some(Tag::Long(i64::MIN + 1)));
+    assert_eq!(decode_tag::<u64>(Tag::Long(i64::MIN + 1)).unwrap(), huge);
+
+    let error = options(UnsignedOverflow::Error);
+    assert!(matches!(encode_tag_with(&huge, &error), Err(NBTError::UnsignedOutOfRange { .. })));
+    assert!(matches!(decode_tag_with::<u64>(Tag::Long(-1), &error), Err(NBTError::UnsignedOutOfRange { value: -1 })));
+
+    let saturate = options(UnsignedOverflow::Saturate);
+    assert_eq!(encode_tag_with(&u64::MAX, &saturate).unwrap(), Some(Tag::Long(i64::MAX)));
+
+    for policy in [UnsignedOverflow::String, UnsignedOverflow::ByteArray] {
+        let tag = encode_tag_with(&vec![huge, u64::MAX], &options(policy)).unwrap().unwrap();
+        assert_eq!(decode_tag_with::<Vec<u64>>(tag, &options(policy)).unwrap(), vec![huge, u64::MAX]);
+    }
+    assert_eq!(encode_tag_with(&u64::MAX, &options(UnsignedOverflow::String)).unwrap(), Some(Tag::String(u64::MAX.to_string())));
+
+    // Escape hatches are only accepted under their own policy.
+    assert!(decode_tag_with::<u64>(Tag::String("1".to_string()), &saturate).is_err());
+    assert!(decode_tag_with::<u64>(Tag::ByteArray(vec![1; 7]), &options(UnsignedOverflow::ByteArray)).is_err());
+}
+
+#[test]
+fn snbt_writer() {
+    use crate::Tag;
+
+    let tag = Tag::compound_from_iter(vec![
+        ("b", Tag::Byte(-1)),
+        ("s", Tag::Short(2)),
+        ("i", Tag::Int(3)),
+        ("l", Tag::Long(4)),
+        ("f", Tag::Float(0.5)),
+        ("d", Tag::Double(1.0)),
+        ("display name", Tag::String(r#"say "hi" \o/"#.to_string())),
+        ("bytes", Tag::ByteArray(vec![1, -2])),
+    ]);
+    let snbt = r#"{b:-1b,bytes:[B;1b,-2b],d:1.0d,"display name":"say \"hi\" \\o/",f:0.5f,i:3,l:4L,s:2s}"#;
+    assert_eq!(tag.to_snbt(), snbt);
+    assert_eq!(tag.to_string(), snbt);
+    assert_eq!(format!("{:#}", Tag::List(vec![Tag::Int(1)])), "[\n    1\n]");
+    assert_eq!(Tag::List(vec![Tag::Int(1)]).to_snbt_pretty(), "[\n    1\n]");
+
+    let mut blob = Blob::create("ignored");
+    blob.insert("Count", Tag::Byte(1));
+    assert_eq!(blob.to_snbt(), "{Count:1b}");
+    assert_eq!(blob.to_snbt_pretty(), "{\n    Count: 1b\n}");
+    assert_eq!(Blob::new().to_snbt(), "{}");
+}
+
+#[test]
+fn compound_entries() {
+    use crate::{Compound, Entry, Tag};
+
+    let mut blob = Blob::new();
+    assert_eq!(*blob.entry("LevelName").or_insert("World"), Tag::String("World".to_string()));
+    assert_eq!(*blob.entry("LevelName").or_insert_with(|| -> &str { unreachable!() }), Tag::String("World".to_string()));
+
+    // The default is only built for missing elements, and modification only happens for present ones.
+    blob.entry("Time").and_modify(|_| unreachable!()).or_insert(0_i64);
+    blob.entry("Time").and_modify(|x| *x = Tag::Long(20)).or_insert(0_i64);
+    assert_eq!(blob.get::<i64>("Time"), Some(&20));
+
+    match blob.entry("Time") {
+        Entry::Occupied(mut entry) => {
+            assert_eq!(entry.key(), "Time");
+            assert_eq!(entry.insert(40_i64), Tag::Long(20));
+            assert_eq!(entry.remove(), Tag::Long(40));
+        }
+        Entry::Vacant(_) => panic!("expected an occupied entry"),
+    }
+    assert!(matches!(blob.entry("Time"), Entry::Vacant(entry) if entry.key() == "Time"));
+
+    let mut compound = Compound::new();
+    if let Entry::Vacant(entry) = compound.entry("Data") {
+        entry.insert(vec![1_i32, 2]);
+    }
+    assert_eq!(compound.elements["Data"], Tag::IntArray(vec![1, 2]));
+}
+
+#[cfg(feature="serde")]
+#[test]
+fn serde_bytes() {
+    use crate::{encode, decode, encode_tag, decode_tag, NBTError, Tag, TagIdent};
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+    use serde::de::{Visitor, Error};
+    use std::fmt;
+
+    // A byte buffer, as `serde_bytes::ByteBuf` serializes it.
+    #[derive(PartialEq, Debug)]
+    struct Bytes(Vec<u8>);
+
+    impl Serialize for Bytes {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Bytes {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct BytesVisitor;
+            impl<'de> Visitor<'de> for BytesVisitor {
+                type Value = Bytes;
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result { f.write_str("bytes") }
+                fn visit_byte_buf<E: Error>(self, v: Vec<u8>) -> Result<Bytes, E> { Ok(Bytes(v)) }
+            }
+            deserializer.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Section {
+        #[serde(rename = "BlockLight")]
+        block_light: Bytes,
+    }
+
+    let section = Section { block_light: Bytes(vec![0, 127, 128, 255]) };
+    let blob = encode(&section).unwrap();
+    assert_eq!(blob.compound_ref()["BlockLight"], Tag::ByteArray(vec![0, 127, -128, -1]));
+    assert_eq!(decode::<Section>(blob).unwrap(), section);
+
+    // Unsigned storage is accepted too, but not other tags.
+    assert_eq!(decode_tag::<Bytes>(Tag::UnsignedByteArray(vec![1, 2])).unwrap(), Bytes(vec![1, 2]));
+    assert!(matches!(decode_tag::<Bytes>(Tag::List(Vec::new())), Err(NBTError::InvalidType { found: TagIdent::TAG_List, .. })));
+    assert_eq!(encode_tag(&Bytes(Vec::new())).unwrap(), Some(Tag::ByteArray(Vec::new())));
+}
+
+#[cfg(feature="serde")]
+#[test]
+fn serde_native_arrays() {
+    use crate::{encode, decode, decode_tag, IntArray, LongArray, Tag, NBTError};
+    use serde::{Serialize, Deserialize};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Entity {
+        #[serde(rename = "UUID")]
+        uuid: IntArray,
+        states: LongArray,
+        plain: Vec<i32>,
+        nothing: Option<IntArray>,
+    }
+
+    let entity = Entity {
+        uuid: IntArray(vec![1, -2, 3, -4]),
+        states: LongArray(vec![i64::MAX]),
+        plain: vec![5],
+        nothing: None,
+    };
+    let blob = encode(&entity).unwrap();
+    assert_eq!(blob.compound_ref()["UUID"], Tag::IntArray(vec![1, -2, 3, -4]));
+    assert_eq!(blob.compound_ref()["states"], Tag::LongArray(vec![i64::MAX]));
+    assert_eq!(blob.compound_ref()["plain"], Tag::List(vec![Tag::Int(5)]));
+    assert!(!blob.compound_ref().contains_key("nothing"));
+    assert_eq!(decode::<Entity>(blob).unwrap(), entity);
+
+    // Either representation is accepted, by wrappers and plain vectors alike.
+    assert_eq!(decode_tag::<IntArray>(Tag::List(vec![Tag::Int(7)])).unwrap(), IntArray(vec![7]));
+    assert_eq!(decode_tag::<Vec<i64>>(Tag::LongArray(vec![8])).unwrap(), vec![8]);
+    assert_eq!(decode_tag::<Vec<i8>>(Tag::ByteArray(vec![-1])).unwrap(), vec![-1]);
+    assert!(decode_tag::<IntArray>(Tag::LongArray(vec![1])).is_err());
+
+    // The serializer rejects wrappers holding something other than the array's items.
+    #[derive(Serialize)]
+    struct Wrong(#[serde(serialize_with = "wrong")] ());
+    fn wrong<S: serde::Serializer>(_: &(), serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct("$nbt::IntArray", &vec![1i8])
+    }
+    assert!(matches!(crate::encode_tag(&Wrong(())), Err(NBTError::InvalidList { .. })));
+}
+
+#[cfg(feature="serde")]
+#[test]
+fn serde_root_handling() {
+    use crate::{encode, encode_named_with, encode_bare, decode_tag, SerdeOptions, RootHandling, Tag, NBTError, NBTRead};
+    use serde::{Serialize, Deserialize};
+
+    // Non-compound roots are rejected by default.
+    assert!(matches!(encode(&vec![1i32, 2]), Err(NBTError::InvalidImplicit { .. })));
+    assert!(matches!(encode(&42i32), Err(NBTError::InvalidImplicit { .. })));
+
+    // Or wrapped into a compound under the configured key.
+    let options = SerdeOptions::new().root_handling(RootHandling::Wrap("value".to_string()));
+    let blob = encode_named_with(&42i32, "root", &options).unwrap();
+    assert_eq!(blob.root, "root");
+    assert_eq!(blob.elements.len(), 1);
+    assert_eq!(blob.get::<i32>("value"), Some(&42));
+    assert!(encode_named_with(&None::<i32>, "", &options).unwrap().elements.is_empty());
+
+    // Compound roots are left alone.
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Player { name: String }
+    let player = Player { name: "Notch".to_string() };
+    let blob = encode_named_with(&player, "", &options).unwrap();
+    assert_eq!(blob.get::<String>("name"), Some(&"Notch".to_string()));
+
+    // Bare tags carry only the type and payload, and read back through `Tag::read`.
+    assert_eq!(encode_bare(&42i32).unwrap(), vec![3, 0, 0, 0, 42]);
+    let bytes = encode_bare(&player).unwrap();
+    assert_eq!(bytes[0], 10);
+    assert_eq!(decode_tag::<Player>(Tag::from_bytes(&bytes).unwrap()).unwrap(), player);
+    assert!(matches!(encode_bare(&None::<i32>), Err(NBTError::InvalidImplicit { .. })));
+}
+
+#[test]
+fn validate_before_writing() {
+    use crate::{Tag, Blob, WriteOptions, NBTError, NBTWrite};
+    use crate::flavor::NetworkLittleEndian;
+
+    // A mixed list nested inside a compound within a list is found.
+    let bad = Tag::List(vec![Tag::compound_from_iter(vec![
+        ("inner", Tag::List(vec![Tag::Int(1), Tag::String("2".to_string())])),
+    ])]);
+    assert!(matches!(bad.validate_lists(), Err(NBTError::InvalidList { .. })));
+    assert!(bad.validate_write(&WriteOptions::new()).is_err());
+    assert!(bad.bytes().is_err());
+
+    // Unless mixed lists are coerced when writing.
+    let options = WriteOptions::new().mixed_lists(true);
+    assert!(bad.validate_write(&options).is_ok());
+    assert!(bad.write_with(&mut Vec::new(), &options).is_ok());
+
+    // Strings and keys too long for the dialect are found.
+    let long = "a".repeat(u16::MAX as usize + 1);
+    let mut blob = Blob::new();
+    blob.insert("name", long.as_str());
+    assert!(blob.validate_lists().is_ok());
+    assert!(matches!(blob.validate_write(&WriteOptions::new()), Err(NBTError::StringTooLong { .. })));
+    assert!(blob.validate_write_flavor::<NetworkLittleEndian>(&WriteOptions::new()).is_ok());
+
+    let mut blob = Blob::new();
+    blob.insert(&long, 1i32);
+    assert!(matches!(blob.validate_write(&WriteOptions::new()), Err(NBTError::StringTooLong { .. })));
+    assert!(blob.bytes().is_err());
+
+    assert!(Blob::new().validate_write(&WriteOptions::new()).is_ok());
+}
+
+#[cfg(all(feature="async", feature="serde"))]
+#[test]
+fn async_serde() {
+    use crate::{Blob, Tag, NBTRead, NBTWrite, NBTError, ReadOptions, MemoryBudget, from_async_reader, to_async_writer};
+    use serde::{Serialize, Deserialize};
+
+    let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Player {
+        name: String,
+        pos: Vec<f64>,
+        inventory: Vec<Item>,
+        empty: Vec<Item>,
+        seed: i64,
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Item {
+        id: String,
+        count: i8,
+    }
+
+    let player = Player {
+        name: "Notch ☃".to_string(),
+        pos: vec![0.5, 64.0, -3.25],
+        inventory: vec![Item { id: "minecraft:stone".to_string(), count: 64 }, Item { id: "minecraft:dirt".to_string(), count: 1 }],
+        empty: vec![],
+        seed: -42,
+    };
+
+    // Serde values round trip, and the bytes are the ones written synchronously.
+    let mut bytes = Vec::new();
+    runtime.block_on(to_async_writer(&mut bytes, &player)).unwrap();
+    assert_eq!(Blob::from_bytes(&bytes).unwrap().compound(), crate::encode(&player).unwrap().compound());
+    assert_eq!(runtime.block_on(from_async_reader::<Player, _>(&bytes[..])).unwrap(), player);
+
+    // Deeply nested documents and every kind of tag read the same as synchronously.
+    let mut blob = Blob::create("root");
+    blob.insert("bytes", Tag::ByteArray(vec![-1, 0, 1]));
+    blob.insert("ints", Tag::IntArray(vec![i32::MIN, 7]));
+    blob.insert("longs", Tag::LongArray(vec![i64::MAX]));
+    blob.insert("numbers", Tag::List(vec![Tag::Short(1), Tag::Short(-1)]));
+    blob.insert("float", 1.5f32);
+    let mut nested = Tag::Int(0);
+    for _ in 0..100 {
+        nested = Tag::List(vec![Tag::compound_from_iter(vec![("inner", nested)])]);
+    }
+    blob.insert("nested", nested);
+    let bytes = blob.bytes().unwrap();
+    let read = runtime.block_on(Blob::read_async(&mut &bytes[..])).unwrap();
+    assert_eq!(read.root, "root");
+    assert_eq!(read.compound(), blob.compound());
+
+    // Reads can be spawned onto multi-threaded runtimes.
+    fn assert_send<T: Send>(_: &T) {}
+    assert_send(&Blob::read_async(&mut &bytes[..]));
+    assert_send(&from_async_reader::<Player, _>(&bytes[..]));
+
+    // Truncated documents and the memory budget are rejected.
+    let truncated = &bytes[..bytes.len() - 1];
+    assert!(matches!(runtime.block_on(Blob::read_async(&mut &truncated[..])), Err(NBTError::IO { .. })));
+    let options = ReadOptions::new().memory_budget(MemoryBudget::new(64));
+    assert!(matches!(
+        runtime.block_on(Blob::read_async_with_options(&mut &bytes[..], &options)),
+        Err(NBTError::MemoryBudgetExceeded { limit: 64 })
+    ));
+
+    // As are documents nested deeper than the limit.
+    let mut deep = vec![10, 0, 0];
+    for _ in 0..1000 {
+        deep.extend_from_slice(&[10, 0, 0]);
+    }
+    let options = ReadOptions::new().max_depth(8);
+    assert!(matches!(
+        runtime.block_on(Blob::read_async_with_options(&mut &deep[..], &options)),
+        Err(NBTError::DepthLimitExceeded { limit: 8 })
+    ));
+}
+
+#[test]
+fn nameless_network_root() {
+    use crate::{Blob, Tag, NBTWrite, NBTError, TagGenerator};
+
+    // The network form is the document without the root name's length prefix.
+    let mut blob = Blob::create("ignored");
+    blob.insert("nested", Tag::compound_from_iter(vec![("name", Tag::String("x".to_string()))]));
+    let mut network = Vec::new();
+    blob.write_network(&mut network).unwrap();
+    let named = Blob { root: String::new(), ..blob.clone() }.bytes().unwrap();
+    assert_eq!(network[0], 10);
+    assert_eq!(&network[1..], &named[3..]);
+
+    let read = Blob::read_network(&mut &network[..]).unwrap();
+    assert_eq!(read.root, "");
+    assert_eq!(read.compound_ref(), blob.compound_ref());
+
+    // Generated documents round trip, and leave nothing unread.
+    let mut generator = TagGenerator::new(3).max_depth(2).max_length(8);
+    for _ in 0..20 {
+        let blob = generator.generate_blob();
+        let mut buffer = Vec::new();
+        blob.write_network(&mut buffer).unwrap();
+        buffer.push(0xff);
+        let mut reader = &buffer[..];
+        assert_eq!(Blob::read_network(&mut reader).unwrap().compound_ref(), blob.compound_ref());
+        assert_eq!(reader, &[0xff]);
+    }
+
+    // The root must still be a compound.
+    assert!(matches!(Blob::read_network(&mut &[3, 0, 0, 0, 1][..]), Err(NBTError::InvalidImplicit { .. })));
+    assert!(Blob::read_network(&mut &[10][..]).is_err());
+
+    // Serde values encoded as bare tags are read the same way.
+    #[cfg(feature="serde-ser")]
+    {
+        #[derive(serde::Serialize)]
+        struct Packet { id: i32 }
+        let bytes = crate::encode_bare(&Packet { id: 7 }).unwrap();
+        assert_eq!(Blob::read_network(&mut &bytes[..]).unwrap().get::<i32>("id"), Some(&7));
+    }
+}
+
+#[test]
+fn stored_types() {
+    use crate::{Blob, Compound, Tag, TagIdent};
+
+    let mut blob = Blob::new();
+    blob.insert("legacy", Tag::List(vec![Tag::Int(1), Tag::Int(2)]));
+    blob.insert("modern", Tag::IntArray(vec![1, 2]));
+    blob.insert("empty", Tag::List(vec![]));
+    blob.insert("bytes", Tag::UnsignedByteArray(vec![1]));
+    blob.insert("nested", Tag::List(vec![Tag::List(vec![])]));
+    blob.insert("name", "x");
+
+    assert_eq!(blob.type_of("legacy"), Some(TagIdent::TAG_List));
+    assert_eq!(blob.type_of("modern"), Some(TagIdent::TAG_Int_Array));
+    assert_eq!(blob.type_of("bytes"), Some(TagIdent::TAG_Byte_Array));
+    assert_eq!(blob.type_of("missing"), None);
+
+    let element = |key: &str| blob.compound_ref()[key].list_element_type();
+    assert_eq!(element("legacy"), Some(TagIdent::TAG_Int));
+    assert_eq!(element("modern"), Some(TagIdent::TAG_Int));
+    assert_eq!(element("empty"), Some(TagIdent::TAG_End));
+    assert_eq!(element("bytes"), Some(TagIdent::TAG_Byte));
+    assert_eq!(element("nested"), Some(TagIdent::TAG_List));
+    assert_eq!(element("name"), None);
+    assert_eq!(Tag::LongArray(vec![]).list_element_type(), Some(TagIdent::TAG_Long));
+
+    let mut compound = Compound::new();
+    compound.elements.insert("x".to_string(), Tag::Double(1.0));
+    assert_eq!(compound.type_of("x"), Some(TagIdent::TAG_Double));
+    assert_eq!(compound.type_of("y"), None);
+}
+
+#[test]
+fn tag_accessors() {
+    use crate::Tag;
+
+    assert_eq!(Tag::Byte(-1).as_i8(), Some(-1));
+    assert_eq!(Tag::Short(300).as_i16(), Some(300));
+    assert_eq!(Tag::Int(7).as_i32(), Some(7));
+    assert_eq!(Tag::Long(1 << 40).as_i64(), Some(1 << 40));
+    assert_eq!(Tag::Float(0.5).as_f32(), Some(0.5));
+    assert_eq!(Tag::Double(0.25).as_f64(), Some(0.25));
+    assert_eq!(Tag::Int(7).as_i8(), None);
+    assert_eq!(Tag::Float(0.5).as_f64(), None);
+    assert_eq!(Tag::String("a".to_string()).as_str(), Some("a"));
+    assert_eq!(Tag::Int(1).as_str(), None);
+    assert_eq!(Tag::IntArray(vec![1]).as_int_array(), Some(&[1][..]));
+    assert_eq!(Tag::LongArray(vec![2]).as_long_array(), Some(&[2][..]));
+    assert_eq!(Tag::List(vec![]).as_int_array(), None);
+
+    // Containers can be edited in place.
+    let mut tag = Tag::compound_from_iter(vec![("items", Tag::List(vec![Tag::Int(1)]))]);
+    tag.as_compound_mut().unwrap().get_mut("items").unwrap().as_list_mut().unwrap().push(Tag::Int(2));
+    assert_eq!(tag.as_compound().unwrap()["items"].as_list(), Some(&vec![Tag::Int(1), Tag::Int(2)]));
+    assert!(tag.as_list().is_none());
+    assert!(Tag::Int(1).as_compound_mut().is_none());
+
+    // Exactly one predicate holds for each tag.
+    let tags = [
+        Tag::Byte(0), Tag::Short(0), Tag::Int(0), Tag::Long(0), Tag::Float(0.0), Tag::Double(0.0),
+        Tag::ByteArray(vec![]), Tag::String(String::new()), Tag::List(vec![]), Tag::compound_from_iter(Vec::<(&str, Tag)>::new()),
+        Tag::IntArray(vec![]), Tag::LongArray(vec![]), Tag::UnsignedByteArray(vec![]),
+    ];
+    for tag in &tags {
+        let predicates = [
+            tag.is_byte(), tag.is_short(), tag.is_int(), tag.is_long(), tag.is_float(), tag.is_double(),
+            tag.is_byte_array(), tag.is_string(), tag.is_list(), tag.is_compound(), tag.is_int_array(), tag.is_long_array(),
+        ];
+        assert_eq!(predicates.iter().filter(|x| **x).count(), 1);
+    }
+    assert!(Tag::UnsignedByteArray(vec![]).is_byte_array());
+}
+
+#[test]
+fn batch_insertion() {
+    use crate::{Blob, Compound, Tag};
+
+    let mut blob = Blob::new();
+    blob.insert("kept", 1);
+    blob.insert("replaced", "old");
+    blob.insert_all((0..100).map(|i| (format!("row{}", i), i as i64)));
+    blob.insert_all(vec![("replaced", Tag::Byte(2))]);
+    assert_eq!(blob.len(), 102);
+    assert!(blob.capacity() >= 102);
+    assert_eq!(blob.get::<i64>("row99"), Some(&99));
+    assert_eq!(blob.get::<i32>("kept"), Some(&1));
+    assert_eq!(blob.get::<i8>("replaced"), Some(&2));
+
+    let mut compound = Compound::with_capacity(0);
+    compound.extend_from(vec![("name".to_string(), "Steve"), ("world".to_string(), "overworld")]);
+    assert!(compound.capacity() >= 2);
+    assert_eq!(compound.elements.get("world"), Some(&Tag::String("overworld".to_string())));
+}
+
+#[test]
+fn tag_indexing() {
+    use crate::Tag;
+
+    let palette = Tag::List(vec![Tag::compound_from_iter(vec![("Name", Tag::String("minecraft:air".to_string()))])]);
+    let section = Tag::compound_from_iter(vec![("Palette", palette), ("0", Tag::Byte(1))]);
+    let level = Tag::compound_from_iter(vec![("Sections", Tag::List(vec![section]))]);
+    let mut chunk = Tag::compound_from_iter(vec![("Level", level)]);
+
+    assert_eq!(chunk["Level"]["Sections"][0]["Palette"][0]["Name"].as_str(), Some("minecraft:air"));
+    assert_eq!(chunk.pointer("Level/Sections/0/Palette/0/Name"), Some(&Tag::String("minecraft:air".to_string())));
+    assert_eq!(chunk.pointer("/Level/Sections/0/Palette/0/Name"), chunk.pointer("Level/Sections/0/Palette/0/Name"));
+    assert_eq!(chunk.pointer(""), Some(&chunk));
+    // Numeric steps are keys within compounds.
+    assert_eq!(chunk.pointer("Level/Sections/0/0"), Some(&Tag::Byte(1)));
+    assert_eq!(chunk.pointer("Level/Sections/x"), None);
+    assert_eq!(chunk.pointer("Level/Sections/1"), None);
+    assert_eq!(chunk.pointer("Level/Sections/0/0/0"), None);
+
+    chunk["Level"]["Sections"][0]["0"] = Tag::Byte(2);
+    *chunk.pointer_mut("Level/Sections/0/Palette/0/Name").unwrap() = Tag::String("minecraft:stone".to_string());
+    assert_eq!(chunk.pointer("Level/Sections/0/0"), Some(&Tag::Byte(2)));
+    assert_eq!(chunk["Level"]["Sections"][0]["Palette"][0]["Name"].as_str(), Some("minecraft:stone"));
+
+    assert!(std::panic::catch_unwind(|| Tag::Int(1)["key"].clone()).is_err());
+    assert!(std::panic::catch_unwind(|| Tag::List(vec![])[0].clone()).is_err());
+}
+
+#[cfg(feature="hexdump")]
+#[test]
+fn hexdump() {
+    use crate::{Blob, Tag};
+
+    let mut blob = Blob::create("");
+    blob.insert("Pos", Tag::List(vec![Tag::Short(1), Tag::Short(2)]));
+    assert_eq!(blob.hexdump().unwrap(), concat!(
+        "00000000  0a 00 00                                         // TAG_Compound ''\n",
+        "00000003  09 00 03 50 6f 73 02 00 00 00 02                 //   TAG_List 'Pos' of 2 TAG_Short\n",
+        "0000000e  00 01                                            //     [0] TAG_Short\n",
+        "00000010  00 02                                            //     [1] TAG_Short\n",
+        "00000012  00                                               //   TAG_End\n",
+    ));
+
+    // Tags are dumped without a type or name, and long arrays continue over several rows.
+    let dump = Tag::IntArray(vec![0; 5]).hexdump().unwrap();
+    assert_eq!(dump.lines().collect::<Vec<_>>(), vec![
+        "00000000  00 00 00 05 00 00 00 00 00 00 00 00 00 00 00 00  // TAG_IntArray of 5",
+        "00000010  00 00 00 00 00 00 00 00",
+    ]);
+    let dump = Tag::compound_from_iter(vec![("a", Tag::compound_from_iter(Vec::<(&str, Tag)>::new()))]).hexdump().unwrap();
+    assert_eq!(dump.lines().map(|x| x.split("// ").nth(1).unwrap()).collect::<Vec<_>>(), vec![
+        "TAG_Compound", "  TAG_Compound 'a'", "    TAG_End", "  TAG_End",
+    ]);
+}
+
+#[test]
+fn nbt_macro() {
+    use crate::{nbt, Tag, Blob, NBTWrite};
+
+    let key = String::from("dynamic");
+    let tag = nbt!({
+        "name": "Steve",
+        "Health": 20.0f32,
+        "Pos": [1.0, 2.0, 3.0],
+        key: 5i64,
+        "Nested": { "Empty": {}, "List": [], "Lists": [[1i16], [2i16]] },
+        "Sum": 1 + 2
+    });
+
+    let mut expected = Blob::new();
+    expected.insert("name", "Steve");
+    expected.insert("Health", 20.0f32);
+    expected.insert("Pos", Tag::List(vec![Tag::Double(1.0), Tag::Double(2.0), Tag::Double(3.0)]));
+    expected.insert("dynamic", 5i64);
+    expected.insert("Nested", Tag::compound_from_iter(vec![
+        ("Empty", Tag::compound_from_iter(Vec::<(&str, Tag)>::new())),
+        ("List", Tag::List(vec![])),
+        ("Lists", Tag::List(vec![Tag::List(vec![Tag::Short(1)]), Tag::List(vec![Tag::Short(2)])])),
+    ]));
+    expected.insert("Sum", 3);
+    assert_eq!(tag, expected.clone().compound());
+
+    assert_eq!(nbt!([]), Tag::List(vec![]));
+    assert_eq!(nbt!([{}, { "a": 1 },]), Tag::List(vec![Tag::compound_from_iter(Vec::<(&str, Tag)>::new()), Tag::compound_from_iter(vec![("a", Tag::Int(1))])]));
+    assert_eq!(nbt!(-1i8), Tag::Byte(-1));
+
+    let blob = nbt!(Blob { "name": "Steve" });
+    assert_eq!(blob.root, "");
+    let root = String::from("player");
+    let blob = nbt!(Blob(root) { "DataVersion": 2586 });
+    assert_eq!(blob.root, "player");
+    assert_eq!(blob.get::<i32>("DataVersion"), Some(&2586));
+    assert!(blob.bytes().is_ok());
+}
+
+#[cfg(feature="preserve_order")]
+#[test]
+fn preserve_order() {
+    use crate::{Blob, Tag, NBTWrite, NBTRead, CompoundMap};
+
+    let keys = ["zebra", "apple", "mango", "kiwi", "banana", "cherry"];
+    let mut blob = Blob::create("root");
+    for (i, key) in keys.iter().enumerate() {
+        blob.insert(key, Tag::compound_from_iter(keys.iter().rev().map(|x| (*x, Tag::Int(i as i32)))));
+    }
+
+    // Documents round-trip byte for byte, with elements in the order they were inserted.
+    let bytes = blob.bytes().unwrap();
+    let decoded = Blob::from_bytes(bytes.clone()).unwrap();
+    assert_eq!(decoded.keys().collect::<Vec<_>>(), keys);
+    assert_eq!(decoded.bytes().unwrap(), bytes);
+
+    // Replacing keeps the position, removing shifts the rest down.
+    let mut map: CompoundMap = keys.iter().map(|x| (x.to_string(), Tag::Byte(0))).collect();
+    map.insert("mango".to_string(), Tag::Byte(1));
+    assert_eq!(map.remove("apple"), Some(Tag::Byte(0)));
+    map.entry("apple".to_string()).or_insert(Tag::Byte(2));
+    map.retain(|key, _| key != "kiwi");
+    assert_eq!(map.keys().collect::<Vec<_>>(), vec!["zebra", "mango", "banana", "cherry", "apple"]);
+    assert_eq!(map["mango"], Tag::Byte(1));
+    assert_eq!(map.get("banana"), Some(&Tag::Byte(0)));
+
+    // Maps are equal regardless of order.
+    let reversed: CompoundMap = map.clone().into_iter().rev().collect();
+    assert!(reversed == map);
+    assert_eq!(reversed.keys().next().map(String::as_str), Some("apple"));
+}
+
+#[test]
+fn extract_path_streaming() {
+    use crate::{extract_path, extract_path_flavor, nbt, NBTError, NBTWrite, Tag, BedrockFlavor};
+
+    let chunk = nbt!(Blob("chunk") {
+        "DataVersion": 2586,
+        "Level": {
+            "Sections": [
+                { "Y": 0i8, "Palette": [{ "Name": "minecraft:bedrock" }] },
+                { "Y": 1i8, "Palette": [{ "Name": "minecraft:stone" }, { "Name": "minecraft:air" }] },
+            ],
+            "Biomes": vec![1; 1024],
+            "Lights": [[1i16], [2i16, 3i16]],
+        },
+    });
+    let bytes = chunk.bytes().unwrap();
+    let extract = |path: &str| extract_path(&mut &bytes[..], path);
+
+    assert_eq!(extract("DataVersion").unwrap(), vec![Tag::Int(2586)]);
+    assert_eq!(extract("Level.Sections[*].Palette[*].Name").unwrap(), vec![
+        Tag::String("minecraft:bedrock".to_string()),
+        Tag::String("minecraft:stone".to_string()),
+        Tag::String("minecraft:air".to_string()),
+    ]);
+    assert_eq!(extract("Level.Sections[1].Palette").unwrap(), vec![chunk["Level"]["Sections"][1]["Palette"].clone()]);
+    assert_eq!(extract("Level.Lights[*][1]").unwrap(), vec![Tag::Short(3)]);
+    assert_eq!(extract("").unwrap(), vec![chunk.clone().compound()]);
+
+    // Missing keys, indices past the end, and paths through leaves match nothing.
+    assert!(extract("Level.Entities").unwrap().is_empty());
+    assert!(extract("Level.Sections[2].Y").unwrap().is_empty());
+    assert!(extract("DataVersion.Y").unwrap().is_empty());
+    assert!(extract("[0]").unwrap().is_empty());
+
+    // The reader is left after the document.
+    let mut reader = &bytes.repeat(2)[..];
+    assert_eq!(extract_path(&mut reader, "Level.Sections[0].Y").unwrap(), vec![Tag::Byte(0)]);
+    assert_eq!(reader.len(), bytes.len());
+
+    assert!(matches!(extract("Level..Sections"), Err(NBTError::InvalidPath { .. })));
+    assert!(matches!(extract("Level.Sections[x]"), Err(NBTError::InvalidPath { .. })));
+    assert!(extract_path(&mut &bytes[..bytes.len() - 1], "DataVersion").is_err());
+
+    let mut bedrock = Vec::new();
+    chunk.write_flavor::<BedrockFlavor, _>(&mut bedrock).unwrap();
+    assert_eq!(extract_path_flavor::<BedrockFlavor, _>(&mut &bedrock[..], "Level.Sections[*].Y").unwrap(), vec![Tag::Byte(0), Tag::Byte(1)]);
+}
+
+#[test]
+fn snbt_parsing() {
+    use crate::{snbt, Tag, NBTError};
+
+    let source = "{\n    name: \"Steve\",\n    'Health': 20.0f,\n    Pos: [1.0d, 2.0d, 3.0d],\n    Scores: [I; 1, 2],\n    Flags: [B;],\n    Empty: {},\n    word: minecraft:stone\n}";
+    let tag = snbt::from_str(&source.replace("minecraft:stone", "stone")).unwrap();
+    assert_eq!(tag["name"], Tag::String("Steve".to_string()));
+    assert_eq!(tag["Health"], Tag::Float(20.0));
+    assert_eq!(tag["Pos"][2], Tag::Double(3.0));
+    assert_eq!(tag["Scores"], Tag::IntArray(vec![1, 2]));
+    assert_eq!(tag["Flags"], Tag::ByteArray(vec![]));
+    assert_eq!(tag["word"], Tag::String("stone".to_string()));
+
+    // The formatter's output parses back to the same tag.
+    let mut all = Tag::compound_from_iter(vec![
+        ("b", Tag::Byte(-1)), ("s", Tag::Short(2)), ("l", Tag::Long(i64::MIN)), ("f", Tag::Float(f32::INFINITY)),
+        ("d", Tag::Double(0.1)), ("q", Tag::String("say \"hi\" \\ o/".to_string())), ("la", Tag::LongArray(vec![1, -2])),
+        ("nested", Tag::List(vec![Tag::List(vec![]), Tag::List(vec![Tag::Int(1)])])),
+    ]);
+    all.as_compound_mut().unwrap().insert("not bare".to_string(), Tag::Byte(0));
+    assert_eq!(snbt::from_str(&all.to_snbt()).unwrap(), all);
+    assert_eq!(all.to_snbt_pretty().parse::<Tag>().unwrap(), all);
+    assert_eq!(Tag::from_snbt(" 1b ").unwrap(), Tag::Byte(1));
+
+    // Errors point at the problem.
+    let location = |source: &str| match snbt::from_str(source) {
+        Err(NBTError::InvalidSnbt { line, column, .. }) => (line, column),
+        other => panic!("parsed {:?}", other.map(|x| x.to_snbt())),
+    };
+    assert_eq!(location(source), (8, 20));
+    assert_eq!(location("{a: 1,\n a: 2}"), (2, 2));
+    assert_eq!(location("[1, 2b]"), (1, 5));
+    assert_eq!(location("{a: \"open}"), (1, 5));
+    assert_eq!(location("[I; 1, 2b]"), (1, 8));
+    assert_eq!(location("{a: 1} x"), (1, 8));
+    assert_eq!(location("{a 1}"), (1, 4));
+    assert_eq!(location("[1 2]"), (1, 3));
+    assert_eq!(location(""), (1, 1));
+
+    // Nesting is limited, so deep input fails rather than overflowing the stack.
+    let deep = format!("{}{}", "[".repeat(512), "]".repeat(512));
+    assert!(snbt::from_str(&deep).is_ok());
+    assert_eq!(location(&"[".repeat(100_000)), (1, 513));
+    assert_eq!(location(&"{a:".repeat(100_000)), (1, 1537));
+
+    // Spans locate each value and key.
+    let spanned = snbt::parse_spanned(source.replace("minecraft:stone", "stone").as_str()).unwrap();
+    let source = source.replace("minecraft:stone", "stone");
+    let text = |span: snbt::Span| &source[span.start..span.end];
+    assert_eq!(text(spanned.span), source);
+    assert_eq!(text(spanned.span_of("Pos[1]").unwrap()), "2.0d");
+    assert_eq!(text(spanned.span_of("Scores").unwrap()), "[I; 1, 2]");
+    assert_eq!(spanned.span_of("Pos[1]").unwrap().line_column(&source), (4, 17));
+    assert_eq!(spanned.span_of("Pos[3]"), None);
+    if let snbt::SpannedValue::Compound(entries) = &spanned.value {
+        assert_eq!(text(entries[1].key_span), "'Health'");
+    } else {
+        panic!("not a compound");
+    }
+    assert_eq!(spanned.into_tag(), tag);
+}
+
+#[test]
+fn sorted_write() {
+    use crate::{nbt, NBTWrite, WriteOptions, BedrockFlavor, Tag};
+
+    let options = WriteOptions::new().sorted_keys(true);
+    let keys: Vec<String> = (0..50).map(|i| format!("key{}", i)).collect();
+    let build = |order: &mut dyn Iterator<Item = &String>| {
+        let mut blob = nbt!(Blob("root") {});
+        for key in order {
+            blob.insert(key, nbt!({ "b": 1, "a": [{ "z": 1i8, "y": 2i8 }] }));
+        }
+        blob
+    };
+    let forward = build(&mut keys.iter());
+    let backward = build(&mut keys.iter().rev());
+
+    let (mut a, mut b) = (Vec::new(), Vec::new());
+    forward.write_with(&mut a, &options).unwrap();
+    backward.write_with(&mut b, &options).unwrap();
+    assert_eq!(a, b);
+
+    // Keys are compared by bytes, so "key10" comes before "key2".
+    let small = nbt!({ "key2": 0i8, "key10": 0i8, "B": 0i8, "a": 0i8 });
+    let mut bytes = Vec::new();
+    small.write_with(&mut bytes, &options).unwrap();
+    assert_eq!(bytes, [
+        &[1, 0, 1, b'B', 0][..], &[1, 0, 1, b'a', 0], &[1, 0, 5, b'k', b'e', b'y', b'1', b'0', 0], &[1, 0, 4, b'k', b'e', b'y', b'2', 0], &[0],
+    ].concat());
+    assert_eq!(Tag::read(&mut &[&[10][..], &bytes].concat()[..]).unwrap(), small);
+
+    let (mut a, mut b) = (Vec::new(), Vec::new());
+    forward.write_flavor_with::<BedrockFlavor, _>(&mut a, &options).unwrap();
+    backward.write_flavor_with::<BedrockFlavor, _>(&mut b, &options).unwrap();
+    assert_eq!(a, b);
+}
+
+#[cfg(feature="serde")]
+#[test]
+fn from_slice_borrowed() {
+    use crate::{from_slice, decode, encode, nbt, Blob, NBTRead, NBTWrite, NBTError, Tag};
+    use serde::{Serialize, Deserialize};
+    use std::borrow::Cow;
+    use std::collections::HashMap;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Borrowed<'a> {
+        name: &'a str,
+        #[serde(borrow)]
+        title: Cow<'a, str>,
+        data: &'a [u8],
+    }
+
+    let blob = nbt!(Blob("root") { "name": "Notch ☃", "title": "a\0b", "data": Tag::ByteArray(vec![1, -1]), "extra": [{ "a": 1 }] });
+    let bytes = blob.bytes().unwrap();
+    let value: Borrowed = from_slice(&bytes).unwrap();
+    assert_eq!(value, Borrowed { name: "Notch ☃", title: Cow::Borrowed("a\0b"), data: &[1, 255] });
+
+    // Plain strings and byte arrays point into the buffer, while the nul is decoded into a copy.
+    let range = bytes.as_ptr_range();
+    assert!(range.contains(&value.name.as_ptr()));
+    assert!(range.contains(&value.data.as_ptr()));
+    assert!(matches!(value.title, Cow::Owned(_)));
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Nul<'a> {
+        title: &'a str,
+    }
+    assert!(from_slice::<Nul>(&bytes).is_err());
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    enum Shape {
+        Empty,
+        Circle(f32),
+        Point(i32, i32),
+        Rect { w: i16, h: i16 },
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct World {
+        name: String,
+        empty: Shape,
+        shapes: Vec<Shape>,
+        scores: HashMap<String, i64>,
+        spawn: Option<(i32, i32)>,
+        missing: Option<i8>,
+        seeds: Vec<i64>,
+    }
+
+    let world = World {
+        name: "overworld".to_string(),
+        empty: Shape::Empty,
+        shapes: vec![Shape::Circle(1.5), Shape::Point(-1, 2), Shape::Rect { w: 3, h: 4 }],
+        scores: vec![("Notch".to_string(), 42), ("jeb_".to_string(), -7)].into_iter().collect(),
+        spawn: Some((8, -8)),
+        missing: None,
+        seeds: vec![i64::MIN, 0, i64::MAX],
+    };
+
+    // Values read the same as through a blob, including ones the visitor stops reading early.
+    let mut blob = encode(&world).unwrap();
+    blob.insert("ignored", nbt!({ "deep": [[1, 2], [3]] }));
+    blob.insert("seeds", Tag::LongArray(world.seeds.clone()));
+    let bytes = blob.bytes().unwrap();
+    let decoded: World = from_slice(&bytes).unwrap();
+    assert_eq!(decoded, world);
+    assert_eq!(decoded, decode::<World>(Blob::from_bytes(&bytes).unwrap()).unwrap());
+    let pair: HashMap<String, (i32,)> = from_slice(&nbt!(Blob { "a": [1, 2, 3], "b": [4] }).bytes().unwrap()).unwrap();
+    assert_eq!(pair["a"], (1,));
+    assert_eq!(pair["b"], (4,));
+
+    // Truncated documents and other roots are rejected.
+    assert!(matches!(from_slice::<World>(&bytes[..bytes.len() - 3]), Err(NBTError::IO { .. })));
+    assert!(matches!(from_slice::<World>(&[8, 0, 0, 0, 0]), Err(NBTError::InvalidImplicit { .. })));
+
+    // Deep nesting fails rather than overflowing the stack. Unoptimized builds need the stack of a
+    // main thread rather than a test thread to reach the limit.
+    let nested = |depth: usize| [&[10, 0, 0, 9, 0, 1, b'a'][..], &[9, 0, 0, 0, 1].repeat(depth), &[0, 0, 0, 0, 0, 0]].concat();
+    std::thread::Builder::new().stack_size(8 << 20).spawn(move || {
+        assert!(from_slice::<HashMap<String, Tag>>(&nested(510)).is_ok());
+        assert!(matches!(from_slice::<HashMap<String, Tag>>(&nested(511)), Err(NBTError::DepthLimitExceeded { limit: 512 })));
+        assert!(matches!(from_slice::<HashMap<String, Tag>>(&nested(10_000)), Err(NBTError::DepthLimitExceeded { limit: 512 })));
+    }).unwrap().join().unwrap();
+}
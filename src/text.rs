@@ -0,0 +1,159 @@
+//! # Text Components
+//! Conversion of chat (text) components between their JSON and NBT forms.
+//!
+//! Since Minecraft 1.20.3, text components are sent over the network as NBT rather than JSON. The
+//! structure is the same, with a few differences in encoding:
+//!
+//! - A plain component is a `TAG_String`, and any other is a compound.
+//! - Booleans such as `bold` are bytes of 0 or 1.
+//! - Lists must hold a single type, so plain components in an `extra` or `with` list are written as
+//!   `{text: "..."}` compounds when mixed with others. Other mixed lists have each item wrapped in a
+//!   compound under the key `""`, which is unwrapped again when converting back.
+//! - A JSON array is shorthand for its first component with the rest as `extra`.
+//!
+//! ## Example
+//! ```
+//! use nbt::{text, Tag};
+//! use serde_json::json;
+//!
+//! let component = json!({"text": "Hello ", "bold": true, "extra": ["world", {"text": "!", "color": "red"}]});
+//! let tag = text::from_json(&component).unwrap();
+//! assert!(matches!(&tag, Tag::Compound(map) if map["bold"] == Tag::Byte(1)));
+//!
+//! assert_eq!(text::to_json(&tag).unwrap(), json!({
+//!     "text": "Hello ", "bold": true, "extra": [{"text": "world"}, {"text": "!", "color": "red"}]
+//! }));
+//! ```
+
+use crate::tags::{Tag, CompoundMap};
+use crate::error::{NBTResult, NBTError};
+use crate::normalize::coerce_list;
+use serde_json::{Value, Map, Number};
+
+// Keys holding lists of components.
+const COMPONENT_LISTS: &[&str] = &["extra", "with"];
+
+// Keys holding booleans.
+const BOOLEANS: &[&str] = &["bold", "italic", "underlined", "strikethrough", "obfuscated", "interpret"];
+
+/// Convert a JSON text component into its NBT form.
+///
+/// Fails with `NBTError::Custom` if the component holds a `null`, which has no NBT form.
+pub fn from_json(component: &Value) -> NBTResult<Tag> {
+    match component {
+        Value::Array(items) => match items.split_first() {
+            Some((first, rest)) => {
+                let mut first = match from_json(first)? {
+                    Tag::String(text) => text_compound(text),
+                    tag => tag,
+                };
+                if let (Tag::Compound(map), false) = (&mut first, rest.is_empty()) {
+                    let mut extra = match map.remove("extra") {
+                        Some(Tag::List(extra)) => extra,
+                        _ => Vec::new(),
+                    };
+                    for item in rest {
+                        extra.push(from_json(item)?);
+                    }
+                    map.insert("extra".to_string(), Tag::List(component_list(extra)));
+                }
+                Ok(first)
+            }
+            None => Ok(Tag::String(String::new())),
+        },
+        // Bare numbers and booleans are shorthand for their text.
+        Value::Bool(x) => Ok(Tag::String(x.to_string())),
+        Value::Number(x) => Ok(Tag::String(x.to_string())),
+        _ => value_to_tag(component, None),
+    }
+}
+
+/// Parse a JSON text component and convert it into its NBT form.
+pub fn from_json_str(component: &str) -> NBTResult<Tag> {
+    let value: Value = serde_json::from_str(component).map_err(|x| NBTError::Custom(x.to_string()))?;
+    from_json(&value)
+}
+
+/// Convert the NBT form of a text component into JSON.
+pub fn to_json(component: &Tag) -> NBTResult<Value> {
+    tag_to_value(component, None)
+}
+
+// A compound holding plain text.
+fn text_compound(text: String) -> Tag {
+    let mut map = CompoundMap::default();
+    map.insert("text".to_string(), Tag::String(text));
+    Tag::Compound(map)
+}
+
+// Make a list of components hold a single type, writing plain components as compounds if needed.
+fn component_list(items: Vec<Tag>) -> Vec<Tag> {
+    if items.iter().all(|x| matches!(x, Tag::String(_))) || items.iter().all(|x| matches!(x, Tag::Compound(_))) {
+        return items;
+    }
+    items.into_iter().map(|x| match x {
+        Tag::String(text) => text_compound(text),
+        tag => tag,
+    }).collect()
+}
+
+fn value_to_tag(value: &Value, key: Option<&str>) -> NBTResult<Tag> {
+    Ok(match value {
+        Value::Null => return Err(NBTError::Custom(format!("null has no NBT form{}", key.map(|x| format!(" at key '{}'", x)).unwrap_or_default()))),
+        Value::Bool(x) => Tag::from_bool(*x),
+        Value::Number(x) => match (x.as_i64(), x.as_f64()) {
+            (Some(x), _) if x >= i32::MIN as i64 && x <= i32::MAX as i64 => Tag::Int(x as i32),
+            (Some(x), _) => Tag::Long(x),
+            (None, Some(x)) => Tag::Double(x),
+            (None, None) => return Err(NBTError::Custom(format!("number {} has no NBT form", x))),
+        },
+        Value::String(x) => Tag::String(x.clone()),
+        Value::Array(items) => {
+            if key.is_some_and(|x| COMPONENT_LISTS.contains(&x)) {
+                let items = items.iter().map(from_json).collect::<NBTResult<Vec<Tag>>>()?;
+                Tag::List(component_list(items))
+            } else {
+                let items = items.iter().map(|x| value_to_tag(x, None)).collect::<NBTResult<Vec<Tag>>>()?;
+                Tag::List(coerce_list(&items))
+            }
+        }
+        Value::Object(map) => {
+            let mut compound = CompoundMap::default();
+            for (key, value) in map {
+                compound.insert(key.clone(), value_to_tag(value, Some(key))?);
+            }
+            Tag::Compound(compound)
+        }
+    })
+}
+
+fn tag_to_value(tag: &Tag, key: Option<&str>) -> NBTResult<Value> {
+    let number = |x: f64| Number::from_f64(x).map(Value::Number).ok_or_else(|| NBTError::Custom(format!("{} has no JSON form", x)));
+    Ok(match tag {
+        Tag::Byte(x) if key.is_some_and(|x| BOOLEANS.contains(&x)) => Value::Bool(*x != 0),
+        Tag::Byte(x) => Value::from(*x),
+        Tag::Short(x) => Value::from(*x),
+        Tag::Int(x) => Value::from(*x),
+        Tag::Long(x) => Value::from(*x),
+        Tag::Float(x) => number(*x as f64)?,
+        Tag::Double(x) => number(*x)?,
+        Tag::String(x) => Value::String(x.clone()),
+        Tag::List(items) => Value::Array(items.iter().map(|x| match x {
+            // Unwrap items of mixed lists.
+            Tag::Compound(map) if map.len() == 1 && map.contains_key("") => tag_to_value(&map[""], None),
+            x => tag_to_value(x, None),
+        }).collect::<NBTResult<Vec<Value>>>()?),
+        Tag::Compound(compound) => {
+            let mut map = Map::new();
+            for (key, value) in compound {
+                map.insert(key.clone(), tag_to_value(value, Some(key))?);
+            }
+            Value::Object(map)
+        }
+        array => Value::Array(match array {
+            Tag::IntArray(x) => x.iter().map(|x| Value::from(*x)).collect(),
+            Tag::LongArray(x) => x.iter().map(|x| Value::from(*x)).collect(),
+            _ => array.byte_array_as_i8().unwrap_or_default().iter().map(|x| Value::from(*x)).collect(),
+        }),
+    })
+}
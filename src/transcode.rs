@@ -0,0 +1,178 @@
+//! # Transcoding
+//! Conversion between NBT and other Serde formats, such as JSON, CBOR or MessagePack.
+//!
+//! `Tag` implements `Serialize` and `Deserialize`, so a tree can be passed to any Serde format
+//! directly. The functions here do the same for encoded NBT documents.
+//!
+//! The serializing half requires the `serde-ser` feature, and the deserializing half `serde-de`.
+//!
+//! ### Type Mapping
+//! When serializing, each tag is written as its closest Serde type:
+//! - `Byte`, `Short`, `Int` and `Long` as `i8`, `i16`, `i32` and `i64`.
+//! - `Float` and `Double` as `f32` and `f64`.
+//! - `String` as a string.
+//! - `ByteArray`, `IntArray` and `LongArray` as sequences of their integer type.
+//! - `List` as a sequence, and `Compound` as a map.
+//!
+//! When deserializing, values are mapped back losslessly where the format allows it:
+//! - `i8`, `i16`, `i32` and `i64` become `Byte`, `Short`, `Int` and `Long`.
+//! - `u8`, `u16` and `u32` widen to `Short`, `Int` and `Long`. `u64` becomes `Long`, and fails
+//!   if it is above `i64::MAX`.
+//! - `bool` becomes a `Byte` of 0 or 1, and `char` a `String`.
+//! - Byte strings become a `ByteArray`.
+//! - Sequences become a `List`, and fail if their elements are not all of the same type.
+//! - Maps become a `Compound`.
+//!
+//! Formats without sized integers lose precision. JSON, for example, produces a `Long` for every
+//! integer and a `Double` for every float, and arrays come back as lists.
+//!
+//! ## Example
+//! ```
+//! # #[cfg(all(feature = "serde-ser", feature = "serde-de"))] {
+//! use nbt::transcode::{nbt_to_format, format_to_nbt};
+//!
+//! let data = vec![10, 0, 0, 1, 0, 1, 97, 42, 0];
+//!
+//! let mut json = Vec::new();
+//! nbt_to_format(&data, &mut serde_json::Serializer::new(&mut json)).unwrap();
+//! assert_eq!(json, br#"{"a":42}"#);
+//!
+//! let bytes = format_to_nbt(&mut serde_json::Deserializer::from_slice(&json), "").unwrap();
+//! # assert_eq!(bytes, vec![10, 0, 0, 4, 0, 1, 97, 0, 0, 0, 0, 0, 0, 0, 42, 0]);
+//! # }
+//! ```
+
+use crate::blob::Blob;
+use crate::tags::Tag;
+#[cfg(feature= "serde-ser")]
+use crate::front::NBTRead;
+#[cfg(feature= "serde-ser")]
+use serde::{Serialize, Serializer};
+#[cfg(feature= "serde-ser")]
+use serde::ser::{SerializeSeq, SerializeMap, Error as _};
+#[cfg(feature= "serde-de")]
+use crate::front::NBTWrite;
+#[cfg(feature= "serde-de")]
+use crate::tags::CompoundMap;
+#[cfg(feature= "serde-de")]
+use serde::{Deserialize, Deserializer};
+#[cfg(feature= "serde-de")]
+use serde::de::{self, Visitor, SeqAccess, MapAccess};
+#[cfg(feature= "serde-de")]
+use std::fmt;
+
+#[cfg(feature= "serde-ser")]
+/// Serialize an encoded NBT document into another format, as a map of its root compound.
+///
+/// The name of the root compound is discarded.
+pub fn nbt_to_format<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    let blob = Blob::from_bytes(data).map_err(S::Error::custom)?;
+    blob.compound().serialize(serializer)
+}
+
+#[cfg(feature= "serde-de")]
+/// Deserialize a map from another format and encode it as a NBT document, with the given root name.
+pub fn format_to_nbt<'de, D: Deserializer<'de>>(deserializer: D, root: &str) -> Result<Vec<u8>, D::Error> {
+    match Tag::deserialize(deserializer)? {
+        Tag::Compound(elements) => Blob { root: root.to_string(), elements, source: None }.bytes().map_err(de::Error::custom),
+        tag => Err(de::Error::custom(format!("Expected a map for the root compound, found {}", tag.ident())))
+    }
+}
+
+#[cfg(feature= "serde-ser")]
+fn serialize_seq<S: Serializer, T: Serialize>(serializer: S, items: &[T]) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(Some(items.len()))?;
+    for item in items {
+        seq.serialize_element(item)?;
+    }
+    seq.end()
+}
+
+#[cfg(feature= "serde-ser")]
+impl Serialize for Tag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Tag::Byte(x) => serializer.serialize_i8(*x),
+            Tag::Short(x) => serializer.serialize_i16(*x),
+            Tag::Int(x) => serializer.serialize_i32(*x),
+            Tag::Long(x) => serializer.serialize_i64(*x),
+            Tag::Float(x) => serializer.serialize_f32(*x),
+            Tag::Double(x) => serializer.serialize_f64(*x),
+            Tag::String(x) => serializer.serialize_str(x),
+            Tag::ByteArray(x) => serialize_seq(serializer, x),
+            // Serialized as signed bytes, like the `ByteArray` it is equal to.
+            Tag::UnsignedByteArray(_) => serialize_seq(serializer, self.byte_array_as_i8().unwrap_or_default()),
+            Tag::IntArray(x) => serialize_seq(serializer, x),
+            Tag::LongArray(x) => serialize_seq(serializer, x),
+            Tag::List(x) => serialize_seq(serializer, x),
+            Tag::Compound(x) => {
+                let mut map = serializer.serialize_map(Some(x.len()))?;
+                for (key, value) in x {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+#[cfg(feature= "serde-de")]
+struct TagVisitor;
+
+#[cfg(feature= "serde-de")]
+impl<'de> Visitor<'de> for TagVisitor {
+    type Value = Tag;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a value representable as NBT")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Tag, E> { Ok(Tag::Byte(v as i8)) }
+    fn visit_i8<E: de::Error>(self, v: i8) -> Result<Tag, E> { Ok(Tag::Byte(v)) }
+    fn visit_i16<E: de::Error>(self, v: i16) -> Result<Tag, E> { Ok(Tag::Short(v)) }
+    fn visit_i32<E: de::Error>(self, v: i32) -> Result<Tag, E> { Ok(Tag::Int(v)) }
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Tag, E> { Ok(Tag::Long(v)) }
+    fn visit_u8<E: de::Error>(self, v: u8) -> Result<Tag, E> { Ok(Tag::Short(v as i16)) }
+    fn visit_u16<E: de::Error>(self, v: u16) -> Result<Tag, E> { Ok(Tag::Int(v as i32)) }
+    fn visit_u32<E: de::Error>(self, v: u32) -> Result<Tag, E> { Ok(Tag::Long(v as i64)) }
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Tag, E> {
+        if v > i64::MAX as u64 {
+            return Err(E::invalid_value(de::Unexpected::Unsigned(v), &"an integer no larger than i64::MAX"));
+        }
+        Ok(Tag::Long(v as i64))
+    }
+    fn visit_f32<E: de::Error>(self, v: f32) -> Result<Tag, E> { Ok(Tag::Float(v)) }
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Tag, E> { Ok(Tag::Double(v)) }
+    fn visit_char<E: de::Error>(self, v: char) -> Result<Tag, E> { Ok(Tag::String(v.to_string())) }
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Tag, E> { Ok(Tag::String(v.to_string())) }
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Tag, E> { Ok(Tag::String(v)) }
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Tag, E> { Ok(Tag::byte_array_from_u8(v.to_vec())) }
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Tag, E> { Ok(Tag::byte_array_from_u8(v)) }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Tag, D::Error> {
+        Tag::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Tag, A::Error> {
+        let mut list: Vec<Tag> = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            list.push(item);
+        }
+        Tag::list_from_iter(list).map_err(de::Error::custom)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Tag, A::Error> {
+        let mut compound = CompoundMap::with_capacity_and_hasher(map.size_hint().unwrap_or(0), Default::default());
+        while let Some((key, value)) = map.next_entry::<String, Tag>()? {
+            compound.insert(key, value);
+        }
+        Ok(Tag::Compound(compound))
+    }
+}
+
+#[cfg(feature= "serde-de")]
+impl<'de> Deserialize<'de> for Tag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Tag, D::Error> {
+        deserializer.deserialize_any(TagVisitor)
+    }
+}
@@ -0,0 +1,52 @@
+use crate::tags::Tag;
+
+impl Tag {
+    /// Replace every tag matching a predicate, returning the number of replacements.
+    ///
+    /// The predicate is given the path of each tag, written as `key.key[index]` relative to this tag,
+    /// and the tag itself. Matching tags are replaced with the result of `f`, and their children are
+    /// not visited. The tag itself is visited first, with an empty path.
+    ///
+    /// ```
+    /// # use nbt::Tag;
+    /// let item = |id: &str| Tag::compound_from_iter(vec![("id", id)]);
+    /// let mut inventory = Tag::List(vec![item("minecraft:grass"), item("minecraft:stone"), item("minecraft:grass")]);
+    ///
+    /// let count = inventory.replace_where(
+    ///     |path, tag| path.ends_with(".id") && tag == &Tag::String("minecraft:grass".to_string()),
+    ///     |_| Tag::String("minecraft:short_grass".to_string())
+    /// );
+    ///
+    /// assert_eq!(count, 2);
+    /// # assert_eq!(inventory, Tag::List(vec![item("minecraft:short_grass"), item("minecraft:stone"), item("minecraft:short_grass")]));
+    /// ```
+    pub fn replace_where<P: FnMut(&str, &Tag) -> bool, F: FnMut(Tag) -> Tag>(&mut self, mut predicate: P, mut f: F) -> usize {
+        replace_where(self, &mut String::new(), &mut predicate, &mut f)
+    }
+}
+
+fn replace_where(tag: &mut Tag, path: &mut String, predicate: &mut dyn FnMut(&str, &Tag) -> bool, f: &mut dyn FnMut(Tag) -> Tag) -> usize {
+    if predicate(path, tag) {
+        let old = std::mem::replace(tag, Tag::Byte(0));
+        *tag = f(old);
+        return 1;
+    }
+
+    let length = path.len();
+    let mut count = 0;
+    match tag {
+        Tag::Compound(map) => for (key, value) in map.iter_mut() {
+            if !path.is_empty() { path.push('.'); }
+            path.push_str(key);
+            count += replace_where(value, path, predicate, f);
+            path.truncate(length);
+        },
+        Tag::List(list) => for (index, value) in list.iter_mut().enumerate() {
+            path.push_str(&format!("[{}]", index));
+            count += replace_where(value, path, predicate, f);
+            path.truncate(length);
+        },
+        _ => {}
+    }
+    count
+}
@@ -1,4 +1,4 @@
-use crate::tags::Tag;
+use crate::tags::{Tag, TagIdent};
 use std::collections::HashMap;
 
 /// A trait to convert rust types into their respective NBT Tag.
@@ -10,11 +10,17 @@ pub trait ToTag {
 pub trait FromTag: Sized {
     fn from_tag(tag: Tag) -> Option<Self>;
     fn from_borrowed_tag(tag: &Tag) -> Option<&Self>;
+
+    /// Mutably borrow a tag as this type. Returns `None` by default.
+    fn from_borrowed_tag_mut(_tag: &mut Tag) -> Option<&mut Self> { None }
+
+    /// The tag type this converts from, if there is a single one. Used when reporting errors.
+    fn expected_ident() -> Option<TagIdent> { None }
 }
 
 
 impl ToTag for Tag { fn into_tag(self) -> Tag { self }}
-impl FromTag for Tag { fn from_tag(tag: Tag) -> Option<Self> { Some(tag) } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { Some(&tag) } }
+impl FromTag for Tag { fn from_tag(tag: Tag) -> Option<Self> { Some(tag) } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { Some(tag) } fn from_borrowed_tag_mut(tag: &mut Tag) -> Option<&mut Self> { Some(tag) } }
 
 impl ToTag for i8 { fn into_tag(self) -> Tag { Tag::Byte(self) } }
 impl ToTag for i16 { fn into_tag(self) -> Tag { Tag::Short(self) } }
@@ -26,16 +32,17 @@ impl ToTag for String { fn into_tag(self) -> Tag { Tag::String(self) } }
 impl ToTag for &str { fn into_tag(self) -> Tag { Tag::String(self.to_string()) } }
 
 impl ToTag for Vec<i8> { fn into_tag(self) -> Tag { Tag::ByteArray(self) }}
+impl ToTag for Vec<u8> { fn into_tag(self) -> Tag { Tag::UnsignedByteArray(self) }}
 impl ToTag for Vec<i32> { fn into_tag(self) -> Tag { Tag::IntArray(self) }}
 impl ToTag for Vec<i64> { fn into_tag(self) -> Tag { Tag::LongArray(self) }}
 
 //impl ToTag for Vec<i16> { fn into_tag(self) -> Tag { Tag::List(self.into_iter().map(|x| x.into_tag()).collect()) } }
-impl<T: ToTag> ToTag for HashMap<String, T> { fn into_tag(self) -> Tag { Tag::Compound(self.into_iter().map(|(k, v)| (k, v.into_tag())).collect()) } }
-
-impl FromTag for i8 { fn from_tag(tag: Tag) -> Option<Self> { if let Tag::Byte(v) = tag { Some(v) } else { None } } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { if let Tag::Byte(v) = tag { Some(v) } else { None } } }
-impl FromTag for i16 { fn from_tag(tag: Tag) -> Option<Self> { if let Tag::Short(v) = tag { Some(v) } else { None } } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { if let Tag::Short(v) = tag { Some(v) } else { None } } }
-impl FromTag for i32 { fn from_tag(tag: Tag) -> Option<Self> { if let Tag::Int(v) = tag { Some(v) } else { None } } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { if let Tag::Int(v) = tag { Some(v) } else { None } } }
-impl FromTag for i64 { fn from_tag(tag: Tag) -> Option<Self> { if let Tag::Long(v) = tag { Some(v) } else { None } } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { if let Tag::Long(v) = tag { Some(v) } else { None } } }
-impl FromTag for f32 { fn from_tag(tag: Tag) -> Option<Self> { if let Tag::Float(v) = tag { Some(v) } else { None } } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { if let Tag::Float(v) = tag { Some(v) } else { None } } }
-impl FromTag for f64 { fn from_tag(tag: Tag) -> Option<Self> { if let Tag::Double(v) = tag { Some(v) } else { None } } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { if let Tag::Double(v) = tag { Some(v) } else { None } } }
-impl FromTag for String { fn from_tag(tag: Tag) -> Option<Self> { if let Tag::String(v) = tag { Some(v) } else { None } } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { if let Tag::String(v) = tag { Some(v) } else { None } } }
+impl<T: ToTag, S> ToTag for HashMap<String, T, S> { fn into_tag(self) -> Tag { Tag::Compound(self.into_iter().map(|(k, v)| (k, v.into_tag())).collect()) } }
+
+impl FromTag for i8 { fn from_tag(tag: Tag) -> Option<Self> { if let Tag::Byte(v) = tag { Some(v) } else { None } } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { if let Tag::Byte(v) = tag { Some(v) } else { None } } fn from_borrowed_tag_mut(tag: &mut Tag) -> Option<&mut Self> { if let Tag::Byte(v) = tag { Some(v) } else { None } } fn expected_ident() -> Option<TagIdent> { Some(TagIdent::TAG_Byte) } }
+impl FromTag for i16 { fn from_tag(tag: Tag) -> Option<Self> { if let Tag::Short(v) = tag { Some(v) } else { None } } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { if let Tag::Short(v) = tag { Some(v) } else { None } } fn from_borrowed_tag_mut(tag: &mut Tag) -> Option<&mut Self> { if let Tag::Short(v) = tag { Some(v) } else { None } } fn expected_ident() -> Option<TagIdent> { Some(TagIdent::TAG_Short) } }
+impl FromTag for i32 { fn from_tag(tag: Tag) -> Option<Self> { if let Tag::Int(v) = tag { Some(v) } else { None } } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { if let Tag::Int(v) = tag { Some(v) } else { None } } fn from_borrowed_tag_mut(tag: &mut Tag) -> Option<&mut Self> { if let Tag::Int(v) = tag { Some(v) } else { None } } fn expected_ident() -> Option<TagIdent> { Some(TagIdent::TAG_Int) } }
+impl FromTag for i64 { fn from_tag(tag: Tag) -> Option<Self> { if let Tag::Long(v) = tag { Some(v) } else { None } } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { if let Tag::Long(v) = tag { Some(v) } else { None } } fn from_borrowed_tag_mut(tag: &mut Tag) -> Option<&mut Self> { if let Tag::Long(v) = tag { Some(v) } else { None } } fn expected_ident() -> Option<TagIdent> { Some(TagIdent::TAG_Long) } }
+impl FromTag for f32 { fn from_tag(tag: Tag) -> Option<Self> { if let Tag::Float(v) = tag { Some(v) } else { None } } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { if let Tag::Float(v) = tag { Some(v) } else { None } } fn from_borrowed_tag_mut(tag: &mut Tag) -> Option<&mut Self> { if let Tag::Float(v) = tag { Some(v) } else { None } } fn expected_ident() -> Option<TagIdent> { Some(TagIdent::TAG_Float) } }
+impl FromTag for f64 { fn from_tag(tag: Tag) -> Option<Self> { if let Tag::Double(v) = tag { Some(v) } else { None } } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { if let Tag::Double(v) = tag { Some(v) } else { None } } fn from_borrowed_tag_mut(tag: &mut Tag) -> Option<&mut Self> { if let Tag::Double(v) = tag { Some(v) } else { None } } fn expected_ident() -> Option<TagIdent> { Some(TagIdent::TAG_Double) } }
+impl FromTag for String { fn from_tag(tag: Tag) -> Option<Self> { if let Tag::String(v) = tag { Some(v) } else { None } } fn from_borrowed_tag(tag: &Tag) -> Option<&Self> { if let Tag::String(v) = tag { Some(v) } else { None } } fn from_borrowed_tag_mut(tag: &mut Tag) -> Option<&mut Self> { if let Tag::String(v) = tag { Some(v) } else { None } } fn expected_ident() -> Option<TagIdent> { Some(TagIdent::TAG_String) } }
@@ -0,0 +1,255 @@
+use crate::tags::{Tag, TagIdent, CompoundMap};
+use crate::blob::Blob;
+use crate::error::{NBTResult, NBTError, digest_io};
+use crate::decode::read_ident;
+use crate::encode::ensure_list_integrity;
+use crate::options::WriteOptions;
+use crate::flavor::{NbtFlavor, JavaFlavor};
+use byteorder::ReadBytesExt;
+use std::io::{Read, ErrorKind};
+
+#[derive(Clone, Copy)]
+#[cfg_attr(feature="debug", derive(Debug))]
+/// Limits checked by `validate`.
+///
+/// By default lists and compounds may nest 512 levels deep, as in Minecraft, and lengths are only
+/// limited by the format.
+pub struct ValidateLimits {
+    pub(crate) max_depth: usize,
+    pub(crate) max_length: Option<usize>,
+}
+
+impl Default for ValidateLimits {
+    fn default() -> Self {
+        ValidateLimits { max_depth: 512, max_length: None }
+    }
+}
+
+impl ValidateLimits {
+    /// Create the default limits.
+    pub fn new() -> ValidateLimits {
+        ValidateLimits::default()
+    }
+
+    /// Limit how deeply lists and compounds are nested, counting the root compound.
+    pub fn max_depth(mut self, depth: usize) -> ValidateLimits {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Limit the number of elements in lists and arrays, and the number of bytes in strings.
+    pub fn max_length(mut self, length: usize) -> ValidateLimits {
+        self.max_length = Some(length);
+        self
+    }
+}
+
+/// Check that a document is well formed, without building it.
+///
+/// Tag ids, lengths, string encoding and nesting depth are checked as the data is streamed, and
+/// array payloads are skipped over, so only a string's worth of memory is used. The first problem
+/// found is returned, as the error a full read would fail with, or as
+/// `NBTError::DepthLimitExceeded` or `NBTError::LengthLimitExceeded` for the limits.
+///
+/// ## Example
+/// ```
+/// use nbt::{validate, ValidateLimits, NBTError};
+///
+/// let data = vec![10, 0, 0, 9, 0, 1, 97, 3, 0, 0, 0, 1, 0, 0, 0, 42, 0];
+/// assert!(validate(&mut &data[..], &ValidateLimits::new()).is_ok());
+///
+/// let limits = ValidateLimits::new().max_length(0);
+/// assert!(matches!(validate(&mut &data[..], &limits), Err(NBTError::LengthLimitExceeded { length: 1, limit: 0 })));
+/// ```
+pub fn validate<R: Read + ?Sized>(reader: &mut R, limits: &ValidateLimits) -> NBTResult<()> {
+    validate_flavor::<JavaFlavor, R>(reader, limits)
+}
+
+/// Check that a document of the given flavor is well formed, without building it. See `validate`.
+pub fn validate_flavor<F: NbtFlavor, R: Read + ?Sized>(reader: &mut R, limits: &ValidateLimits) -> NBTResult<()> {
+    let mut validator = Validator::new(limits);
+    match read_ident(reader)? {
+        TagIdent::TAG_Compound => {}
+        found => return Err(NBTError::InvalidImplicit { found }),
+    }
+    validator.string::<F, R>(reader)?;
+    validator.payload::<F, R>(reader, &TagIdent::TAG_Compound)
+}
+
+// Walks a document without building it. Also used to skip the parts `extract_path` doesn't match.
+pub(crate) struct Validator<'a> {
+    limits: &'a ValidateLimits,
+    depth: usize,
+    // Reused for the bytes of every string.
+    scratch: Vec<u8>,
+}
+
+impl<'a> Validator<'a> {
+    pub(crate) fn new(limits: &'a ValidateLimits) -> Self {
+        Validator { limits, depth: 0, scratch: Vec::new() }
+    }
+
+    fn check_length(&self, length: usize) -> NBTResult<()> {
+        match self.limits.max_length {
+            Some(limit) if length > limit => Err(NBTError::LengthLimitExceeded { length, limit }),
+            _ => Ok(())
+        }
+    }
+
+    fn string<F: NbtFlavor, R: Read + ?Sized>(&mut self, reader: &mut R) -> NBTResult<()> {
+        let length = digest_io(F::read_string_length(reader))?;
+        self.check_length(length)?;
+        // Read through `take` so a bogus length can't allocate more than the data sent.
+        self.scratch.clear();
+        digest_io(Read::take(&mut *reader, length as u64).read_to_end(&mut self.scratch))?;
+        if self.scratch.len() < length {
+            return digest_io(Err(ErrorKind::UnexpectedEof.into()));
+        }
+        F::decode_string(&self.scratch).map(drop)
+    }
+
+    // Skip over the items of an array, reading them one by one if their width varies.
+    fn array<F: NbtFlavor, R: Read + ?Sized>(&mut self, reader: &mut R, size: u64, read: impl Fn(&mut R) -> std::io::Result<()>) -> NBTResult<()> {
+        let length = digest_io(F::read_length(reader))?;
+        self.check_length(length as usize)?;
+        if !F::FIXED_WIDTH && size > 1 {
+            for _ in 0..length {
+                digest_io(read(reader))?;
+            }
+            return Ok(());
+        }
+        let bytes = length as u64 * size;
+        if digest_io(std::io::copy(&mut Read::take(&mut *reader, bytes), &mut std::io::sink()))? < bytes {
+            return digest_io(Err(ErrorKind::UnexpectedEof.into()));
+        }
+        Ok(())
+    }
+
+    fn descend(&mut self) -> NBTResult<()> {
+        self.depth += 1;
+        if self.depth > self.limits.max_depth {
+            return Err(NBTError::DepthLimitExceeded { limit: self.limits.max_depth });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn payload<F: NbtFlavor, R: Read + ?Sized>(&mut self, reader: &mut R, ident: &TagIdent) -> NBTResult<()> {
+        match ident {
+            TagIdent::TAG_End => return Err(NBTError::UnexpectedEndTag),
+            TagIdent::TAG_Byte => { digest_io(reader.read_i8())?; }
+            TagIdent::TAG_Short => { digest_io(F::read_short(reader))?; }
+            TagIdent::TAG_Int => { digest_io(F::read_int(reader))?; }
+            TagIdent::TAG_Long => { digest_io(F::read_long(reader))?; }
+            TagIdent::TAG_Float => { digest_io(F::read_float(reader))?; }
+            TagIdent::TAG_Double => { digest_io(F::read_double(reader))?; }
+            TagIdent::TAG_Byte_Array => self.array::<F, R>(reader, 1, |r| r.read_i8().map(drop))?,
+            TagIdent::TAG_Int_Array => self.array::<F, R>(reader, 4, |r| F::read_int(r).map(drop))?,
+            TagIdent::TAG_Long_Array => self.array::<F, R>(reader, 8, |r| F::read_long(r).map(drop))?,
+            TagIdent::TAG_String => self.string::<F, R>(reader)?,
+            TagIdent::TAG_List => {
+                self.descend()?;
+                let item = read_ident(reader)?;
+                let length = digest_io(F::read_length(reader))?;
+                self.check_length(length as usize)?;
+                // Lists of end tags are only valid when empty, which `payload` rejects otherwise.
+                for _ in 0..length {
+                    self.payload::<F, R>(reader, &item)?;
+                }
+                self.depth -= 1;
+            }
+            TagIdent::TAG_Compound => {
+                self.descend()?;
+                loop {
+                    let ident = read_ident(reader)?;
+                    if ident == TagIdent::TAG_End {
+                        break;
+                    }
+                    self.string::<F, R>(reader)?;
+                    self.payload::<F, R>(reader, &ident)?;
+                }
+                self.depth -= 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Tag {
+    /// Check that every list within the tag holds a single type of tag, as writing requires.
+    ///
+    /// Returns the `NBTError::InvalidList` that writing would fail with, so a tree built in memory
+    /// can be checked before any bytes are sent.
+    ///
+    /// ```
+    /// # use nbt::Tag;
+    /// assert!(Tag::List(vec![Tag::Int(1), Tag::Int(2)]).validate_lists().is_ok());
+    /// assert!(Tag::List(vec![Tag::Int(1), Tag::Byte(2)]).validate_lists().is_err());
+    /// ```
+    pub fn validate_lists(&self) -> NBTResult<()> {
+        match self {
+            Tag::List(list) => {
+                ensure_list_integrity(list)?;
+                list.iter().try_for_each(Tag::validate_lists)
+            }
+            Tag::Compound(compound) => compound.values().try_for_each(Tag::validate_lists),
+            _ => Ok(())
+        }
+    }
+
+    /// Check that the tag can be written with the given options, without writing it.
+    ///
+    /// As well as lists, unless `WriteOptions::mixed_lists` is set, strings and compound keys are
+    /// checked against the length limit of the Java dialect. Writing can then only fail on IO.
+    pub fn validate_write(&self, options: &WriteOptions) -> NBTResult<()> {
+        self.validate_write_flavor::<JavaFlavor>(options)
+    }
+
+    /// Check that the tag can be written in the given dialect with the given options, without
+    /// writing it. See `validate_write`.
+    pub fn validate_write_flavor<F: NbtFlavor>(&self, options: &WriteOptions) -> NBTResult<()> {
+        match self {
+            Tag::String(string) => validate_string::<F>(string),
+            Tag::List(list) => {
+                match ensure_list_integrity(list) {
+                    Err(NBTError::InvalidList { .. }) if options.mixed_lists => {}
+                    result => { result?; }
+                }
+                list.iter().try_for_each(|item| item.validate_write_flavor::<F>(options))
+            }
+            Tag::Compound(compound) => validate_compound::<F>(compound, options),
+            _ => Ok(())
+        }
+    }
+}
+
+impl Blob {
+    /// Check that every list within the blob holds a single type of tag. See `Tag::validate_lists`.
+    pub fn validate_lists(&self) -> NBTResult<()> {
+        self.elements.values().try_for_each(Tag::validate_lists)
+    }
+
+    /// Check that the blob can be written with the given options, without writing it. See
+    /// `Tag::validate_write`.
+    pub fn validate_write(&self, options: &WriteOptions) -> NBTResult<()> {
+        self.validate_write_flavor::<JavaFlavor>(options)
+    }
+
+    /// Check that the blob can be written in the given dialect with the given options, without
+    /// writing it. See `Tag::validate_write`.
+    pub fn validate_write_flavor<F: NbtFlavor>(&self, options: &WriteOptions) -> NBTResult<()> {
+        validate_string::<F>(&self.root)?;
+        validate_compound::<F>(&self.elements, options)
+    }
+}
+
+fn validate_string<F: NbtFlavor>(string: &str) -> NBTResult<()> {
+    F::write_string_length(&mut std::io::sink(), F::encode_string(string).len())
+}
+
+fn validate_compound<F: NbtFlavor>(compound: &CompoundMap, options: &WriteOptions) -> NBTResult<()> {
+    for (key, payload) in compound {
+        validate_string::<F>(key)?;
+        payload.validate_write_flavor::<F>(options)?;
+    }
+    Ok(())
+}
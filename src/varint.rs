@@ -0,0 +1,67 @@
+// Variable length integers, as used by the Bedrock network protocol.
+// Each byte holds 7 bits of the value, least significant first, with the high bit set on every
+// byte but the last. Signed values are zigzag encoded first, so small negative numbers stay short.
+
+use std::io::{Read, Write, Result, Error, ErrorKind};
+use byteorder::ReadBytesExt;
+
+// Read an unsigned varint of at most `bits` bits.
+fn read_var<R: Read + ?Sized>(reader: &mut R, bits: u32) -> Result<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = reader.read_u8()?;
+        // Reject values too long for the type, and bits past its width in the final byte.
+        if shift >= bits || (shift + 7 > bits && (byte & 0x7f) >> (bits - shift) != 0) {
+            return Err(Error::new(ErrorKind::InvalidData, "varint is too long"));
+        }
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+fn write_var<W: Write + ?Sized>(writer: &mut W, mut value: u64) -> Result<()> {
+    let mut buffer = [0u8; 10];
+    let mut length = 0;
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buffer[length] = byte;
+            length += 1;
+            break;
+        }
+        buffer[length] = byte | 0x80;
+        length += 1;
+    }
+    writer.write_all(&buffer[..length])
+}
+
+pub(crate) fn read_var_u32<R: Read + ?Sized>(reader: &mut R) -> Result<u32> {
+    read_var(reader, 32).map(|x| x as u32)
+}
+
+pub(crate) fn write_var_u32<W: Write + ?Sized>(writer: &mut W, value: u32) -> Result<()> {
+    write_var(writer, value as u64)
+}
+
+pub(crate) fn read_var_i32<R: Read + ?Sized>(reader: &mut R) -> Result<i32> {
+    let value = read_var(reader, 32)? as u32;
+    Ok((value >> 1) as i32 ^ -((value & 1) as i32))
+}
+
+pub(crate) fn write_var_i32<W: Write + ?Sized>(writer: &mut W, value: i32) -> Result<()> {
+    write_var(writer, ((value << 1) ^ (value >> 31)) as u32 as u64)
+}
+
+pub(crate) fn read_var_i64<R: Read + ?Sized>(reader: &mut R) -> Result<i64> {
+    let value = read_var(reader, 64)?;
+    Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+}
+
+pub(crate) fn write_var_i64<W: Write + ?Sized>(writer: &mut W, value: i64) -> Result<()> {
+    write_var(writer, ((value << 1) ^ (value >> 63)) as u64)
+}
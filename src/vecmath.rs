@@ -0,0 +1,64 @@
+//! Conversions between vector lists and vector types.
+//!
+//! Positions, rotations and motions are stored as lists of floating point numbers, such as an
+//! entity's `Pos` (3 doubles) and `Rotation` (2 floats). These map to `[f64; 3]` and `[f32; 2]`,
+//! and to `glam::DVec3` and `glam::Vec2`.
+//!
+//! Serde already encodes arrays and `glam` vectors as lists, so only `ToTag` and `FromTag` are
+//! implemented here. A vector cannot be borrowed out of a list, so `from_borrowed_tag` always
+//! returns `None` for these types: read them with `Blob::get_owned` or `FromTag::from_tag`, as
+//! `Blob::get` is always `None` and `Blob::try_get` always fails.
+//!
+//! ```
+//! use nbt::Blob;
+//! use glam::DVec3;
+//!
+//! let mut blob = Blob::new();
+//! blob.insert("Pos", DVec3::new(1.0, 64.0, -3.5));
+//!
+//! assert_eq!(blob.get_owned::<DVec3>("Pos"), Some(DVec3::new(1.0, 64.0, -3.5)));
+//! assert_eq!(blob.get::<DVec3>("Pos"), None);
+//! ```
+
+use crate::tags::{Tag, TagIdent};
+use crate::util::{ToTag, FromTag};
+use glam::{DVec3, Vec2};
+
+impl ToTag for [f64; 3] { fn into_tag(self) -> Tag { Tag::List(self.iter().map(|x| Tag::Double(*x)).collect()) } }
+impl ToTag for [f32; 2] { fn into_tag(self) -> Tag { Tag::List(self.iter().map(|x| Tag::Float(*x)).collect()) } }
+impl ToTag for DVec3 { fn into_tag(self) -> Tag { self.to_array().into_tag() } }
+impl ToTag for Vec2 { fn into_tag(self) -> Tag { self.to_array().into_tag() } }
+
+impl FromTag for [f64; 3] {
+    fn from_tag(tag: Tag) -> Option<Self> {
+        match tag.into_list_of::<f64>()?.collect::<Vec<f64>>().as_slice() {
+            [x, y, z] => Some([*x, *y, *z]),
+            _ => None
+        }
+    }
+    fn from_borrowed_tag(_tag: &Tag) -> Option<&Self> { None }
+    fn expected_ident() -> Option<TagIdent> { Some(TagIdent::TAG_List) }
+}
+
+impl FromTag for [f32; 2] {
+    fn from_tag(tag: Tag) -> Option<Self> {
+        match tag.into_list_of::<f32>()?.collect::<Vec<f32>>().as_slice() {
+            [x, y] => Some([*x, *y]),
+            _ => None
+        }
+    }
+    fn from_borrowed_tag(_tag: &Tag) -> Option<&Self> { None }
+    fn expected_ident() -> Option<TagIdent> { Some(TagIdent::TAG_List) }
+}
+
+impl FromTag for DVec3 {
+    fn from_tag(tag: Tag) -> Option<Self> { <[f64; 3]>::from_tag(tag).map(DVec3::from) }
+    fn from_borrowed_tag(_tag: &Tag) -> Option<&Self> { None }
+    fn expected_ident() -> Option<TagIdent> { Some(TagIdent::TAG_List) }
+}
+
+impl FromTag for Vec2 {
+    fn from_tag(tag: Tag) -> Option<Self> { <[f32; 2]>::from_tag(tag).map(Vec2::from) }
+    fn from_borrowed_tag(_tag: &Tag) -> Option<&Self> { None }
+    fn expected_ident() -> Option<TagIdent> { Some(TagIdent::TAG_List) }
+}
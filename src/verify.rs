@@ -0,0 +1,75 @@
+use crate::tags::Tag;
+use crate::blob::Blob;
+use crate::error::{NBTResult, NBTError};
+use crate::front::{NBTRead, NBTWrite};
+use crate::options::WriteOptions;
+use crate::tags::CompoundMap;
+
+// Find the path of the first place two tags differ.
+// Leaves are compared with `Tag`'s equality, which compares floats by their bits.
+pub(crate) fn divergence(a: &Tag, b: &Tag, path: &str) -> Option<String> {
+    match (a, b) {
+        (Tag::List(x), Tag::List(y)) => {
+            if x.len() != y.len() {
+                return Some(path.to_string());
+            }
+            x.iter().zip(y.iter()).enumerate()
+                .find_map(|(i, (x, y))| divergence(x, y, &format!("{}[{}]", path, i)))
+        }
+        (Tag::Compound(x), Tag::Compound(y)) => compound_divergence(x, y, path),
+        (a, b) => if a == b { None } else { Some(path.to_string()) }
+    }
+}
+
+pub(crate) fn compound_divergence(a: &CompoundMap, b: &CompoundMap, path: &str) -> Option<String> {
+    let child = |key: &str| if path.is_empty() { key.to_string() } else { format!("{}.{}", path, key) };
+
+    // Sort keys so the reported divergence does not depend on hash order.
+    let mut keys: Vec<&String> = a.keys().chain(b.keys().filter(|k| !a.contains_key(*k))).collect();
+    keys.sort();
+
+    for key in keys {
+        match (a.get(key), b.get(key)) {
+            (Some(x), Some(y)) => if let Some(found) = divergence(x, y, &child(key)) {
+                return Some(found);
+            },
+            _ => return Some(child(key))
+        }
+    }
+    None
+}
+
+/// Verify that a NBT document survives a round trip.
+///
+/// The bytes are parsed into a `Blob`, re-encoded canonically, with compound keys sorted as by
+/// `WriteOptions::sorted_keys`, and parsed again, and the two blobs are compared semantically. Returns `NBTError::RoundtripMismatch` with the path of the first divergence.
+///
+/// ### Example
+/// ```
+/// use nbt::verify;
+///
+/// let data = vec![10, 0, 0, 1, 0, 1, 97, 42, 0];
+/// assert!(verify(&data).is_ok());
+/// ```
+pub fn verify<B: AsRef<[u8]>>(data: B) -> NBTResult<()> {
+    Blob::from_bytes(data)?.verify_roundtrip()
+}
+
+impl Blob {
+    /// Verify that the blob survives being encoded and decoded again.
+    ///
+    /// See [`verify`](crate::verify())
+    pub fn verify_roundtrip(&self) -> NBTResult<()> {
+        let mut canonical = Vec::new();
+        self.write_with(&mut canonical, &WriteOptions::new().sorted_keys(true))?;
+        let decoded = Blob::from_bytes(canonical)?;
+
+        if self.root != decoded.root {
+            return Err(NBTError::RoundtripMismatch { path: String::new() });
+        }
+        match compound_divergence(&self.elements, &decoded.elements, "") {
+            Some(path) => Err(NBTError::RoundtripMismatch { path }),
+            None => Ok(())
+        }
+    }
+}